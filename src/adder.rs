@@ -1,25 +1,43 @@
-use std::{collections::HashSet, future::ready};
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    future::ready,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use kameo::{
     Actor,
+    actor::{ActorRef, WeakActorRef},
+    error::{ActorStopReason, Infallible},
+    mailbox,
     message::{Context, Message},
 };
+use rand::{RngCore, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use tokio::sync::mpsc::UnboundedSender;
-use tracing::{info, warn};
+use tracing::{info, info_span, warn};
 use uuid::Uuid;
 
 use crate::{
-    graph::GraphStore,
+    audit::{AuditLog, AuditProposal},
+    export::{DotExportOptions, render_dot_with},
+    graph::{
+        CycleInfo, GraphSnapshot, GraphStore, MergeReport, SnapshotProblem, VersionedSnapshot,
+    },
     model::{
-        ALLOWED_TAGS, Decision, Edge, EdgeProposal, Granularity, InventoryEntry, MAX_NODE_LEVEL,
-        Node, NodeKind, NodeProposal, Relation, clean_text, normalize_text,
+        Decision, Edge, EdgeProposal, Explanation, GeneratorInfo, InventoryEntry, Node, NodeKind,
+        NodeProposal, NodeRules, ProposalSubject, Relation, clean_text, normalize_text,
     },
-    summary::{Summary, TopLearningOutcome},
-    viz::Event,
+    read_view::{GraphReadView, ReadHandle},
+    summary::{GeneratorStats, RejectionReport, Summary, TopLearningOutcome},
+    validation::{
+        BatchState, ValidEdge, ValidNode, check_similarity, validate_edge, validate_node,
+        validate_node_update,
+    },
+    viz::{BatchKind, CapacityResource, Event},
 };
 
-const MAX_TAGS_PER_NODE: usize = 3;
-
 /// Message requesting that new nodes be considered.
 pub struct AddNodes(pub Vec<NodeProposal>);
 
@@ -34,14 +52,394 @@ pub struct Inventory;
 #[derive(Default)]
 pub struct Summarize;
 
-/// Message requesting DOT export of the graph.
-pub struct ExportDot;
+/// Message requesting the serializable snapshot used to render every export.
+pub struct ExportBundle;
+
+/// Message requesting the versioned snapshot, left for the caller to
+/// encode.
+pub struct ExportJson;
+
+/// Message requesting the graph rendered as DOT with the given styling.
+pub struct ExportDotStyled(pub DotExportOptions);
+
+/// Message requesting a teaching order from the prerequisite edges.
+pub struct TopoOrder;
+
+/// Message requesting one node by id.
+pub struct GetNode(pub Uuid);
+
+/// Message requesting several nodes by id, answered in the same order.
+pub struct GetNodes(pub Vec<Uuid>);
+
+/// Message requesting the edges touching a node; unknown ids have none.
+pub struct GetEdgesOf(pub Uuid);
+
+/// Message requesting the nodes that pass every given filter.
+///
+/// Results are ordered by level, then normalized text, then id, and cut to
+/// `limit` afterwards.
+#[derive(Debug, Clone, Default)]
+pub struct FindNodes {
+    pub kind:          Option<NodeKind>,
+    pub level:         Option<u8>,
+    /// Nodes carrying at least one of these tags; empty matches any.
+    pub tags_any:      Vec<String>,
+    /// Case-insensitive substring of the normalized text.
+    pub text_contains: Option<String>,
+    pub limit:         Option<usize>,
+}
+
+/// Message requesting rejection counts by category over the adder's
+/// lifetime, zeroing them afterwards when `reset` is set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RejectionStats {
+    pub reset: bool,
+}
+
+/// Which way an edge runs relative to the node it was looked up from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeDirection {
+    Outgoing,
+    Incoming,
+}
+
+/// An edge reported by [`GetEdgesOf`], with the node at its other end.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IncidentEdge {
+    pub relation:   Relation,
+    pub direction:  EdgeDirection,
+    pub other_id:   Uuid,
+    pub other_text: Arc<str>,
+    pub rationale:  String,
+}
+
+/// Which replacements inherit the edges of a split node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SplitEdges {
+    /// Re-point every edge to the first replacement.
+    #[default]
+    First,
+    /// Duplicate every edge onto each replacement.
+    All,
+    /// Re-point every edge to the replacement at this position of `into`.
+    Replacement(usize),
+}
+
+/// Message replacing one node with several, re-attaching its edges.
+pub struct SplitNode {
+    pub node_id:  Uuid,
+    pub into:     Vec<String>,
+    pub edges_to: SplitEdges,
+}
+
+/// Result of a [`SplitNode`] request.
+///
+/// When `decision` is rejected the graph is unchanged; `replacements` then
+/// holds the per-text validation results that caused the rejection.
+/// Otherwise its `assigned_id` is the first replacement.
+#[derive(Debug, Clone, kameo::Reply)]
+pub struct SplitOutcome {
+    pub decision:     Decision,
+    /// Replacement node ids in `into` order; empty when rejected.
+    pub ids:          Vec<Uuid>,
+    pub replacements: Vec<Decision>,
+    pub edges:        Vec<Decision>,
+}
+
+/// Message folding `remove` into `keep`: its edges move to `keep`, its tags
+/// join `keep`'s up to the per-node cap, and it is removed.
+pub struct MergeNodes {
+    pub keep:   Uuid,
+    pub remove: Uuid,
+}
+
+/// An edge [`MergeNodes`] could not move onto the kept node, with the
+/// endpoints it would have had.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedEdge {
+    pub from:     Uuid,
+    pub to:       Uuid,
+    pub relation: Relation,
+    pub reason:   String,
+}
+
+/// Result of a [`MergeNodes`] request; the graph is unchanged when
+/// `decision` is rejected.
+#[derive(Debug, Clone, kameo::Reply)]
+pub struct MergeOutcome {
+    pub decision: Decision,
+    /// Edges re-pointed onto the kept node.
+    pub moved:    usize,
+    pub dropped:  Vec<DroppedEdge>,
+}
+
+/// Message setting or clearing the pin on a node.
+pub struct PinNode {
+    pub node_id: Uuid,
+    pub pinned:  bool,
+}
+
+/// Message setting or clearing the pin on an edge.
+pub struct PinEdge {
+    pub from:     Uuid,
+    pub to:       Uuid,
+    pub relation: Relation,
+    pub pinned:   bool,
+}
+
+/// Message removing every unpinned node and edge before regeneration.
+pub struct ClearUnpinned;
+
+/// Message adding nodes and then edges as one batch.
+///
+/// With `atomic` set, the batch is staged and commits only when enough of
+/// it is accepted (see [`AdderConfig::atomic_threshold`]); otherwise
+/// nothing is applied and no events are sent. Edges can only name nodes
+/// accepted before the batch, since ids are assigned on acceptance.
+pub struct ApplyBatch {
+    pub nodes:  Vec<NodeProposal>,
+    pub edges:  Vec<EdgeProposal>,
+    pub atomic: bool,
+}
+
+/// Result of an [`ApplyBatch`] request.
+///
+/// When `committed` is false, every decision is rejected: proposals that
+/// passed validation carry the rollback as their reason.
+#[derive(Debug, Clone, kameo::Reply)]
+pub struct BatchOutcome {
+    pub committed: bool,
+    pub nodes:     Vec<Decision>,
+    pub edges:     Vec<Decision>,
+}
+
+/// Message reverting the most recent batch that added nodes or edges.
+///
+/// Batches are [`AddNodes`], [`AddEdges`], and committed [`ApplyBatch`]
+/// calls; up to [`AdderConfig::undo_depth`] of them can be undone in turn.
+pub struct UndoLastBatch;
+
+/// What [`UndoLastBatch`] removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, kameo::Reply)]
+pub struct UndoReport {
+    pub nodes: Vec<Uuid>,
+    pub edges: Vec<EdgeRemoval>,
+}
+
+/// Why [`UndoLastBatch`] left the graph unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum UndoError {
+    #[error("no batch to undo")]
+    NothingToUndo,
+    #[error("graph changed since the last batch was applied")]
+    GraphChanged,
+}
+
+/// Nodes and edges added by one batch, and the revisions of the graph it
+/// was applied to and left behind.
+#[derive(Debug, Clone)]
+struct AppliedBatch {
+    before: u64,
+    after:  u64,
+    nodes:  Vec<Uuid>,
+    edges:  Vec<EdgeRemoval>,
+}
+
+/// Message loading a saved graph into the adder.
+///
+/// The snapshot must be consistent and its prerequisites acyclic. A
+/// non-empty store is refused unless `merge` is set, which folds the
+/// snapshot in as [`GraphStore::merge_from`] does.
+pub struct ImportGraph {
+    pub snapshot: GraphSnapshot,
+    pub merge:    bool,
+}
+
+/// Why [`ImportGraph`] left the graph unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ImportGraphError {
+    #[error("snapshot has {} problem(s)", .0.len())]
+    Invalid(Vec<SnapshotProblem>),
+    #[error("snapshot has a {0}")]
+    Cycle(CycleInfo),
+    #[error("graph already holds {nodes} node(s) and {edges} edge(s); set merge to import")]
+    NotEmpty { nodes: usize, edges: usize },
+}
+
+/// Message retracting accepted nodes along with their incident edges.
+pub struct RemoveNodes(pub Vec<Uuid>);
+
+/// Message editing an accepted node in place, keeping its id and edges.
+///
+/// `None` leaves a field unchanged; `tags: Some(None)` clears the tags.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateNode {
+    pub id:    Uuid,
+    pub text:  Option<String>,
+    pub level: Option<u8>,
+    pub tags:  Option<Option<Vec<String>>>,
+}
+
+/// Message retracting accepted edges.
+pub struct RemoveEdges(pub Vec<EdgeRemoval>);
+
+/// One edge named by [`RemoveEdges`]; related-to endpoints may come in
+/// either order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeRemoval {
+    pub from_id:  Uuid,
+    pub to_id:    Uuid,
+    pub relation: Relation,
+}
+
+/// Counts of elements removed by [`ClearUnpinned`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, kameo::Reply)]
+pub struct ClearReport {
+    pub nodes_removed: usize,
+    pub edges_removed: usize,
+}
+
+/// Message requesting mailbox and store health signals.
+pub struct Health;
+
+/// Load signals reported by [`Health`].
+#[derive(Debug, Clone, Serialize, kameo::Reply)]
+pub struct HealthReport {
+    /// Messages handled since spawn, including this one.
+    pub messages_processed: u64,
+    /// Wall time of the most recent `AddNodes` or `AddEdges` batch.
+    pub last_batch_latency: Option<Duration>,
+    /// Mailbox bound set by [`GraphAdder::spawn_bounded`], if any.
+    pub mailbox_capacity:   Option<usize>,
+    pub nodes:              usize,
+    pub edges:              usize,
+}
+
+/// How the adder treats edge rationales that do not mention their endpoints.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RationalePolicy {
+    /// Skip the consistency check.
+    #[default]
+    Off,
+    /// Accept the edge but attach a warning to its decision.
+    Warn,
+    /// Reject the edge.
+    Strict,
+}
+
+/// Quality checks on non-empty edge rationales; the defaults accept any.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RationaleRules {
+    /// Fewest whitespace-separated words.
+    pub min_words:        usize,
+    /// Most characters; `None` means unlimited.
+    pub max_chars:        Option<usize>,
+    /// Reject rationales that are not one complete sentence.
+    pub require_sentence: bool,
+}
+
+/// Tunable validation behavior for the GraphAdder.
+#[derive(Debug, Clone)]
+pub struct AdderConfig {
+    pub rationale_policy:       RationalePolicy,
+    /// Hard cap on accepted nodes; `None` means unlimited.
+    pub max_nodes:              Option<usize>,
+    /// Hard cap on accepted edges; `None` means unlimited.
+    pub max_edges:              Option<usize>,
+    /// Namespace given to proposals that arrive without one.
+    pub namespace:              Option<String>,
+    /// Seed for node ids; `None` draws random v4 ids.
+    pub id_seed:                Option<u64>,
+    /// Level, tag, and text rules for nodes.
+    pub node_rules:             NodeRules,
+    /// Reject edges without a rationale.
+    pub require_edge_rationale: bool,
+    /// Word, length, and sentence checks for edge rationales.
+    pub rationale_rules:        RationaleRules,
+    /// Fraction of proposals an atomic [`ApplyBatch`] must have accepted to
+    /// commit; `None` requires all of them.
+    pub atomic_threshold:       Option<f64>,
+    /// Reject nodes whose words match an existing node's at this
+    /// similarity or above; `None` only rejects exact duplicates.
+    pub similarity_threshold:   Option<f64>,
+    /// Batches kept for [`UndoLastBatch`]; 0 disables undo.
+    pub undo_depth:             usize,
+    /// Supports edges a learning outcome needs before coverage warnings
+    /// stop; 0 disables them.
+    pub min_supports:           usize,
+}
+
+impl Default for AdderConfig {
+    fn default() -> Self {
+        Self {
+            rationale_policy:       RationalePolicy::default(),
+            max_nodes:              None,
+            max_edges:              None,
+            namespace:              None,
+            id_seed:                None,
+            node_rules:             NodeRules::default(),
+            require_edge_rationale: true,
+            rationale_rules:        RationaleRules::default(),
+            atomic_threshold:       None,
+            similarity_threshold:   None,
+            undo_depth:             1,
+            min_supports:           1,
+        }
+    }
+}
 
 /// Primary mutator actor that validates and applies graph updates.
-#[derive(Debug, Actor)]
+#[derive(Debug)]
 pub struct GraphAdder {
-    store:        GraphStore,
-    event_sender: Option<UnboundedSender<Event>>,
+    store:              GraphStore,
+    event_sender:       Option<UnboundedSender<Event>>,
+    config:             AdderConfig,
+    capacity_warned:    HashSet<CapacityResource>,
+    generator_stats:    BTreeMap<String, GeneratorStats>,
+    rejection_stats:    RejectionReport,
+    read_view:          ReadHandle,
+    messages_processed: u64,
+    last_batch_latency: Option<Duration>,
+    mailbox_capacity:   Option<usize>,
+    id_rng:             Option<StdRng>,
+    /// Events held back while an atomic batch is staged.
+    staged_events:      Option<Vec<Event>>,
+    /// Bumped whenever a new read view is published.
+    revision:           u64,
+    batch_history:      VecDeque<AppliedBatch>,
+    /// The revision the last undo published and the earlier revision whose
+    /// graph it restored.
+    undone_to:          Option<(u64, u64)>,
+    /// Where every decided proposal is recorded, when enabled.
+    audit_log:          Option<AuditLog>,
+}
+
+impl Actor for GraphAdder {
+    type Args = Self;
+    type Error = Infallible;
+
+    fn name() -> &'static str {
+        "GraphAdder"
+    }
+
+    async fn on_start(state: Self::Args, _actor_ref: ActorRef<Self>) -> Result<Self, Self::Error> {
+        Ok(state)
+    }
+
+    async fn on_stop(
+        &mut self,
+        _actor_ref: WeakActorRef<Self>,
+        _reason: ActorStopReason,
+    ) -> Result<(), Self::Error> {
+        if let Some(log) = self.audit_log.as_mut()
+            && let Err(err) = log.flush()
+        {
+            warn!(error = %err, "audit.flush_failed");
+        }
+        Ok(())
+    }
 }
 
 impl GraphAdder {
@@ -49,124 +447,207 @@ impl GraphAdder {
         store: GraphStore,
         event_sender: Option<UnboundedSender<Event>>,
     ) -> Self {
+        let read_view =
+            ReadHandle::new(GraphReadView::from_store(&store, AdderConfig::default().min_supports));
         Self {
             store,
             event_sender,
+            config: AdderConfig::default(),
+            capacity_warned: HashSet::new(),
+            generator_stats: BTreeMap::new(),
+            rejection_stats: RejectionReport::default(),
+            read_view,
+            messages_processed: 0,
+            last_batch_latency: None,
+            mailbox_capacity: None,
+            id_rng: None,
+            staged_events: None,
+            revision: 0,
+            batch_history: VecDeque::new(),
+            undone_to: None,
+            audit_log: None,
         }
     }
 
-    fn handle_add_nodes(&mut self, proposals: Vec<NodeProposal>) -> Vec<Decision> {
-        let mut decisions = Vec::with_capacity(proposals.len());
-        let mut batch_seen = HashSet::new();
+    /// Spawn with a mailbox holding at most `capacity` pending messages.
+    ///
+    /// Callers that must not wait for room use `try_send`, which fails with
+    /// `SendError::MailboxFull` once the bound is reached.
+    pub fn spawn_bounded(mut self, capacity: usize) -> ActorRef<Self> {
+        self.mailbox_capacity = Some(capacity);
+        Self::spawn_with_mailbox(self, mailbox::bounded(capacity))
+    }
 
-        for proposal in proposals {
-            let decision = self.validate_and_add_node(proposal, &mut batch_seen);
-            decisions.push(decision);
+    fn health(&self) -> HealthReport {
+        HealthReport {
+            messages_processed: self.messages_processed,
+            last_batch_latency: self.last_batch_latency,
+            mailbox_capacity:   self.mailbox_capacity,
+            nodes:              self.store.node_count(),
+            edges:              self.store.edge_count(),
         }
+    }
 
-        decisions
+    /// Handle for lock-free reads of the last completed batch.
+    pub fn read_handle(&self) -> ReadHandle {
+        self.read_view.clone()
     }
 
-    fn validate_and_add_node(
-        &mut self,
-        proposal: NodeProposal,
-        batch_seen: &mut HashSet<String>,
-    ) -> Decision {
-        let NodeProposal {
-            kind,
-            granularity,
-            level,
-            text,
-            tags,
-        } = proposal;
+    /// Rebuild the read view; called once per mutation batch.
+    ///
+    /// The replaced view releases its copies of removed or renamed texts
+    /// here, so this is also where the interner is purged.
+    fn publish_read_view(&mut self) {
+        self.revision += 1;
+        self.read_view
+            .publish(GraphReadView::from_store(&self.store, self.config.min_supports));
+        self.store.purge_unused_text();
+    }
 
-        if granularity != Granularity::Sentence {
-            let reason = "granularity must be sentence";
-            warn!(reason = reason, "node.rejected");
-            self.emit_event(Event::NodeRejected {
-                text,
-                reason: reason.to_string(),
-            });
-            return Decision::rejected(reason);
-        }
+    /// Replace the validation configuration.
+    pub fn with_config(mut self, config: AdderConfig) -> Self {
+        self.id_rng = config.id_seed.map(StdRng::seed_from_u64);
+        self.config = config;
+        self.read_view
+            .publish(GraphReadView::from_store(&self.store, self.config.min_supports));
+        self
+    }
 
-        if level > MAX_NODE_LEVEL {
-            let reason = "level must be between 0 and 3";
-            warn!(reason = reason, "node.rejected");
-            self.emit_event(Event::NodeRejected {
-                text,
-                reason: reason.to_string(),
-            });
-            return Decision::rejected(reason);
+    /// Record every decided proposal in `log`.
+    pub fn with_audit_log(mut self, log: AuditLog) -> Self {
+        self.audit_log = Some(log);
+        self
+    }
+
+    /// Append a batch's proposals and final decisions to the audit log; a
+    /// failed write is logged and the batch stands.
+    fn write_audit<'a>(
+        &mut self,
+        proposals: Option<Vec<AuditProposal>>,
+        decisions: impl IntoIterator<Item = &'a Decision>,
+    ) {
+        let (Some(log), Some(proposals)) = (self.audit_log.as_mut(), proposals) else {
+            return;
+        };
+        if let Err(err) = log.write_batch(proposals, decisions) {
+            warn!(error = %err, "audit.write_failed");
         }
+    }
 
-        let cleaned_text = clean_text(&text);
-        if cleaned_text.is_empty() {
-            let reason = "node text is empty after trimming";
-            warn!(reason = reason, "node.rejected");
-            self.emit_event(Event::NodeRejected {
-                text,
-                reason: reason.to_string(),
-            });
-            return Decision::rejected(reason);
+    /// A seeded sequence restarts when a saved graph is resumed, so ids the
+    /// store already holds are skipped.
+    fn next_node_id(&mut self) -> Uuid {
+        loop {
+            let id = next_node_id(self.id_rng.as_mut());
+            if self.store.find_by_id(&id).is_none() {
+                return id;
+            }
         }
+    }
 
-        if !Self::is_single_sentence(&cleaned_text) {
-            let reason = "node text must be a single sentence";
-            warn!(reason = reason, "node.rejected");
-            self.emit_event(Event::NodeRejected {
-                text:   cleaned_text,
-                reason: reason.to_string(),
-            });
-            return Decision::rejected(reason);
+    fn handle_add_nodes(&mut self, proposals: Vec<NodeProposal>) -> Vec<Decision> {
+        let audited = self
+            .audit_log
+            .is_some()
+            .then(|| proposals.iter().cloned().map(AuditProposal::Node).collect());
+        let decisions = self.add_nodes(proposals);
+        self.finish_batch(&decisions, &[]);
+        self.write_audit(audited, &decisions);
+        decisions
+    }
+
+    fn add_nodes(&mut self, proposals: Vec<NodeProposal>) -> Vec<Decision> {
+        let mut decisions = Vec::with_capacity(proposals.len());
+        let mut batch = BatchState::default();
+        self.emit_event(Event::BatchStarted {
+            kind: BatchKind::Nodes,
+            size: proposals.len(),
+        });
+
+        for (index, mut proposal) in proposals.into_iter().enumerate() {
+            if proposal.namespace.is_none() {
+                proposal.namespace = self.config.namespace.clone();
+            }
+            let generator = GeneratorInfo::label(proposal.generator.as_ref());
+            let subject = ProposalSubject::from(&proposal);
+            let decision = self
+                .validate_and_add_node(proposal, &mut batch)
+                .with_context(index, subject);
+            self.generator_entry(generator).nodes.record(&decision);
+            self.rejection_stats.record_node(&decision);
+            decisions.push(decision);
         }
+        self.emit_batch_completed(&decisions);
+        decisions
+    }
 
-        if matches!(kind, NodeKind::LearningOutcome) {
-            let lowered = cleaned_text.to_lowercase();
-            if !(lowered.starts_with("i can ") || lowered.starts_with("students can ")) {
-                let reason = "learning outcomes must start with 'I can' or 'Students can'";
-                warn!(reason = reason, "node.rejected");
+    fn validate_and_add_node(
+        &mut self,
+        proposal: NodeProposal,
+        batch: &mut BatchState,
+    ) -> Decision {
+        let raw_text = proposal.text.clone();
+        let valid = match validate_node(proposal, &self.config.node_rules, batch, &self.store) {
+            Ok(valid) => valid,
+            Err(rejection) => {
+                warn!(reason = %rejection.reason, "node.rejected");
+                let cleaned = clean_text(&raw_text);
                 self.emit_event(Event::NodeRejected {
-                    text:   cleaned_text,
-                    reason: reason.to_string(),
+                    text:   if cleaned.is_empty() {
+                        raw_text
+                    } else {
+                        cleaned
+                    },
+                    reason: rejection.reason.clone(),
                 });
-                return Decision::rejected(reason);
+                return rejection.into();
             }
-        }
-
-        if !batch_seen.insert(normalize_text(&cleaned_text)) {
-            let reason = "duplicate node within batch";
-            warn!(reason = reason, "node.rejected");
+        };
+        if let Some(threshold) = self.config.similarity_threshold
+            && let Err(rejection) = check_similarity(&valid, threshold, &self.store)
+        {
+            warn!(reason = %rejection.reason, "node.rejected");
             self.emit_event(Event::NodeRejected {
-                text:   cleaned_text,
-                reason: reason.to_string(),
+                text:   valid.text,
+                reason: rejection.reason.clone(),
             });
-            return Decision::rejected(reason);
+            return rejection.into();
         }
 
-        if self.store.find_by_text(&cleaned_text).is_some() {
-            let reason = "duplicate node already present";
-            warn!(reason = reason, "node.rejected");
+        if let Some(reason) = self.capacity_exceeded(CapacityResource::Nodes) {
+            warn!(reason = %reason, "node.rejected");
             self.emit_event(Event::NodeRejected {
-                text:   cleaned_text,
-                reason: reason.to_string(),
+                text:   valid.text,
+                reason: reason.clone(),
             });
             return Decision::rejected(reason);
         }
 
-        let tags = Self::sanitize_tags(tags);
-
-        let node = Node {
-            id: Uuid::new_v4(),
-            kind: kind.clone(),
-            granularity,
-            level,
-            text: cleaned_text.clone(),
-            tags: tags.clone(),
-        };
+        let warning = valid.warning.clone();
+        let dropped_tags = valid.dropped_tags.clone();
+        let decision =
+            Decision::accepted(Some(self.insert_node(valid))).with_dropped_tags(dropped_tags);
+        match warning {
+            Some(warning) => {
+                warn!(warning = %warning, "node.tags_dropped");
+                decision.with_warning(warning)
+            }
+            None => decision,
+        }
+    }
 
-        let node_id = node.id;
-        self.store.add_node(node);
+    fn insert_node(&mut self, mut valid: ValidNode) -> Uuid {
+        let id = self.next_node_id();
+        let dropped_tags = std::mem::take(&mut valid.dropped_tags);
+        let index = self.store.add_node(valid.into_node(id));
+        let node = &self.store.graph()[index];
+        let (node_id, kind, level, tags, text) = (
+            node.id,
+            node.kind.clone(),
+            node.level,
+            node.tags.clone(),
+            Arc::clone(&node.text),
+        );
 
         info!(node_id = %node_id, kind = ?kind, level, "node.accepted");
         self.emit_event(Event::NodeAccepted {
@@ -174,174 +655,687 @@ impl GraphAdder {
             kind,
             level,
             tags,
-            text: cleaned_text,
+            text,
+            dropped_tags,
         });
 
-        Decision::accepted(Some(node_id))
+        node_id
     }
 
-    fn handle_add_edges(&mut self, proposals: Vec<EdgeProposal>) -> Vec<Decision> {
-        let mut decisions = Vec::with_capacity(proposals.len());
-        let mut batch_seen: HashSet<(Uuid, Uuid, Relation)> = HashSet::new();
+    fn handle_split_node(&mut self, msg: SplitNode) -> SplitOutcome {
+        let rejected = |reason: String, replacements| {
+            warn!(reason = %reason, "node.split_rejected");
+            SplitOutcome {
+                decision: Decision::rejected(reason),
+                ids: Vec::new(),
+                replacements,
+                edges: Vec::new(),
+            }
+        };
 
-        for proposal in proposals {
-            let decision = self.validate_and_add_edge(proposal, &mut batch_seen);
-            decisions.push(decision);
+        let Some(index) = self.store.find_by_id(&msg.node_id) else {
+            return rejected(format!("unknown node {}", msg.node_id), Vec::new());
+        };
+        let Some(original) = self.store.node(index).cloned() else {
+            return rejected("node missing from store".to_string(), Vec::new());
+        };
+        if msg.into.len() < 2 {
+            return rejected(
+                "a split needs at least two replacement texts".to_string(),
+                Vec::new(),
+            );
+        }
+        if let SplitEdges::Replacement(position) = msg.edges_to
+            && position >= msg.into.len()
+        {
+            return rejected(
+                format!("no replacement #{position} to re-point edges to"),
+                Vec::new(),
+            );
+        }
+        if let Some(limit) = self.config.max_nodes
+            && self.store.node_count() - 1 + msg.into.len() > limit
+        {
+            return rejected(capacity_reason(CapacityResource::Nodes, limit), Vec::new());
         }
 
-        decisions
-    }
+        let mut batch = BatchState::default();
+        let mut valid_nodes = Vec::new();
+        let mut replacements = Vec::new();
+        for text in msg.into {
+            let proposal = NodeProposal {
+                kind: original.kind.clone(),
+                granularity: original.granularity.clone(),
+                level: original.level,
+                text,
+                tags: original.tags.clone(),
+                generator: original.generator.clone(),
+                namespace: original.namespace.clone(),
+                topic: original.topic.clone(),
+            };
+            match validate_node(proposal, &self.config.node_rules, &mut batch, &self.store) {
+                Ok(valid) => {
+                    replacements.push(Decision::accepted(None));
+                    valid_nodes.push(valid);
+                }
+                Err(rejection) => replacements.push(rejection.into()),
+            }
+        }
+        if let Some(position) = replacements.iter().position(|d| !d.accepted) {
+            let reason = replacements[position].reason.clone().unwrap_or_default();
+            return rejected(format!("replacement #{position} rejected: {reason}"), replacements);
+        }
 
-    fn validate_and_add_edge(
-        &mut self,
-        proposal: EdgeProposal,
-        batch_seen: &mut HashSet<(Uuid, Uuid, Relation)>,
-    ) -> Decision {
-        let EdgeProposal {
-            relation,
-            from_id,
-            to_id,
-            rationale,
-        } = proposal;
+        let incident = self
+            .store
+            .remove_node(&original.id)
+            .map(|(_, edges)| edges)
+            .unwrap_or_default();
+        self.emit_event(Event::NodeRemoved {
+            id:     original.id,
+            reason: "split".to_string(),
+        });
 
-        let key = (from_id, to_id, relation.clone());
-        if !batch_seen.insert(key) {
-            let reason = "duplicate edge within batch";
-            warn!(reason = reason, relation = ?relation, "edge.rejected");
-            self.emit_event(Event::EdgeRejected {
-                relation,
-                reason: reason.to_string(),
-            });
-            return Decision::rejected(reason);
+        let mut replacement_ids = Vec::with_capacity(valid_nodes.len());
+        for (valid, decision) in valid_nodes.into_iter().zip(&mut replacements) {
+            let id = self.insert_node(valid);
+            decision.assigned_id = Some(id);
+            replacement_ids.push(id);
         }
 
-        let Some(from_index) = self.store.find_by_id(&from_id) else {
-            let reason = format!("unknown from_id {}", from_id);
-            warn!(relation = ?relation, from_id = %from_id, reason = %reason, "edge.rejected");
-            self.emit_event(Event::EdgeRejected {
-                relation,
-                reason: reason.clone(),
-            });
-            return Decision::rejected(reason);
+        let targets = match msg.edges_to {
+            SplitEdges::First => &replacement_ids[..1],
+            SplitEdges::All => &replacement_ids[..],
+            SplitEdges::Replacement(position) => &replacement_ids[position..=position],
         };
+        let mut edge_batch = BatchState::default();
+        let mut edges = Vec::new();
+        for edge in incident {
+            for &target in targets {
+                let repoint = |id: Uuid| if id == original.id { target } else { id };
+                let proposal = EdgeProposal {
+                    relation:  edge.relation.clone(),
+                    from_id:   repoint(edge.from),
+                    to_id:     repoint(edge.to),
+                    rationale: edge.rationale.clone(),
+                    generator: edge.generator.clone(),
+                };
+                edges.push(self.validate_and_add_edge(proposal, &mut edge_batch));
+            }
+        }
+        self.publish_read_view();
+
+        info!(node_id = %original.id, replacements = replacement_ids.len(), "node.split");
+        SplitOutcome {
+            decision: Decision::accepted(replacement_ids.first().copied()),
+            ids: replacement_ids,
+            replacements,
+            edges,
+        }
+    }
 
-        let Some(to_index) = self.store.find_by_id(&to_id) else {
-            let reason = format!("unknown to_id {}", to_id);
-            warn!(relation = ?relation, to_id = %to_id, reason = %reason, "edge.rejected");
-            self.emit_event(Event::EdgeRejected {
-                relation,
-                reason: reason.clone(),
-            });
-            return Decision::rejected(reason);
+    fn handle_merge_nodes(&mut self, msg: MergeNodes) -> MergeOutcome {
+        let MergeNodes { keep, remove } = msg;
+        let rejected = |reason: String| {
+            warn!(reason = %reason, "node.merge_rejected");
+            MergeOutcome {
+                decision: Decision::rejected(reason),
+                moved:    0,
+                dropped:  Vec::new(),
+            }
         };
+        if keep == remove {
+            return rejected("cannot merge a node into itself".to_string());
+        }
+        let node = |id: &Uuid| {
+            self.store
+                .find_by_id(id)
+                .and_then(|index| self.store.node(index))
+                .cloned()
+        };
+        let Some(kept) = node(&keep) else {
+            return rejected(format!("unknown node {keep}"));
+        };
+        let Some(removed) = node(&remove) else {
+            return rejected(format!("unknown node {remove}"));
+        };
+        if kept.kind != removed.kind {
+            return rejected(format!("cannot merge a {:?} into a {:?}", removed.kind, kept.kind));
+        }
 
-        if from_index == to_index {
-            let reason = "self-loops are not allowed";
-            warn!(relation = ?relation, reason = reason, "edge.rejected");
-            self.emit_event(Event::EdgeRejected {
-                relation,
-                reason: reason.to_string(),
+        let incident = self
+            .store
+            .remove_node(&remove)
+            .map(|(_, edges)| edges)
+            .unwrap_or_default();
+        self.emit_event(Event::NodeRemoved {
+            id:     remove,
+            reason: format!("merged into {keep}"),
+        });
+
+        let mut tags = kept.tags.clone().unwrap_or_default();
+        for tag in removed.tags.iter().flatten() {
+            if tags.len() < self.config.node_rules.max_tags_per_node && !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        let tags = (!tags.is_empty()).then_some(tags);
+        if tags != kept.tags {
+            let _ = self.store.update_node_tags(&keep, tags.clone());
+            self.emit_event(Event::NodeUpdated {
+                id: keep,
+                level: kept.level,
+                tags,
+                text: Arc::clone(&kept.text),
             });
-            return Decision::rejected(reason);
         }
 
-        if let Some(existing) = self.store.graph().find_edge(from_index, to_index)
-            && let Some(weight) = self.store.graph().edge_weight(existing)
-            && weight.relation == relation
-        {
-            let reason = "edge already exists";
-            warn!(relation = ?relation, reason = reason, "edge.rejected");
-            self.emit_event(Event::EdgeRejected {
-                relation,
-                reason: reason.to_string(),
-            });
-            return Decision::rejected(reason);
+        let mut batch = BatchState::default();
+        let mut moved = 0;
+        let mut dropped = Vec::new();
+        for edge in incident {
+            let repoint = |id: Uuid| if id == remove { keep } else { id };
+            let (from, to) = (repoint(edge.from), repoint(edge.to));
+            let proposal = EdgeProposal {
+                relation:  edge.relation.clone(),
+                from_id:   from,
+                to_id:     to,
+                rationale: edge.rationale,
+                generator: edge.generator,
+            };
+            let decision = self.validate_and_add_edge(proposal, &mut batch);
+            if decision.accepted {
+                moved += 1;
+            } else {
+                dropped.push(DroppedEdge {
+                    from,
+                    to,
+                    relation: edge.relation,
+                    reason: decision.reason.unwrap_or_default(),
+                });
+            }
         }
+        self.publish_read_view();
 
-        let Some(from_node) = self.store.node(from_index).cloned() else {
-            let reason = "from node missing from store";
-            warn!(relation = ?relation, reason = reason, "edge.rejected");
-            self.emit_event(Event::EdgeRejected {
-                relation,
-                reason: reason.to_string(),
-            });
+        info!(keep = %keep, remove = %remove, moved, dropped = dropped.len(), "node.merged");
+        MergeOutcome {
+            decision: Decision::accepted(Some(keep)),
+            moved,
+            dropped,
+        }
+    }
+
+    fn handle_pin_node(&mut self, msg: PinNode) -> Decision {
+        let Some(node) = self
+            .store
+            .find_by_id(&msg.node_id)
+            .and_then(|index| self.store.node_mut(index))
+        else {
+            let reason = format!("unknown node {}", msg.node_id);
+            warn!(reason = %reason, "node.pin_rejected");
             return Decision::rejected(reason);
         };
+        node.pinned = msg.pinned;
+        self.publish_read_view();
 
-        let Some(to_node) = self.store.node(to_index).cloned() else {
-            let reason = "to node missing from store";
-            warn!(relation = ?relation, reason = reason, "edge.rejected");
-            self.emit_event(Event::EdgeRejected {
-                relation,
-                reason: reason.to_string(),
-            });
+        info!(node_id = %msg.node_id, pinned = msg.pinned, "node.pinned");
+        Decision::accepted(Some(msg.node_id))
+    }
+
+    fn handle_pin_edge(&mut self, msg: PinEdge) -> Decision {
+        let (from, to) = msg.relation.canonical_endpoints(msg.from, msg.to);
+        let edge = match (self.store.find_by_id(&from), self.store.find_by_id(&to)) {
+            (Some(from_index), Some(to_index)) => {
+                self.store.find_edge(from_index, to_index, &msg.relation)
+            }
+            _ => None,
+        };
+        let Some(edge) = edge.and_then(|index| self.store.edge_weight_mut(index)) else {
+            let reason = format!("unknown edge {:?} {from} -> {to}", msg.relation);
+            warn!(reason = %reason, "edge.pin_rejected");
             return Decision::rejected(reason);
         };
+        edge.pinned = msg.pinned;
+        self.publish_read_view();
 
-        if matches!(relation, Relation::PrerequisiteFor)
-            && self.store.would_cycle_prereq(from_index, to_index)
-        {
-            let reason = "edge would introduce a prerequisite cycle";
-            warn!(relation = ?relation, reason = reason, "edge.rejected");
-            self.emit_event(Event::EdgeRejected {
-                relation,
-                reason: reason.to_string(),
+        info!(relation = ?msg.relation, from = %from, to = %to, pinned = msg.pinned, "edge.pinned");
+        Decision::accepted(None)
+    }
+
+    fn handle_clear_unpinned(&mut self) -> ClearReport {
+        let (nodes, edges) = self.store.clear_unpinned();
+        let report = ClearReport {
+            nodes_removed: nodes.len(),
+            edges_removed: edges.len(),
+        };
+
+        for edge in edges {
+            self.emit_event(Event::EdgeRemoved {
+                relation: edge.relation,
+                from:     edge.from,
+                to:       edge.to,
+                reason:   "unpinned".to_string(),
+            });
+        }
+        for node in nodes {
+            self.emit_event(Event::NodeRemoved {
+                id:     node.id,
+                reason: "unpinned".to_string(),
             });
-            return Decision::rejected(reason);
         }
+        self.publish_read_view();
+
+        info!(
+            nodes_removed = report.nodes_removed,
+            edges_removed = report.edges_removed,
+            "graph.cleared_unpinned"
+        );
+        report
+    }
 
-        if matches!(relation, Relation::PrerequisiteFor)
-            && !matches!(from_node.kind, NodeKind::Concept)
-        {
-            let reason = "prerequisite edges must originate from a concept";
-            warn!(relation = ?relation, reason = reason, "edge.rejected");
-            self.emit_event(Event::EdgeRejected {
-                relation,
-                reason: reason.to_string(),
+    fn handle_remove_nodes(&mut self, ids: Vec<Uuid>) -> Vec<Decision> {
+        let mut decisions = Vec::with_capacity(ids.len());
+        for id in ids {
+            let Ok((_, edges)) = self.store.remove_node(&id) else {
+                let reason = format!("unknown id {id}");
+                warn!(reason = %reason, "node.remove_rejected");
+                decisions.push(Decision::rejected(reason));
+                continue;
+            };
+            for edge in &edges {
+                self.emit_event(Event::EdgeRemoved {
+                    relation: edge.relation.clone(),
+                    from:     edge.from,
+                    to:       edge.to,
+                    reason:   "node removed".to_string(),
+                });
+            }
+            self.emit_event(Event::NodeRemoved {
+                id,
+                reason: "removed".to_string(),
             });
-            return Decision::rejected(reason);
+            info!(node_id = %id, edges_removed = edges.len(), "node.removed");
+            decisions.push(Decision::accepted(Some(id)));
         }
+        self.publish_read_view();
 
-        if matches!(relation, Relation::PrerequisiteFor)
-            && !matches!(to_node.kind, NodeKind::Concept | NodeKind::LearningOutcome)
-        {
-            let reason = "prerequisite edges must target a concept or learning outcome";
-            warn!(relation = ?relation, reason = reason, "edge.rejected");
-            self.emit_event(Event::EdgeRejected {
+        decisions
+    }
+
+    fn handle_update_node(&mut self, msg: UpdateNode) -> Decision {
+        let id = msg.id;
+        let rejected = |decision: Decision| {
+            warn!(node_id = %id, reason = ?decision.reason, "node.update_rejected");
+            decision
+        };
+        let Some(node) = self
+            .store
+            .find_by_id(&id)
+            .and_then(|index| self.store.node(index))
+        else {
+            return rejected(Decision::rejected(format!("unknown node {id}")));
+        };
+        let valid = match validate_node_update(node, msg, &self.config.node_rules, &self.store) {
+            Ok(valid) => valid,
+            Err(rejection) => return rejected(rejection.into()),
+        };
+        if let Some(level) = valid.level
+            && let Some((from_level, to_level)) = self.level_conflict(&id, level)
+        {
+            return rejected(
+                Decision::rejected("prerequisite edges must not decrease level").with_explanation(
+                    Explanation::LevelDecrease {
+                        from_level,
+                        to_level,
+                    },
+                ),
+            );
+        }
+
+        if let Some(text) = &valid.text
+            && let Err(err) = self.store.update_node_text(&id, text)
+        {
+            return rejected(Decision::rejected(err.to_string()));
+        }
+        if let Some(level) = valid.level
+            && let Some(node) = self
+                .store
+                .find_by_id(&id)
+                .and_then(|i| self.store.node_mut(i))
+        {
+            node.level = level;
+        }
+        if let Some(tags) = valid.tags {
+            let _ = self.store.update_node_tags(&id, tags);
+        }
+
+        if let Some(node) = self
+            .store
+            .find_by_id(&id)
+            .and_then(|index| self.store.node(index))
+        {
+            self.emit_event(Event::NodeUpdated {
+                id,
+                level: node.level,
+                tags: node.tags.clone(),
+                text: Arc::clone(&node.text),
+            });
+        }
+        self.publish_read_view();
+
+        info!(node_id = %id, "node.updated");
+        let decision = Decision::accepted(Some(id));
+        match valid.warning {
+            Some(warning) => decision.with_warning(warning),
+            None => decision,
+        }
+    }
+
+    /// Levels of the first prerequisite edge that moving `id` to `level`
+    /// would make decrease, as `(from_level, to_level)`.
+    fn level_conflict(&self, id: &Uuid, level: u8) -> Option<(u8, u8)> {
+        let before = self.store.prerequisites_of(id).into_iter();
+        let after = self.store.dependents_of(id).into_iter();
+        before
+            .map(|(node, _)| (node.level, level))
+            .chain(after.map(|(node, _)| (level, node.level)))
+            .find(|(from_level, to_level)| from_level > to_level)
+    }
+
+    fn handle_remove_edges(&mut self, removals: Vec<EdgeRemoval>) -> Vec<Decision> {
+        let mut decisions = Vec::with_capacity(removals.len());
+        for EdgeRemoval {
+            from_id,
+            to_id,
+            relation,
+        } in removals
+        {
+            let (from, to) = relation.canonical_endpoints(from_id, to_id);
+            if !self.store.remove_edge(&from, &to, relation.clone()) {
+                warn!(relation = ?relation, from = %from, to = %to, "edge.remove_rejected");
+                decisions.push(Decision::rejected("edge not found".to_string()));
+                continue;
+            }
+            info!(relation = ?relation, from = %from, to = %to, "edge.removed");
+            self.emit_event(Event::EdgeRemoved {
                 relation,
-                reason: reason.to_string(),
+                from,
+                to,
+                reason: "removed".to_string(),
             });
-            return Decision::rejected(reason);
+            decisions.push(Decision::accepted(None));
         }
+        self.publish_read_view();
 
-        if matches!(relation, Relation::PrerequisiteFor) && from_node.level > to_node.level {
-            let reason = "prerequisite edges must not decrease level";
-            warn!(relation = ?relation, reason = reason, "edge.rejected");
+        decisions
+    }
+
+    fn handle_add_edges(&mut self, proposals: Vec<EdgeProposal>) -> Vec<Decision> {
+        let audited = self
+            .audit_log
+            .is_some()
+            .then(|| proposals.iter().cloned().map(AuditProposal::Edge).collect());
+        let decisions = self.add_edges(proposals);
+        self.finish_batch(&[], &decisions);
+        self.warn_coverage();
+        self.write_audit(audited, &decisions);
+        decisions
+    }
+
+    /// Publish what the batch accepted and remember it for undo; a batch
+    /// that accepted nothing leaves the view and history alone.
+    fn finish_batch(&mut self, nodes: &[Decision], edges: &[Decision]) {
+        let nodes: Vec<Uuid> = nodes
+            .iter()
+            .filter(|decision| decision.accepted)
+            .filter_map(|decision| decision.assigned_id)
+            .collect();
+        let edges: Vec<EdgeRemoval> = edges
+            .iter()
+            .filter(|decision| decision.accepted)
+            .filter_map(|decision| match &decision.context.as_ref()?.subject {
+                ProposalSubject::Edge { from, to, relation } => {
+                    let (from_id, to_id) = relation.canonical_endpoints(*from, *to);
+                    Some(EdgeRemoval {
+                        from_id,
+                        to_id,
+                        relation: relation.clone(),
+                    })
+                }
+                ProposalSubject::Node { .. } => None,
+            })
+            .collect();
+        if nodes.is_empty() && edges.is_empty() {
+            return;
+        }
+        let before = self.graph_revision();
+        self.publish_read_view();
+        if self.config.undo_depth == 0 {
+            return;
+        }
+        if self.batch_history.len() == self.config.undo_depth {
+            self.batch_history.pop_front();
+        }
+        self.batch_history.push_back(AppliedBatch {
+            before,
+            after: self.revision,
+            nodes,
+            edges,
+        });
+    }
+
+    /// The revision whose graph is current: the one an undo restored while
+    /// nothing has changed since, otherwise the latest.
+    fn graph_revision(&self) -> u64 {
+        match self.undone_to {
+            Some((published, restored)) if published == self.revision => restored,
+            _ => self.revision,
+        }
+    }
+
+    fn handle_import_graph(&mut self, msg: ImportGraph) -> Result<MergeReport, ImportGraphError> {
+        let (nodes, edges) = (self.store.node_count(), self.store.edge_count());
+        if !msg.merge && nodes + edges > 0 {
+            return Err(ImportGraphError::NotEmpty { nodes, edges });
+        }
+        let imported =
+            GraphStore::from_snapshot(msg.snapshot).map_err(ImportGraphError::Invalid)?;
+        imported
+            .prerequisite_topo_order()
+            .map_err(ImportGraphError::Cycle)?;
+
+        let before = self.store.snapshot();
+        let known_nodes: HashSet<Uuid> = before.nodes.iter().map(|node| node.id).collect();
+        let known_edges: HashSet<(Uuid, Uuid, Relation)> = before
+            .edges
+            .into_iter()
+            .map(|edge| (edge.from, edge.to, edge.relation))
+            .collect();
+        let report = self.store.merge_from(&imported);
+
+        let after = self.store.snapshot();
+        for node in after.nodes {
+            if !known_nodes.contains(&node.id) {
+                self.emit_event(Event::NodeAccepted {
+                    id:           node.id,
+                    kind:         node.kind,
+                    level:        node.level,
+                    tags:         node.tags,
+                    text:         node.text,
+                    dropped_tags: Vec::new(),
+                });
+            }
+        }
+        for edge in after.edges {
+            if !known_edges.contains(&(edge.from, edge.to, edge.relation.clone())) {
+                self.emit_event(Event::EdgeAccepted {
+                    relation:  edge.relation,
+                    from:      edge.from,
+                    to:        edge.to,
+                    rationale: edge.rationale,
+                });
+            }
+        }
+        self.publish_read_view();
+        info!(
+            nodes_added = report.nodes_added,
+            edges_added = report.edges_added,
+            edges_skipped = report.edges_skipped.len(),
+            "graph.imported"
+        );
+        Ok(report)
+    }
+
+    fn handle_undo_last_batch(&mut self) -> Result<UndoReport, UndoError> {
+        let Some(last) = self.batch_history.pop_back() else {
+            return Err(UndoError::NothingToUndo);
+        };
+        let revision = self.graph_revision();
+        if last.after != revision {
+            warn!(batch_revision = last.after, revision, "batch.undo_rejected");
+            self.batch_history.push_back(last);
+            return Err(UndoError::GraphChanged);
+        }
+        let AppliedBatch {
+            before,
+            nodes,
+            edges,
+            ..
+        } = last;
+
+        let mut report = UndoReport::default();
+        for removal in edges {
+            if self
+                .store
+                .remove_edge(&removal.from_id, &removal.to_id, removal.relation.clone())
+            {
+                self.emit_event(Event::EdgeRemoved {
+                    relation: removal.relation.clone(),
+                    from:     removal.from_id,
+                    to:       removal.to_id,
+                    reason:   "undone".to_string(),
+                });
+                report.edges.push(removal);
+            }
+        }
+        for id in nodes {
+            let Ok((_, incident)) = self.store.remove_node(&id) else {
+                continue;
+            };
+            for edge in incident {
+                self.emit_event(Event::EdgeRemoved {
+                    relation: edge.relation.clone(),
+                    from:     edge.from,
+                    to:       edge.to,
+                    reason:   "node removed".to_string(),
+                });
+                report.edges.push(EdgeRemoval {
+                    from_id:  edge.from,
+                    to_id:    edge.to,
+                    relation: edge.relation,
+                });
+            }
+            self.emit_event(Event::NodeRemoved {
+                id,
+                reason: "undone".to_string(),
+            });
+            report.nodes.push(id);
+        }
+        self.publish_read_view();
+        // The graph is back to what the batch was applied to, so the batch
+        // before it can be undone next if nothing came in between.
+        self.undone_to = Some((self.revision, before));
+        info!(nodes = report.nodes.len(), edges = report.edges.len(), "batch.undone");
+        Ok(report)
+    }
+
+    fn add_edges(&mut self, proposals: Vec<EdgeProposal>) -> Vec<Decision> {
+        let mut decisions = Vec::with_capacity(proposals.len());
+        let mut batch = BatchState::default();
+        self.emit_event(Event::BatchStarted {
+            kind: BatchKind::Edges,
+            size: proposals.len(),
+        });
+
+        for (index, proposal) in proposals.into_iter().enumerate() {
+            let generator = GeneratorInfo::label(proposal.generator.as_ref());
+            let subject = ProposalSubject::from(&proposal);
+            let decision = self
+                .validate_and_add_edge(proposal, &mut batch)
+                .with_context(index, subject);
+            self.generator_entry(generator).edges.record(&decision);
+            self.rejection_stats.edges.record(&decision);
+            decisions.push(decision);
+        }
+        self.emit_batch_completed(&decisions);
+        decisions
+    }
+
+    fn validate_and_add_edge(
+        &mut self,
+        proposal: EdgeProposal,
+        batch: &mut BatchState,
+    ) -> Decision {
+        let relation = proposal.relation.clone();
+        let valid = match validate_edge(
+            proposal,
+            self.config.rationale_policy,
+            self.config.require_edge_rationale,
+            &self.config.rationale_rules,
+            batch,
+            &self.store,
+        ) {
+            Ok(valid) => valid,
+            Err(rejection) => {
+                warn!(relation = ?relation, reason = %rejection.reason, "edge.rejected");
+                self.emit_event(Event::EdgeRejected {
+                    relation,
+                    reason: rejection.reason.clone(),
+                });
+                return rejection.into();
+            }
+        };
+
+        if let Some(warning) = &valid.warning {
+            warn!(relation = ?relation, warning = %warning, "edge.rationale_unreferenced");
+        }
+
+        if let Some(reason) = self.capacity_exceeded(CapacityResource::Edges) {
+            warn!(relation = ?relation, reason = %reason, "edge.rejected");
             self.emit_event(Event::EdgeRejected {
                 relation,
-                reason: reason.to_string(),
+                reason: reason.clone(),
             });
             return Decision::rejected(reason);
         }
 
-        let rationale = rationale.trim();
-        if rationale.is_empty() {
-            let reason = "edge rationale missing";
+        let ValidEdge {
+            relation,
+            from: from_id,
+            to: to_id,
+            rationale,
+            generator,
+            warning,
+        } = valid;
+        let (Some(from_index), Some(to_index)) =
+            (self.store.find_by_id(&from_id), self.store.find_by_id(&to_id))
+        else {
+            let reason = "edge endpoint missing from store";
             warn!(relation = ?relation, reason = reason, "edge.rejected");
             self.emit_event(Event::EdgeRejected {
                 relation,
                 reason: reason.to_string(),
             });
             return Decision::rejected(reason);
-        }
-
-        let rationale = rationale.to_string();
+        };
 
         let edge = Edge {
-            from:      from_index,
-            to:        to_index,
-            relation:  relation.clone(),
+            from: from_index,
+            to: to_index,
+            relation: relation.clone(),
             rationale: rationale.clone(),
+            generator,
+            pinned: false,
         };
 
         self.store.add_edge(edge);
@@ -355,66 +1349,260 @@ impl GraphAdder {
             rationale,
         });
 
-        Decision::accepted(None)
+        match warning {
+            Some(warning) => Decision::accepted(None).with_warning(warning),
+            None => Decision::accepted(None),
+        }
+    }
+
+    fn generator_entry(&mut self, generator: String) -> &mut GeneratorStats {
+        self.generator_stats
+            .entry(generator.clone())
+            .or_insert_with(|| GeneratorStats {
+                generator,
+                ..GeneratorStats::default()
+            })
     }
 
     fn summarize(&self) -> Summary {
-        Summary::from_store(&self.store)
+        let mut summary = self.read_view.load().summary.clone();
+        summary.remaining_node_capacity = self
+            .config
+            .max_nodes
+            .map(|limit| limit.saturating_sub(self.store.node_count()));
+        summary.remaining_edge_capacity = self
+            .config
+            .max_edges
+            .map(|limit| limit.saturating_sub(self.store.edge_count()));
+        summary.generators = self.generator_stats.values().cloned().collect();
+        summary.check_coverage(&self.store, self.config.min_supports);
+        summary
     }
 
-    fn sanitize_tags(tags: Option<Vec<String>>) -> Option<Vec<String>> {
-        let mut result = Vec::new();
-        let tags = tags?;
+    /// Emit a coverage warning for every learning outcome below the
+    /// supports minimum.
+    fn warn_coverage(&mut self) {
+        let minimum = self.config.min_supports;
+        let under_supported: Vec<(Uuid, Arc<str>, usize)> = self
+            .store
+            .under_supported(minimum)
+            .into_iter()
+            .map(|(node, supports)| (node.id, Arc::clone(&node.text), supports))
+            .collect();
+        for (id, text, supports) in under_supported {
+            warn!(node_id = %id, supports, minimum, "graph.coverage_low");
+            self.emit_event(Event::CoverageWarning {
+                id,
+                text,
+                supports,
+                minimum,
+            });
+        }
+    }
 
-        for tag in tags {
-            if result.len() == MAX_TAGS_PER_NODE {
-                break;
-            }
-            let normalized = tag.trim().to_lowercase();
-            if normalized.is_empty() {
-                continue;
-            }
-            if !ALLOWED_TAGS.contains(&normalized.as_str()) {
-                continue;
-            }
-            if !result
-                .iter()
-                .any(|existing: &String| existing == &normalized)
-            {
-                result.push(normalized);
-            }
+    /// Returns the CapacityExceeded rejection reason when `resource` is full.
+    ///
+    /// The first time a cap is hit a CapacityReached warning event is emitted.
+    fn capacity_exceeded(&mut self, resource: CapacityResource) -> Option<String> {
+        let (limit, current) = match resource {
+            CapacityResource::Nodes => (self.config.max_nodes?, self.store.node_count()),
+            CapacityResource::Edges => (self.config.max_edges?, self.store.edge_count()),
+        };
+        if current < limit {
+            return None;
         }
 
-        if result.is_empty() {
-            None
-        } else {
-            Some(result)
+        if self.capacity_warned.insert(resource) {
+            warn!(resource = ?resource, limit, "graph.capacity_reached");
+            self.emit_event(Event::CapacityReached { resource, limit });
         }
+
+        Some(capacity_reason(resource, limit))
+    }
+
+    fn node_by_id(&self, id: &Uuid) -> Option<Node> {
+        self.store
+            .find_by_id(id)
+            .and_then(|index| self.store.node(index))
+            .cloned()
+    }
+
+    fn find_nodes(&self, query: &FindNodes) -> Vec<Node> {
+        let mut candidates: Vec<&Node> = if query.tags_any.is_empty() {
+            self.store
+                .node_indices()
+                .filter_map(|index| self.store.node(index))
+                .collect()
+        } else {
+            let mut seen = HashSet::new();
+            query
+                .tags_any
+                .iter()
+                .flat_map(|tag| self.store.find_by_tag(&tag.trim().to_lowercase()))
+                .filter(|node| seen.insert(node.id))
+                .collect()
+        };
+        let needle = query.text_contains.as_deref().map(normalize_text);
+        candidates.retain(|node| {
+            query.kind.as_ref().is_none_or(|kind| node.kind == *kind)
+                && query.level.is_none_or(|level| node.level == level)
+                && needle
+                    .as_deref()
+                    .is_none_or(|needle| normalize_text(&node.text).contains(needle))
+        });
+        candidates.sort_by_cached_key(|node| (node.level, normalize_text(&node.text), node.id));
+        candidates
+            .into_iter()
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
     }
 
-    fn is_single_sentence(text: &str) -> bool {
-        let trimmed = text.trim();
-        if trimmed.is_empty() {
-            return false;
+    fn edges_of(&self, id: &Uuid) -> Vec<IncidentEdge> {
+        let Some(index) = self.store.find_by_id(id) else {
+            return Vec::new();
+        };
+        self.store
+            .incident_edges(index)
+            .into_iter()
+            .filter_map(|edge| {
+                let (direction, other_id) = if edge.from == *id {
+                    (EdgeDirection::Outgoing, edge.to)
+                } else {
+                    (EdgeDirection::Incoming, edge.from)
+                };
+                let other = self.store.node(self.store.find_by_id(&other_id)?)?;
+                Some(IncidentEdge {
+                    relation: edge.relation,
+                    direction,
+                    other_id,
+                    other_text: Arc::clone(&other.text),
+                    rationale: edge.rationale,
+                })
+            })
+            .collect()
+    }
+
+    fn handle_apply_batch(&mut self, msg: ApplyBatch) -> BatchOutcome {
+        let audited = self.audit_log.is_some().then(|| {
+            let nodes = msg.nodes.iter().cloned().map(AuditProposal::Node);
+            nodes
+                .chain(msg.edges.iter().cloned().map(AuditProposal::Edge))
+                .collect()
+        });
+        let outcome = self.apply_batch(msg);
+        self.write_audit(audited, outcome.nodes.iter().chain(&outcome.edges));
+        outcome
+    }
+
+    fn apply_batch(&mut self, msg: ApplyBatch) -> BatchOutcome {
+        if !msg.atomic {
+            let nodes = self.add_nodes(msg.nodes);
+            let edges = self.add_edges(msg.edges);
+            self.finish_batch(&nodes, &edges);
+            self.warn_coverage();
+            return BatchOutcome {
+                committed: true,
+                nodes,
+                edges,
+            };
         }
 
-        let mut ender_count = 0;
-        for ch in trimmed.chars() {
-            if matches!(ch, '.' | '!' | '?') {
-                ender_count += 1;
+        let store = self.store.clone();
+        let generator_stats = self.generator_stats.clone();
+        let rejection_stats = self.rejection_stats.clone();
+        let capacity_warned = self.capacity_warned.clone();
+        let id_rng = self.id_rng.clone();
+        self.staged_events = Some(Vec::new());
+        let mut nodes = self.add_nodes(msg.nodes);
+        let mut edges = self.add_edges(msg.edges);
+        let staged = self.staged_events.take().unwrap_or_default();
+
+        let total = nodes.len() + edges.len();
+        let accepted = nodes.iter().chain(&edges).filter(|d| d.accepted).count();
+        let committed = match self.config.atomic_threshold {
+            _ if total == 0 => true,
+            Some(threshold) => accepted as f64 / total as f64 >= threshold,
+            None => accepted == total,
+        };
+        if committed {
+            for event in staged {
+                self.emit_event(event);
+            }
+            self.finish_batch(&nodes, &edges);
+            self.warn_coverage();
+            info!(accepted, total, "batch.committed");
+        } else {
+            self.store = store;
+            self.generator_stats = generator_stats;
+            self.capacity_warned = capacity_warned;
+            self.id_rng = id_rng;
+            let reason = format!("batch rolled back: {accepted} of {total} proposals accepted");
+            for decision in nodes.iter_mut().chain(&mut edges) {
+                if decision.accepted {
+                    decision.accepted = false;
+                    decision.assigned_id = None;
+                    decision.reason = Some(reason.clone());
+                    decision.dropped_tags.clear();
+                }
+            }
+            self.rejection_stats = rejection_stats;
+            for decision in &nodes {
+                self.rejection_stats.record_node(decision);
             }
+            for decision in &edges {
+                self.rejection_stats.edges.record(decision);
+            }
+            warn!(accepted, total, "batch.rolled_back");
+        }
+        BatchOutcome {
+            committed,
+            nodes,
+            edges,
         }
+    }
 
-        matches!(trimmed.chars().last(), Some('.') | Some('!') | Some('?')) && ender_count == 1
+    fn emit_batch_completed(&mut self, decisions: &[Decision]) {
+        let accepted = decisions
+            .iter()
+            .filter(|decision| decision.accepted)
+            .count();
+        self.emit_event(Event::BatchCompleted {
+            accepted,
+            rejected: decisions.len() - accepted,
+        });
     }
 
-    fn emit_event(&self, event: Event) {
-        if let Some(sender) = &self.event_sender {
+    fn emit_event(&mut self, event: Event) {
+        if let Some(staged) = &mut self.staged_events {
+            staged.push(event);
+        } else if let Some(sender) = &self.event_sender {
             let _ = sender.send(event);
         }
     }
 }
 
+/// Draw a node id from the seeded generator, or a random v4 id without one.
+pub(crate) fn next_node_id(rng: Option<&mut StdRng>) -> Uuid {
+    match rng {
+        Some(rng) => {
+            let mut bytes = [0; 16];
+            rng.fill_bytes(&mut bytes);
+            uuid::Builder::from_random_bytes(bytes).into_uuid()
+        }
+        None => Uuid::new_v4(),
+    }
+}
+
+/// Rejection reason for a proposal that would exceed a capacity cap.
+pub(crate) fn capacity_reason(resource: CapacityResource, limit: usize) -> String {
+    let noun = match resource {
+        CapacityResource::Nodes => "node",
+        CapacityResource::Edges => "edge",
+    };
+    format!("CapacityExceeded: {noun} limit of {limit} reached")
+}
+
 impl Message<AddNodes> for GraphAdder {
     type Reply = Vec<Decision>;
 
@@ -423,7 +1611,11 @@ impl Message<AddNodes> for GraphAdder {
         msg: AddNodes,
         _ctx: &mut Context<Self, Self::Reply>,
     ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        let _span = info_span!("adder.batch", kind = "nodes", size = msg.0.len()).entered();
+        let started = Instant::now();
         let decisions = self.handle_add_nodes(msg.0);
+        self.last_batch_latency = Some(started.elapsed());
         ready(decisions)
     }
 }
@@ -436,11 +1628,102 @@ impl Message<AddEdges> for GraphAdder {
         msg: AddEdges,
         _ctx: &mut Context<Self, Self::Reply>,
     ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        let _span = info_span!("adder.batch", kind = "edges", size = msg.0.len()).entered();
+        let started = Instant::now();
         let decisions = self.handle_add_edges(msg.0);
+        self.last_batch_latency = Some(started.elapsed());
         ready(decisions)
     }
 }
 
+impl Message<ApplyBatch> for GraphAdder {
+    type Reply = BatchOutcome;
+
+    fn handle(
+        &mut self,
+        msg: ApplyBatch,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        let size = msg.nodes.len() + msg.edges.len();
+        let _span = info_span!("adder.batch", kind = "mixed", size).entered();
+        let started = Instant::now();
+        let outcome = self.handle_apply_batch(msg);
+        self.last_batch_latency = Some(started.elapsed());
+        ready(outcome)
+    }
+}
+
+impl Message<GetNode> for GraphAdder {
+    type Reply = Option<Node>;
+
+    fn handle(
+        &mut self,
+        msg: GetNode,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.node_by_id(&msg.0))
+    }
+}
+
+impl Message<GetNodes> for GraphAdder {
+    type Reply = Vec<Option<Node>>;
+
+    fn handle(
+        &mut self,
+        msg: GetNodes,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(msg.0.iter().map(|id| self.node_by_id(id)).collect())
+    }
+}
+
+impl Message<FindNodes> for GraphAdder {
+    type Reply = Vec<Node>;
+
+    fn handle(
+        &mut self,
+        msg: FindNodes,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.find_nodes(&msg))
+    }
+}
+
+impl Message<RejectionStats> for GraphAdder {
+    type Reply = RejectionReport;
+
+    fn handle(
+        &mut self,
+        msg: RejectionStats,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(if msg.reset {
+            std::mem::take(&mut self.rejection_stats)
+        } else {
+            self.rejection_stats.clone()
+        })
+    }
+}
+
+impl Message<GetEdgesOf> for GraphAdder {
+    type Reply = Vec<IncidentEdge>;
+
+    fn handle(
+        &mut self,
+        msg: GetEdgesOf,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.edges_of(&msg.0))
+    }
+}
+
 impl Message<Inventory> for GraphAdder {
     type Reply = Vec<InventoryEntry>;
 
@@ -449,7 +1732,8 @@ impl Message<Inventory> for GraphAdder {
         _msg: Inventory,
         _ctx: &mut Context<Self, Self::Reply>,
     ) -> impl std::future::Future<Output = Self::Reply> + Send {
-        ready(self.store.inventory())
+        self.messages_processed += 1;
+        ready(self.read_view.load().inventory.clone())
     }
 }
 
@@ -461,6 +1745,7 @@ impl Message<Summarize> for GraphAdder {
         _msg: Summarize,
         _ctx: &mut Context<Self, Self::Reply>,
     ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
         let summary = self.summarize();
 
         for TopLearningOutcome { id, text, supports } in &summary.top_learning_outcomes {
@@ -473,68 +1758,277 @@ impl Message<Summarize> for GraphAdder {
     }
 }
 
-impl Message<ExportDot> for GraphAdder {
-    type Reply = String;
+impl Message<PinNode> for GraphAdder {
+    type Reply = Decision;
 
     fn handle(
         &mut self,
-        _msg: ExportDot,
+        msg: PinNode,
         _ctx: &mut Context<Self, Self::Reply>,
     ) -> impl std::future::Future<Output = Self::Reply> + Send {
-        ready(self.store.export_dot())
+        self.messages_processed += 1;
+        ready(self.handle_pin_node(msg))
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use uuid::Uuid;
-
-    use super::*;
-    use crate::model::{Granularity, NodeKind};
+impl Message<PinEdge> for GraphAdder {
+    type Reply = Decision;
 
-    fn sample_concept(text: &str) -> NodeProposal {
-        NodeProposal {
-            kind:        NodeKind::Concept,
-            granularity: Granularity::Sentence,
-            level:       0,
-            text:        text.to_string(),
-            tags:        Some(vec!["tests".to_string()]),
-        }
+    fn handle(
+        &mut self,
+        msg: PinEdge,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.handle_pin_edge(msg))
     }
+}
 
-    #[test]
-    fn test_add_node_validation() {
-        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+impl Message<ClearUnpinned> for GraphAdder {
+    type Reply = ClearReport;
 
-        let proposals = vec![
-            sample_concept("Graphs model dependencies with directed edges."),
-            sample_concept("Graphs model dependencies with directed edges."),
-            NodeProposal {
-                kind:        NodeKind::LearningOutcome,
-                granularity: Granularity::Sentence,
-                level:       2,
-                text:        "Understand recursion across modules.".to_string(),
-                tags:        Some(vec!["purpose".to_string()]),
-            },
-            NodeProposal {
-                kind:        NodeKind::LearningOutcome,
-                granularity: Granularity::Sentence,
-                level:       2,
-                text:        "I can trace prerequisite chains in a learning network.".to_string(),
-                tags:        Some(vec!["implementation".to_string()]),
-            },
-        ];
+    fn handle(
+        &mut self,
+        _msg: ClearUnpinned,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.handle_clear_unpinned())
+    }
+}
 
-        let decisions = adder.handle_add_nodes(proposals);
+impl Message<RemoveNodes> for GraphAdder {
+    type Reply = Vec<Decision>;
 
-        assert!(decisions[0].accepted, "first node should be accepted");
-        assert!(!decisions[1].accepted, "duplicate node should be rejected");
-        assert!(!decisions[2].accepted, "learning outcome without prefix should be rejected");
-        assert!(decisions[3].accepted, "valid learning outcome should be accepted");
+    fn handle(
+        &mut self,
+        msg: RemoveNodes,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.handle_remove_nodes(msg.0))
     }
+}
 
-    #[test]
-    fn test_add_edge_cycle_rejected() {
+impl Message<UpdateNode> for GraphAdder {
+    type Reply = Decision;
+
+    fn handle(
+        &mut self,
+        msg: UpdateNode,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.handle_update_node(msg))
+    }
+}
+
+impl Message<RemoveEdges> for GraphAdder {
+    type Reply = Vec<Decision>;
+
+    fn handle(
+        &mut self,
+        msg: RemoveEdges,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.handle_remove_edges(msg.0))
+    }
+}
+
+impl Message<MergeNodes> for GraphAdder {
+    type Reply = MergeOutcome;
+
+    fn handle(
+        &mut self,
+        msg: MergeNodes,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.handle_merge_nodes(msg))
+    }
+}
+
+impl Message<SplitNode> for GraphAdder {
+    type Reply = SplitOutcome;
+
+    fn handle(
+        &mut self,
+        msg: SplitNode,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.handle_split_node(msg))
+    }
+}
+
+impl Message<Health> for GraphAdder {
+    type Reply = HealthReport;
+
+    fn handle(
+        &mut self,
+        _msg: Health,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.health())
+    }
+}
+
+impl Message<ExportBundle> for GraphAdder {
+    type Reply = GraphSnapshot;
+
+    fn handle(
+        &mut self,
+        _msg: ExportBundle,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.read_view.load().snapshot.clone())
+    }
+}
+
+impl Message<ExportJson> for GraphAdder {
+    type Reply = VersionedSnapshot;
+
+    fn handle(
+        &mut self,
+        _msg: ExportJson,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.read_view.load().snapshot.clone().into())
+    }
+}
+
+impl Message<ExportDotStyled> for GraphAdder {
+    type Reply = String;
+
+    fn handle(
+        &mut self,
+        msg: ExportDotStyled,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(render_dot_with(&self.read_view.load().snapshot, &msg.0))
+    }
+}
+
+impl Message<ImportGraph> for GraphAdder {
+    type Reply = Result<MergeReport, ImportGraphError>;
+
+    fn handle(
+        &mut self,
+        msg: ImportGraph,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.handle_import_graph(msg))
+    }
+}
+
+impl Message<UndoLastBatch> for GraphAdder {
+    type Reply = Result<UndoReport, UndoError>;
+
+    fn handle(
+        &mut self,
+        _msg: UndoLastBatch,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.handle_undo_last_batch())
+    }
+}
+
+impl Message<TopoOrder> for GraphAdder {
+    type Reply = Result<Vec<Uuid>, CycleInfo>;
+
+    fn handle(
+        &mut self,
+        _msg: TopoOrder,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        self.messages_processed += 1;
+        ready(self.store.prerequisite_topo_order())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        audit::AuditRecord,
+        export::render_dot,
+        model::{
+            Explanation, GeneratorInfo, Granularity, NodeKind, ProposalContext, ProposalSubject,
+            Relation,
+        },
+        summary::RejectionCategory,
+    };
+
+    fn sample_concept(text: &str) -> NodeProposal {
+        NodeProposal {
+            kind:        NodeKind::Concept,
+            granularity: Granularity::Sentence,
+            level:       0,
+            text:        text.to_string(),
+            tags:        Some(vec!["tests".to_string()]),
+            generator:   None,
+            namespace:   None,
+            topic:       None,
+        }
+    }
+
+    #[test]
+    fn test_add_node_validation() {
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+
+        let proposals = vec![
+            sample_concept("Graphs model dependencies with directed edges."),
+            sample_concept("Graphs model dependencies with directed edges."),
+            NodeProposal {
+                kind:        NodeKind::LearningOutcome,
+                granularity: Granularity::Sentence,
+                level:       2,
+                text:        "Understand recursion across modules.".to_string(),
+                tags:        Some(vec!["purpose".to_string()]),
+                generator:   None,
+                namespace:   None,
+                topic:       None,
+            },
+            NodeProposal {
+                kind:        NodeKind::LearningOutcome,
+                granularity: Granularity::Sentence,
+                level:       2,
+                text:        "I can trace prerequisite chains in a learning network.".to_string(),
+                tags:        Some(vec!["implementation".to_string()]),
+                generator:   None,
+                namespace:   None,
+                topic:       None,
+            },
+        ];
+
+        let decisions = adder.handle_add_nodes(proposals);
+
+        assert!(decisions[0].accepted, "first node should be accepted");
+        assert!(!decisions[1].accepted, "duplicate node should be rejected");
+        assert!(!decisions[2].accepted, "learning outcome without prefix should be rejected");
+        assert!(decisions[3].accepted, "valid learning outcome should be accepted");
+        assert_eq!(
+            decisions[1].context,
+            Some(ProposalContext {
+                index:   1,
+                subject: ProposalSubject::Node {
+                    text: "Graphs model dependencies with directed edges.".to_string(),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_add_edge_cycle_rejected() {
         let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
 
         let decisions = adder.handle_add_nodes(vec![
@@ -552,18 +2046,21 @@ mod tests {
                 from_id:   node_ids[0],
                 to_id:     node_ids[1],
                 rationale: "Concept A informs Concept B.".to_string(),
+                generator: None,
             },
             EdgeProposal {
                 relation:  Relation::PrerequisiteFor,
                 from_id:   node_ids[1],
                 to_id:     node_ids[2],
                 rationale: "Concept B prepares learners for Concept C.".to_string(),
+                generator: None,
             },
             EdgeProposal {
                 relation:  Relation::PrerequisiteFor,
                 from_id:   node_ids[2],
                 to_id:     node_ids[0],
                 rationale: "Concept C loops back to Concept A.".to_string(),
+                generator: None,
             },
         ];
 
@@ -571,6 +2068,1317 @@ mod tests {
         assert!(decisions[0].accepted);
         assert!(decisions[1].accepted);
         assert!(!decisions[2].accepted, "cycle-forming edge must be rejected");
+        let context = decisions[2].context.as_ref().expect("context");
+        assert_eq!(context.index, 2);
+        assert_eq!(
+            context.subject,
+            ProposalSubject::Edge {
+                from:     node_ids[2],
+                to:       node_ids[0],
+                relation: Relation::PrerequisiteFor,
+            }
+        );
+        let json = serde_json::to_value(&decisions[2]).expect("serialize");
+        assert_eq!(
+            (json["context"]["index"].as_u64(), json["context"]["proposal"].as_str()),
+            (Some(2), Some("edge"))
+        );
+
+        let Some(Explanation::CyclePath { path }) = &decisions[2].explanation else {
+            panic!("cycle rejection should carry the offending path");
+        };
+        let path_ids: Vec<Uuid> = path.iter().map(|node| node.id).collect();
+        assert_eq!(path_ids, node_ids, "path runs from the proposed target back to the source");
+        assert_eq!(path[0].text, "Concept A establishes foundational syntax.");
+    }
+
+    fn rationale_fixture(policy: RationalePolicy) -> (GraphAdder, Vec<Uuid>) {
+        let mut adder =
+            GraphAdder::with_event_sender(GraphStore::new(), None).with_config(AdderConfig {
+                rationale_policy: policy,
+                ..AdderConfig::default()
+            });
+        let decisions = adder.handle_add_nodes(vec![
+            sample_concept("A method signature names parameter types."),
+            sample_concept("Write tests before implementation."),
+        ]);
+        let ids = decisions.iter().filter_map(|d| d.assigned_id).collect();
+        (adder, ids)
+    }
+
+    fn stray_rationale_edge(ids: &[Uuid]) -> EdgeProposal {
+        EdgeProposal {
+            relation:  Relation::Supports,
+            from_id:   ids[0],
+            to_id:     ids[1],
+            rationale: "Recursion Base Cases underpin each method signature.".to_string(),
+            generator: None,
+        }
+    }
+
+    #[test]
+    fn test_rationale_mismatch_ignored_by_default() {
+        let (mut adder, ids) = rationale_fixture(RationalePolicy::default());
+
+        let decisions = adder.handle_add_edges(vec![stray_rationale_edge(&ids)]);
+
+        assert!(decisions[0].accepted);
+        assert!(decisions[0].warnings.is_empty(), "the default policy skips the check");
+    }
+
+    #[test]
+    fn test_rationale_mismatch_warns_under_warn_policy() {
+        let (mut adder, ids) = rationale_fixture(RationalePolicy::Warn);
+
+        let decisions = adder.handle_add_edges(vec![stray_rationale_edge(&ids)]);
+
+        assert!(decisions[0].accepted, "warn policy must not reject");
+        assert_eq!(decisions[0].warnings.len(), 1);
+        assert!(decisions[0].warnings[0].contains("Recursion Base Cases"));
+    }
+
+    #[test]
+    fn test_rationale_mismatch_rejected_when_strict() {
+        let (mut adder, ids) = rationale_fixture(RationalePolicy::Strict);
+
+        let decisions = adder.handle_add_edges(vec![
+            stray_rationale_edge(&ids),
+            EdgeProposal {
+                relation:  Relation::PrerequisiteFor,
+                from_id:   ids[0],
+                to_id:     ids[1],
+                rationale: "Knowing the method signature lets tests be written first.".to_string(),
+                generator: None,
+            },
+        ]);
+
+        assert!(!decisions[0].accepted, "strict policy must reject stray rationales");
+        assert!(
+            decisions[0]
+                .reason
+                .as_deref()
+                .is_some_and(|reason| reason.contains("to node"))
+        );
+        assert!(decisions[1].accepted, "rationale naming both endpoints passes");
+        assert!(decisions[1].warnings.is_empty());
+    }
+
+    #[test]
+    fn test_node_capacity_fills_in_batch_order() {
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), Some(event_tx))
+            .with_config(AdderConfig {
+                max_nodes: Some(10),
+                ..AdderConfig::default()
+            });
+
+        let proposals = (0..15)
+            .map(|i| sample_concept(&format!("Concept number {i} introduces a new idea.")))
+            .collect();
+        let decisions = adder.handle_add_nodes(proposals);
+
+        let accepted: Vec<bool> = decisions.iter().map(|d| d.accepted).collect();
+        assert_eq!(accepted, [vec![true; 10], vec![false; 5]].concat());
+        assert!(decisions[10..].iter().all(|d| {
+            d.reason
+                .as_deref()
+                .is_some_and(|reason| reason.starts_with("CapacityExceeded"))
+        }));
+        assert_eq!(adder.summarize().remaining_node_capacity, Some(0));
+
+        let mut capacity_events = 0;
+        while let Ok(event) = event_rx.try_recv() {
+            if matches!(event, Event::CapacityReached { .. }) {
+                capacity_events += 1;
+            }
+        }
+        assert_eq!(capacity_events, 1, "capacity warning is emitted once");
+    }
+
+    #[test]
+    fn test_batches_are_bracketed_by_lifecycle_events() {
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), Some(event_tx));
+
+        let decisions = adder.handle_add_nodes(vec![
+            sample_concept("Loops repeat a block of statements."),
+            sample_concept("Loops repeat a block of statements."),
+        ]);
+        adder.handle_add_edges(vec![EdgeProposal {
+            relation:  Relation::RelatedTo,
+            from_id:   decisions[0].assigned_id.unwrap(),
+            to_id:     Uuid::new_v4(),
+            rationale: "Loops relate to recursion.".to_string(),
+            generator: None,
+        }]);
+
+        let mut sequence = Vec::new();
+        while let Ok(event) = event_rx.try_recv() {
+            sequence.push(match event {
+                Event::BatchStarted { kind, size } => format!("start {kind} {size}"),
+                Event::BatchCompleted { accepted, rejected } => {
+                    format!("done {accepted}/{rejected}")
+                }
+                Event::NodeAccepted { .. } => "node accepted".to_string(),
+                Event::NodeRejected { .. } => "node rejected".to_string(),
+                Event::EdgeRejected { .. } => "edge rejected".to_string(),
+                other => format!("{other:?}"),
+            });
+        }
+        assert_eq!(
+            sequence,
+            [
+                "start nodes 2",
+                "node accepted",
+                "node rejected",
+                "done 1/1",
+                "start edges 1",
+                "edge rejected",
+                "done 0/1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_related_edges_deduplicate_symmetrically() {
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+        let ids: Vec<Uuid> = adder
+            .handle_add_nodes(vec![
+                sample_concept("Closures capture their environment."),
+                sample_concept("Iterators yield items lazily."),
+            ])
+            .iter()
+            .filter_map(|d| d.assigned_id)
+            .collect();
+        let related = |from: Uuid, to: Uuid| EdgeProposal {
+            relation:  Relation::RelatedTo,
+            from_id:   from,
+            to_id:     to,
+            rationale: "Closures often drive iterator adapters.".to_string(),
+            generator: None,
+        };
+
+        let decisions =
+            adder.handle_add_edges(vec![related(ids[1], ids[0]), related(ids[0], ids[1])]);
+        assert!(decisions[0].accepted);
+        assert_eq!(decisions[1].reason.as_deref(), Some("duplicate edge within batch"));
+
+        let later = adder.handle_add_edges(vec![related(ids[1], ids[0])]);
+        assert_eq!(later[0].reason.as_deref(), Some("edge already exists"));
+
+        let snapshot = adder.store.snapshot();
+        assert_eq!(snapshot.edges.len(), 1);
+        let edge = &snapshot.edges[0];
+        assert!(edge.from < edge.to, "endpoints are stored smaller UUID first");
+        assert_eq!(adder.summarize().related_edges, 1);
+        assert!(render_dot(&snapshot).contains("dir=none"));
+    }
+
+    #[test]
+    fn test_parallel_edges_each_reject_their_duplicate() {
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+        let ids: Vec<Uuid> = adder
+            .handle_add_nodes(vec![
+                sample_concept("Closures capture their environment."),
+                sample_concept("Iterators yield items lazily."),
+            ])
+            .iter()
+            .filter_map(|d| d.assigned_id)
+            .collect();
+        let edge = |relation: Relation| EdgeProposal {
+            relation,
+            from_id: ids[0],
+            to_id: ids[1],
+            rationale: "Closures often drive iterator adapters.".to_string(),
+            generator: None,
+        };
+
+        for relation in [Relation::PrerequisiteFor, Relation::Supports] {
+            assert!(adder.handle_add_edges(vec![edge(relation)])[0].accepted);
+        }
+        for relation in [Relation::PrerequisiteFor, Relation::Supports] {
+            let decisions = adder.handle_add_edges(vec![edge(relation.clone())]);
+            assert_eq!(decisions[0].reason.as_deref(), Some("edge already exists"), "{relation:?}");
+        }
+        assert_eq!(adder.store.edge_count(), 2);
+    }
+
+    #[test]
+    fn test_removed_parallel_edges_can_be_added_again() {
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+        let ids: Vec<Uuid> = adder
+            .handle_add_nodes(vec![
+                sample_concept("Closures capture their environment."),
+                sample_concept("Iterators yield items lazily."),
+            ])
+            .iter()
+            .filter_map(|d| d.assigned_id)
+            .collect();
+        let edge = |relation: Relation| EdgeProposal {
+            relation,
+            from_id: ids[0],
+            to_id: ids[1],
+            rationale: "Closures often drive iterator adapters.".to_string(),
+            generator: None,
+        };
+        let removal = |relation: Relation| EdgeRemoval {
+            from_id: ids[0],
+            to_id: ids[1],
+            relation,
+        };
+        let relations = [Relation::PrerequisiteFor, Relation::Supports];
+        adder.handle_add_edges(relations.clone().map(edge).to_vec());
+
+        for (removed, kept) in [(0, 1), (1, 0)] {
+            let decisions = adder.handle_remove_edges(vec![
+                removal(relations[removed].clone()),
+                removal(relations[removed].clone()),
+            ]);
+            assert!(decisions[0].accepted);
+            assert_eq!(decisions[1].reason.as_deref(), Some("edge not found"));
+            let snapshot = adder.store.snapshot();
+            assert_eq!(snapshot.edges.len(), 1);
+            assert_eq!(snapshot.edges[0].relation, relations[kept]);
+
+            let readded = adder.handle_add_edges(vec![edge(relations[removed].clone())]);
+            assert!(readded[0].accepted, "{:?}", relations[removed]);
+            assert_eq!(adder.store.edge_count(), 2);
+        }
+    }
+
+    fn split_fixture() -> (GraphAdder, Vec<Uuid>) {
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+        let ids: Vec<Uuid> = adder
+            .handle_add_nodes(vec![
+                sample_concept("Contracts name inputs and outputs."),
+                sample_concept("Students design contracts and write tests first."),
+                NodeProposal {
+                    level: 1,
+                    ..sample_concept("Examples expose edge cases.")
+                },
+            ])
+            .iter()
+            .filter_map(|d| d.assigned_id)
+            .collect();
+        let edge = |from: Uuid, to: Uuid| EdgeProposal {
+            relation:  Relation::PrerequisiteFor,
+            from_id:   from,
+            to_id:     to,
+            rationale: "Earlier design steps come first.".to_string(),
+            generator: None,
+        };
+        let decisions = adder.handle_add_edges(vec![edge(ids[0], ids[1]), edge(ids[1], ids[2])]);
+        assert!(decisions.iter().all(|d| d.accepted));
+        (adder, ids)
+    }
+
+    #[tokio::test]
+    async fn test_node_and_edge_queries_read_without_events() {
+        let (mut adder, ids) = split_fixture();
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        adder.event_sender = Some(event_tx);
+        let adder_ref = GraphAdder::spawn(adder);
+        let unknown = Uuid::new_v4();
+
+        let node = adder_ref.ask(GetNode(ids[1])).await.expect("node query");
+        assert_eq!(
+            node.map(|node| node.text),
+            Some("Students design contracts and write tests first.".into())
+        );
+        assert!(
+            adder_ref
+                .ask(GetNode(unknown))
+                .await
+                .expect("node query")
+                .is_none()
+        );
+        let nodes = adder_ref
+            .ask(GetNodes(vec![ids[2], unknown, ids[0]]))
+            .await
+            .expect("nodes query");
+        let found: Vec<_> = nodes
+            .iter()
+            .map(|node| node.as_ref().map(|n| n.id))
+            .collect();
+        assert_eq!(found, vec![Some(ids[2]), None, Some(ids[0])]);
+
+        let edges = adder_ref
+            .ask(GetEdgesOf(ids[1]))
+            .await
+            .expect("edges query");
+        let ends: Vec<_> = edges
+            .iter()
+            .map(|edge| (edge.direction, edge.other_id))
+            .collect();
+        assert_eq!(
+            ends,
+            vec![
+                (EdgeDirection::Outgoing, ids[2]),
+                (EdgeDirection::Incoming, ids[0])
+            ]
+        );
+        assert_eq!(&*edges[1].other_text, "Contracts name inputs and outputs.");
+        assert_eq!(edges[0].relation, Relation::PrerequisiteFor);
+        assert!(
+            adder_ref
+                .ask(GetEdgesOf(unknown))
+                .await
+                .expect("edges query")
+                .is_empty()
+        );
+        assert!(event_rx.try_recv().is_err(), "queries send no events");
+    }
+
+    #[tokio::test]
+    async fn test_rejection_stats_count_categories_until_reset() {
+        let (mut adder, ids) = split_fixture();
+        adder.handle_add_nodes(vec![
+            sample_concept("Examples expose edge cases."),
+            sample_concept("Contracts name inputs and outputs."),
+            sample_concept("Tests come first. Code comes second."),
+        ]);
+        let edge = |from: Uuid, to: Uuid| EdgeProposal {
+            relation:  Relation::Supports,
+            from_id:   from,
+            to_id:     to,
+            rationale: "Contracts shape the examples.".to_string(),
+            generator: None,
+        };
+        adder.handle_add_edges(vec![
+            edge(ids[0], Uuid::new_v4()),
+            edge(ids[1], Uuid::new_v4()),
+            edge(Uuid::new_v4(), ids[2]),
+        ]);
+        let adder_ref = GraphAdder::spawn(adder);
+
+        let stats = adder_ref
+            .ask(RejectionStats { reset: true })
+            .await
+            .expect("rejection stats");
+        assert_eq!((stats.nodes.accepted, stats.nodes.rejected), (3, 3));
+        assert_eq!(
+            stats.nodes.ranked(),
+            vec![
+                (RejectionCategory::DuplicateNode, 2),
+                (RejectionCategory::NotSingleSentence, 1)
+            ]
+        );
+        assert_eq!((stats.edges.accepted, stats.edges.rejected), (2, 3));
+        assert_eq!(
+            stats.edges.ranked(),
+            vec![
+                (RejectionCategory::UnknownToId, 2),
+                (RejectionCategory::UnknownFromId, 1)
+            ]
+        );
+
+        let after_reset = adder_ref
+            .ask(RejectionStats::default())
+            .await
+            .expect("rejection stats");
+        assert_eq!(after_reset, RejectionReport::default());
+    }
+
+    #[test]
+    fn test_undo_reverts_batches_up_to_the_configured_depth() {
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), Some(event_tx))
+            .with_config(AdderConfig {
+                undo_depth: 2,
+                ..AdderConfig::default()
+            });
+        let ids: Vec<Uuid> = adder
+            .handle_add_nodes(vec![
+                sample_concept("Lists hold items in order."),
+                sample_concept("Sorting reorders the items of a list."),
+            ])
+            .iter()
+            .filter_map(|d| d.assigned_id)
+            .collect();
+        let edges = adder.handle_add_edges(vec![EdgeProposal {
+            relation:  Relation::RelatedTo,
+            from_id:   ids[1],
+            to_id:     ids[0],
+            rationale: "Sorting rearranges lists.".to_string(),
+            generator: None,
+        }]);
+        assert!(edges[0].accepted);
+        while event_rx.try_recv().is_ok() {}
+
+        let report = adder.handle_undo_last_batch().expect("edge batch undone");
+        let (from_id, to_id) = Relation::RelatedTo.canonical_endpoints(ids[1], ids[0]);
+        assert_eq!(
+            report,
+            UndoReport {
+                nodes: Vec::new(),
+                edges: vec![EdgeRemoval {
+                    from_id,
+                    to_id,
+                    relation: Relation::RelatedTo,
+                }],
+            }
+        );
+        assert!(matches!(
+            event_rx.try_recv(),
+            Ok(Event::EdgeRemoved { reason, .. }) if reason == "undone"
+        ));
+
+        let report = adder.handle_undo_last_batch().expect("node batch undone");
+        assert_eq!(report.nodes, ids);
+        assert_eq!((adder.store.node_count(), adder.store.edge_count()), (0, 0));
+        assert_eq!(adder.handle_undo_last_batch(), Err(UndoError::NothingToUndo));
+
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+        adder.handle_add_nodes(vec![sample_concept("Lists hold items in order.")]);
+        adder.handle_add_nodes(vec![sample_concept("Sorting reorders the items of a list.")]);
+        assert!(adder.handle_undo_last_batch().is_ok());
+        assert_eq!(adder.handle_undo_last_batch(), Err(UndoError::NothingToUndo));
+        assert_eq!(adder.store.node_count(), 1, "only one batch is kept by default");
+    }
+
+    #[test]
+    fn test_undo_refuses_after_an_unrelated_mutation() {
+        let (mut adder, ids) = split_fixture();
+        let duplicate = adder.handle_add_nodes(vec![sample_concept("Examples expose edge cases.")]);
+        assert!(!duplicate[0].accepted);
+        assert_eq!(
+            adder.batch_history.back().map(|batch| batch.after),
+            Some(adder.revision),
+            "a batch that accepted nothing keeps the last one undoable"
+        );
+
+        adder.handle_remove_nodes(vec![ids[2]]);
+        assert_eq!(adder.handle_undo_last_batch(), Err(UndoError::GraphChanged));
+        assert_eq!((adder.store.node_count(), adder.store.edge_count()), (2, 1));
+    }
+
+    #[test]
+    fn test_undo_refuses_past_a_mutation_between_batches() {
+        let mut adder =
+            GraphAdder::with_event_sender(GraphStore::new(), None).with_config(AdderConfig {
+                undo_depth: 3,
+                ..AdderConfig::default()
+            });
+        let first = adder.handle_add_nodes(vec![sample_concept("Lists hold items in order.")]);
+        let id = first[0].assigned_id.expect("batch A accepted");
+        let updated = adder.handle_update_node(UpdateNode {
+            id,
+            text: Some("Lists keep items in order.".to_string()),
+            level: None,
+            tags: None,
+        });
+        assert!(updated.accepted);
+        adder.handle_add_nodes(vec![sample_concept("Sorting reorders the items of a list.")]);
+
+        assert!(adder.handle_undo_last_batch().is_ok(), "batch B is the latest change");
+        assert_eq!(adder.handle_undo_last_batch(), Err(UndoError::GraphChanged));
+        assert_eq!(adder.store.node_count(), 1);
+
+        let mut adder =
+            GraphAdder::with_event_sender(GraphStore::new(), None).with_config(AdderConfig {
+                undo_depth: 3,
+                ..AdderConfig::default()
+            });
+        adder.handle_add_nodes(vec![sample_concept("Lists hold items in order.")]);
+        adder.handle_add_nodes(vec![sample_concept("Sorting reorders the items of a list.")]);
+        assert!(adder.handle_undo_last_batch().is_ok());
+        adder.handle_add_nodes(vec![sample_concept("Searching finds an item in a list.")]);
+        assert!(adder.handle_undo_last_batch().is_ok());
+        assert!(adder.handle_undo_last_batch().is_ok(), "A is undoable once B and C are undone");
+        assert_eq!(adder.store.node_count(), 0);
+    }
+
+    #[test]
+    fn test_find_nodes_combines_filters_and_limit() {
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+        let at = |level: u8, text: &str, tags: &[&str]| NodeProposal {
+            level,
+            tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+            ..sample_concept(text)
+        };
+        let decisions = adder.handle_add_nodes(vec![
+            at(2, "Recursion  needs a base case.", &["tests"]),
+            at(2, "Tail RECURSION reuses the frame.", &["implementation", "tests"]),
+            at(2, "Recursion mirrors induction.", &["purpose"]),
+            at(1, "Recursion appears early in tracing.", &["tests"]),
+            at(2, "Loops repeat statements.", &["tests"]),
+            NodeProposal {
+                kind: NodeKind::LearningOutcome,
+                ..at(2, "I can test a recursive function.", &["tests"])
+            },
+        ]);
+        assert!(decisions.iter().all(|d| d.accepted));
+        let texts = |nodes: Vec<Node>| -> Vec<String> {
+            nodes.iter().map(|node| node.text.to_string()).collect()
+        };
+
+        let query = FindNodes {
+            kind:          Some(NodeKind::Concept),
+            level:         Some(2),
+            tags_any:      vec!["Tests".to_string()],
+            text_contains: Some("recursion".to_string()),
+            limit:         None,
+        };
+        assert_eq!(
+            texts(adder.find_nodes(&query)),
+            vec![
+                "Recursion needs a base case.",
+                "Tail RECURSION reuses the frame."
+            ]
+        );
+        let limited = FindNodes {
+            limit: Some(1),
+            ..query.clone()
+        };
+        assert_eq!(texts(adder.find_nodes(&limited)), vec!["Recursion needs a base case."]);
+
+        let any_tag = FindNodes {
+            tags_any: vec!["purpose".to_string(), "implementation".to_string()],
+            ..FindNodes::default()
+        };
+        assert_eq!(
+            texts(adder.find_nodes(&any_tag)),
+            vec![
+                "Recursion mirrors induction.",
+                "Tail RECURSION reuses the frame."
+            ]
+        );
+        assert_eq!(adder.find_nodes(&FindNodes::default()).len(), 6);
+        assert_eq!(adder.find_nodes(&FindNodes::default())[0].level, 1);
+    }
+
+    #[test]
+    fn test_split_node_duplicates_edges_to_all_replacements() {
+        let (mut adder, ids) = split_fixture();
+
+        let outcome = adder.handle_split_node(SplitNode {
+            node_id:  ids[1],
+            into:     vec![
+                "Students design contracts first.".to_string(),
+                "Students write tests before code.".to_string(),
+            ],
+            edges_to: SplitEdges::All,
+        });
+
+        assert!(outcome.decision.accepted);
+        assert_eq!(outcome.decision.assigned_id, outcome.ids.first().copied());
+        assert_eq!(outcome.ids.len(), 2);
+        assert_eq!(outcome.edges.len(), 4);
+        assert!(outcome.edges.iter().all(|d| d.accepted));
+        assert!(adder.store.find_by_id(&ids[1]).is_none(), "original node is removed");
+
+        let snapshot = adder.store.snapshot();
+        assert_eq!(snapshot.nodes.len(), 4);
+        assert_eq!(snapshot.edges.len(), 4);
+        for replacement in outcome.replacements.iter().filter_map(|d| d.assigned_id) {
+            assert!(
+                snapshot
+                    .edges
+                    .iter()
+                    .any(|e| e.from == ids[0] && e.to == replacement)
+            );
+            assert!(
+                snapshot
+                    .edges
+                    .iter()
+                    .any(|e| e.from == replacement && e.to == ids[2])
+            );
+        }
+        assert!(adder.store.is_prerequisite_dag());
+    }
+
+    #[test]
+    fn test_split_node_repoints_edges_to_a_chosen_replacement() {
+        let (mut adder, ids) = split_fixture();
+        let split = |edges_to| SplitNode {
+            node_id: ids[1],
+            into: vec![
+                "Students design contracts first.".to_string(),
+                "Students write tests before code.".to_string(),
+            ],
+            edges_to,
+        };
+
+        let outcome = adder.handle_split_node(split(SplitEdges::Replacement(2)));
+        assert_eq!(
+            outcome.decision.reason.as_deref(),
+            Some("no replacement #2 to re-point edges to")
+        );
+        assert!(adder.store.find_by_id(&ids[1]).is_some(), "graph is unchanged");
+
+        let outcome = adder.handle_split_node(split(SplitEdges::Replacement(1)));
+        assert!(outcome.decision.accepted);
+        let snapshot = adder.store.snapshot();
+        let endpoints: Vec<_> = snapshot.edges.iter().map(|e| (e.from, e.to)).collect();
+        assert_eq!(endpoints.len(), 2);
+        assert!(endpoints.contains(&(ids[0], outcome.ids[1])));
+        assert!(endpoints.contains(&(outcome.ids[1], ids[2])));
+    }
+
+    #[test]
+    fn test_merge_nodes_drops_edges_that_would_cycle_or_loop() {
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+        let ids: Vec<Uuid> = adder
+            .handle_add_nodes(vec![
+                sample_concept("Functions map inputs to outputs."),
+                sample_concept("Composition chains functions together."),
+                NodeProposal {
+                    tags: Some(vec!["contract".to_string()]),
+                    ..sample_concept("A function turns inputs into outputs.")
+                },
+                sample_concept("Pipelines pass data through stages."),
+            ])
+            .iter()
+            .filter_map(|d| d.assigned_id)
+            .collect();
+        let (keep, middle, remove, other) = (ids[0], ids[1], ids[2], ids[3]);
+        let edge = |relation, from_id, to_id| EdgeProposal {
+            relation,
+            from_id,
+            to_id,
+            rationale: "Functions come up in both.".to_string(),
+            generator: None,
+        };
+        let decisions = adder.handle_add_edges(vec![
+            edge(Relation::PrerequisiteFor, keep, middle),
+            edge(Relation::PrerequisiteFor, middle, remove),
+            edge(Relation::RelatedTo, keep, remove),
+            edge(Relation::Supports, other, remove),
+        ]);
+        assert!(decisions.iter().all(|d| d.accepted));
+
+        let outcome = adder.handle_merge_nodes(MergeNodes { keep, remove });
+        assert!(outcome.decision.accepted);
+        assert_eq!(outcome.moved, 1, "the supports edge moves to the kept node");
+        let mut reasons: Vec<(Uuid, Uuid, &str)> = outcome
+            .dropped
+            .iter()
+            .map(|dropped| (dropped.from, dropped.to, dropped.reason.as_str()))
+            .collect();
+        reasons.sort_by_key(|(_, _, reason)| *reason);
+        assert_eq!(
+            reasons,
+            vec![
+                (middle, keep, "edge would introduce a prerequisite cycle"),
+                (keep, keep, "self-loops are not allowed"),
+            ]
+        );
+
+        assert!(adder.store.find_by_id(&remove).is_none());
+        assert!(adder.store.is_prerequisite_dag());
+        assert_eq!(adder.store.edge_count(), 2);
+        assert!(
+            adder
+                .store
+                .supporters_of(&keep)
+                .iter()
+                .any(|(node, _)| node.id == other)
+        );
+        let kept = adder
+            .store
+            .node(adder.store.find_by_id(&keep).unwrap())
+            .unwrap();
+        assert_eq!(kept.tags, Some(vec!["tests".to_string(), "contract".to_string()]));
+
+        let unknown = adder.handle_merge_nodes(MergeNodes {
+            keep,
+            remove: Uuid::new_v4(),
+        });
+        assert!(!unknown.decision.accepted);
+        assert_eq!(adder.store.node_count(), 3);
+    }
+
+    #[test]
+    fn test_unsupported_outcome_warns_and_fails_coverage() {
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), Some(event_tx));
+        let outcome = |text: &str| NodeProposal {
+            kind: NodeKind::LearningOutcome,
+            ..sample_concept(text)
+        };
+        let ids: Vec<Uuid> = adder
+            .handle_add_nodes(vec![
+                sample_concept("Loops repeat statements."),
+                outcome("I can write a loop."),
+                outcome("I can trace a recursive call."),
+            ])
+            .iter()
+            .filter_map(|d| d.assigned_id)
+            .collect();
+        let decisions = adder.handle_add_edges(vec![EdgeProposal {
+            relation:  Relation::Supports,
+            from_id:   ids[0],
+            to_id:     ids[1],
+            rationale: "Writing a loop needs repetition.".to_string(),
+            generator: None,
+        }]);
+        assert!(decisions[0].accepted);
+
+        let mut warnings = Vec::new();
+        while let Ok(event) = event_rx.try_recv() {
+            if let Event::CoverageWarning {
+                id,
+                supports,
+                minimum,
+                ..
+            } = event
+            {
+                warnings.push((id, supports, minimum));
+            }
+        }
+        assert_eq!(warnings, vec![(ids[2], 0, 1)]);
+
+        let summary = adder.summarize();
+        assert!(!summary.coverage_ok);
+        assert_eq!(summary.under_supported.len(), 1);
+        assert_eq!(summary.under_supported[0].id, ids[2]);
+        assert_eq!(&*summary.under_supported[0].text, "I can trace a recursive call.");
+        let view = adder.read_handle().load();
+        assert!(!view.summary.coverage_ok, "readers of the view see the gap too");
+        assert_eq!(view.summary.under_supported[0].id, ids[2]);
+
+        let adder = adder.with_config(AdderConfig {
+            min_supports: 0,
+            ..AdderConfig::default()
+        });
+        assert!(adder.read_handle().load().summary.coverage_ok);
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_records_every_decision() {
+        let path = std::env::temp_dir().join(format!("weaver-audit-{}.jsonl", Uuid::new_v4()));
+        let log = AuditLog::open(&path).expect("open audit log");
+        let adder_ref = GraphAdder::spawn(
+            GraphAdder::with_event_sender(GraphStore::new(), None).with_audit_log(log),
+        );
+
+        let nodes = adder_ref
+            .ask(AddNodes(vec![
+                sample_concept("Loops repeat statements."),
+                sample_concept("Loops repeat statements."),
+            ]))
+            .await
+            .expect("add nodes");
+        let from_id = nodes[0].assigned_id.expect("accepted");
+        let edges = adder_ref
+            .ask(AddEdges(vec![EdgeProposal {
+                relation: Relation::RelatedTo,
+                from_id,
+                to_id: Uuid::new_v4(),
+                rationale: "Loops need something to repeat.".to_string(),
+                generator: None,
+            }]))
+            .await
+            .expect("add edges");
+        adder_ref.stop_gracefully().await.expect("stop");
+        adder_ref.wait_for_shutdown().await;
+
+        let contents = std::fs::read_to_string(&path).expect("read audit log");
+        std::fs::remove_file(&path).ok();
+        let records: Vec<AuditRecord> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("audit record"))
+            .collect();
+        assert_eq!(records.len(), 3);
+        for (record, decision) in records.iter().zip(nodes.iter().chain(&edges)) {
+            assert_eq!(record.accepted, decision.accepted);
+            assert_eq!(record.reason, decision.reason);
+            assert_eq!(record.assigned_id, decision.assigned_id);
+        }
+        assert_eq!(records[0].batch_id, records[1].batch_id);
+        assert_ne!(records[1].batch_id, records[2].batch_id);
+        assert!(matches!(
+            &records[1].proposal,
+            AuditProposal::Node(proposal) if proposal.text == "Loops repeat statements."
+        ));
+        assert!(matches!(
+            &records[2].proposal,
+            AuditProposal::Edge(proposal) if proposal.from_id == from_id
+        ));
+        assert!(records[2].timestamp_ms >= records[0].timestamp_ms);
+    }
+
+    #[test]
+    fn test_split_node_rejects_duplicate_replacement() {
+        let (mut adder, ids) = split_fixture();
+
+        let outcome = adder.handle_split_node(SplitNode {
+            node_id:  ids[1],
+            into:     vec![
+                "Students design contracts first.".to_string(),
+                "Contracts name inputs and outputs.".to_string(),
+            ],
+            edges_to: SplitEdges::First,
+        });
+
+        assert!(!outcome.decision.accepted);
+        assert!(outcome.decision.reason.as_deref().is_some_and(|reason| {
+            reason.starts_with("replacement #1") && reason.contains("duplicate node")
+        }));
+        assert!(adder.store.find_by_id(&ids[1]).is_some(), "graph is unchanged");
+        assert_eq!(adder.store.edge_count(), 2);
+    }
+
+    #[test]
+    fn test_removed_and_renamed_texts_leave_the_interner() {
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+        let decisions = adder.handle_add_nodes(vec![
+            sample_concept("Closures capture their environment."),
+            sample_concept("Iterators yield items lazily."),
+        ]);
+        let ids: Vec<Uuid> = decisions.iter().filter_map(|d| d.assigned_id).collect();
+        let interned = |adder: &GraphAdder, text: &str| adder.store.interner().get(text).is_some();
+
+        adder.handle_remove_nodes(vec![ids[0]]);
+        assert!(!interned(&adder, "Closures capture their environment."));
+
+        adder.handle_update_node(UpdateNode {
+            id:    ids[1],
+            text:  Some("Iterators yield items on demand.".to_string()),
+            level: None,
+            tags:  None,
+        });
+        assert!(!interned(&adder, "Iterators yield items lazily."));
+        assert!(interned(&adder, "Iterators yield items on demand."));
+    }
+
+    #[test]
+    fn test_update_node_keeps_id_and_edges_and_guards_levels() {
+        let (mut adder, ids) = split_fixture();
+        let update = |text: Option<&str>, level: Option<u8>| UpdateNode {
+            id: ids[1],
+            text: text.map(str::to_string),
+            level,
+            tags: None,
+        };
+
+        let decision = adder.handle_update_node(UpdateNode {
+            tags: Some(Some(vec!["Implementation".to_string()])),
+            ..update(Some("  Students design contracts before coding. "), Some(1))
+        });
+        assert_eq!(decision.assigned_id, Some(ids[1]));
+        let index = adder.store.find_by_id(&ids[1]).expect("same id");
+        let node = adder.store.node(index).expect("node");
+        assert_eq!((&*node.text, node.level), ("Students design contracts before coding.", 1));
+        assert_eq!(adder.store.find_by_tag("implementation").len(), 1);
+        assert_eq!(adder.store.find_by_tag("tests").len(), 2);
+        assert_eq!(adder.store.edge_count(), 2);
+
+        let raised = adder.handle_update_node(update(None, Some(2)));
+        assert_eq!(raised.reason.as_deref(), Some("prerequisite edges must not decrease level"));
+        assert_eq!(
+            raised.explanation,
+            Some(Explanation::LevelDecrease {
+                from_level: 2,
+                to_level:   1,
+            })
+        );
+        let duplicate =
+            adder.handle_update_node(update(Some("Contracts name inputs and outputs."), None));
+        assert_eq!(duplicate.reason.as_deref(), Some("duplicate node already present"));
+        let rambling = adder.handle_update_node(update(Some("Design first. Then test."), None));
+        assert_eq!(rambling.reason.as_deref(), Some("node text must be a single sentence"));
+        let unknown = adder.handle_update_node(UpdateNode {
+            id: Uuid::new_v4(),
+            ..update(None, Some(0))
+        });
+        assert!(!unknown.accepted);
+
+        let node = adder.store.node(index).expect("node");
+        assert_eq!((&*node.text, node.level), ("Students design contracts before coding.", 1));
+        assert!(adder.handle_update_node(update(None, Some(0))).accepted);
+    }
+
+    #[test]
+    fn test_atomic_batch_commits_all_or_nothing() {
+        let (mut adder, ids) = split_fixture();
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        adder.event_sender = Some(event_tx);
+        let edge = |relation: Relation, from: Uuid, to: Uuid| EdgeProposal {
+            relation,
+            from_id: from,
+            to_id: to,
+            rationale: "Earlier design steps come first.".to_string(),
+            generator: None,
+        };
+        let batch = |atomic| ApplyBatch {
+            nodes: vec![sample_concept("Assertions document assumptions.")],
+            edges: vec![
+                edge(Relation::RelatedTo, ids[0], ids[2]),
+                edge(Relation::PrerequisiteFor, ids[2], ids[0]),
+            ],
+            atomic,
+        };
+
+        let outcome = adder.handle_apply_batch(batch(true));
+        assert!(!outcome.committed);
+        assert!(
+            outcome
+                .nodes
+                .iter()
+                .chain(&outcome.edges)
+                .all(|d| !d.accepted)
+        );
+        assert_eq!(
+            outcome.nodes[0].reason.as_deref(),
+            Some("batch rolled back: 2 of 3 proposals accepted")
+        );
+        assert_eq!(outcome.nodes[0].assigned_id, None);
+        assert!(
+            outcome.edges[1]
+                .reason
+                .as_deref()
+                .is_some_and(|r| r.contains("cycle"))
+        );
+        assert_eq!((adder.store.node_count(), adder.store.edge_count()), (3, 2));
+        assert_eq!(adder.read_handle().load().node_count(), 3);
+        assert!(event_rx.try_recv().is_err(), "rolled-back batches send no events");
+
+        adder.config.atomic_threshold = Some(0.6);
+        let outcome = adder.handle_apply_batch(batch(true));
+        assert!(outcome.committed);
+        assert!(outcome.nodes[0].accepted && outcome.edges[0].accepted);
+        assert_eq!((adder.store.node_count(), adder.store.edge_count()), (4, 3));
+        let mut accepted_events = 0;
+        while let Ok(event) = event_rx.try_recv() {
+            accepted_events += usize::from(matches!(
+                event,
+                Event::NodeAccepted { .. } | Event::EdgeAccepted { .. }
+            ));
+        }
+        assert_eq!(accepted_events, 2);
+
+        adder.config.atomic_threshold = None;
+        let outcome = adder.handle_apply_batch(ApplyBatch {
+            nodes: vec![sample_concept("Invariants hold between method calls.")],
+            ..batch(false)
+        });
+        assert!(outcome.committed);
+        assert!(outcome.nodes[0].accepted && !outcome.edges[1].accepted);
+        assert_eq!(adder.store.node_count(), 5);
+    }
+
+    #[test]
+    fn test_clear_unpinned_keeps_edges_only_between_pinned_nodes() {
+        let (mut adder, ids) = split_fixture();
+        let pin_edge = |from, to| PinEdge {
+            from,
+            to,
+            relation: Relation::PrerequisiteFor,
+            pinned: true,
+        };
+
+        for &id in &ids[..2] {
+            assert!(
+                adder
+                    .handle_pin_node(PinNode {
+                        node_id: id,
+                        pinned:  true,
+                    })
+                    .accepted
+            );
+        }
+        assert!(adder.handle_pin_edge(pin_edge(ids[0], ids[1])).accepted);
+        // Pinned edge with an unpinned endpoint is dropped with that endpoint.
+        assert!(adder.handle_pin_edge(pin_edge(ids[1], ids[2])).accepted);
+        assert!(!adder.handle_pin_edge(pin_edge(ids[2], ids[0])).accepted);
+
+        let report = adder.handle_clear_unpinned();
+
+        assert_eq!(
+            report,
+            ClearReport {
+                nodes_removed: 1,
+                edges_removed: 1,
+            }
+        );
+        let snapshot = adder.store.snapshot();
+        assert_eq!(snapshot.nodes.len(), 2);
+        assert!(snapshot.nodes.iter().all(|node| node.pinned));
+        assert_eq!(snapshot.edges.len(), 1);
+        assert_eq!((snapshot.edges[0].from, snapshot.edges[0].to), (ids[0], ids[1]));
+        let summary = adder.summarize();
+        assert_eq!((summary.pinned_nodes, summary.pinned_edges), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_remove_nodes_cascades_through_a_prerequisite_chain() {
+        let (mut adder, ids) = split_fixture();
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        adder.event_sender = Some(event_tx);
+        let adder_ref = GraphAdder::spawn(adder);
+        let unknown = Uuid::new_v4();
+
+        let decisions = adder_ref
+            .ask(RemoveNodes(vec![ids[1], unknown, ids[1]]))
+            .await
+            .expect("removal handled");
+
+        assert_eq!(decisions[0].assigned_id, Some(ids[1]));
+        assert_eq!(decisions[1].reason, Some(format!("unknown id {unknown}")));
+        assert!(!decisions[2].accepted, "a removed id is no longer known");
+        let summary = adder_ref.ask(Summarize).await.expect("summary");
+        assert_eq!((summary.total_nodes, summary.total_edges), (2, 0));
+        assert!(summary.prerequisite_dag_ok);
+
+        let mut removed_edges = Vec::new();
+        let mut removed_nodes = Vec::new();
+        while let Ok(event) = event_rx.try_recv() {
+            match event {
+                Event::EdgeRemoved { from, to, .. } => removed_edges.push((from, to)),
+                Event::NodeRemoved { id, .. } => removed_nodes.push(id),
+                _ => {}
+            }
+        }
+        removed_edges.sort_unstable_by_key(|&(from, _)| from != ids[0]);
+        assert_eq!(removed_edges, vec![(ids[0], ids[1]), (ids[1], ids[2])]);
+        assert_eq!(removed_nodes, vec![ids[1]]);
+
+        let readded = adder_ref
+            .ask(AddNodes(vec![sample_concept(
+                "Students design contracts and write tests first.",
+            )]))
+            .await
+            .expect("batch applied");
+        assert!(readded[0].accepted, "the removed text is free again");
+    }
+
+    #[test]
+    fn test_pinned_elements_survive_clear_and_regenerate() {
+        let (mut adder, ids) = split_fixture();
+        adder.handle_pin_node(PinNode {
+            node_id: ids[2],
+            pinned:  true,
+        });
+
+        adder.handle_clear_unpinned();
+        let decisions = adder.handle_add_nodes(vec![
+            sample_concept("Contracts name inputs and outputs."),
+            sample_concept("Examples expose edge cases."),
+        ]);
+
+        assert!(decisions[0].accepted, "unpinned text can be regenerated");
+        assert!(!decisions[1].accepted, "pinned node still blocks duplicates");
+        let index = adder.store.find_by_id(&ids[2]).expect("pinned node kept");
+        assert!(adder.store.node(index).is_some_and(|node| node.pinned));
+        assert_eq!(adder.read_handle().load().node_count(), 2);
+    }
+
+    #[test]
+    fn test_same_text_is_deduplicated_per_namespace() {
+        let mut adder =
+            GraphAdder::with_event_sender(GraphStore::new(), None).with_config(AdderConfig {
+                namespace: Some("recursion".to_string()),
+                ..AdderConfig::default()
+            });
+        let in_namespace = |namespace: &str| NodeProposal {
+            namespace: Some(namespace.to_string()),
+            ..sample_concept("Base cases stop the descent.")
+        };
+
+        let decisions = adder.handle_add_nodes(vec![
+            sample_concept("Base cases stop the descent."),
+            in_namespace("induction"),
+            in_namespace("recursion"),
+        ]);
+
+        assert!(decisions[0].accepted, "unset namespace falls back to the config");
+        assert!(decisions[1].accepted, "same text in another namespace is distinct");
+        assert_eq!(decisions[2].reason.as_deref(), Some("duplicate node within batch"));
+
+        let prereq = EdgeProposal {
+            relation:  Relation::PrerequisiteFor,
+            from_id:   decisions[1].assigned_id.unwrap(),
+            to_id:     decisions[0].assigned_id.unwrap(),
+            rationale: "Induction proofs justify recursive definitions.".to_string(),
+            generator: None,
+        };
+        assert!(adder.handle_add_edges(vec![prereq])[0].accepted);
+
+        let summary = adder.summarize();
+        assert_eq!(summary.cross_namespace_prerequisites, 1);
+        let names: Vec<_> = summary
+            .namespaces
+            .iter()
+            .map(|stats| {
+                (stats.namespace.as_deref(), stats.nodes, stats.outgoing_cross_prerequisites)
+            })
+            .collect();
+        assert_eq!(names, vec![(Some("induction"), 1, 1), (Some("recursion"), 1, 0)]);
+
+        let dot = crate::export::render_dot(&adder.store.snapshot());
+        assert!(dot.contains("style=dashed, color=red"));
+        let filtered = adder.store.snapshot().in_namespace("recursion");
+        assert_eq!((filtered.nodes.len(), filtered.edges.len()), (1, 0));
+    }
+
+    #[test]
+    fn test_similarity_threshold_rejects_near_duplicates() {
+        let original = "Recursive functions call themselves on smaller inputs until reaching base.";
+        let reworded =
+            "Recursive functions call themselves on smaller inputs until reaching base cases.";
+        let with_threshold = |threshold| {
+            GraphAdder::with_event_sender(GraphStore::new(), None).with_config(AdderConfig {
+                similarity_threshold: Some(threshold),
+                ..AdderConfig::default()
+            })
+        };
+
+        let mut adder = with_threshold(0.9);
+        let decisions =
+            adder.handle_add_nodes(vec![sample_concept(original), sample_concept(reworded)]);
+        assert!(decisions[0].accepted);
+        assert!(!decisions[1].accepted, "10 of 11 words shared");
+        let reason = decisions[1].reason.as_deref().unwrap_or_default();
+        assert!(reason.starts_with("near-duplicate of node"), "{reason}");
+        assert!(reason.ends_with("(similarity 0.91)"), "{reason}");
+        assert_eq!(adder.store.node_count(), 1);
+
+        let mut adder = with_threshold(0.95);
+        let decisions =
+            adder.handle_add_nodes(vec![sample_concept(original), sample_concept(reworded)]);
+        assert!(decisions.iter().all(|decision| decision.accepted));
+
+        // The threshold is inclusive: 10/11 sits between these two.
+        let mut adder = with_threshold(0.909);
+        let decisions =
+            adder.handle_add_nodes(vec![sample_concept(original), sample_concept(reworded)]);
+        assert!(!decisions[1].accepted, "0.909 is just below 10/11");
+        let mut adder = with_threshold(0.91);
+        let decisions =
+            adder.handle_add_nodes(vec![sample_concept(original), sample_concept(reworded)]);
+        assert!(decisions[1].accepted, "0.91 is just above 10/11");
+
+        let reordered =
+            "Until reaching base, recursive functions call themselves on smaller inputs.";
+        let mut adder = with_threshold(0.99);
+        let decisions = adder.handle_add_nodes(vec![
+            sample_concept(original),
+            sample_concept(reworded),
+            sample_concept(reordered),
+        ]);
+        assert!(decisions[1].accepted);
+        assert!(!decisions[2].accepted, "same words in another order");
+        let reason = decisions[2].reason.as_deref().unwrap_or_default();
+        assert!(reason.ends_with("(similarity 1.00)"), "{reason}");
+    }
+
+    #[test]
+    fn test_stats_grouped_by_generator() {
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+        let tagged = |text: &str, model: &str| NodeProposal {
+            generator: Some(GeneratorInfo {
+                model:       model.to_string(),
+                provider:    "mock".to_string(),
+                prompt_hash: "0".to_string(),
+            }),
+            ..sample_concept(text)
+        };
+
+        adder.handle_add_nodes(vec![
+            tagged("Closures capture their environment.", "model-a"),
+            tagged("Closures capture their environment.", "model-a"),
+            tagged("Iterators yield items lazily.", "model-a"),
+        ]);
+        adder.handle_add_nodes(vec![
+            tagged("Iterators yield items lazily.", "model-b"),
+            tagged("Traits describe shared behavior.", "model-b"),
+            sample_concept("Modules group related items."),
+        ]);
+
+        let summary = adder.summarize();
+        let labels: Vec<_> = summary
+            .generators
+            .iter()
+            .map(|g| g.generator.as_str())
+            .collect();
+        assert_eq!(labels, vec!["mock/model-a", "mock/model-b", "unknown"]);
+
+        let model_a = &summary.generators[0].nodes;
+        assert_eq!((model_a.proposed, model_a.accepted), (3, 2));
+        assert_eq!(model_a.rejection_reasons["duplicate node within batch"], 1);
+
+        let model_b = &summary.generators[1].nodes;
+        assert_eq!((model_b.proposed, model_b.accepted), (2, 1));
+        assert_eq!(model_b.rejection_reasons["duplicate node already present"], 1);
+        assert_eq!(summary.generators[2].nodes.accepted, 1);
+    }
+
+    #[tokio::test]
+    async fn test_bounded_mailbox_sheds_bursts_and_keeps_store_consistent() {
+        const CAPACITY: usize = 4;
+
+        let adder_ref =
+            GraphAdder::with_event_sender(GraphStore::new(), None).spawn_bounded(CAPACITY);
+
+        // The current-thread runtime cannot run the actor until this loop
+        // yields, so the burst outpaces the mailbox deterministically.
+        let mut delivered = 0;
+        let mut shed = 0;
+        for i in 0..32 {
+            let proposals = vec![sample_concept(&format!("Burst concept {i} adds an idea."))];
+            match adder_ref.tell(AddNodes(proposals)).try_send() {
+                Ok(()) => delivered += 1,
+                Err(kameo::error::SendError::MailboxFull(_)) => shed += 1,
+                Err(other) => panic!("unexpected send error: {other}"),
+            }
+        }
+        assert!(delivered >= 1 && shed >= 1, "delivered {delivered}, shed {shed}");
+        assert_eq!(delivered + shed, 32);
+
+        let health = adder_ref.ask(Health).await.expect("health");
+        assert_eq!(health.nodes, delivered);
+        assert_eq!(health.messages_processed, delivered as u64 + 1);
+        assert_eq!(health.mailbox_capacity, Some(CAPACITY));
+        assert!(health.last_batch_latency.is_some());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_readers_never_see_partial_batches() {
+        const BATCH: usize = 400;
+
+        let adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+        let view = adder.read_handle();
+        let adder_ref = GraphAdder::spawn(adder);
+        let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        let readers: Vec<_> = (0..3)
+            .map(|_| {
+                let view = view.clone();
+                let done = done.clone();
+                tokio::spawn(async move {
+                    let mut observed = HashSet::new();
+                    while !done.load(std::sync::atomic::Ordering::Acquire) {
+                        let current = view.load();
+                        assert_eq!(current.inventory.len(), current.node_count());
+                        assert_eq!(current.summary.total_nodes, current.node_count());
+                        observed.insert(current.node_count());
+                        tokio::task::yield_now().await;
+                    }
+                    observed
+                })
+            })
+            .collect();
+
+        for round in 0..2 {
+            let proposals = (0..BATCH)
+                .map(|i| sample_concept(&format!("Round {round} concept {i} adds an idea.")))
+                .collect();
+            adder_ref
+                .ask(AddNodes(proposals))
+                .await
+                .expect("batch applied");
+        }
+        done.store(true, std::sync::atomic::Ordering::Release);
+
+        for reader in readers {
+            let observed = reader.await.expect("reader finished");
+            assert!(
+                observed.iter().all(|count| count % BATCH == 0),
+                "reader saw a partial batch: {observed:?}"
+            );
+        }
+        assert_eq!(view.load().node_count(), 2 * BATCH);
     }
 
     #[test]
@@ -589,13 +3397,98 @@ mod tests {
             from_id:   ids[0],
             to_id:     ids[1],
             rationale: "Closures provide reusable iterator adapters.".to_string(),
+            generator: None,
         }];
 
         let edge_result = adder.handle_add_edges(edges);
         assert!(edge_result[0].accepted);
 
-        let dot = adder.store.export_dot();
+        let dot = render_dot(&adder.store.snapshot());
         assert!(dot.contains("Concept A explores closures"));
         assert!(dot.contains("supports"));
     }
+
+    #[test]
+    fn test_import_graph_announces_loaded_items_and_guards_the_store() {
+        let (source, ids) = split_fixture();
+        let snapshot = source.store.snapshot();
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut adder = GraphAdder::with_event_sender(GraphStore::new(), Some(event_tx));
+        let import = |merge| ImportGraph {
+            snapshot: snapshot.clone(),
+            merge,
+        };
+
+        let report = adder
+            .handle_import_graph(import(false))
+            .expect("empty store");
+        assert_eq!((report.nodes_added, report.edges_added), (3, 2));
+        let mut announced = Vec::new();
+        while let Ok(event) = event_rx.try_recv() {
+            announced.push(match event {
+                Event::NodeAccepted { .. } => "node",
+                Event::EdgeAccepted { .. } => "edge",
+                _ => "other",
+            });
+        }
+        assert_eq!(announced, ["node", "node", "node", "edge", "edge"]);
+        assert_eq!(adder.read_handle().load().snapshot.nodes.len(), 3);
+        assert!(
+            adder
+                .store
+                .find_by_text(None, "Examples expose edge cases.")
+                .is_some()
+        );
+
+        assert_eq!(
+            adder.handle_import_graph(import(false)).unwrap_err(),
+            ImportGraphError::NotEmpty { nodes: 3, edges: 2 }
+        );
+        let report = adder.handle_import_graph(import(true)).expect("merge");
+        assert_eq!((report.nodes_matched, report.edges_skipped.len()), (3, 2));
+        assert!(event_rx.try_recv().is_err(), "nothing new to announce");
+
+        let mut dangling = snapshot.clone();
+        dangling.edges[0].to = Uuid::new_v4();
+        let mut fresh = GraphAdder::with_event_sender(GraphStore::new(), None);
+        let err = fresh.handle_import_graph(ImportGraph {
+            snapshot: dangling,
+            merge:    false,
+        });
+        assert!(matches!(err, Err(ImportGraphError::Invalid(problems)) if problems.len() == 1));
+
+        let mut cyclic = snapshot;
+        cyclic.edges[1].from = ids[1];
+        cyclic.edges[1].to = ids[0];
+        let err = fresh.handle_import_graph(ImportGraph {
+            snapshot: cyclic,
+            merge:    false,
+        });
+        assert!(matches!(err, Err(ImportGraphError::Cycle(_))));
+        assert_eq!(fresh.store.node_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_export_messages_share_the_read_view_snapshot() {
+        let (adder, ids) = split_fixture();
+        let adder_ref = GraphAdder::spawn(adder);
+
+        let export = adder_ref.ask(ExportJson).await.expect("json export");
+        assert_eq!(export.version, crate::graph::SNAPSHOT_VERSION);
+        let compact = serde_json::to_string(&export).expect("encode");
+        assert!(compact.starts_with("{\"version\":1,\"nodes\":["), "{compact}");
+        let reloaded: GraphSnapshot = serde_json::from_str(&compact).expect("plain snapshot");
+        let store = GraphStore::from_snapshot(reloaded).expect("consistent snapshot");
+        assert_eq!((store.node_count(), store.edge_count()), (3, 2));
+        assert!(store.find_by_id(&ids[2]).is_some());
+
+        let styled = adder_ref
+            .ask(ExportDotStyled(DotExportOptions {
+                cluster_by_level: true,
+                ..DotExportOptions::default()
+            }))
+            .await
+            .expect("dot export");
+        assert!(styled.contains("subgraph cluster_level1"), "{styled}");
+    }
 }
@@ -0,0 +1,82 @@
+//! Durable record of every proposal the adder decides, one JSON object per
+//! line.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::model::{Decision, EdgeProposal, NodeProposal};
+
+/// One decided proposal, as written to the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Milliseconds since the Unix epoch when the batch was decided.
+    pub timestamp_ms: u64,
+    /// Shared by every record of one batch message.
+    pub batch_id:     Uuid,
+    pub proposal:     AuditProposal,
+    pub accepted:     bool,
+    pub reason:       Option<String>,
+    pub assigned_id:  Option<Uuid>,
+}
+
+/// The proposal payload as the adder received it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditProposal {
+    Node(NodeProposal),
+    Edge(EdgeProposal),
+}
+
+/// Buffered JSONL writer; lines reach the file when a batch is flushed.
+#[derive(Debug)]
+pub struct AuditLog {
+    writer: BufWriter<File>,
+}
+
+impl AuditLog {
+    /// Open `path` for appending, creating it when missing.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    /// Write one line per decision, paired with proposals in order, then
+    /// flush.
+    pub fn write_batch<'a>(
+        &mut self,
+        proposals: Vec<AuditProposal>,
+        decisions: impl IntoIterator<Item = &'a Decision>,
+    ) -> io::Result<()> {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        let batch_id = Uuid::new_v4();
+        for (proposal, decision) in proposals.into_iter().zip(decisions) {
+            let record = AuditRecord {
+                timestamp_ms,
+                batch_id,
+                proposal,
+                accepted: decision.accepted,
+                reason: decision.reason.clone(),
+                assigned_id: decision.assigned_id,
+            };
+            serde_json::to_writer(&mut self.writer, &record)?;
+            self.writer.write_all(b"\n")?;
+        }
+        self.flush()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    graph::GraphSnapshot,
+    model::{Node, NodeKind, Relation, TextKey, text_key},
+};
+
+/// Relations in the order their edge changes are reported.
+const RELATION_ORDER: [Relation; 3] = [
+    Relation::PrerequisiteFor,
+    Relation::Supports,
+    Relation::RelatedTo,
+];
+
+/// The fields of a node that are compared between graphs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffNode {
+    pub id:    Uuid,
+    pub kind:  NodeKind,
+    pub level: u8,
+    pub text:  String,
+    /// Sorted; a node without tags has none here.
+    pub tags:  Vec<String>,
+}
+
+impl DiffNode {
+    fn new(node: &Node) -> Self {
+        let mut tags = node.tags.clone().unwrap_or_default();
+        tags.sort();
+        Self {
+            id: node.id,
+            kind: node.kind.clone(),
+            level: node.level,
+            text: node.text.to_string(),
+            tags,
+        }
+    }
+}
+
+/// A node present in both graphs with different fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeChange {
+    pub old:    DiffNode,
+    pub new:    DiffNode,
+    /// Names of the differing fields: `kind`, `level`, `tags` or `text`.
+    pub fields: Vec<String>,
+}
+
+/// An edge by the texts of its endpoints.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DiffEdge {
+    pub from: String,
+    pub to:   String,
+}
+
+/// Edges of one relation that only one of the graphs has.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelationDiff {
+    pub relation: Relation,
+    pub added:    Vec<DiffEdge>,
+    pub removed:  Vec<DiffEdge>,
+}
+
+/// What changed from one graph export to another.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphDiff {
+    pub added_nodes:   Vec<DiffNode>,
+    pub removed_nodes: Vec<DiffNode>,
+    pub changed_nodes: Vec<NodeChange>,
+    /// Only relations with added or removed edges, in a fixed order.
+    pub edges:         Vec<RelationDiff>,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.edges.is_empty()
+    }
+}
+
+/// Compare two graphs without relying on their node ids.
+///
+/// Nodes match by namespace and normalized text, as the adder detects
+/// duplicates; nodes left over on both sides then match by id. Edges match
+/// by their endpoints' match keys and relation, so regenerated ids never
+/// show up as changes.
+pub fn diff_graphs(old: &GraphSnapshot, new: &GraphSnapshot) -> GraphDiff {
+    let key = |node: &Node| text_key(node.namespace.as_deref(), &node.text);
+    let mut new_by_key: HashMap<TextKey, &Node> = HashMap::new();
+    for node in &new.nodes {
+        new_by_key.entry(key(node)).or_insert(node);
+    }
+    let new_by_id: HashMap<Uuid, &Node> = new.nodes.iter().map(|node| (node.id, node)).collect();
+
+    // Matches take the new node's key, so an edge whose endpoint was only
+    // matched by id still lines up with its counterpart.
+    let mut old_keys: HashMap<Uuid, TextKey> = HashMap::new();
+    let mut matched_new: HashSet<Uuid> = HashSet::new();
+    let mut pairs: Vec<(&Node, &Node)> = Vec::new();
+    let mut unmatched: Vec<&Node> = Vec::new();
+    for node in &old.nodes {
+        match new_by_key.get(&key(node)) {
+            Some(partner) if matched_new.insert(partner.id) => {
+                old_keys.insert(node.id, key(node));
+                pairs.push((node, partner));
+            }
+            _ => unmatched.push(node),
+        }
+    }
+
+    let mut diff = GraphDiff::default();
+    for node in unmatched {
+        match new_by_id.get(&node.id) {
+            Some(partner) if matched_new.insert(partner.id) => {
+                old_keys.insert(node.id, key(partner));
+                pairs.push((node, partner));
+            }
+            _ => {
+                old_keys.insert(node.id, key(node));
+                diff.removed_nodes.push(DiffNode::new(node));
+            }
+        }
+    }
+    diff.added_nodes = new
+        .nodes
+        .iter()
+        .filter(|node| !matched_new.contains(&node.id))
+        .map(DiffNode::new)
+        .collect();
+    diff.changed_nodes = pairs
+        .into_iter()
+        .filter_map(|(old, new)| node_change(DiffNode::new(old), DiffNode::new(new)))
+        .collect();
+    diff.added_nodes.sort_by(|a, b| a.text.cmp(&b.text));
+    diff.removed_nodes.sort_by(|a, b| a.text.cmp(&b.text));
+    diff.changed_nodes
+        .sort_by(|a, b| a.new.text.cmp(&b.new.text));
+
+    let new_keys: HashMap<Uuid, TextKey> =
+        new.nodes.iter().map(|node| (node.id, key(node))).collect();
+    let old_edges = edge_index(old, &old_keys);
+    let new_edges = edge_index(new, &new_keys);
+    for relation in RELATION_ORDER {
+        let only_in = |side: &HashMap<EdgeKey, DiffEdge>, other: &HashMap<EdgeKey, DiffEdge>| {
+            let mut edges: Vec<DiffEdge> = side
+                .iter()
+                .filter(|(edge_key, _)| edge_key.0 == relation && !other.contains_key(edge_key))
+                .map(|(_, edge)| edge.clone())
+                .collect();
+            edges.sort();
+            edges
+        };
+        let added = only_in(&new_edges, &old_edges);
+        let removed = only_in(&old_edges, &new_edges);
+        if !added.is_empty() || !removed.is_empty() {
+            diff.edges.push(RelationDiff {
+                relation: relation.clone(),
+                added,
+                removed,
+            });
+        }
+    }
+
+    diff
+}
+
+fn node_change(old: DiffNode, new: DiffNode) -> Option<NodeChange> {
+    let mut fields = Vec::new();
+    if old.kind != new.kind {
+        fields.push("kind".to_string());
+    }
+    if old.level != new.level {
+        fields.push("level".to_string());
+    }
+    if old.tags != new.tags {
+        fields.push("tags".to_string());
+    }
+    if old.text != new.text {
+        fields.push("text".to_string());
+    }
+    (!fields.is_empty()).then_some(NodeChange { old, new, fields })
+}
+
+type EdgeKey = (Relation, TextKey, TextKey);
+
+/// Edges keyed by relation and endpoint keys, with their endpoint texts.
+fn edge_index(
+    snapshot: &GraphSnapshot,
+    keys: &HashMap<Uuid, TextKey>,
+) -> HashMap<EdgeKey, DiffEdge> {
+    let texts: HashMap<Uuid, &str> = snapshot
+        .nodes
+        .iter()
+        .map(|node| (node.id, node.text.as_ref()))
+        .collect();
+    let mut index = HashMap::new();
+    for edge in &snapshot.edges {
+        let (Some(from_key), Some(to_key)) = (keys.get(&edge.from), keys.get(&edge.to)) else {
+            continue;
+        };
+        let text = |id: &Uuid| texts.get(id).copied().unwrap_or_default().to_string();
+        let mut ends = ((from_key.clone(), text(&edge.from)), (to_key.clone(), text(&edge.to)));
+        if edge.relation.is_symmetric() && ends.1.0 < ends.0.0 {
+            ends = (ends.1, ends.0);
+        }
+        let ((from_key, from), (to_key, to)) = ends;
+        index.insert((edge.relation.clone(), from_key, to_key), DiffEdge { from, to });
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph::SnapshotEdge, model::Granularity};
+
+    fn node(kind: NodeKind, level: u8, text: &str) -> Node {
+        Node {
+            id: Uuid::new_v4(),
+            kind,
+            granularity: Granularity::Sentence,
+            level,
+            text: text.into(),
+            tags: None,
+            generator: None,
+            namespace: Some("course".to_string()),
+            topic: None,
+            pinned: false,
+        }
+    }
+
+    fn edge(from: &Node, to: &Node, relation: Relation) -> SnapshotEdge {
+        SnapshotEdge {
+            from: from.id,
+            to: to.id,
+            relation,
+            rationale: String::new(),
+            generator: None,
+            pinned: false,
+        }
+    }
+
+    /// A copy of `node` with a fresh id, as a rerun would produce.
+    fn regenerated(node: &Node) -> Node {
+        Node {
+            id: Uuid::new_v4(),
+            ..node.clone()
+        }
+    }
+
+    #[test]
+    fn regenerated_ids_alone_produce_no_diff() {
+        let basics = node(NodeKind::Concept, 0, "Basics come first.");
+        let loops = node(NodeKind::Concept, 1, "Loops repeat statements.");
+        let old = GraphSnapshot {
+            edges: vec![
+                edge(&basics, &loops, Relation::PrerequisiteFor),
+                edge(&loops, &basics, Relation::RelatedTo),
+            ],
+            nodes: vec![basics.clone(), loops.clone()],
+        };
+        let (basics, loops) = (regenerated(&basics), regenerated(&loops));
+        let new = GraphSnapshot {
+            edges: vec![
+                edge(&basics, &loops, Relation::PrerequisiteFor),
+                edge(&basics, &loops, Relation::RelatedTo),
+            ],
+            nodes: vec![basics, loops],
+        };
+
+        let diff = diff_graphs(&old, &new);
+        assert!(diff.is_empty(), "{diff:?}");
+    }
+
+    #[test]
+    fn reports_node_and_edge_changes_by_relation() {
+        let basics = node(NodeKind::Concept, 0, "Basics come first.");
+        let loops = node(NodeKind::Concept, 1, "Loops repeat statements.");
+        let dropped = node(NodeKind::Concept, 0, "Dropped in the new run.");
+        let renamed = node(NodeKind::LearningOutcome, 2, "Write a loop.");
+        let old = GraphSnapshot {
+            edges: vec![
+                edge(&basics, &loops, Relation::PrerequisiteFor),
+                edge(&dropped, &loops, Relation::PrerequisiteFor),
+                edge(&loops, &renamed, Relation::Supports),
+            ],
+            nodes: vec![basics.clone(), loops.clone(), dropped, renamed.clone()],
+        };
+
+        let basics = regenerated(&basics);
+        let loops = Node {
+            level: 2,
+            tags: Some(vec!["implementation".to_string()]),
+            ..regenerated(&loops)
+        };
+        let renamed = Node {
+            text: "Write a counting loop.".into(),
+            ..renamed
+        };
+        let added = node(NodeKind::Concept, 0, "Added in the new run.");
+        let new = GraphSnapshot {
+            edges: vec![
+                edge(&basics, &loops, Relation::PrerequisiteFor),
+                edge(&loops, &renamed, Relation::Supports),
+                edge(&added, &basics, Relation::RelatedTo),
+            ],
+            nodes: vec![basics, loops, renamed, added],
+        };
+
+        let diff = diff_graphs(&old, &new);
+        let texts = |nodes: &[DiffNode]| nodes.iter().map(|n| n.text.clone()).collect::<Vec<_>>();
+        assert_eq!(texts(&diff.added_nodes), vec!["Added in the new run."]);
+        assert_eq!(texts(&diff.removed_nodes), vec!["Dropped in the new run."]);
+        let changes: Vec<_> = diff
+            .changed_nodes
+            .iter()
+            .map(|change| (change.new.text.as_str(), change.fields.clone()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("Loops repeat statements.", vec!["level".to_string(), "tags".to_string()]),
+                ("Write a counting loop.", vec!["text".to_string()]),
+            ]
+        );
+
+        let relations: Vec<_> = diff
+            .edges
+            .iter()
+            .map(|group| (group.relation.clone(), group.added.len(), group.removed.len()))
+            .collect();
+        assert_eq!(
+            relations,
+            vec![
+                (Relation::PrerequisiteFor, 0, 1),
+                (Relation::RelatedTo, 1, 0)
+            ]
+        );
+        assert_eq!(diff.edges[0].removed[0].from, "Dropped in the new run.");
+    }
+}
@@ -1,14 +1,15 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, time::Duration};
 
 use kameo::{
     Actor,
     message::{Context, Message},
 };
-use tracing::warn;
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+use tracing::{Instrument, Span, field, info_span, warn};
 
 use crate::{
-    llm::{LlmClient, LlmError},
-    model::{EdgeProposal, InventoryEntry, NodeKind, Relation},
+    llm::{LatencyLog, LlmClient, LlmError, NodeBackend, SchemaAudit},
+    model::{EdgeProposal, GeneratorInfo, InventoryEntry, NodeKind, Relation, RoundFeedback},
 };
 
 /// Configuration for generating edge proposals.
@@ -16,6 +17,12 @@ use crate::{
 pub struct EdgeGeneratorConfig {
     pub use_llm:              bool,
     pub default_target_edges: usize,
+    /// Reject responses that omit required schema fields.
+    pub strict_schema:        bool,
+    /// Shuffles fallback concept pairing; `None` pairs in text order.
+    pub seed:                 Option<u64>,
+    /// Receives the latency of every LLM request.
+    pub latency_log:          Option<LatencyLog>,
 }
 
 impl Default for EdgeGeneratorConfig {
@@ -23,6 +30,9 @@ impl Default for EdgeGeneratorConfig {
         Self {
             use_llm:              false,
             default_target_edges: 40,
+            strict_schema:        false,
+            seed:                 None,
+            latency_log:          None,
         }
     }
 }
@@ -31,6 +41,21 @@ impl Default for EdgeGeneratorConfig {
 pub struct GenerateEdges {
     pub inventory:    Vec<InventoryEntry>,
     pub target_edges: usize,
+    /// Time allowed for the LLM request before falling back.
+    pub timeout:      Option<Duration>,
+    /// Caller's span; generation spans nest under it.
+    pub span:         Span,
+    /// Earlier rounds of the run; `None` on the first.
+    pub feedback:     Option<RoundFeedback>,
+}
+
+/// Edge proposals and how the LLM response they came from was shaped.
+#[derive(Debug, Clone, Default, kameo::Reply)]
+pub struct EdgeBatch {
+    pub proposals: Vec<EdgeProposal>,
+    /// Field omissions observed in the raw LLM response; empty when the
+    /// deterministic fallback produced the batch.
+    pub schema:    SchemaAudit,
 }
 
 /// Actor responsible for producing edge proposals.
@@ -43,7 +68,11 @@ pub struct EdgeGenerator {
 impl EdgeGenerator {
     pub fn new(config: EdgeGeneratorConfig) -> Self {
         let llm = match LlmClient::new(config.use_llm) {
-            Ok(client) => Some(client),
+            Ok(client) => Some(
+                client
+                    .with_strict_schema(config.strict_schema)
+                    .with_latency_log(config.latency_log.clone()),
+            ),
             Err(LlmError::Disabled) => None,
             Err(err) => {
                 warn!(error = %err, "edge_generator.llm_unavailable");
@@ -53,9 +82,14 @@ impl EdgeGenerator {
         Self { config, llm }
     }
 
-    fn fallback_edges(inventory: &[InventoryEntry], target_edges: usize) -> Vec<EdgeProposal> {
+    fn fallback_edges(
+        inventory: &[InventoryEntry],
+        target_edges: usize,
+        seed: Option<u64>,
+        feedback: &RoundFeedback,
+    ) -> Vec<EdgeProposal> {
         let mut edges = Vec::new();
-        let mut seen = HashSet::new();
+        let mut seen: HashSet<_> = feedback.edges.iter().cloned().collect();
 
         let mut concepts: Vec<_> = inventory
             .iter()
@@ -68,8 +102,11 @@ impl EdgeGenerator {
             .cloned()
             .collect();
 
-        concepts.sort_by(|a, b| a.3.to_lowercase().cmp(&b.3.to_lowercase()));
-        learning_outcomes.sort_by(|a, b| a.3.to_lowercase().cmp(&b.3.to_lowercase()));
+        concepts.sort_by_key(|entry| entry.3.to_lowercase());
+        learning_outcomes.sort_by_key(|entry| entry.3.to_lowercase());
+        if let Some(seed) = seed {
+            concepts.shuffle(&mut StdRng::seed_from_u64(seed));
+        }
 
         if !concepts.is_empty() {
             for (lo_index, (lo_id, _, _, lo_text, _)) in learning_outcomes.iter().enumerate() {
@@ -87,6 +124,7 @@ impl EdgeGenerator {
                                 truncate_sentence(&concept.3),
                                 truncate_sentence(lo_text)
                             ),
+                            generator: Some(GeneratorInfo::fallback()),
                         });
                     }
                     if edges.len() >= target_edges {
@@ -109,6 +147,7 @@ impl EdgeGenerator {
                             truncate_sentence(&from.3),
                             truncate_sentence(&to.3)
                         ),
+                        generator: Some(GeneratorInfo::fallback()),
                     });
                 }
             }
@@ -122,7 +161,7 @@ impl EdgeGenerator {
 }
 
 impl Message<GenerateEdges> for EdgeGenerator {
-    type Reply = Vec<EdgeProposal>;
+    type Reply = EdgeBatch;
 
     fn handle(
         &mut self,
@@ -136,27 +175,64 @@ impl Message<GenerateEdges> for EdgeGenerator {
             msg.target_edges
         };
 
+        let span = msg.span.clone();
+        let seed = self.config.seed;
+        let feedback = msg.feedback.unwrap_or_default();
         async move {
             if let Some(client) = llm {
-                match client.generate_edges(&msg.inventory, fallback_target).await {
-                    Ok(edges) if !edges.is_empty() => return edges,
-                    Ok(_) => warn!("edge_generator.llm_returned_empty_batch"),
-                    Err(err) => warn!(error = %err, "edge_generator.llm_failed"),
+                let request_span = info_span!(
+                    "llm.request",
+                    model = client.model_name(),
+                    kind = "edges",
+                    prompt_tokens = field::Empty,
+                    completion_tokens = field::Empty,
+                );
+                let request = client
+                    .generate_edges(&msg.inventory, fallback_target, &feedback)
+                    .instrument(request_span);
+                let result = match msg.timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, request).await,
+                    None => Ok(request.await),
+                };
+                match result {
+                    Ok(Ok(response)) if !response.proposals.is_empty() => {
+                        return EdgeBatch {
+                            proposals: response.proposals,
+                            schema:    response.audit,
+                        };
+                    }
+                    Ok(Ok(_)) => warn!("edge_generator.llm_returned_empty_batch"),
+                    Ok(Err(err)) => warn!(error = %err, "edge_generator.llm_failed"),
+                    Err(_) => warn!("edge_generator.deadline_exceeded"),
                 }
             }
 
-            EdgeGenerator::fallback_edges(&msg.inventory, fallback_target)
+            EdgeBatch {
+                proposals: EdgeGenerator::fallback_edges(
+                    &msg.inventory,
+                    fallback_target,
+                    seed,
+                    &feedback,
+                ),
+                schema:    SchemaAudit::default(),
+            }
         }
+        .instrument(span)
     }
 }
 
 pub(crate) fn truncate_sentence(sentence: &str) -> String {
-    const LIMIT: usize = 80;
+    truncate_sentence_to(sentence, 80)
+}
+
+/// The trimmed `sentence` cut to its first `limit` characters, with `...`
+/// marking a cut.
+pub(crate) fn truncate_sentence_to(sentence: &str, limit: usize) -> String {
     let cleaned = sentence.trim();
     let mut chars = cleaned.chars();
-    let mut truncated = String::with_capacity(LIMIT + 3);
+    let mut truncated = String::with_capacity(limit + 3);
 
-    for _ in 0..LIMIT {
+    for _ in 0..limit {
         match chars.next() {
             Some(ch) => truncated.push(ch),
             None => return truncated,
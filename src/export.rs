@@ -0,0 +1,593 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::io::AsyncWriteExt;
+use tracing::{Instrument, info_span};
+
+use crate::{
+    edge_synth::truncate_sentence_to,
+    graph::GraphSnapshot,
+    model::{NodeKind, Relation, clean_text},
+};
+
+pub mod csv;
+pub mod cytoscape;
+pub mod gexf;
+pub mod graphml;
+pub mod lms;
+pub mod mermaid;
+pub mod verify;
+
+use verify::VerifyError;
+
+/// Output formats that can be written at the end of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Dot,
+    /// Learning-outcome-centric JSON for LMS import.
+    Lms,
+    /// The full [`GraphSnapshot`] as pretty-printed JSON.
+    Json,
+    /// GraphML for yEd, Gephi, and curriculum tools.
+    Graphml,
+    /// Mermaid flowchart for Markdown documentation.
+    Mermaid,
+    /// Cytoscape.js `elements` JSON.
+    Cytoscape,
+    /// GEXF 1.3 for Gephi.
+    Gexf,
+    /// Node table of a CSV pair.
+    CsvNodes,
+    /// Edge table of a CSV pair.
+    CsvEdges,
+}
+
+/// The formats and file names written into a CSV export directory.
+pub const CSV_FILES: [(ExportFormat, &str); 2] = [
+    (ExportFormat::CsvNodes, "nodes.csv"),
+    (ExportFormat::CsvEdges, "edges.csv"),
+];
+
+impl ExportFormat {
+    /// Human-readable name used in status lines.
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Dot => "DOT graph",
+            ExportFormat::Lms => "LMS JSON",
+            ExportFormat::Json => "JSON graph",
+            ExportFormat::Graphml => "GraphML graph",
+            ExportFormat::Mermaid => "Mermaid flowchart",
+            ExportFormat::Cytoscape => "Cytoscape JSON",
+            ExportFormat::Gexf => "GEXF graph",
+            ExportFormat::CsvNodes => "nodes CSV",
+            ExportFormat::CsvEdges => "edges CSV",
+        }
+    }
+
+    fn render(
+        self,
+        snapshot: &GraphSnapshot,
+        topic: Option<&str>,
+        dot: &DotExportOptions,
+    ) -> String {
+        match self {
+            ExportFormat::Dot => render_dot_with(snapshot, dot),
+            ExportFormat::Lms => lms::render_lms(snapshot, topic),
+            ExportFormat::Json => render_json(snapshot),
+            ExportFormat::Graphml => graphml::render_graphml(snapshot),
+            ExportFormat::Mermaid => mermaid::render_mermaid(snapshot),
+            ExportFormat::Cytoscape => cytoscape::render_cytoscape(snapshot),
+            ExportFormat::Gexf => gexf::render_gexf(snapshot),
+            ExportFormat::CsvNodes => csv::render_csv_nodes(snapshot),
+            ExportFormat::CsvEdges => csv::render_csv_edges(snapshot),
+        }
+    }
+
+    /// Re-read a rendered artifact and check it parses as this format.
+    pub fn verify(self, contents: &str) -> Result<(), VerifyError> {
+        match self {
+            ExportFormat::Dot => verify::verify_dot(contents),
+            ExportFormat::Lms => lms::verify_lms(contents),
+            ExportFormat::Json => serde_json::from_str::<GraphSnapshot>(contents)
+                .map(|_| ())
+                .map_err(|err| VerifyError {
+                    line:    err.line(),
+                    message: err.to_string(),
+                }),
+            ExportFormat::Graphml => graphml::verify_graphml(contents),
+            ExportFormat::Mermaid => mermaid::verify_mermaid(contents),
+            ExportFormat::Cytoscape => cytoscape::verify_cytoscape(contents),
+            ExportFormat::Gexf => gexf::verify_gexf(contents),
+            ExportFormat::CsvNodes | ExportFormat::CsvEdges => csv::verify_csv(contents),
+        }
+    }
+}
+
+/// Export path that stands for standard output.
+pub const STDOUT_PATH: &str = "-";
+
+/// Whether `path` is [`STDOUT_PATH`].
+pub fn is_stdout(path: &Path) -> bool {
+    path.as_os_str() == STDOUT_PATH
+}
+
+/// A requested artifact: which format to render and where to write it.
+#[derive(Debug, Clone)]
+pub struct ExportRequest {
+    pub format:    ExportFormat,
+    pub path:      PathBuf,
+    /// Read the written file back and validate it.
+    pub check:     bool,
+    /// Restrict the artifact to one namespace's nodes and internal edges.
+    pub namespace: Option<String>,
+    /// Run topic, recorded by formats that carry metadata.
+    pub topic:     Option<String>,
+    /// Styling used when `format` is DOT.
+    pub dot:       DotExportOptions,
+}
+
+impl ExportRequest {
+    pub fn to_stdout(&self) -> bool {
+        is_stdout(&self.path)
+    }
+}
+
+/// Result of rendering and writing a single artifact.
+#[derive(Debug, Clone)]
+pub struct ArtifactOutcome {
+    pub format:  ExportFormat,
+    pub path:    PathBuf,
+    pub elapsed: Duration,
+    pub error:   Option<String>,
+}
+
+/// Render and write every requested artifact concurrently.
+///
+/// Rendering runs on the blocking pool and each file is written on its own
+/// task, so a failure in one artifact never prevents the others from being
+/// produced. Outcomes are returned in request order.
+pub async fn write_exports(
+    snapshot: GraphSnapshot,
+    requests: Vec<ExportRequest>,
+) -> Vec<ArtifactOutcome> {
+    let snapshot = Arc::new(snapshot);
+
+    let handles: Vec<_> = requests
+        .into_iter()
+        .map(|request| {
+            let snapshot = Arc::clone(&snapshot);
+            let span = info_span!("export.render", format = request.format.label());
+            let handle = tokio::spawn(write_export(snapshot, request.clone()).instrument(span));
+            (request, handle)
+        })
+        .collect();
+
+    let mut outcomes = Vec::with_capacity(handles.len());
+    for (request, handle) in handles {
+        let outcome = handle.await.unwrap_or_else(|err| ArtifactOutcome {
+            format:  request.format,
+            path:    request.path,
+            elapsed: Duration::ZERO,
+            error:   Some(format!("export task failed: {err}")),
+        });
+        outcomes.push(outcome);
+    }
+
+    outcomes
+}
+
+async fn write_export(snapshot: Arc<GraphSnapshot>, request: ExportRequest) -> ArtifactOutcome {
+    let started = Instant::now();
+    let ExportRequest {
+        format,
+        path,
+        check,
+        namespace,
+        topic,
+        dot,
+    } = request;
+
+    let rendered = tokio::task::spawn_blocking(move || match namespace {
+        Some(namespace) => {
+            format.render(&snapshot.in_namespace(&namespace), topic.as_deref(), &dot)
+        }
+        None => format.render(&snapshot, topic.as_deref(), &dot),
+    })
+    .await;
+    let mut error = match rendered {
+        Ok(contents) if is_stdout(&path) => write_stdout(format, contents, check).await.err(),
+        Ok(contents) => write_file(format, &path, contents).await.err(),
+        Err(err) => Some(format!("render failed: {err}")),
+    };
+    if check && error.is_none() && !is_stdout(&path) {
+        error = check_export(format, &path).await.err();
+    }
+
+    ArtifactOutcome {
+        format,
+        path,
+        elapsed: started.elapsed(),
+        error,
+    }
+}
+
+/// Write an artifact to `path`, creating the directory of a CSV pair.
+async fn write_file(format: ExportFormat, path: &Path, contents: String) -> Result<(), String> {
+    if CSV_FILES.iter().any(|(csv, _)| *csv == format)
+        && let Some(directory) = path.parent()
+    {
+        tokio::fs::create_dir_all(directory)
+            .await
+            .map_err(|err| err.to_string())?;
+    }
+    tokio::fs::write(path, contents)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Write an artifact to stdout; with `check` it is validated first, since
+/// stdout cannot be re-read.
+async fn write_stdout(format: ExportFormat, contents: String, check: bool) -> Result<(), String> {
+    if check {
+        check_contents(format, contents.clone()).await?;
+    }
+    let mut stdout = tokio::io::stdout();
+    stdout
+        .write_all(contents.as_bytes())
+        .await
+        .map_err(|err| err.to_string())?;
+    stdout.flush().await.map_err(|err| err.to_string())
+}
+
+async fn check_export(format: ExportFormat, path: &Path) -> Result<(), String> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|err| format!("re-read for check failed: {err}"))?;
+    check_contents(format, contents).await
+}
+
+async fn check_contents(format: ExportFormat, contents: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || format.verify(&contents))
+        .await
+        .map_err(|err| format!("check failed: {err}"))?
+        .map_err(|err| format!("invalid {} at {err}", format.label()))
+}
+
+/// Render the snapshot as pretty-printed JSON with edges keyed by node UUID.
+pub fn render_json(snapshot: &GraphSnapshot) -> String {
+    let mut output = serde_json::to_string_pretty(snapshot).unwrap_or_default();
+    output.push('\n');
+    output
+}
+
+/// Styling for [`render_dot_with`]; the default renders what
+/// [`render_dot`] always has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DotExportOptions {
+    /// Draw concepts as boxes and learning outcomes as filled double
+    /// octagons, and give supports and related-to edges their own line
+    /// styles.
+    pub color_by_kind:    bool,
+    /// Group nodes into a `cluster_levelN` subgraph per level.
+    pub cluster_by_level: bool,
+    /// Cut node and edge labels to this many characters, followed by `...`.
+    pub max_label_chars:  Option<usize>,
+}
+
+/// Fill color of learning outcome nodes under
+/// [`DotExportOptions::color_by_kind`].
+const OUTCOME_FILL: &str = "#fde9a9";
+
+/// Render the snapshot as a Graphviz DOT document.
+///
+/// Prerequisite edges that cross namespaces are drawn dashed and red.
+pub fn render_dot(snapshot: &GraphSnapshot) -> String {
+    render_dot_with(snapshot, &DotExportOptions::default())
+}
+
+/// Render the snapshot as DOT with the given styling.
+pub fn render_dot_with(snapshot: &GraphSnapshot, options: &DotExportOptions) -> String {
+    let mut output = String::from("digraph weaver {\n");
+    let namespaces: HashMap<_, _> = snapshot
+        .nodes
+        .iter()
+        .map(|node| (node.id, node.namespace.as_deref()))
+        .collect();
+    let label = |text: String| {
+        let text = match options.max_label_chars {
+            Some(limit) => truncate_sentence_to(&text, limit),
+            None => text,
+        };
+        escape_dot(&text)
+    };
+
+    let mut levels: BTreeMap<u8, Vec<String>> = BTreeMap::new();
+    for node in &snapshot.nodes {
+        let shape = match (options.color_by_kind, &node.kind) {
+            (false, _) => String::new(),
+            (true, NodeKind::Concept) => ", shape=box".to_string(),
+            (true, NodeKind::LearningOutcome) => {
+                format!(", shape=doubleoctagon, style=filled, fillcolor=\"{OUTCOME_FILL}\"")
+            }
+        };
+        let statement =
+            format!("\"{}\" [label=\"{}\"{shape}];\n", node.id, label(clean_text(&node.text)));
+        if options.cluster_by_level {
+            levels.entry(node.level).or_default().push(statement);
+        } else {
+            output.push_str("  ");
+            output.push_str(&statement);
+        }
+    }
+    for (level, statements) in levels {
+        output.push_str(&format!(
+            "  subgraph cluster_level{level} {{\n    label=\"Level {level}\";\n"
+        ));
+        for statement in statements {
+            output.push_str("    ");
+            output.push_str(&statement);
+        }
+        output.push_str("  }\n");
+    }
+
+    for edge in &snapshot.edges {
+        let relation = match edge.relation {
+            Relation::PrerequisiteFor => "prerequisite_for",
+            Relation::Supports => "supports",
+            Relation::RelatedTo => "related_to",
+        };
+        let rationale: Vec<String> = edge
+            .rationale
+            .lines()
+            .map(clean_text)
+            .filter(|line| !line.is_empty())
+            .collect();
+        let label = if rationale.is_empty() {
+            label(relation.to_string())
+        } else {
+            label(format!("{}: {}", relation, rationale.join("\n")))
+        };
+        let direction = if edge.relation.is_symmetric() {
+            ", dir=none"
+        } else {
+            ""
+        };
+        let crosses = matches!(edge.relation, Relation::PrerequisiteFor)
+            && namespaces.get(&edge.from) != namespaces.get(&edge.to);
+        let style = match edge.relation {
+            _ if crosses => ", style=dashed, color=red",
+            Relation::Supports if options.color_by_kind => ", style=dashed",
+            Relation::RelatedTo if options.color_by_kind => ", style=dotted",
+            _ => "",
+        };
+        output.push_str(&format!(
+            "  \"{}\" -> \"{}\" [label=\"{}\"{direction}{style}];\n",
+            edge.from, edge.to, label
+        ));
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+/// Escape text for a quoted DOT string; line breaks become `\n`.
+fn escape_dot(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::model::{Granularity, Node, NodeKind};
+
+    fn sample_snapshot() -> GraphSnapshot {
+        GraphSnapshot {
+            nodes: vec![Node {
+                id:          Uuid::new_v4(),
+                kind:        NodeKind::Concept,
+                granularity: Granularity::Sentence,
+                level:       0,
+                text:        "Tests capture expected behavior.".into(),
+                tags:        None,
+                generator:   None,
+                pinned:      false,
+                namespace:   None,
+                topic:       None,
+            }],
+            edges: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn json_export_round_trips() {
+        let snapshot = sample_snapshot();
+        let rendered = render_json(&snapshot);
+        ExportFormat::Json
+            .verify(&rendered)
+            .expect("valid JSON export");
+
+        let parsed: GraphSnapshot = serde_json::from_str(&rendered).expect("parse");
+        assert_eq!(parsed.nodes.len(), 1);
+        assert_eq!(parsed.nodes[0].id, snapshot.nodes[0].id);
+        assert_eq!(parsed.nodes[0].text, snapshot.nodes[0].text);
+    }
+
+    #[test]
+    fn styled_dot_marks_kinds_clusters_levels_and_truncates() {
+        let mut snapshot = sample_snapshot();
+        let concept = snapshot.nodes[0].id;
+        let outcome = Uuid::new_v4();
+        snapshot.nodes.push(Node {
+            id: outcome,
+            kind: NodeKind::LearningOutcome,
+            level: 2,
+            text: "I can write tests that capture the expected behavior of a function.".into(),
+            ..snapshot.nodes[0].clone()
+        });
+        snapshot.edges.push(crate::graph::SnapshotEdge {
+            from:      concept,
+            to:        outcome,
+            relation:  Relation::Supports,
+            rationale: "Captured behavior is what the tests check.".to_string(),
+            generator: None,
+            pinned:    false,
+        });
+
+        let plain = render_dot(&snapshot);
+        assert_eq!(plain, render_dot_with(&snapshot, &DotExportOptions::default()));
+        assert!(!plain.contains("shape=") && !plain.contains("subgraph"));
+
+        let styled = render_dot_with(
+            &snapshot,
+            &DotExportOptions {
+                color_by_kind:    true,
+                cluster_by_level: true,
+                max_label_chars:  Some(18),
+            },
+        );
+        ExportFormat::Dot.verify(&styled).expect("valid styled DOT");
+        assert!(styled.contains("shape=box"));
+        assert!(styled.contains("shape=doubleoctagon, style=filled"));
+        assert!(styled.contains("subgraph cluster_level0 {"));
+        assert!(styled.contains("subgraph cluster_level2 {\n    label=\"Level 2\";"));
+        assert!(styled.contains("[label=\"I can write tests ...\""));
+        assert!(styled.contains("[label=\"supports: Captured...\", style=dashed]"));
+    }
+
+    #[test]
+    fn dot_labels_escape_backslashes_quotes_and_newlines() {
+        let mut snapshot = sample_snapshot();
+        let first = snapshot.nodes[0].id;
+        snapshot.nodes[0].text = r#"Paths like C:\tmp\ end in \"quoted\" names."#.into();
+        let second = Node {
+            id: Uuid::new_v4(),
+            text: "A trailing backslash \\".into(),
+            ..snapshot.nodes[0].clone()
+        };
+        let long = format!("{}\nSecond \"line\" ends in \\", "word ".repeat(100));
+        let edge = |from: Uuid, to: Uuid, relation: Relation, rationale: String| {
+            crate::graph::SnapshotEdge {
+                from,
+                to,
+                relation,
+                rationale,
+                generator: None,
+                pinned: false,
+            }
+        };
+        snapshot.edges = vec![
+            edge(
+                first,
+                second.id,
+                Relation::PrerequisiteFor,
+                "Escape \\ first.\r\nThen \"quote\".".to_string(),
+            ),
+            edge(second.id, first, Relation::Supports, long),
+        ];
+        snapshot.nodes.push(second);
+
+        for options in [
+            DotExportOptions::default(),
+            DotExportOptions {
+                max_label_chars: Some(80),
+                ..DotExportOptions::default()
+            },
+        ] {
+            let rendered = render_dot_with(&snapshot, &options);
+            ExportFormat::Dot.verify(&rendered).expect("valid DOT");
+            for line in rendered.lines() {
+                let unescaped = line.replace("\\\\", "").replace("\\\"", "");
+                assert!(unescaped.matches('"').count().is_multiple_of(2), "{line}");
+            }
+            let imported = crate::import::dot::parse_dot(&rendered).expect("re-import");
+            assert_eq!(imported.nodes[0].text, snapshot.nodes[0].text);
+            assert_eq!(imported.nodes[1].text.as_ref(), "A trailing backslash \\");
+            assert_eq!(imported.edges[0].rationale, "Escape \\ first. Then \"quote\".");
+        }
+
+        let plain = render_dot(&snapshot);
+        assert!(plain.contains(r#"label="prerequisite_for: Escape \\ first.\nThen \"quote\".""#));
+        let limited = render_dot_with(
+            &snapshot,
+            &DotExportOptions {
+                max_label_chars: Some(80),
+                ..DotExportOptions::default()
+            },
+        );
+        let longest = limited.lines().map(|line| line.chars().count()).max();
+        assert!(longest.is_some_and(|length| length < 200), "{limited}");
+        assert!(limited.contains("supports: word word"));
+        assert!(!limited.contains("Second"));
+    }
+
+    #[tokio::test]
+    async fn failed_artifact_does_not_block_others() {
+        let dir = std::env::temp_dir().join(format!("weaver-export-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let styled = DotExportOptions {
+            color_by_kind:    true,
+            cluster_by_level: true,
+            max_label_chars:  Some(12),
+        };
+
+        let requests = vec![
+            ExportRequest {
+                format:    ExportFormat::Dot,
+                path:      dir.join("first.dot"),
+                check:     true,
+                namespace: None,
+                topic:     None,
+                dot:       styled,
+            },
+            ExportRequest {
+                format:    ExportFormat::Json,
+                path:      dir.join("missing").join("broken.json"),
+                check:     true,
+                namespace: None,
+                topic:     None,
+                dot:       DotExportOptions::default(),
+            },
+            ExportRequest {
+                format:    ExportFormat::Graphml,
+                path:      dir.join("third.graphml"),
+                check:     false,
+                namespace: None,
+                topic:     None,
+                dot:       DotExportOptions::default(),
+            },
+        ];
+
+        let snapshot = sample_snapshot();
+        let outcomes = write_exports(snapshot.clone(), requests).await;
+
+        assert_eq!(outcomes.len(), 3);
+        assert!(outcomes[0].error.is_none());
+        assert!(outcomes[1].error.is_some(), "unwritable path must report an error");
+        assert!(outcomes[2].error.is_none());
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).expect("read export");
+        assert_eq!(read("first.dot"), render_dot_with(&snapshot, &styled));
+        let graphml = read("third.graphml");
+        assert_eq!(graphml, graphml::render_graphml(&snapshot));
+        ExportFormat::Graphml
+            .verify(&graphml)
+            .expect("valid GraphML export");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
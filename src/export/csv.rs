@@ -0,0 +1,212 @@
+use super::verify::VerifyError;
+use crate::{
+    graph::GraphSnapshot,
+    model::{NodeKind, Relation, clean_text},
+};
+
+/// Render the nodes as CSV with columns `id,kind,level,text,tags`.
+///
+/// Tags are joined with `;`, as [`crate::import`] reads them.
+pub fn render_csv_nodes(snapshot: &GraphSnapshot) -> String {
+    let mut output = String::from("id,kind,level,text,tags\n");
+    for node in &snapshot.nodes {
+        let kind = match node.kind {
+            NodeKind::Concept => "concept",
+            NodeKind::LearningOutcome => "learning_outcome",
+        };
+        let tags = node.tags.as_deref().unwrap_or_default().join(";");
+        push_record(
+            &mut output,
+            &[
+                &node.id.to_string(),
+                kind,
+                &node.level.to_string(),
+                &clean_text(&node.text),
+                &tags,
+            ],
+        );
+    }
+    output
+}
+
+/// Render the edges as CSV with columns `from_id,to_id,relation,rationale`.
+pub fn render_csv_edges(snapshot: &GraphSnapshot) -> String {
+    let mut output = String::from("from_id,to_id,relation,rationale\n");
+    for edge in &snapshot.edges {
+        let relation = match edge.relation {
+            Relation::PrerequisiteFor => "prerequisite_for",
+            Relation::Supports => "supports",
+            Relation::RelatedTo => "related_to",
+        };
+        push_record(
+            &mut output,
+            &[
+                &edge.from.to_string(),
+                &edge.to.to_string(),
+                relation,
+                &edge.rationale,
+            ],
+        );
+    }
+    output
+}
+
+/// Split CSV into records of fields, honoring quoted commas, quotes, and
+/// line breaks.
+pub fn parse_csv(contents: &str) -> Result<Vec<Vec<String>>, VerifyError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut chars = contents.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '"' if field.is_empty() => {
+                let start = line;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.next_if_eq(&'"').is_some() => field.push('"'),
+                        Some('"') => break,
+                        Some(char) => {
+                            if char == '\n' {
+                                line += 1;
+                            }
+                            field.push(char);
+                        }
+                        None => {
+                            return Err(VerifyError {
+                                line:    start,
+                                message: "unterminated quoted field".to_string(),
+                            });
+                        }
+                    }
+                }
+                if !matches!(chars.peek(), None | Some(',' | '\n' | '\r')) {
+                    return Err(VerifyError {
+                        line,
+                        message: "expected ',' after quoted field".to_string(),
+                    });
+                }
+            }
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+                line += 1;
+            }
+            _ => field.push(char),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Check that CSV parses and every record has as many fields as the header.
+pub fn verify_csv(contents: &str) -> Result<(), VerifyError> {
+    let records = parse_csv(contents)?;
+    let Some(header) = records.first() else {
+        return Err(VerifyError {
+            line:    1,
+            message: "missing header row".to_string(),
+        });
+    };
+    match records
+        .iter()
+        .position(|record| record.len() != header.len())
+    {
+        None => Ok(()),
+        Some(index) => Err(VerifyError {
+            line:    index + 1,
+            message: format!(
+                "record {} has {} field(s), expected {}",
+                index + 1,
+                records[index].len(),
+                header.len()
+            ),
+        }),
+    }
+}
+
+fn push_record(output: &mut String, fields: &[&str]) {
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        output.push_str(&quote(field));
+    }
+    output.push('\n');
+}
+
+/// Quote a field when it holds a delimiter, quote, or line break.
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        graph::SnapshotEdge,
+        model::{Granularity, Node},
+    };
+
+    #[test]
+    fn quoted_text_round_trips_through_the_parser() {
+        let node = Node {
+            id:          Uuid::new_v4(),
+            kind:        NodeKind::LearningOutcome,
+            granularity: Granularity::Sentence,
+            level:       1,
+            text:        "I can say \"stop\", then recurse.".into(),
+            tags:        Some(vec!["base, cases".to_string(), "proofs".to_string()]),
+            generator:   None,
+            namespace:   None,
+            topic:       None,
+            pinned:      false,
+        };
+        let snapshot = GraphSnapshot {
+            edges: vec![SnapshotEdge {
+                from:      node.id,
+                to:        node.id,
+                relation:  Relation::Supports,
+                rationale: "First line, \"quoted\".\nSecond line.".to_string(),
+                generator: None,
+                pinned:    false,
+            }],
+            nodes: vec![node.clone()],
+        };
+
+        let nodes = render_csv_nodes(&snapshot);
+        verify_csv(&nodes).expect("well-formed nodes CSV");
+        let records = parse_csv(&nodes).expect("parse");
+        assert_eq!(
+            records[1],
+            vec![
+                node.id.to_string(),
+                "learning_outcome".to_string(),
+                "1".to_string(),
+                "I can say \"stop\", then recurse.".to_string(),
+                "base, cases;proofs".to_string(),
+            ]
+        );
+
+        let edges = render_csv_edges(&snapshot);
+        verify_csv(&edges).expect("well-formed edges CSV");
+        let records = parse_csv(&edges).expect("parse");
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1][3], "First line, \"quoted\".\nSecond line.");
+
+        assert!(verify_csv("a,b\n1,2,3\n").is_err());
+        assert!(verify_csv("a,b\n\"open,2\n").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::verify::VerifyError;
+use crate::{
+    graph::GraphSnapshot,
+    model::{NodeKind, Relation},
+};
+
+/// Cytoscape.js `elements` document; node ids are the graph's UUIDs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CytoscapeExport {
+    pub elements: CytoscapeElements,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CytoscapeElements {
+    pub nodes: Vec<CytoscapeElement<CytoscapeNode>>,
+    pub edges: Vec<CytoscapeElement<CytoscapeEdge>>,
+}
+
+/// Cytoscape.js keeps element fields under `data`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CytoscapeElement<T> {
+    pub data: T,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CytoscapeNode {
+    pub id:    Uuid,
+    pub label: String,
+    pub kind:  NodeKind,
+    pub level: u8,
+    pub tags:  Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CytoscapeEdge {
+    pub source:    Uuid,
+    pub target:    Uuid,
+    pub relation:  Relation,
+    pub rationale: String,
+}
+
+/// Render the snapshot as pretty-printed Cytoscape.js JSON.
+pub fn render_cytoscape(snapshot: &GraphSnapshot) -> String {
+    let mut output = serde_json::to_string_pretty(&build_cytoscape(snapshot)).unwrap_or_default();
+    output.push('\n');
+    output
+}
+
+pub fn build_cytoscape(snapshot: &GraphSnapshot) -> CytoscapeExport {
+    let nodes = snapshot
+        .nodes
+        .iter()
+        .map(|node| CytoscapeElement {
+            data: CytoscapeNode {
+                id:    node.id,
+                label: node.text.to_string(),
+                kind:  node.kind.clone(),
+                level: node.level,
+                tags:  node.tags.clone().unwrap_or_default(),
+            },
+        })
+        .collect();
+    let edges = snapshot
+        .edges
+        .iter()
+        .map(|edge| CytoscapeElement {
+            data: CytoscapeEdge {
+                source:    edge.from,
+                target:    edge.to,
+                relation:  edge.relation.clone(),
+                rationale: edge.rationale.clone(),
+            },
+        })
+        .collect();
+    CytoscapeExport {
+        elements: CytoscapeElements { nodes, edges },
+    }
+}
+
+/// Re-read a Cytoscape.js document and check it matches the expected shape.
+pub fn verify_cytoscape(contents: &str) -> Result<(), VerifyError> {
+    serde_json::from_str::<CytoscapeExport>(contents)
+        .map(|_| ())
+        .map_err(|err| VerifyError {
+            line:    err.line(),
+            message: err.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        graph::SnapshotEdge,
+        model::{Granularity, Node},
+    };
+
+    fn node(kind: NodeKind, text: &str) -> Node {
+        Node {
+            id: Uuid::new_v4(),
+            kind,
+            granularity: Granularity::Sentence,
+            level: 1,
+            text: text.into(),
+            tags: Some(vec!["iteration".to_string()]),
+            generator: None,
+            namespace: None,
+            topic: None,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn edge_endpoints_resolve_to_nodes_in_the_document() {
+        let loops = node(NodeKind::Concept, "Loops repeat statements.");
+        let ranges = node(NodeKind::Concept, "Ranges bound a loop.");
+        let outcome = node(NodeKind::LearningOutcome, "I can write a counted loop.");
+        let edge = |from: &Node, to: &Node, relation| SnapshotEdge {
+            from: from.id,
+            to: to.id,
+            relation,
+            rationale: format!("{} then {}", from.text, to.text),
+            generator: None,
+            pinned: false,
+        };
+        let snapshot = GraphSnapshot {
+            edges: vec![
+                edge(&loops, &ranges, Relation::PrerequisiteFor),
+                edge(&ranges, &outcome, Relation::Supports),
+            ],
+            nodes: vec![loops.clone(), ranges, outcome],
+        };
+
+        let rendered = render_cytoscape(&snapshot);
+        verify_cytoscape(&rendered).expect("rendered document reparses");
+        let parsed: CytoscapeExport = serde_json::from_str(&rendered).expect("parse");
+        let ids: HashSet<Uuid> = parsed.elements.nodes.iter().map(|n| n.data.id).collect();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(parsed.elements.edges.len(), 2);
+        for edge in &parsed.elements.edges {
+            assert!(ids.contains(&edge.data.source) && ids.contains(&edge.data.target));
+        }
+        let first = &parsed.elements.nodes[0].data;
+        assert_eq!((first.id, first.label.as_str()), (loops.id, "Loops repeat statements."));
+        assert_eq!(first.tags, vec!["iteration".to_string()]);
+
+        let raw: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(raw["elements"]["nodes"][0]["data"]["id"], loops.id.to_string());
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+
+use super::verify::VerifyError;
+use crate::{
+    graph::GraphSnapshot,
+    model::{NodeKind, Relation, clean_text},
+};
+
+/// Attributes declared before the graph, as `(class, id, type)`.
+const ATTRIBUTES: &[(&str, &str, &str)] = &[
+    ("node", "kind", "string"),
+    ("node", "level", "integer"),
+    ("node", "tags", "string"),
+    ("edge", "relation", "string"),
+    ("edge", "rationale", "string"),
+];
+
+/// Render the snapshot as a GEXF 1.3 document with typed attributes.
+///
+/// Node labels are the cleaned texts and tags are joined with `;`. Every
+/// edge is directed, related-to included.
+pub fn render_gexf(snapshot: &GraphSnapshot) -> String {
+    let mut output = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gexf xmlns=\"http://gexf.net/1.3\" \
+         version=\"1.3\">\n  <graph mode=\"static\" defaultedgetype=\"directed\">\n",
+    );
+    for class in ["node", "edge"] {
+        output.push_str(&format!("    <attributes class=\"{class}\">\n"));
+        for (_, id, kind) in ATTRIBUTES.iter().filter(|(owner, ..)| *owner == class) {
+            output.push_str(&format!(
+                "      <attribute id=\"{id}\" title=\"{id}\" type=\"{kind}\"/>\n"
+            ));
+        }
+        output.push_str("    </attributes>\n");
+    }
+
+    output.push_str(&format!("    <nodes count=\"{}\">\n", snapshot.nodes.len()));
+    for node in &snapshot.nodes {
+        let kind = match node.kind {
+            NodeKind::Concept => "concept",
+            NodeKind::LearningOutcome => "learning_outcome",
+        };
+        output.push_str(&format!(
+            "      <node id=\"{}\" label=\"{}\">\n        <attvalues>\n",
+            node.id,
+            escape(&clean_text(&node.text))
+        ));
+        push_value(&mut output, "kind", kind);
+        push_value(&mut output, "level", &node.level.to_string());
+        if let Some(tags) = node.tags.as_ref().filter(|tags| !tags.is_empty()) {
+            push_value(&mut output, "tags", &tags.join(";"));
+        }
+        output.push_str("        </attvalues>\n      </node>\n");
+    }
+    output.push_str("    </nodes>\n");
+
+    output.push_str(&format!("    <edges count=\"{}\">\n", snapshot.edges.len()));
+    for (index, edge) in snapshot.edges.iter().enumerate() {
+        let relation = match edge.relation {
+            Relation::PrerequisiteFor => "prerequisite_for",
+            Relation::Supports => "supports",
+            Relation::RelatedTo => "related_to",
+        };
+        output.push_str(&format!(
+            "      <edge id=\"{index}\" source=\"{}\" target=\"{}\" type=\"directed\">\n        \
+             <attvalues>\n",
+            edge.from, edge.to
+        ));
+        push_value(&mut output, "relation", relation);
+        push_value(&mut output, "rationale", &edge.rationale);
+        output.push_str("        </attvalues>\n      </edge>\n");
+    }
+    output.push_str("    </edges>\n  </graph>\n</gexf>\n");
+    output
+}
+
+/// Re-read a GEXF document and check its structure: declared counts match,
+/// edges join known nodes, and every attvalue names a declared attribute.
+pub fn verify_gexf(contents: &str) -> Result<(), VerifyError> {
+    let document = roxmltree::Document::parse(contents).map_err(|err| VerifyError {
+        line:    err.pos().row as usize,
+        message: err.to_string(),
+    })?;
+    let line = |node: roxmltree::Node| document.text_pos_at(node.range().start).row as usize;
+    let fail = |node: roxmltree::Node, message: String| {
+        Err(VerifyError {
+            line: line(node),
+            message,
+        })
+    };
+    let root = document.root_element();
+    if !root.has_tag_name("gexf") {
+        return fail(root, "root element is not <gexf>".to_string());
+    }
+
+    let mut declared = HashSet::new();
+    for attributes in root
+        .descendants()
+        .filter(|node| node.has_tag_name("attributes"))
+    {
+        let class = attributes.attribute("class").unwrap_or("node");
+        for attribute in attributes
+            .children()
+            .filter(|node| node.has_tag_name("attribute"))
+        {
+            declared.insert((class, attribute.attribute("id").unwrap_or_default()));
+        }
+    }
+
+    let mut node_ids = HashSet::new();
+    for (list, item) in [("nodes", "node"), ("edges", "edge")] {
+        for parent in root.descendants().filter(|node| node.has_tag_name(list)) {
+            let items: Vec<_> = parent
+                .children()
+                .filter(|node| node.has_tag_name(item))
+                .collect();
+            if let Some(count) = parent.attribute("count")
+                && count.parse() != Ok(items.len())
+            {
+                return fail(
+                    parent,
+                    format!("<{list}> declares {count} but holds {}", items.len()),
+                );
+            }
+            for element in items {
+                if item == "node" {
+                    node_ids.insert(element.attribute("id").unwrap_or_default());
+                } else if let Some(missing) = ["source", "target"]
+                    .into_iter()
+                    .map(|end| element.attribute(end).unwrap_or_default())
+                    .find(|end| !node_ids.contains(end))
+                {
+                    return fail(element, format!("edge references unknown node '{missing}'"));
+                }
+                for value in element
+                    .descendants()
+                    .filter(|node| node.has_tag_name("attvalue"))
+                {
+                    let name = value.attribute("for").unwrap_or_default();
+                    if !declared.contains(&(item, name)) {
+                        return fail(value, format!("undeclared {item} attribute '{name}'"));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn push_value(output: &mut String, key: &str, value: &str) {
+    output.push_str(&format!("          <attvalue for=\"{key}\" value=\"{}\"/>\n", escape(value)));
+}
+
+/// Escape text for use in a quoted attribute.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        graph::SnapshotEdge,
+        model::{Granularity, Node},
+    };
+
+    fn node(kind: NodeKind, text: &str) -> Node {
+        Node {
+            id: Uuid::new_v4(),
+            kind,
+            granularity: Granularity::Sentence,
+            level: 1,
+            text: text.into(),
+            tags: Some(vec!["syntax".to_string()]),
+            generator: None,
+            namespace: None,
+            topic: None,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn gexf_declares_attributes_and_counts() {
+        let generic = node(NodeKind::Concept, "Vec<T> & \"slices\" share 'views'.");
+        let outcome = node(NodeKind::LearningOutcome, "I can index a Vec<T>.");
+        let snapshot = GraphSnapshot {
+            edges: vec![SnapshotEdge {
+                from:      generic.id,
+                to:        outcome.id,
+                relation:  Relation::Supports,
+                rationale: "Indexing needs <T> & bounds.\nThen slicing.".to_string(),
+                generator: None,
+                pinned:    false,
+            }],
+            nodes: vec![generic, outcome],
+        };
+
+        let rendered = render_gexf(&snapshot);
+        verify_gexf(&rendered).expect("well-formed GEXF");
+        let document = roxmltree::Document::parse(&rendered).expect("parse");
+        let count = |name: &str| {
+            document
+                .descendants()
+                .filter(|element| element.has_tag_name(name))
+                .count()
+        };
+        assert_eq!((count("attribute"), count("node"), count("edge")), (5, 2, 1));
+        let label = document
+            .descendants()
+            .find(|element| element.has_tag_name("node"))
+            .and_then(|element| element.attribute("label"));
+        assert_eq!(label, Some("Vec<T> & \"slices\" share 'views'."));
+        let rationale = document
+            .descendants()
+            .find(|element| element.attribute("for") == Some("rationale"))
+            .and_then(|element| element.attribute("value"));
+        assert_eq!(rationale, Some("Indexing needs <T> & bounds.\nThen slicing."));
+        assert!(rendered.contains("<attribute id=\"level\" title=\"level\" type=\"integer\"/>"));
+
+        let undeclared = rendered.replace("for=\"kind\"", "for=\"shape\"");
+        assert!(verify_gexf(&undeclared).is_err());
+        let miscounted = rendered.replace("<nodes count=\"2\">", "<nodes count=\"3\">");
+        assert!(verify_gexf(&miscounted).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use super::verify::VerifyError;
+use crate::{
+    graph::GraphSnapshot,
+    model::{NodeKind, Relation, clean_text},
+};
+
+/// Data keys declared before the graph, as `(id, for, name, type)`.
+const KEYS: &[(&str, &str, &str, &str)] = &[
+    ("kind", "node", "kind", "string"),
+    ("level", "node", "level", "int"),
+    ("text", "node", "text", "string"),
+    ("tags", "node", "tags", "string"),
+    ("relation", "edge", "relation", "string"),
+    ("rationale", "edge", "rationale", "string"),
+];
+
+/// Render the snapshot as a GraphML document.
+///
+/// Tags are joined with `;`. Related-to edges are marked undirected so
+/// importers do not draw an arrowhead.
+pub fn render_graphml(snapshot: &GraphSnapshot) -> String {
+    let mut output = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml \
+         xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+    );
+    for (id, domain, name, kind) in KEYS {
+        output.push_str(&format!(
+            "  <key id=\"{id}\" for=\"{domain}\" attr.name=\"{name}\" attr.type=\"{kind}\"/>\n"
+        ));
+    }
+    output.push_str("  <graph id=\"weaver\" edgedefault=\"directed\">\n");
+
+    for node in &snapshot.nodes {
+        let kind = match node.kind {
+            NodeKind::Concept => "concept",
+            NodeKind::LearningOutcome => "learning_outcome",
+        };
+        output.push_str(&format!("    <node id=\"{}\">\n", node.id));
+        push_data(&mut output, "kind", kind);
+        push_data(&mut output, "level", &node.level.to_string());
+        push_data(&mut output, "text", &clean_text(&node.text));
+        if let Some(tags) = node.tags.as_ref().filter(|tags| !tags.is_empty()) {
+            push_data(&mut output, "tags", &tags.join(";"));
+        }
+        output.push_str("    </node>\n");
+    }
+
+    for edge in &snapshot.edges {
+        let relation = match edge.relation {
+            Relation::PrerequisiteFor => "prerequisite_for",
+            Relation::Supports => "supports",
+            Relation::RelatedTo => "related_to",
+        };
+        let directed = if edge.relation.is_symmetric() {
+            " directed=\"false\""
+        } else {
+            ""
+        };
+        output.push_str(&format!(
+            "    <edge source=\"{}\" target=\"{}\"{directed}>\n",
+            edge.from, edge.to
+        ));
+        push_data(&mut output, "relation", relation);
+        push_data(&mut output, "rationale", &edge.rationale);
+        output.push_str("    </edge>\n");
+    }
+
+    output.push_str("  </graph>\n</graphml>\n");
+    output
+}
+
+/// Re-read a GraphML document and check it is well-formed.
+pub fn verify_graphml(contents: &str) -> Result<(), VerifyError> {
+    let document = roxmltree::Document::parse(contents).map_err(|err| VerifyError {
+        line:    err.pos().row as usize,
+        message: err.to_string(),
+    })?;
+    if document.root_element().tag_name().name() == "graphml" {
+        Ok(())
+    } else {
+        Err(VerifyError {
+            line:    1,
+            message: "root element is not <graphml>".to_string(),
+        })
+    }
+}
+
+fn push_data(output: &mut String, key: &str, value: &str) {
+    output.push_str(&format!("      <data key=\"{key}\">{}</data>\n", escape(value)));
+}
+
+/// Escape text for use in element content or a quoted attribute.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        graph::SnapshotEdge,
+        model::{Granularity, Node},
+    };
+
+    fn node(kind: NodeKind, text: &str) -> Node {
+        Node {
+            id: Uuid::new_v4(),
+            kind,
+            granularity: Granularity::Sentence,
+            level: 2,
+            text: text.into(),
+            tags: Some(vec!["syntax".to_string(), "types".to_string()]),
+            generator: None,
+            namespace: None,
+            topic: None,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn graphml_declares_keys_and_escapes_text() {
+        let generic = node(NodeKind::Concept, "Vec<T> & \"slices\" share 'views'.");
+        let outcome = node(NodeKind::LearningOutcome, "I can index a Vec<T>.");
+        let snapshot = GraphSnapshot {
+            edges: vec![
+                SnapshotEdge {
+                    from:      generic.id,
+                    to:        outcome.id,
+                    relation:  Relation::Supports,
+                    rationale: "Indexing needs <T> & bounds.\nThen slicing.".to_string(),
+                    generator: None,
+                    pinned:    false,
+                },
+                SnapshotEdge {
+                    from:      generic.id,
+                    to:        outcome.id,
+                    relation:  Relation::RelatedTo,
+                    rationale: "Both use vectors.".to_string(),
+                    generator: None,
+                    pinned:    false,
+                },
+            ],
+            nodes: vec![generic, outcome],
+        };
+
+        let rendered = render_graphml(&snapshot);
+        verify_graphml(&rendered).expect("well-formed GraphML");
+        let document = roxmltree::Document::parse(&rendered).expect("parse");
+        let count = |name: &str| {
+            document
+                .descendants()
+                .filter(|element| element.has_tag_name(name))
+                .count()
+        };
+        assert_eq!((count("key"), count("node"), count("edge")), (6, 2, 2));
+
+        let text = document
+            .descendants()
+            .find(|element| element.attribute("key") == Some("text"))
+            .and_then(|element| element.text());
+        assert_eq!(text, Some("Vec<T> & \"slices\" share 'views'."));
+        let rationale = document
+            .descendants()
+            .find(|element| element.attribute("key") == Some("rationale"))
+            .and_then(|element| element.text());
+        assert_eq!(rationale, Some("Indexing needs <T> & bounds.\nThen slicing."));
+        assert!(rendered.contains("<data key=\"tags\">syntax;types</data>"));
+        assert!(rendered.contains("directed=\"false\""));
+
+        assert!(verify_graphml("<graphml><graph></graphml>").is_err());
+    }
+}
@@ -0,0 +1,319 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::verify::VerifyError;
+use crate::{
+    graph::GraphSnapshot,
+    model::{Node, NodeKind, Relation},
+};
+
+/// Learning-outcome-centric document for LMS import.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LmsExport {
+    pub metadata: LmsMetadata,
+    pub outcomes: Vec<LmsOutcome>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LmsMetadata {
+    pub topic:             Option<String>,
+    /// Seconds since the Unix epoch.
+    pub generated_at:      u64,
+    pub outcome_count:     usize,
+    pub concept_count:     usize,
+    /// Outcomes whose supporting concepts sit on a prerequisite cycle.
+    pub unavailable_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LmsOutcome {
+    pub id:                  Uuid,
+    pub text:                String,
+    pub level:               u8,
+    pub tags:                Vec<String>,
+    /// Supporting concepts in pathway order.
+    pub supports:            Vec<LmsSupport>,
+    /// Prerequisites of the supports, earliest first; empty when unavailable.
+    pub prerequisites:       Vec<LmsConcept>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pathway_unavailable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LmsSupport {
+    pub id:        Uuid,
+    pub text:      String,
+    pub rationale: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LmsConcept {
+    pub id:   Uuid,
+    pub text: String,
+}
+
+/// Render the snapshot as pretty-printed LMS JSON.
+pub fn render_lms(snapshot: &GraphSnapshot, topic: Option<&str>) -> String {
+    let mut export = build_lms(snapshot, topic);
+    export.metadata.generated_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    let mut output = serde_json::to_string_pretty(&export).unwrap_or_default();
+    output.push('\n');
+    output
+}
+
+/// Build the LMS document with `generated_at` left at zero.
+pub fn build_lms(snapshot: &GraphSnapshot, topic: Option<&str>) -> LmsExport {
+    let nodes: HashMap<Uuid, &Node> = snapshot.nodes.iter().map(|node| (node.id, node)).collect();
+    let mut prerequisites_of: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    let mut supports_of: HashMap<Uuid, Vec<(Uuid, &str)>> = HashMap::new();
+    for edge in &snapshot.edges {
+        match edge.relation {
+            Relation::PrerequisiteFor => {
+                prerequisites_of.entry(edge.to).or_default().push(edge.from)
+            }
+            Relation::Supports => supports_of
+                .entry(edge.to)
+                .or_default()
+                .push((edge.from, &edge.rationale)),
+            Relation::RelatedTo => {}
+        }
+    }
+
+    let mut outcomes = Vec::new();
+    let mut concept_count = 0;
+    for node in &snapshot.nodes {
+        match node.kind {
+            NodeKind::Concept => concept_count += 1,
+            NodeKind::LearningOutcome => outcomes.push(outcome_entry(
+                node,
+                supports_of
+                    .get(&node.id)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+                &prerequisites_of,
+                &nodes,
+            )),
+        }
+    }
+
+    LmsExport {
+        metadata: LmsMetadata {
+            topic: topic.map(str::to_string),
+            generated_at: 0,
+            outcome_count: outcomes.len(),
+            concept_count,
+            unavailable_count: outcomes
+                .iter()
+                .filter(|outcome| outcome.pathway_unavailable)
+                .count(),
+        },
+        outcomes,
+    }
+}
+
+/// Re-read an LMS document and check it matches the schema.
+pub fn verify_lms(contents: &str) -> Result<(), VerifyError> {
+    serde_json::from_str::<LmsExport>(contents)
+        .map(|_| ())
+        .map_err(|err| VerifyError {
+            line:    err.line(),
+            message: err.to_string(),
+        })
+}
+
+fn outcome_entry(
+    outcome: &Node,
+    supports: &[(Uuid, &str)],
+    prerequisites_of: &HashMap<Uuid, Vec<Uuid>>,
+    nodes: &HashMap<Uuid, &Node>,
+) -> LmsOutcome {
+    let text_of = |id: &Uuid| {
+        nodes
+            .get(id)
+            .map(|node| node.text.to_string())
+            .unwrap_or_default()
+    };
+    let rationales: HashMap<Uuid, &str> = supports.iter().copied().collect();
+    let support_ids: HashSet<Uuid> = rationales.keys().copied().collect();
+
+    let order = pathway(&support_ids, prerequisites_of, &text_of);
+    let pathway_unavailable = order.is_none();
+    let order = order.unwrap_or_else(|| {
+        let mut fallback: Vec<_> = support_ids.iter().copied().collect();
+        fallback.sort_by_key(|id| (text_of(id), *id));
+        fallback
+    });
+
+    let mut entry = LmsOutcome {
+        id: outcome.id,
+        text: outcome.text.to_string(),
+        level: outcome.level,
+        tags: outcome.tags.clone().unwrap_or_default(),
+        supports: Vec::new(),
+        prerequisites: Vec::new(),
+        pathway_unavailable,
+    };
+    for id in order {
+        match rationales.get(&id) {
+            Some(rationale) => entry.supports.push(LmsSupport {
+                id,
+                text: text_of(&id),
+                rationale: rationale.to_string(),
+            }),
+            None => entry.prerequisites.push(LmsConcept {
+                id,
+                text: text_of(&id),
+            }),
+        }
+    }
+    entry
+}
+
+/// Topological order of `targets` and everything upstream of them.
+///
+/// Ties break by text so the output is stable; `None` means the upstream
+/// subgraph contains a cycle.
+fn pathway(
+    targets: &HashSet<Uuid>,
+    prerequisites_of: &HashMap<Uuid, Vec<Uuid>>,
+    text_of: &impl Fn(&Uuid) -> String,
+) -> Option<Vec<Uuid>> {
+    let mut members: HashSet<Uuid> = HashSet::new();
+    let mut stack: Vec<Uuid> = targets.iter().copied().collect();
+    while let Some(id) = stack.pop() {
+        if members.insert(id) {
+            stack.extend(prerequisites_of.get(&id).into_iter().flatten().copied());
+        }
+    }
+
+    let mut indegree: HashMap<Uuid, usize> = members.iter().map(|id| (*id, 0)).collect();
+    let mut dependents: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for id in &members {
+        for prerequisite in prerequisites_of.get(id).into_iter().flatten() {
+            *indegree.entry(*id).or_default() += 1;
+            dependents.entry(*prerequisite).or_default().push(*id);
+        }
+    }
+
+    let mut ready: BTreeSet<(String, Uuid)> = indegree
+        .iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(id, _)| (text_of(id), *id))
+        .collect();
+    let mut order = Vec::with_capacity(members.len());
+    while let Some((_, id)) = ready.pop_first() {
+        order.push(id);
+        for dependent in dependents.get(&id).into_iter().flatten() {
+            let degree = indegree.entry(*dependent).or_default();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.insert((text_of(dependent), *dependent));
+            }
+        }
+    }
+
+    (order.len() == members.len()).then_some(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph::SnapshotEdge, model::Granularity};
+
+    fn node(kind: NodeKind, text: &str) -> Node {
+        Node {
+            id: Uuid::new_v4(),
+            kind,
+            granularity: Granularity::Sentence,
+            level: 1,
+            text: text.into(),
+            tags: None,
+            generator: None,
+            namespace: None,
+            topic: None,
+            pinned: false,
+        }
+    }
+
+    fn edge(from: &Node, to: &Node, relation: Relation) -> SnapshotEdge {
+        SnapshotEdge {
+            from: from.id,
+            to: to.id,
+            relation,
+            rationale: format!("{} then {}", from.text, to.text),
+            generator: None,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn outcomes_nest_supports_and_ordered_prerequisites() {
+        let basics = node(NodeKind::Concept, "Basics come first.");
+        let middle = node(NodeKind::Concept, "Middle builds on basics.");
+        let support = node(NodeKind::Concept, "Support uses the middle.");
+        let outcome = node(NodeKind::LearningOutcome, "Apply the support.");
+        let lonely = node(NodeKind::LearningOutcome, "Nothing supports this.");
+        let snapshot = GraphSnapshot {
+            edges: vec![
+                edge(&middle, &support, Relation::PrerequisiteFor),
+                edge(&basics, &middle, Relation::PrerequisiteFor),
+                edge(&support, &outcome, Relation::Supports),
+            ],
+            nodes: vec![
+                basics.clone(),
+                middle.clone(),
+                support.clone(),
+                outcome,
+                lonely,
+            ],
+        };
+
+        let export = build_lms(&snapshot, Some("Testing"));
+        assert_eq!(export.metadata.outcome_count, 2);
+        assert_eq!(export.metadata.concept_count, 3);
+
+        let supported = &export.outcomes[0];
+        assert!(!supported.pathway_unavailable);
+        assert_eq!(supported.supports.len(), 1);
+        assert_eq!(supported.supports[0].id, support.id);
+        let prerequisites: Vec<_> = supported.prerequisites.iter().map(|c| c.id).collect();
+        assert_eq!(prerequisites, vec![basics.id, middle.id]);
+
+        let unsupported = &export.outcomes[1];
+        assert!(unsupported.supports.is_empty() && unsupported.prerequisites.is_empty());
+        assert!(!unsupported.pathway_unavailable);
+
+        verify_lms(&render_lms(&snapshot, Some("Testing"))).expect("rendered LMS JSON reparses");
+    }
+
+    #[test]
+    fn cyclic_prerequisites_flag_the_outcome_instead_of_failing() {
+        let first = node(NodeKind::Concept, "First needs second.");
+        let second = node(NodeKind::Concept, "Second needs first.");
+        let outcome = node(NodeKind::LearningOutcome, "Use both ideas.");
+        let snapshot = GraphSnapshot {
+            edges: vec![
+                edge(&first, &second, Relation::PrerequisiteFor),
+                edge(&second, &first, Relation::PrerequisiteFor),
+                edge(&first, &outcome, Relation::Supports),
+            ],
+            nodes: vec![first.clone(), second, outcome],
+        };
+
+        let export = build_lms(&snapshot, None);
+        let flagged = &export.outcomes[0];
+        assert!(flagged.pathway_unavailable);
+        assert_eq!(export.metadata.unavailable_count, 1);
+        assert_eq!(flagged.supports[0].id, first.id);
+        assert!(flagged.prerequisites.is_empty());
+    }
+}
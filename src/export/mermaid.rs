@@ -0,0 +1,196 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::verify::VerifyError;
+use crate::{
+    graph::GraphSnapshot,
+    model::{Relation, clean_text},
+};
+
+/// Labels longer than this many characters are cut and end in `…`.
+const LABEL_CHARS: usize = 60;
+
+/// Render the snapshot as a Mermaid `flowchart TD`, one subgraph per level.
+///
+/// Nodes get ids `n1`, `n2`, ... in snapshot order. Prerequisites draw as
+/// `-->`, supports as `-.->`, and related-to as an undirected `---`.
+pub fn render_mermaid(snapshot: &GraphSnapshot) -> String {
+    let ids: HashMap<_, _> = snapshot
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.id, format!("n{}", index + 1)))
+        .collect();
+    let mut levels: BTreeMap<u8, Vec<String>> = BTreeMap::new();
+    for node in &snapshot.nodes {
+        levels.entry(node.level).or_default().push(format!(
+            "    {}[\"{}\"]\n",
+            ids[&node.id],
+            escape(&truncate(&clean_text(&node.text)))
+        ));
+    }
+
+    let mut output = String::from("flowchart TD\n");
+    for (level, nodes) in levels {
+        output.push_str(&format!("  subgraph level_{level}[\"Level {level}\"]\n"));
+        for node in nodes {
+            output.push_str(&node);
+        }
+        output.push_str("  end\n");
+    }
+    for edge in &snapshot.edges {
+        let (Some(from), Some(to)) = (ids.get(&edge.from), ids.get(&edge.to)) else {
+            continue;
+        };
+        let arrow = match edge.relation {
+            Relation::PrerequisiteFor => "-->",
+            Relation::Supports => "-.->",
+            Relation::RelatedTo => "---",
+        };
+        output.push_str(&format!("  {from} {arrow} {to}\n"));
+    }
+    output
+}
+
+/// Check a Mermaid flowchart for the header, balanced subgraphs, and
+/// closed label quotes.
+pub fn verify_mermaid(contents: &str) -> Result<(), VerifyError> {
+    let mut lines = contents.lines().enumerate();
+    if !lines
+        .next()
+        .is_some_and(|(_, line)| line.trim_start().starts_with("flowchart"))
+    {
+        return Err(VerifyError {
+            line:    1,
+            message: "expected a flowchart header".to_string(),
+        });
+    }
+    let mut depth = 0usize;
+    for (index, line) in lines {
+        let line_number = index + 1;
+        let statement = line.trim();
+        if !statement.matches('"').count().is_multiple_of(2) {
+            return Err(VerifyError {
+                line:    line_number,
+                message: "unbalanced quote in label".to_string(),
+            });
+        }
+        if statement.starts_with("subgraph ") {
+            depth += 1;
+        } else if statement == "end" {
+            depth = depth.checked_sub(1).ok_or_else(|| VerifyError {
+                line:    line_number,
+                message: "'end' without a subgraph".to_string(),
+            })?;
+        }
+    }
+    if depth == 0 {
+        Ok(())
+    } else {
+        Err(VerifyError {
+            line:    contents.lines().count(),
+            message: format!("{depth} subgraph(s) never closed"),
+        })
+    }
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= LABEL_CHARS {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(LABEL_CHARS - 1).collect();
+    cut.truncate(cut.trim_end().len());
+    cut.push('…');
+    cut
+}
+
+/// Replace characters Mermaid would read as syntax with entity codes.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '#' => escaped.push_str("#35;"),
+            '"' => escaped.push_str("#quot;"),
+            '<' => escaped.push_str("#lt;"),
+            '>' => escaped.push_str("#gt;"),
+            '[' => escaped.push_str("#91;"),
+            ']' => escaped.push_str("#93;"),
+            '(' => escaped.push_str("#40;"),
+            ')' => escaped.push_str("#41;"),
+            '{' => escaped.push_str("#123;"),
+            '}' => escaped.push_str("#125;"),
+            '|' => escaped.push_str("#124;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        graph::SnapshotEdge,
+        model::{Granularity, Node, NodeKind},
+    };
+
+    fn node(level: u8, text: &str) -> Node {
+        Node {
+            id: Uuid::new_v4(),
+            kind: NodeKind::Concept,
+            granularity: Granularity::Sentence,
+            level,
+            text: text.into(),
+            tags: None,
+            generator: None,
+            namespace: None,
+            topic: None,
+            pinned: false,
+        }
+    }
+
+    fn edge(from: &Node, to: &Node, relation: Relation) -> SnapshotEdge {
+        SnapshotEdge {
+            from: from.id,
+            to: to.id,
+            relation,
+            rationale: String::new(),
+            generator: None,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn special_characters_stay_inside_labels() {
+        let quoted = node(0, "A \"quoted\" [word] (in parens) {braced} | #1.");
+        let long = node(1, &format!("Long {}.", "word ".repeat(20)));
+        let snapshot = GraphSnapshot {
+            edges: vec![
+                edge(&quoted, &long, Relation::PrerequisiteFor),
+                edge(&long, &quoted, Relation::Supports),
+                edge(&quoted, &long, Relation::RelatedTo),
+            ],
+            nodes: vec![quoted, long],
+        };
+
+        let rendered = render_mermaid(&snapshot);
+        verify_mermaid(&rendered).expect("well-formed flowchart");
+        let labels: Vec<&str> = rendered
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("n")?.split_once("[\""))
+            .map(|(_, label)| label.strip_suffix("\"]").expect("label closes"))
+            .collect();
+        assert_eq!(labels.len(), 2);
+        for label in &labels {
+            assert!(!label.contains(['"', '[', ']', '(', ')', '{', '}', '|']), "{label}");
+        }
+        assert!(labels[0].contains("#quot;quoted#quot;") && labels[0].ends_with("#35;1."));
+        assert!(labels[1].ends_with('…') && labels[1].chars().count() <= LABEL_CHARS);
+        assert!(rendered.contains("  n1 --> n2\n  n2 -.-> n1\n  n1 --- n2\n"));
+        assert!(rendered.contains("subgraph level_0[\"Level 0\"]"));
+
+        assert!(verify_mermaid("flowchart TD\n  subgraph a\n    n1[\"open]\n  end\n").is_err());
+        assert!(verify_mermaid("flowchart TD\n  subgraph a\n").is_err());
+    }
+}
@@ -0,0 +1,529 @@
+use std::fmt;
+
+/// A problem found while re-reading an exported artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    /// 1-based line of the offending token.
+    pub line:    usize,
+    pub message: String,
+}
+
+impl VerifyError {
+    pub(crate) fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token {
+    /// Identifier, numeral, quoted or HTML string.
+    Id(String),
+    Keyword(Keyword),
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Semi,
+    Comma,
+    Colon,
+    Equals,
+    Arrow,
+    DashDash,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Keyword {
+    Strict,
+    Graph,
+    Digraph,
+    Node,
+    Edge,
+    Subgraph,
+}
+
+impl Keyword {
+    fn parse(word: &str) -> Option<Self> {
+        match word.to_ascii_lowercase().as_str() {
+            "strict" => Some(Keyword::Strict),
+            "graph" => Some(Keyword::Graph),
+            "digraph" => Some(Keyword::Digraph),
+            "node" => Some(Keyword::Node),
+            "edge" => Some(Keyword::Edge),
+            "subgraph" => Some(Keyword::Subgraph),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Id(id) => write!(f, "'{id}'"),
+            Token::Keyword(keyword) => write!(f, "keyword {keyword:?}"),
+            Token::LBrace => f.write_str("'{'"),
+            Token::RBrace => f.write_str("'}'"),
+            Token::LBracket => f.write_str("'['"),
+            Token::RBracket => f.write_str("']'"),
+            Token::Semi => f.write_str("';'"),
+            Token::Comma => f.write_str("','"),
+            Token::Colon => f.write_str("':'"),
+            Token::Equals => f.write_str("'='"),
+            Token::Arrow => f.write_str("'->'"),
+            Token::DashDash => f.write_str("'--'"),
+        }
+    }
+}
+
+/// Split DOT source into tokens tagged with their line numbers.
+pub(crate) fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, VerifyError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut at_line_start = true;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        let start_line = line;
+        match ch {
+            '\n' => {
+                line += 1;
+                at_line_start = true;
+                i += 1;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '#' if at_line_start => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(VerifyError::new(start_line, "unterminated comment")),
+                        Some('*') if chars.get(i + 1) == Some(&'/') => {
+                            i += 2;
+                            break;
+                        }
+                        Some('\n') => line += 1,
+                        Some(_) => {}
+                    }
+                    i += 1;
+                }
+                continue;
+            }
+            '"' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => {
+                            return Err(VerifyError::new(start_line, "unterminated quoted string"));
+                        }
+                        Some('"') => break,
+                        Some('\\') if chars.get(i + 1).is_some() => {
+                            value.push('\\');
+                            value.push(chars[i + 1]);
+                            if chars[i + 1] == '\n' {
+                                line += 1;
+                            }
+                            i += 1;
+                        }
+                        Some(&c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            value.push(c);
+                        }
+                    }
+                    i += 1;
+                }
+                i += 1;
+                tokens.push((start_line, Token::Id(value)));
+            }
+            '<' => {
+                let mut depth = 0usize;
+                let mut value = String::new();
+                loop {
+                    match chars.get(i) {
+                        None => {
+                            return Err(VerifyError::new(start_line, "unterminated HTML string"));
+                        }
+                        Some('<') => depth += 1,
+                        Some('>') => depth -= 1,
+                        Some('\n') => line += 1,
+                        Some(_) => {}
+                    }
+                    value.push(chars[i]);
+                    i += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                tokens.push((start_line, Token::Id(value)));
+            }
+            '-' if chars.get(i + 1) == Some(&'>') => {
+                tokens.push((line, Token::Arrow));
+                i += 2;
+            }
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                tokens.push((line, Token::DashDash));
+                i += 2;
+            }
+            '{' | '}' | '[' | ']' | ';' | ',' | ':' | '=' => {
+                let token = match ch {
+                    '{' => Token::LBrace,
+                    '}' => Token::RBrace,
+                    '[' => Token::LBracket,
+                    ']' => Token::RBracket,
+                    ';' => Token::Semi,
+                    ',' => Token::Comma,
+                    ':' => Token::Colon,
+                    _ => Token::Equals,
+                };
+                tokens.push((line, token));
+                i += 1;
+            }
+            c if c == '-' || c == '.' || c.is_ascii_digit() => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let numeral: String = chars[start..i].iter().collect();
+                if chars.get(i).is_some_and(|&c| is_id_char(c)) {
+                    return Err(VerifyError::new(
+                        line,
+                        format!("invalid identifier starting with '{numeral}'; quote it"),
+                    ));
+                }
+                if !is_numeral(&numeral) {
+                    return Err(VerifyError::new(line, format!("invalid numeral '{numeral}'")));
+                }
+                tokens.push((line, Token::Id(numeral)));
+            }
+            c if is_id_start(c) => {
+                let start = i;
+                while i < chars.len() && is_id_char(chars[i]) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let token = Keyword::parse(&word).map_or(Token::Id(word), Token::Keyword);
+                tokens.push((line, token));
+            }
+            other => {
+                return Err(VerifyError::new(line, format!("unexpected character '{other}'")));
+            }
+        }
+        at_line_start = false;
+    }
+
+    Ok(tokens)
+}
+
+fn is_id_start(c: char) -> bool {
+    c == '_' || c.is_ascii_alphabetic() || !c.is_ascii()
+}
+
+fn is_id_char(c: char) -> bool {
+    is_id_start(c) || c.is_ascii_digit()
+}
+
+/// `[-]?(.[0-9]+ | [0-9]+(.[0-9]*)?)`
+fn is_numeral(raw: &str) -> bool {
+    let digits = raw.strip_prefix('-').unwrap_or(raw);
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    !(whole.is_empty() && fraction.is_empty())
+        && all_digits(whole)
+        && all_digits(fraction)
+        && digits.matches('.').count() <= 1
+}
+
+/// Recursive-descent check of the DOT grammar over a token stream.
+struct DotParser {
+    tokens:   Vec<(usize, Token)>,
+    position: usize,
+    directed: bool,
+}
+
+impl DotParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |(line, _)| *line)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(_, token)| token.clone());
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, expected: &str) -> VerifyError {
+        let found = self
+            .peek()
+            .map_or_else(|| "end of input".to_string(), Token::to_string);
+        VerifyError::new(self.line(), format!("expected {expected}, found {found}"))
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), VerifyError> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&expected.to_string()))
+        }
+    }
+
+    fn id(&mut self) -> Result<(), VerifyError> {
+        match self.peek() {
+            Some(Token::Id(_)) => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => Err(self.error("an identifier")),
+        }
+    }
+
+    fn graph(&mut self) -> Result<(), VerifyError> {
+        self.eat(&Token::Keyword(Keyword::Strict));
+        match self.next() {
+            Some(Token::Keyword(Keyword::Digraph)) => self.directed = true,
+            Some(Token::Keyword(Keyword::Graph)) => self.directed = false,
+            _ => {
+                self.position -= 1;
+                return Err(self.error("'graph' or 'digraph'"));
+            }
+        }
+        if matches!(self.peek(), Some(Token::Id(_))) {
+            self.position += 1;
+        }
+        self.expect(&Token::LBrace)?;
+        self.stmt_list()?;
+        self.expect(&Token::RBrace)?;
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.error("end of input")),
+        }
+    }
+
+    fn stmt_list(&mut self) -> Result<(), VerifyError> {
+        while !matches!(self.peek(), Some(Token::RBrace) | None) {
+            self.stmt()?;
+            self.eat(&Token::Semi);
+        }
+        Ok(())
+    }
+
+    fn stmt(&mut self) -> Result<(), VerifyError> {
+        match self.peek() {
+            Some(Token::Keyword(Keyword::Graph | Keyword::Node | Keyword::Edge)) => {
+                self.position += 1;
+                self.attr_list()
+            }
+            Some(Token::Id(_))
+                if self.tokens.get(self.position + 1).map(|(_, t)| t) == Some(&Token::Equals) =>
+            {
+                self.position += 2;
+                self.id()
+            }
+            _ => {
+                self.endpoint()?;
+                while let Some(Token::Arrow | Token::DashDash) = self.peek() {
+                    let op = self.next();
+                    let expected = if self.directed {
+                        Token::Arrow
+                    } else {
+                        Token::DashDash
+                    };
+                    if op.as_ref() != Some(&expected) {
+                        self.position -= 1;
+                        return Err(self.error(&format!("edge operator {expected}")));
+                    }
+                    self.endpoint()?;
+                }
+                if matches!(self.peek(), Some(Token::LBracket)) {
+                    self.attr_list()?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// A node id (with optional port) or a subgraph.
+    fn endpoint(&mut self) -> Result<(), VerifyError> {
+        match self.peek() {
+            Some(Token::Keyword(Keyword::Subgraph) | Token::LBrace) => self.subgraph(),
+            Some(Token::Id(_)) => {
+                self.position += 1;
+                if self.eat(&Token::Colon) {
+                    self.id()?;
+                    if self.eat(&Token::Colon) {
+                        self.id()?;
+                    }
+                }
+                Ok(())
+            }
+            _ => Err(self.error("a node identifier")),
+        }
+    }
+
+    fn subgraph(&mut self) -> Result<(), VerifyError> {
+        if self.eat(&Token::Keyword(Keyword::Subgraph)) && matches!(self.peek(), Some(Token::Id(_)))
+        {
+            self.position += 1;
+        }
+        self.expect(&Token::LBrace)?;
+        self.stmt_list()?;
+        self.expect(&Token::RBrace)
+    }
+
+    fn attr_list(&mut self) -> Result<(), VerifyError> {
+        self.expect(&Token::LBracket)?;
+        loop {
+            while !matches!(self.peek(), Some(Token::RBracket)) {
+                self.id()?;
+                self.expect(&Token::Equals)?;
+                self.id()?;
+                if !self.eat(&Token::Comma) {
+                    self.eat(&Token::Semi);
+                }
+            }
+            self.expect(&Token::RBracket)?;
+            if !self.eat(&Token::LBracket) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Check that `source` is a well-formed Graphviz DOT document.
+///
+/// Covers quoting, identifier syntax, statement structure and brace
+/// balance; attribute names and values are not checked against Graphviz's
+/// attribute tables.
+pub fn verify_dot(source: &str) -> Result<(), VerifyError> {
+    let tokens = tokenize(source)?;
+    DotParser {
+        tokens,
+        position: 0,
+        directed: false,
+    }
+    .graph()
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        export::render_dot,
+        graph::{GraphSnapshot, SnapshotEdge},
+        model::{Granularity, Node, NodeKind, Relation},
+    };
+
+    fn rendered() -> String {
+        let node = |text: &str| Node {
+            id:          Uuid::new_v4(),
+            kind:        NodeKind::Concept,
+            granularity: Granularity::Sentence,
+            level:       0,
+            text:        text.into(),
+            tags:        None,
+            generator:   None,
+            pinned:      false,
+            namespace:   None,
+            topic:       None,
+        };
+        let nodes = vec![
+            node("Say \"hello\" first."),
+            node("Then -> arrows {inside} text."),
+        ];
+        let edges = vec![SnapshotEdge {
+            from:      nodes[0].id,
+            to:        nodes[1].id,
+            relation:  Relation::RelatedTo,
+            rationale: "Both use [brackets]; and = signs.".to_string(),
+            generator: None,
+            pinned:    false,
+        }];
+        render_dot(&GraphSnapshot { nodes, edges })
+    }
+
+    #[test]
+    fn rendered_dot_verifies() {
+        assert_eq!(verify_dot(&rendered()), Ok(()));
+        assert_eq!(
+            verify_dot(
+                "strict graph g {\n  a -- b -- c [color=red; w=1.5][x=\".5\"]\n  n:p:ne\n  \
+                 subgraph s { d }\n}"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn corrupted_dot_reports_offending_line() {
+        let source = rendered();
+        let mut lines: Vec<&str> = source.lines().collect();
+
+        let error = verify_dot(&source.replacen("\", dir=none", ", dir=none", 1))
+            .expect_err("unterminated label");
+        assert_eq!(error.to_string(), "line 4: unterminated quoted string");
+
+        lines.pop();
+        let error = verify_dot(&lines.join("\n")).expect_err("missing closing brace");
+        assert!(error.message.starts_with("expected '}'"), "{error}");
+
+        let error = verify_dot("digraph {\n  a -- b;\n}").expect_err("undirected op in digraph");
+        assert_eq!(error.line, 2);
+        assert!(error.message.contains("'->'"));
+
+        let error = verify_dot("digraph {\n  a;\n  1abc -> b;\n}").expect_err("bad id");
+        assert_eq!(error.line, 3);
+
+        let error = verify_dot("digraph {\n  a [label];\n}").expect_err("attr without value");
+        assert_eq!(error.to_string(), "line 2: expected '=', found ']'");
+    }
+}
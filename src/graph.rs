@@ -1,55 +1,1344 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    io,
+    path::Path,
+    sync::Arc,
+};
 
 use petgraph::{
-    Directed, Graph, algo,
+    Directed, Direction, Graph, algo,
     graph::{EdgeIndex, NodeIndex},
-    visit::EdgeRef,
+    unionfind::UnionFind,
+    visit::{EdgeFiltered, EdgeRef, IntoEdgeReferences, IntoEdges},
 };
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use uuid::Uuid;
 
-use crate::model::{Edge, InventoryEntry, Node, Relation, clean_text, normalize_text};
+use crate::model::{
+    Edge, GeneratorInfo, InventoryEntry, Node, NodeKind, Relation, clean_text, normalize_text,
+};
+
+/// Layout version written into every [`VersionedSnapshot`].
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// A [`GraphSnapshot`] tagged with its layout version, answered to
+/// [`crate::adder::ExportJson`].
+#[derive(Debug, Clone, Serialize, Deserialize, kameo::Reply)]
+pub struct VersionedSnapshot {
+    pub version: u32,
+    #[serde(flatten)]
+    pub graph:   GraphSnapshot,
+}
+
+impl From<GraphSnapshot> for VersionedSnapshot {
+    fn from(graph: GraphSnapshot) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            graph,
+        }
+    }
+}
+
+/// Serializable form of the graph with edges keyed by node UUIDs.
+#[derive(Debug, Clone, Serialize, Deserialize, kameo::Reply)]
+pub struct GraphSnapshot {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<SnapshotEdge>,
+}
+
+impl GraphSnapshot {
+    /// Nodes in `namespace` and the edges between them.
+    pub fn in_namespace(&self, namespace: &str) -> GraphSnapshot {
+        let nodes: Vec<Node> = self
+            .nodes
+            .iter()
+            .filter(|node| node.namespace.as_deref() == Some(namespace))
+            .cloned()
+            .collect();
+        let kept: HashSet<Uuid> = nodes.iter().map(|node| node.id).collect();
+        let edges = self
+            .edges
+            .iter()
+            .filter(|edge| kept.contains(&edge.from) && kept.contains(&edge.to))
+            .cloned()
+            .collect();
+        GraphSnapshot { nodes, edges }
+    }
+}
+
+/// Inconsistency found while loading a [`GraphSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SnapshotProblem {
+    #[error("node {0} appears more than once")]
+    DuplicateNode(Uuid),
+    #[error("edge {from} -> {to} references unknown node {missing}")]
+    UnknownEndpoint {
+        from:    Uuid,
+        to:      Uuid,
+        missing: Uuid,
+    },
+}
+
+/// Why [`GraphStore::load`] could not produce a store.
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error("failed to read graph: {0}")]
+    Io(#[from] io::Error),
+    #[error("not a JSON graph: {0}")]
+    Format(#[from] serde_json::Error),
+    #[error("graph has {} problem(s)", .0.len())]
+    Invalid(Vec<SnapshotProblem>),
+}
+
+/// [`GraphStore::remove_node`] was given an id the store does not hold.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("no node with id {0}")]
+pub struct UnknownNode(pub Uuid);
+
+/// Why [`GraphStore::update_node_text`] left a node unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum UpdateTextError {
+    #[error("no node with id {0}")]
+    NotFound(Uuid),
+    #[error("node {0} already has this text")]
+    DuplicateText(Uuid),
+    #[error("node text is empty after trimming")]
+    EmptyText,
+}
+
+/// Why [`GraphStore::prerequisite_topo_order`] has no order to give.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("prerequisite cycle through {} node(s)", .nodes.len())]
+pub struct CycleInfo {
+    /// Every node on a prerequisite cycle, ordered by level then text.
+    pub nodes: Vec<Uuid>,
+}
+
+/// What [`GraphStore::merge_from`] did with the other store's contents.
+#[derive(Debug, Clone, Default, PartialEq, Eq, kameo::Reply)]
+pub struct MergeReport {
+    /// Nodes copied over with their UUIDs.
+    pub nodes_added:    usize,
+    /// Nodes whose UUID was already present.
+    pub nodes_matched:  usize,
+    /// Nodes folded onto an existing node with the same normalized text,
+    /// as `(merged id, existing id)`.
+    pub nodes_remapped: Vec<(Uuid, Uuid)>,
+    pub edges_added:    usize,
+    /// Edges left out, with endpoints already re-pointed.
+    pub edges_skipped:  Vec<SkippedEdge>,
+}
+
+/// An edge [`GraphStore::merge_from`] did not copy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedEdge {
+    pub from:     Uuid,
+    pub to:       Uuid,
+    pub relation: Relation,
+    pub reason:   SkipReason,
+}
+
+/// Everything [`GraphStore::validate`] found wrong with a store.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// One problem found while auditing a [`GraphStore`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IntegrityIssue {
+    #[error("edge {edge:?} records endpoints other than the ones it joins")]
+    DanglingEndpoint { edge: EdgeIndex },
+    #[error("text index entry '{text}' points at {index:?}, which does not hold that text")]
+    StaleTextEntry { text: String, index: NodeIndex },
+    #[error("node {id} is missing from the text index")]
+    MissingTextEntry { id: Uuid },
+    #[error("id index maps {id} to {index:?}, which holds another node")]
+    StaleIdEntry { id: Uuid, index: NodeIndex },
+    #[error("node {id} is missing from the id index")]
+    MissingIdEntry { id: Uuid },
+    #[error("node {id} has empty text")]
+    EmptyText { id: Uuid },
+    #[error("prerequisite {from} -> {to} starts at a learning outcome")]
+    PrerequisiteFromOutcome { from: Uuid, to: Uuid },
+    #[error("prerequisite {from} -> {to} drops from level {from_level} to {to_level}")]
+    PrerequisiteLevelDecrease {
+        from:       Uuid,
+        to:         Uuid,
+        from_level: u8,
+        to_level:   u8,
+    },
+    #[error("prerequisite cycle through {} node(s)", .nodes.len())]
+    PrerequisiteCycle { nodes: Vec<Uuid> },
+}
+
+/// Edge counts at one node, by relation and direction.
+///
+/// Related-to edges have no direction, so they are counted once in
+/// `related`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DegreeInfo {
+    pub prerequisites_in:  usize,
+    pub prerequisites_out: usize,
+    pub supports_in:       usize,
+    pub supports_out:      usize,
+    pub related:           usize,
+}
+
+/// Whole-graph counts and degree figures from [`GraphStore::statistics`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphStatistics {
+    pub nodes:                    usize,
+    pub concepts:                 usize,
+    pub learning_outcomes:        usize,
+    pub pinned_nodes:             usize,
+    /// Nodes with no edges of any relation.
+    pub isolated_nodes:           usize,
+    pub edges:                    usize,
+    pub prerequisite_edges:       usize,
+    pub supports_edges:           usize,
+    pub related_edges:            usize,
+    pub pinned_edges:             usize,
+    /// Edges over ordered node pairs; 0 with fewer than two nodes.
+    pub density:                  f64,
+    /// Mean in-degree, which equals the mean out-degree, per relation.
+    /// Related-to edges count in both directions.
+    pub mean_prerequisite_degree: f64,
+    pub mean_supports_degree:     f64,
+    pub mean_related_degree:      f64,
+    /// Most edges touching one node, counting each edge once per endpoint.
+    pub max_degree:               usize,
+}
+
+/// Why a merged edge was skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum SkipReason {
+    #[error("edge already exists")]
+    Duplicate,
+    #[error("both endpoints merged into one node")]
+    SelfLoop,
+    #[error("edge would introduce a prerequisite cycle")]
+    Cycle,
+}
+
+/// Edge as stored in a [`GraphSnapshot`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEdge {
+    pub from:      Uuid,
+    pub to:        Uuid,
+    pub relation:  Relation,
+    pub rationale: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<GeneratorInfo>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned:    bool,
+}
+
+/// Pool of shared strings so repeated texts are stored once.
+#[derive(Debug, Clone, Default)]
+pub struct TextInterner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl TextInterner {
+    pub fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(existing) = self.strings.get(text) {
+            return Arc::clone(existing);
+        }
+        let interned: Arc<str> = Arc::from(text);
+        self.strings.insert(Arc::clone(&interned));
+        interned
+    }
+
+    /// The interned copy of `text`, without adding it.
+    pub fn get(&self, text: &str) -> Option<Arc<str>> {
+        self.strings.get(text).cloned()
+    }
+
+    /// Drop strings no longer referenced outside the interner.
+    pub fn purge_unused(&mut self) {
+        self.strings.retain(|text| Arc::strong_count(text) > 1);
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+/// Interned namespace and normalized text a node is deduplicated on.
+type InternedTextKey = (Option<Arc<str>>, Arc<str>);
 
 /// Wrapper around the petgraph store with convenient indexes.
-#[derive(Debug)]
+///
+/// Clones share node texts with the original, so they are cheap enough to
+/// keep as a rollback point.
+#[derive(Debug, Clone)]
 pub struct GraphStore {
-    graph:      Graph<Node, Edge, Directed>,
-    text_index: HashMap<String, NodeIndex>,
-    id_index:   HashMap<Uuid, NodeIndex>,
+    graph:         Graph<Node, Edge, Directed>,
+    text_index:    HashMap<InternedTextKey, NodeIndex>,
+    id_index:      HashMap<Uuid, NodeIndex>,
+    tag_index:     HashMap<String, HashSet<NodeIndex>>,
+    /// Nodes by the distinct words of their normalized text.
+    token_index:   HashMap<String, HashSet<NodeIndex>>,
+    /// Distinct word count of each node's text, by node index.
+    token_counts:  Vec<usize>,
+    interner:      TextInterner,
+    /// Rank of each node, by node index, in a topological order of the
+    /// prerequisite edges; kept up to date while they stay acyclic.
+    topo_rank:     Vec<usize>,
+    next_rank:     usize,
+    /// Whether the prerequisite edges contain a cycle.
+    prereq_cyclic: bool,
+}
+
+impl Default for GraphStore {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GraphStore {
     pub fn new() -> Self {
         Self {
-            graph:      Graph::default(),
-            text_index: HashMap::new(),
-            id_index:   HashMap::new(),
+            graph:         Graph::default(),
+            text_index:    HashMap::new(),
+            id_index:      HashMap::new(),
+            tag_index:     HashMap::new(),
+            token_index:   HashMap::new(),
+            token_counts:  Vec::new(),
+            interner:      TextInterner::default(),
+            topo_rank:     Vec::new(),
+            next_rank:     0,
+            prereq_cyclic: false,
+        }
+    }
+
+    /// Rebuild a store from a snapshot, reporting every inconsistency.
+    ///
+    /// Nothing is returned unless the snapshot is fully consistent, so a
+    /// broken file never yields a partially built graph.
+    pub fn from_snapshot(snapshot: GraphSnapshot) -> Result<Self, Vec<SnapshotProblem>> {
+        let mut store = Self::new();
+        let mut problems = Vec::new();
+        for node in snapshot.nodes {
+            if store.id_index.contains_key(&node.id) {
+                problems.push(SnapshotProblem::DuplicateNode(node.id));
+                continue;
+            }
+            store.add_node(node);
+        }
+        for edge in snapshot.edges {
+            let endpoints = (store.id_index.get(&edge.from), store.id_index.get(&edge.to));
+            let (Some(&from), Some(&to)) = endpoints else {
+                let missing = if endpoints.0.is_none() {
+                    edge.from
+                } else {
+                    edge.to
+                };
+                problems.push(SnapshotProblem::UnknownEndpoint {
+                    from: edge.from,
+                    to: edge.to,
+                    missing,
+                });
+                continue;
+            };
+            store.add_edge(Edge {
+                from,
+                to,
+                relation: edge.relation,
+                rationale: edge.rationale,
+                generator: edge.generator,
+                pinned: edge.pinned,
+            });
         }
+
+        if problems.is_empty() {
+            Ok(store)
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Write the graph as pretty-printed snapshot JSON.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut contents =
+            serde_json::to_string_pretty(&self.snapshot()).map_err(io::Error::other)?;
+        contents.push('\n');
+        std::fs::write(path, contents)
     }
 
-    pub fn add_node(&mut self, node: Node) -> NodeIndex {
-        let norm = normalize_text(&node.text);
+    /// Read a graph written by [`GraphStore::save`] or a JSON export.
+    pub fn load(path: &Path) -> Result<Self, LoadError> {
+        let snapshot: GraphSnapshot = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        Self::from_snapshot(snapshot).map_err(LoadError::Invalid)
+    }
+
+    pub fn add_node(&mut self, mut node: Node) -> NodeIndex {
+        node.text = self.interner.intern(&node.text);
+        let key = self.intern_key(node.namespace.as_deref(), &node.text);
         let id = node.id;
+        let tags = node.tags.clone().unwrap_or_default();
+        let tokens = text_tokens(&node.text);
         let index = self.graph.add_node(node);
-        self.text_index.insert(norm, index);
+        self.text_index.insert(key, index);
         self.id_index.insert(id, index);
+        self.topo_rank.push(self.next_rank);
+        self.next_rank += 1;
+        for tag in tags {
+            self.tag_index.entry(tag).or_default().insert(index);
+        }
+        self.token_counts.push(tokens.len());
+        for token in tokens {
+            self.token_index.entry(token).or_default().insert(index);
+        }
         index
     }
 
-    pub fn add_edge(&mut self, edge: Edge) -> EdgeIndex {
-        self.graph.add_edge(edge.from, edge.to, edge)
-    }
+    /// Move the index entries for `tags` from `from` to `to`; `None` drops
+    /// them.
+    fn reindex_tags(&mut self, tags: Option<&[String]>, from: NodeIndex, to: Option<NodeIndex>) {
+        let tags = tags.unwrap_or_default().iter().map(String::as_str);
+        move_entries(&mut self.tag_index, tags, from, to);
+    }
+
+    /// Move the word index entries for `text` like [`Self::reindex_tags`].
+    fn reindex_tokens(&mut self, text: &str, from: NodeIndex, to: Option<NodeIndex>) {
+        let tokens = text_tokens(text);
+        move_entries(&mut self.token_index, tokens.iter().map(String::as_str), from, to);
+    }
+
+    fn intern_key(&mut self, namespace: Option<&str>, text: &str) -> InternedTextKey {
+        (
+            namespace.map(|namespace| self.interner.intern(namespace)),
+            self.interner.intern(&normalize_text(text)),
+        )
+    }
+
+    /// Index key for a lookup; `None` when nothing with this key was interned.
+    fn lookup_key(&self, namespace: Option<&str>, text: &str) -> Option<InternedTextKey> {
+        let namespace = match namespace {
+            Some(namespace) => Some(self.interner.get(namespace)?),
+            None => None,
+        };
+        Some((namespace, self.interner.get(&normalize_text(text))?))
+    }
+
+    pub fn interner(&self) -> &TextInterner {
+        &self.interner
+    }
+
+    /// Drop interned strings that no node, index, or outstanding clone or
+    /// snapshot still holds.
+    ///
+    /// Removing or renaming a node leaves its old strings in the interner
+    /// until this runs; [`GraphStore::clear_unpinned`] calls it itself.
+    pub fn purge_unused_text(&mut self) {
+        self.interner.purge_unused();
+    }
+
+    pub fn add_edge(&mut self, edge: Edge) -> EdgeIndex {
+        let (from, to) = (edge.from, edge.to);
+        let prerequisite = matches!(edge.relation, Relation::PrerequisiteFor);
+        let index = self.graph.add_edge(from, to, edge);
+        if prerequisite {
+            self.order_prerequisite(from, to);
+        }
+        index
+    }
+
+    /// Restore the topological ranks after a `from -> to` prerequisite.
+    ///
+    /// Only nodes ranked between `to` and `from` can be out of order, so
+    /// the searches stay within that window and the affected nodes swap
+    /// ranks among themselves (Pearce and Kelly's dynamic ordering).
+    fn order_prerequisite(&mut self, from: NodeIndex, to: NodeIndex) {
+        if self.prereq_cyclic {
+            return;
+        }
+        let (lower, upper) = (self.topo_rank[to.index()], self.topo_rank[from.index()]);
+        if from != to && upper < lower {
+            return;
+        }
+        let forward = self.ranked_reach(to, Direction::Outgoing, |rank| rank <= upper);
+        if forward.contains(&from) {
+            self.prereq_cyclic = true;
+            return;
+        }
+        let backward = self.ranked_reach(from, Direction::Incoming, |rank| rank >= lower);
+
+        let mut moved = Vec::with_capacity(backward.len() + forward.len());
+        for mut nodes in [backward, forward] {
+            nodes.sort_unstable_by_key(|index| self.topo_rank[index.index()]);
+            moved.extend(nodes);
+        }
+        let mut ranks: Vec<usize> = moved
+            .iter()
+            .map(|index| self.topo_rank[index.index()])
+            .collect();
+        ranks.sort_unstable();
+        for (index, rank) in moved.into_iter().zip(ranks) {
+            self.topo_rank[index.index()] = rank;
+        }
+    }
+
+    /// Nodes reachable from `start` along prerequisite edges in `direction`
+    /// without leaving the ranks `within` accepts, `start` included.
+    fn ranked_reach(
+        &self,
+        start: NodeIndex,
+        direction: Direction,
+        within: impl Fn(usize) -> bool,
+    ) -> Vec<NodeIndex> {
+        let mut seen = HashSet::from([start]);
+        let mut stack = vec![start];
+        while let Some(current) = stack.pop() {
+            for edge in self.graph.edges_directed(current, direction) {
+                let next = match direction {
+                    Direction::Outgoing => edge.target(),
+                    Direction::Incoming => edge.source(),
+                };
+                if matches!(edge.weight().relation, Relation::PrerequisiteFor)
+                    && within(self.topo_rank[next.index()])
+                    && seen.insert(next)
+                {
+                    stack.push(next);
+                }
+            }
+        }
+        seen.into_iter().collect()
+    }
+
+    /// Re-rank every node once removals may have broken a cycle.
+    fn refresh_prerequisite_order(&mut self) {
+        if !self.prereq_cyclic {
+            return;
+        }
+        let mut indegree = vec![0usize; self.graph.node_count()];
+        for edge in self.graph.edge_references() {
+            if matches!(edge.weight().relation, Relation::PrerequisiteFor) {
+                indegree[edge.target().index()] += 1;
+            }
+        }
+        let mut ready: Vec<NodeIndex> = self
+            .graph
+            .node_indices()
+            .filter(|index| indegree[index.index()] == 0)
+            .collect();
+        let mut rank = 0;
+        while let Some(index) = ready.pop() {
+            self.topo_rank[index.index()] = rank;
+            rank += 1;
+            for edge in self.graph.edges(index) {
+                if matches!(edge.weight().relation, Relation::PrerequisiteFor) {
+                    indegree[edge.target().index()] -= 1;
+                    if indegree[edge.target().index()] == 0 {
+                        ready.push(edge.target());
+                    }
+                }
+            }
+        }
+        self.next_rank = rank;
+        self.prereq_cyclic = rank < self.graph.node_count();
+    }
+
+    /// Replace a node's tags, moving its tag index entries with them.
+    pub fn update_node_tags(
+        &mut self,
+        id: &Uuid,
+        tags: Option<Vec<String>>,
+    ) -> Result<(), UnknownNode> {
+        let index = self.find_by_id(id).ok_or(UnknownNode(*id))?;
+        let previous = self.graph[index].tags.take();
+        self.reindex_tags(previous.as_deref(), index, None);
+        for tag in tags.iter().flatten() {
+            self.tag_index.entry(tag.clone()).or_default().insert(index);
+        }
+        self.graph[index].tags = tags;
+        Ok(())
+    }
+
+    /// Replace a node's text, moving its deduplication key to the new text.
+    ///
+    /// Edges, level, tags, and the id are untouched.
+    pub fn update_node_text(&mut self, id: &Uuid, new_text: &str) -> Result<(), UpdateTextError> {
+        let index = self.find_by_id(id).ok_or(UpdateTextError::NotFound(*id))?;
+        let cleaned = clean_text(new_text);
+        if cleaned.is_empty() {
+            return Err(UpdateTextError::EmptyText);
+        }
+        let node = &self.graph[index];
+        let namespace = node.namespace.clone();
+        if let Some(existing) = self.find_by_text(namespace.as_deref(), &cleaned)
+            && existing != index
+        {
+            return Err(UpdateTextError::DuplicateText(self.graph[existing].id));
+        }
+
+        if let Some(key) = self.lookup_key(namespace.as_deref(), &node.text)
+            && self.text_index.get(&key) == Some(&index)
+        {
+            self.text_index.remove(&key);
+        }
+        let previous = Arc::clone(&node.text);
+        self.reindex_tokens(&previous, index, None);
+        let text = self.interner.intern(&cleaned);
+        let key = self.intern_key(namespace.as_deref(), &text);
+        let tokens = text_tokens(&text);
+        self.token_counts[index.index()] = tokens.len();
+        for token in tokens {
+            self.token_index.entry(token).or_default().insert(index);
+        }
+        self.graph[index].text = text;
+        self.text_index.insert(key, index);
+        Ok(())
+    }
+
+    /// Remove the `relation` edge from `from` to `to`, leaving parallel
+    /// edges of other relations in place. Returns whether an edge was found.
+    pub fn remove_edge(&mut self, from: &Uuid, to: &Uuid, relation: Relation) -> bool {
+        let (Some(from), Some(to)) = (self.find_by_id(from), self.find_by_id(to)) else {
+            return false;
+        };
+        self.find_edge(from, to, &relation)
+            .and_then(|index| self.remove_edge_at(index))
+            .is_some()
+    }
+
+    /// Remove the edge at `index`.
+    ///
+    /// petgraph moves the last edge into the vacated slot, so an `EdgeIndex`
+    /// held from before the call may now name a different edge; look edges
+    /// up again with [`GraphStore::find_edge`] afterwards. Node indexes are
+    /// unaffected.
+    pub fn remove_edge_at(&mut self, index: EdgeIndex) -> Option<Edge> {
+        let edge = self.graph.remove_edge(index)?;
+        self.refresh_prerequisite_order();
+        Some(edge)
+    }
+
+    /// Remove a node and its incident edges, returning both.
+    ///
+    /// The removed edges are keyed by node UUIDs, since any `NodeIndex`
+    /// held from before the removal may now name a different node.
+    pub fn remove_node(&mut self, id: &Uuid) -> Result<(Node, Vec<SnapshotEdge>), UnknownNode> {
+        let index = self.find_by_id(id).ok_or(UnknownNode(*id))?;
+        let edges = self.incident_edges(index);
+        let node = self.remove_index(index).ok_or(UnknownNode(*id))?;
+        Ok((node, edges))
+    }
+
+    /// Remove the node at `index`, keeping the indexes consistent.
+    ///
+    /// petgraph moves the last node into the vacated slot, so the moved
+    /// node's index entries and the endpoints cached on edge weights are
+    /// refreshed afterwards.
+    fn remove_index(&mut self, index: NodeIndex) -> Option<Node> {
+        let node = self.graph.remove_node(index)?;
+        if let Some(key) = self.lookup_key(node.namespace.as_deref(), &node.text) {
+            self.text_index.remove(&key);
+        }
+        self.id_index.remove(&node.id);
+        self.reindex_tags(node.tags.as_deref(), index, None);
+        self.reindex_tokens(&node.text, index, None);
+        self.topo_rank.swap_remove(index.index());
+        self.token_counts.swap_remove(index.index());
+
+        if let Some(moved) = self.graph.node_weight(index) {
+            let (namespace, text) = (moved.namespace.clone(), Arc::clone(&moved.text));
+            let (moved_id, tags) = (moved.id, moved.tags.clone());
+            let key = self.intern_key(namespace.as_deref(), &text);
+            self.text_index.insert(key, index);
+            self.id_index.insert(moved_id, index);
+            let previous = NodeIndex::new(self.graph.node_count());
+            self.reindex_tags(tags.as_deref(), previous, Some(index));
+            self.reindex_tokens(&text, previous, Some(index));
+        }
+        for edge in self.graph.edge_indices() {
+            if let Some((from, to)) = self.graph.edge_endpoints(edge) {
+                let weight = &mut self.graph[edge];
+                weight.from = from;
+                weight.to = to;
+            }
+        }
+        self.refresh_prerequisite_order();
+
+        Some(node)
+    }
+
+    /// Edges touching `index` in either direction, keyed by node UUIDs.
+    pub fn incident_edges(&self, index: NodeIndex) -> Vec<SnapshotEdge> {
+        self.graph
+            .edges_directed(index, Direction::Outgoing)
+            .chain(self.graph.edges_directed(index, Direction::Incoming))
+            .filter_map(|edge| {
+                let weight = edge.weight();
+                Some(SnapshotEdge {
+                    from:      self.graph.node_weight(edge.source())?.id,
+                    to:        self.graph.node_weight(edge.target())?.id,
+                    relation:  weight.relation.clone(),
+                    rationale: weight.rationale.clone(),
+                    generator: weight.generator.clone(),
+                    pinned:    weight.pinned,
+                })
+            })
+            .collect()
+    }
+
+    /// Remove every unpinned node and edge.
+    ///
+    /// A pinned edge survives only when both of its endpoints are pinned.
+    /// Returns the removed nodes and the UUID-keyed removed edges.
+    pub fn clear_unpinned(&mut self) -> (Vec<Node>, Vec<SnapshotEdge>) {
+        let removed_edges = self
+            .snapshot()
+            .edges
+            .into_iter()
+            .filter(|edge| {
+                let endpoint_pinned = |id: &Uuid| {
+                    self.find_by_id(id)
+                        .and_then(|index| self.node(index))
+                        .is_some_and(|node| node.pinned)
+                };
+                !(edge.pinned && endpoint_pinned(&edge.from) && endpoint_pinned(&edge.to))
+            })
+            .collect();
+
+        self.graph.retain_edges(|graph, edge| graph[edge].pinned);
+        self.refresh_prerequisite_order();
+        let mut unpinned: Vec<NodeIndex> = self
+            .graph
+            .node_indices()
+            .filter(|&index| !self.graph[index].pinned)
+            .collect();
+        // Removing from the highest index down means the node petgraph swaps
+        // into each freed slot has already been kept.
+        unpinned.sort_unstable_by(|a, b| b.cmp(a));
+        let removed_nodes = unpinned
+            .into_iter()
+            .filter_map(|index| self.remove_index(index))
+            .collect();
+        self.interner.purge_unused();
+
+        (removed_nodes, removed_edges)
+    }
+
+    /// Look up a node by normalized text within `namespace`.
+    pub fn find_by_text(&self, namespace: Option<&str>, text: &str) -> Option<NodeIndex> {
+        self.text_index
+            .get(&self.lookup_key(namespace, text)?)
+            .copied()
+    }
+
+    pub fn find_by_id(&self, id: &Uuid) -> Option<NodeIndex> {
+        self.id_index.get(id).copied()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    pub fn node(&self, index: NodeIndex) -> Option<&Node> {
+        self.graph.node_weight(index)
+    }
+
+    /// Mutable access for fields no index covers, such as `pinned`.
+    ///
+    /// Text and tags are indexed; change text through
+    /// [`GraphStore::update_node_text`].
+    pub fn node_mut(&mut self, index: NodeIndex) -> Option<&mut Node> {
+        self.graph.node_weight_mut(index)
+    }
+
+    /// Nodes whose text shares at least `threshold` of its words with
+    /// `text`, best match first.
+    ///
+    /// The score is the Jaccard similarity of the two sets of distinct words
+    /// after normalization, so 1.0 means the same words in any order. Only
+    /// nodes sharing a word with `text` are scored.
+    pub fn find_similar(&self, text: &str, threshold: f64) -> Vec<(NodeIndex, f64)> {
+        let tokens = text_tokens(text);
+        let mut shared: HashMap<NodeIndex, usize> = HashMap::new();
+        for token in &tokens {
+            for &index in self.token_index.get(token).into_iter().flatten() {
+                *shared.entry(index).or_default() += 1;
+            }
+        }
+        let mut scored: Vec<(NodeIndex, f64)> = shared
+            .into_iter()
+            .map(|(index, shared)| {
+                let union = tokens.len() + self.token_counts[index.index()] - shared;
+                (index, shared as f64 / union as f64)
+            })
+            .filter(|(_, score)| *score >= threshold)
+            .collect();
+        scored.sort_unstable_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scored
+    }
+
+    /// Nodes carrying `tag`, in index order.
+    pub fn find_by_tag(&self, tag: &str) -> Vec<&Node> {
+        let mut indexes: Vec<NodeIndex> = self
+            .tag_index
+            .get(tag)
+            .map(|indexes| indexes.iter().copied().collect())
+            .unwrap_or_default();
+        indexes.sort_unstable();
+        indexes
+            .into_iter()
+            .filter_map(|index| self.graph.node_weight(index))
+            .collect()
+    }
+
+    /// Every tag on at least one node with its node count, sorted by tag.
+    pub fn tags_in_use(&self) -> Vec<(String, usize)> {
+        let mut tags: Vec<(String, usize)> = self
+            .tag_index
+            .iter()
+            .map(|(tag, indexes)| (tag.clone(), indexes.len()))
+            .collect();
+        tags.sort_unstable();
+        tags
+    }
+
+    /// Nodes grouped by level, each group ordered by normalized text.
+    pub fn nodes_by_level(&self) -> BTreeMap<u8, Vec<&Node>> {
+        let mut levels: BTreeMap<u8, Vec<&Node>> = BTreeMap::new();
+        for node in self.graph.node_weights() {
+            levels.entry(node.level).or_default().push(node);
+        }
+        for nodes in levels.values_mut() {
+            sort_by_text(nodes);
+        }
+        levels
+    }
+
+    /// Nodes at `level`, ordered by normalized text.
+    pub fn nodes_at_level(&self, level: u8) -> Vec<&Node> {
+        let mut nodes: Vec<&Node> = self
+            .graph
+            .node_weights()
+            .filter(|node| node.level == level)
+            .collect();
+        sort_by_text(&mut nodes);
+        nodes
+    }
+
+    /// Sources of prerequisite edges into `id`, with each edge's rationale.
+    ///
+    /// Like the other neighbor queries, this is empty for an unknown id and
+    /// lists edges in the order they were added.
+    pub fn prerequisites_of(&self, id: &Uuid) -> Vec<(&Node, &str)> {
+        self.neighbors(id, Relation::PrerequisiteFor, Direction::Incoming)
+    }
+
+    /// Targets of prerequisite edges out of `id`, with each edge's rationale.
+    pub fn dependents_of(&self, id: &Uuid) -> Vec<(&Node, &str)> {
+        self.neighbors(id, Relation::PrerequisiteFor, Direction::Outgoing)
+    }
+
+    /// Sources of supports edges into `id`, with each edge's rationale.
+    pub fn supporters_of(&self, id: &Uuid) -> Vec<(&Node, &str)> {
+        self.neighbors(id, Relation::Supports, Direction::Incoming)
+    }
+
+    /// Learning outcomes with fewer than `minimum` incoming supports edges
+    /// and their counts, fewest first, then by text.
+    pub fn under_supported(&self, minimum: usize) -> Vec<(&Node, usize)> {
+        let mut outcomes: Vec<(&Node, usize)> = self
+            .graph
+            .node_weights()
+            .filter(|node| matches!(node.kind, NodeKind::LearningOutcome))
+            .map(|node| (node, self.supporters_of(&node.id).len()))
+            .filter(|(_, supports)| *supports < minimum)
+            .collect();
+        outcomes.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.text.cmp(&b.0.text)));
+        outcomes
+    }
+
+    /// Audit the indexes, node texts, and prerequisite rules.
+    ///
+    /// Issues are grouped by kind: edge endpoints, then index entries, then
+    /// nodes and edges in index order, then any prerequisite cycle.
+    pub fn validate(&self) -> ValidationReport {
+        let mut issues: Vec<IntegrityIssue> = self
+            .graph
+            .edge_references()
+            .filter(|edge| (edge.weight().from, edge.weight().to) != (edge.source(), edge.target()))
+            .map(|edge| IntegrityIssue::DanglingEndpoint { edge: edge.id() })
+            .collect();
+
+        let mut stale_text: Vec<_> = self
+            .text_index
+            .iter()
+            .filter(|((namespace, text), index)| {
+                self.graph.node_weight(**index).is_none_or(|node| {
+                    node.namespace.as_deref() != namespace.as_deref()
+                        || normalize_text(&node.text) != text.as_ref()
+                })
+            })
+            .map(|((_, text), index)| (*index, text.to_string()))
+            .collect();
+        stale_text.sort_unstable();
+        issues.extend(
+            stale_text
+                .into_iter()
+                .map(|(index, text)| IntegrityIssue::StaleTextEntry { text, index }),
+        );
+        let mut stale_ids: Vec<_> = self
+            .id_index
+            .iter()
+            .filter(|(id, index)| {
+                self.graph
+                    .node_weight(**index)
+                    .is_none_or(|node| node.id != **id)
+            })
+            .map(|(id, index)| (*index, *id))
+            .collect();
+        stale_ids.sort_unstable();
+        issues.extend(
+            stale_ids
+                .into_iter()
+                .map(|(index, id)| IntegrityIssue::StaleIdEntry { id, index }),
+        );
+
+        for index in self.graph.node_indices() {
+            let node = &self.graph[index];
+            if self.find_by_text(node.namespace.as_deref(), &node.text) != Some(index) {
+                issues.push(IntegrityIssue::MissingTextEntry { id: node.id });
+            }
+            if self.id_index.get(&node.id) != Some(&index) {
+                issues.push(IntegrityIssue::MissingIdEntry { id: node.id });
+            }
+            if node.text.trim().is_empty() {
+                issues.push(IntegrityIssue::EmptyText { id: node.id });
+            }
+        }
+
+        for edge in self.graph.edge_references() {
+            if !matches!(edge.weight().relation, Relation::PrerequisiteFor) {
+                continue;
+            }
+            let (from, to) = (&self.graph[edge.source()], &self.graph[edge.target()]);
+            if matches!(from.kind, NodeKind::LearningOutcome) {
+                issues.push(IntegrityIssue::PrerequisiteFromOutcome {
+                    from: from.id,
+                    to:   to.id,
+                });
+            }
+            if from.level > to.level {
+                issues.push(IntegrityIssue::PrerequisiteLevelDecrease {
+                    from:       from.id,
+                    to:         to.id,
+                    from_level: from.level,
+                    to_level:   to.level,
+                });
+            }
+        }
+
+        if let Err(cycle) = self.prerequisite_topo_order() {
+            issues.push(IntegrityIssue::PrerequisiteCycle { nodes: cycle.nodes });
+        }
+        ValidationReport { issues }
+    }
+
+    /// Copy `other` into this store, folding its nodes onto existing ones
+    /// with the same UUID or the same normalized text in the same
+    /// namespace.
+    ///
+    /// Edges are re-pointed through that mapping and skipped when they
+    /// would duplicate an edge, collapse into a self-loop, or close a
+    /// prerequisite cycle.
+    pub fn merge_from(&mut self, other: &GraphStore) -> MergeReport {
+        let mut report = MergeReport::default();
+        let mut mapped: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        for index in other.graph.node_indices() {
+            let node = &other.graph[index];
+            let target = if let Some(existing) = self.find_by_id(&node.id) {
+                report.nodes_matched += 1;
+                existing
+            } else if let Some(existing) = self.find_by_text(node.namespace.as_deref(), &node.text)
+            {
+                report
+                    .nodes_remapped
+                    .push((node.id, self.graph[existing].id));
+                existing
+            } else {
+                report.nodes_added += 1;
+                self.add_node(node.clone())
+            };
+            mapped.insert(index, target);
+        }
+
+        for edge in other.graph.edge_references() {
+            let weight = edge.weight();
+            let (from, to) = weight.relation.canonical_endpoints(
+                self.graph[mapped[&edge.source()]].id,
+                self.graph[mapped[&edge.target()]].id,
+            );
+            let (from_index, to_index) = (self.id_index[&from], self.id_index[&to]);
+            let reason = if from == to {
+                Some(SkipReason::SelfLoop)
+            } else if self.has_edge_between(from_index, to_index, &weight.relation) {
+                Some(SkipReason::Duplicate)
+            } else if matches!(weight.relation, Relation::PrerequisiteFor)
+                && self.would_cycle_prereq(from_index, to_index)
+            {
+                Some(SkipReason::Cycle)
+            } else {
+                None
+            };
+            match reason {
+                Some(reason) => report.edges_skipped.push(SkippedEdge {
+                    from,
+                    to,
+                    relation: weight.relation.clone(),
+                    reason,
+                }),
+                None => {
+                    self.add_edge(Edge {
+                        from: from_index,
+                        to: to_index,
+                        ..weight.clone()
+                    });
+                    report.edges_added += 1;
+                }
+            }
+        }
+        report
+    }
+
+    /// The slice of the graph needed to reach `id`: the node, all of its
+    /// prerequisite ancestors, and the supports edges into any of them along
+    /// with their sources.
+    pub fn ancestor_subgraph(&self, id: &Uuid) -> Result<GraphStore, UnknownNode> {
+        let target = self.find_by_id(id).ok_or(UnknownNode(*id))?;
+        let ancestors: HashSet<NodeIndex> = self
+            .ranked_reach(target, Direction::Incoming, |_| true)
+            .into_iter()
+            .collect();
+        let edges: Vec<EdgeIndex> = self
+            .graph
+            .edge_references()
+            .filter(|edge| match edge.weight().relation {
+                Relation::PrerequisiteFor => {
+                    ancestors.contains(&edge.source()) && ancestors.contains(&edge.target())
+                }
+                Relation::Supports => ancestors.contains(&edge.target()),
+                Relation::RelatedTo => false,
+            })
+            .map(|edge| edge.id())
+            .collect();
+        let mut kept: Vec<NodeIndex> = ancestors.iter().copied().collect();
+        kept.extend(
+            edges
+                .iter()
+                .map(|&edge| self.graph[edge].from)
+                .filter(|source| !ancestors.contains(source)),
+        );
+        kept.sort_unstable();
+        kept.dedup();
+
+        let mut subgraph = GraphStore::new();
+        let moved: HashMap<NodeIndex, NodeIndex> = kept
+            .into_iter()
+            .map(|index| (index, subgraph.add_node(self.graph[index].clone())))
+            .collect();
+        for edge in edges {
+            let edge = &self.graph[edge];
+            subgraph.add_edge(Edge {
+                from: moved[&edge.from],
+                to: moved[&edge.to],
+                ..edge.clone()
+            });
+        }
+        Ok(subgraph)
+    }
+
+    /// Weakly connected components over every relation, largest first.
+    ///
+    /// Nodes with no edges are components of their own. Each component
+    /// lists nodes in index order, and equal-sized components keep the order
+    /// of their first node.
+    pub fn connected_components(&self) -> Vec<Vec<Uuid>> {
+        let mut sets = UnionFind::<usize>::new(self.graph.node_count());
+        for edge in self.graph.edge_references() {
+            sets.union(edge.source().index(), edge.target().index());
+        }
+        let mut components: Vec<Vec<Uuid>> = Vec::new();
+        let mut by_root: HashMap<usize, usize> = HashMap::new();
+        for index in self.graph.node_indices() {
+            let slot = *by_root.entry(sets.find(index.index())).or_insert_with(|| {
+                components.push(Vec::new());
+                components.len() - 1
+            });
+            components[slot].push(self.graph[index].id);
+        }
+        components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+        components
+    }
+
+    /// Whether every node can reach every other ignoring edge direction; an
+    /// empty graph counts as connected.
+    pub fn is_connected(&self) -> bool {
+        self.connected_components().len() <= 1
+    }
+
+    /// Nodes whose removal disconnects their component, ignoring edge
+    /// direction, each with the size of the smallest piece it would cut off.
+    ///
+    /// The most load-bearing come first: larger smallest pieces, then
+    /// level and text.
+    pub fn articulation_points(&self) -> Vec<(Uuid, usize)> {
+        let (points, _) = self.cut_structure();
+        let mut ranked: Vec<_> = points
+            .into_iter()
+            .map(|(index, smallest)| (std::cmp::Reverse(smallest), self.teaching_key(index)))
+            .collect();
+        ranked.sort_unstable();
+        ranked
+            .into_iter()
+            .map(|(std::cmp::Reverse(smallest), (_, _, id, _))| (id, smallest))
+            .collect()
+    }
+
+    /// Edges whose removal disconnects their component, ignoring direction,
+    /// as `(from, to)` in insertion order. Parallel edges are never bridges.
+    pub fn bridges(&self) -> Vec<(Uuid, Uuid)> {
+        let (_, mut bridges) = self.cut_structure();
+        bridges.sort_unstable();
+        bridges
+            .into_iter()
+            .map(|edge| {
+                let (from, to) = self
+                    .graph
+                    .edge_endpoints(edge)
+                    .expect("edge from the graph");
+                (self.graph[from].id, self.graph[to].id)
+            })
+            .collect()
+    }
+
+    /// Articulation points with their smallest cut-off piece, and bridges,
+    /// from one iterative lowlink search over the undirected multigraph.
+    fn cut_structure(&self) -> (Vec<(NodeIndex, usize)>, Vec<EdgeIndex>) {
+        let count = self.graph.node_count();
+        let mut adjacent = vec![Vec::new(); count];
+        for edge in self.graph.edge_references() {
+            let (from, to) = (edge.source().index(), edge.target().index());
+            if from != to {
+                adjacent[from].push((to, edge.id()));
+                adjacent[to].push((from, edge.id()));
+            }
+        }
+
+        let mut discovered = vec![usize::MAX; count];
+        let mut low = vec![0; count];
+        let mut size = vec![1; count];
+        // Per node: how many children it separates, their total size, and
+        // the smallest of them.
+        let mut separated = vec![(0, 0, usize::MAX); count];
+        let mut points = Vec::new();
+        let mut bridges = Vec::new();
+        let mut time = 0;
+        for root in 0..count {
+            if discovered[root] != usize::MAX {
+                continue;
+            }
+            let mut members = vec![root];
+            discovered[root] = time;
+            low[root] = time;
+            time += 1;
+            let mut stack = vec![(root, None, 0)];
+            while let Some((node, parent_edge, next)) = stack.last_mut() {
+                let node = *node;
+                if let Some(&(neighbor, edge)) = adjacent[node].get(*next) {
+                    *next += 1;
+                    if *parent_edge == Some(edge) {
+                        continue;
+                    }
+                    if discovered[neighbor] == usize::MAX {
+                        discovered[neighbor] = time;
+                        low[neighbor] = time;
+                        time += 1;
+                        members.push(neighbor);
+                        stack.push((neighbor, Some(edge), 0));
+                    } else {
+                        low[node] = low[node].min(discovered[neighbor]);
+                    }
+                    continue;
+                }
+                let parent_edge = *parent_edge;
+                stack.pop();
+                let Some(&(parent, ..)) = stack.last() else {
+                    continue;
+                };
+                low[parent] = low[parent].min(low[node]);
+                size[parent] += size[node];
+                if low[node] > discovered[parent] {
+                    bridges.extend(parent_edge);
+                }
+                if low[node] >= discovered[parent] {
+                    let (children, total, smallest) = &mut separated[parent];
+                    *children += 1;
+                    *total += size[node];
+                    *smallest = (*smallest).min(size[node]);
+                }
+            }
+
+            let component = size[root];
+            for node in members {
+                let (children, total, smallest) = separated[node];
+                if node == root && children >= 2 {
+                    points.push((NodeIndex::new(node), smallest));
+                } else if node != root && children >= 1 {
+                    let rest = component - 1 - total;
+                    points.push((NodeIndex::new(node), smallest.min(rest)));
+                }
+            }
+        }
+        (points, bridges)
+    }
+
+    /// Edges leaving `id`, of `relation` or of any relation for `None`.
+    ///
+    /// Related-to edges count in both directions. Unknown ids have no
+    /// edges, here and in [`Self::in_degree`].
+    pub fn out_degree(&self, id: &Uuid, relation: Option<Relation>) -> usize {
+        self.degree(id, relation, Direction::Outgoing)
+    }
+
+    /// Edges entering `id`, of `relation` or of any relation for `None`.
+    pub fn in_degree(&self, id: &Uuid, relation: Option<Relation>) -> usize {
+        self.degree(id, relation, Direction::Incoming)
+    }
+
+    fn degree(&self, id: &Uuid, relation: Option<Relation>, direction: Direction) -> usize {
+        let Some(&index) = self.id_index.get(id) else {
+            return 0;
+        };
+        let wanted = |edge: &Edge| {
+            relation
+                .as_ref()
+                .is_none_or(|relation| edge.relation == *relation)
+        };
+        let along = self
+            .graph
+            .edges_directed(index, direction)
+            .filter(|edge| wanted(edge.weight()))
+            .count();
+        let against = self
+            .graph
+            .edges_directed(index, direction.opposite())
+            .filter(|edge| {
+                wanted(edge.weight())
+                    && edge.weight().relation.is_symmetric()
+                    && edge.source() != edge.target()
+            })
+            .count();
+        along + against
+    }
+
+    /// Degrees of every node, in index order.
+    pub fn degree_table(&self) -> Vec<(Uuid, DegreeInfo)> {
+        let mut degrees = vec![DegreeInfo::default(); self.graph.node_count()];
+        for edge in self.graph.edge_references() {
+            let (from, to) = (edge.source().index(), edge.target().index());
+            match edge.weight().relation {
+                Relation::PrerequisiteFor => {
+                    degrees[from].prerequisites_out += 1;
+                    degrees[to].prerequisites_in += 1;
+                }
+                Relation::Supports => {
+                    degrees[from].supports_out += 1;
+                    degrees[to].supports_in += 1;
+                }
+                Relation::RelatedTo => {
+                    degrees[from].related += 1;
+                    degrees[to].related += 1;
+                }
+            }
+        }
+        self.graph
+            .node_indices()
+            .map(|index| (self.graph[index].id, degrees[index.index()]))
+            .collect()
+    }
+
+    /// Counts by kind, relation, and pinning, with density and degree
+    /// figures, from one pass over nodes and edges.
+    pub fn statistics(&self) -> GraphStatistics {
+        let mut statistics = GraphStatistics::default();
+        for node in self.graph.node_weights() {
+            statistics.nodes += 1;
+            statistics.pinned_nodes += usize::from(node.pinned);
+            match node.kind {
+                NodeKind::Concept => statistics.concepts += 1,
+                NodeKind::LearningOutcome => statistics.learning_outcomes += 1,
+            }
+        }
 
-    pub fn find_by_text(&self, text: &str) -> Option<NodeIndex> {
-        let norm = normalize_text(text);
-        self.text_index.get(&norm).copied()
-    }
+        let mut degrees = vec![0; self.graph.node_count()];
+        for edge in self.graph.edge_references() {
+            statistics.edges += 1;
+            statistics.pinned_edges += usize::from(edge.weight().pinned);
+            match edge.weight().relation {
+                Relation::PrerequisiteFor => statistics.prerequisite_edges += 1,
+                Relation::Supports => statistics.supports_edges += 1,
+                Relation::RelatedTo => statistics.related_edges += 1,
+            }
+            degrees[edge.source().index()] += 1;
+            degrees[edge.target().index()] += 1;
+        }
+        statistics.isolated_nodes = degrees.iter().filter(|&&degree| degree == 0).count();
+        statistics.max_degree = degrees.into_iter().max().unwrap_or_default();
 
-    pub fn find_by_id(&self, id: &Uuid) -> Option<NodeIndex> {
-        self.id_index.get(id).copied()
+        let nodes = statistics.nodes as f64;
+        if statistics.nodes > 1 {
+            statistics.density = statistics.edges as f64 / (nodes * (nodes - 1.0));
+        }
+        if statistics.nodes > 0 {
+            statistics.mean_prerequisite_degree = statistics.prerequisite_edges as f64 / nodes;
+            statistics.mean_supports_degree = statistics.supports_edges as f64 / nodes;
+            statistics.mean_related_degree = 2.0 * statistics.related_edges as f64 / nodes;
+        }
+        statistics
     }
 
-    pub fn node(&self, index: NodeIndex) -> Option<&Node> {
-        self.graph.node_weight(index)
+    fn neighbors(&self, id: &Uuid, relation: Relation, direction: Direction) -> Vec<(&Node, &str)> {
+        let Some(&index) = self.id_index.get(id) else {
+            return Vec::new();
+        };
+        let mut edges: Vec<_> = self
+            .graph
+            .edges_directed(index, direction)
+            .filter(|edge| edge.weight().relation == relation)
+            .collect();
+        edges.sort_unstable_by_key(|edge| edge.id());
+        edges
+            .into_iter()
+            .map(|edge| {
+                let other = match direction {
+                    Direction::Outgoing => edge.target(),
+                    Direction::Incoming => edge.source(),
+                };
+                (&self.graph[other], edge.weight().rationale.as_str())
+            })
+            .collect()
     }
 
     pub fn inventory(&self) -> Vec<InventoryEntry> {
@@ -63,63 +1352,134 @@ impl GraphStore {
             .collect()
     }
 
+    /// Returns true when an edge with `relation` already joins `from` to `to`.
+    pub fn has_edge_between(&self, from: NodeIndex, to: NodeIndex, relation: &Relation) -> bool {
+        self.graph
+            .edges_connecting(from, to)
+            .any(|edge| edge.weight().relation == *relation)
+    }
+
+    /// Index of the `relation` edge joining `from` to `to`, if present.
+    pub fn find_edge(
+        &self,
+        from: NodeIndex,
+        to: NodeIndex,
+        relation: &Relation,
+    ) -> Option<EdgeIndex> {
+        self.graph
+            .edges_connecting(from, to)
+            .find(|edge| edge.weight().relation == *relation)
+            .map(|edge| edge.id())
+    }
+
     pub fn would_cycle_prereq(&self, from: NodeIndex, to: NodeIndex) -> bool {
+        self.prereq_cycle_path(from, to).is_some()
+    }
+
+    /// Find the existing prerequisite path from `to` back to `from`, if any.
+    ///
+    /// Adding a `from -> to` prerequisite edge closes a cycle exactly when
+    /// such a path exists. The returned path starts at `to` and ends at
+    /// `from`.
+    ///
+    /// While the prerequisites are acyclic, a `to` ranked after `from` has
+    /// no such path, and otherwise only nodes ranked before `from` are
+    /// searched.
+    pub fn prereq_cycle_path(&self, from: NodeIndex, to: NodeIndex) -> Option<Vec<NodeIndex>> {
+        if self.prereq_cyclic {
+            return self.prereq_path_within(from, to, usize::MAX);
+        }
+        let bound = self.topo_rank[from.index()];
+        if from != to && self.topo_rank[to.index()] > bound {
+            return None;
+        }
+        self.prereq_path_within(from, to, bound)
+    }
+
+    /// Depth-first search from `to` for `from`, skipping nodes ranked after
+    /// `bound`.
+    fn prereq_path_within(
+        &self,
+        from: NodeIndex,
+        to: NodeIndex,
+        bound: usize,
+    ) -> Option<Vec<NodeIndex>> {
         if from == to {
-            return true;
+            return Some(vec![from]);
         }
 
         let mut stack = vec![to];
         let mut visited = HashSet::new();
+        let mut parent: HashMap<NodeIndex, NodeIndex> = HashMap::new();
         while let Some(current) = stack.pop() {
             if !visited.insert(current) {
                 continue;
             }
             if current == from {
-                return true;
+                let mut path = vec![current];
+                let mut cursor = current;
+                while let Some(&previous) = parent.get(&cursor) {
+                    path.push(previous);
+                    cursor = previous;
+                }
+                path.reverse();
+                return Some(path);
             }
             for edge in self.graph.edges(current) {
-                if matches!(edge.weight().relation, Relation::PrerequisiteFor) {
+                if matches!(edge.weight().relation, Relation::PrerequisiteFor)
+                    && !visited.contains(&edge.target())
+                    && self.topo_rank[edge.target().index()] <= bound
+                {
+                    parent.entry(edge.target()).or_insert(current);
                     stack.push(edge.target());
                 }
             }
         }
 
-        false
+        None
     }
 
-    pub fn export_dot(&self) -> String {
-        let mut output = String::from("digraph weaver {\n");
+    /// The nodes and edges as CSV tables, in that order.
+    pub fn export_csv(&self) -> (String, String) {
+        let snapshot = self.snapshot();
+        (
+            crate::export::csv::render_csv_nodes(&snapshot),
+            crate::export::csv::render_csv_edges(&snapshot),
+        )
+    }
 
-        for index in self.graph.node_indices() {
-            if let Some(node) = self.graph.node_weight(index) {
-                let label = clean_text(&node.text).replace('"', "\\\"");
-                output.push_str(&format!("  \"{}\" [label=\"{}\"];\n", node.id, label));
-            }
-        }
+    /// The graph as a GEXF document.
+    pub fn export_gexf(&self) -> String {
+        crate::export::gexf::render_gexf(&self.snapshot())
+    }
 
-        for edge in self.graph.edge_references() {
-            let weight = edge.weight();
-            let relation = match weight.relation {
-                Relation::PrerequisiteFor => "prerequisite_for",
-                Relation::Supports => "supports",
-            };
-            let mut label = relation.to_string();
-            if !weight.rationale.trim().is_empty() {
-                let sanitized = clean_text(&weight.rationale).replace('"', "\\\"");
-                label = format!("{}: {}", relation, sanitized);
-            }
-            if let (Some(from_node), Some(to_node)) =
-                (self.graph.node_weight(weight.from), self.graph.node_weight(weight.to))
-            {
-                output.push_str(&format!(
-                    "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
-                    from_node.id, to_node.id, label
-                ));
-            }
-        }
+    /// Copy the graph into its serializable, UUID-keyed form.
+    pub fn snapshot(&self) -> GraphSnapshot {
+        let nodes = self
+            .graph
+            .node_indices()
+            .filter_map(|index| self.graph.node_weight(index).cloned())
+            .collect();
+
+        let edges = self
+            .graph
+            .edge_references()
+            .filter_map(|edge| {
+                let weight = edge.weight();
+                let from = self.graph.node_weight(weight.from)?;
+                let to = self.graph.node_weight(weight.to)?;
+                Some(SnapshotEdge {
+                    from:      from.id,
+                    to:        to.id,
+                    relation:  weight.relation.clone(),
+                    rationale: weight.rationale.clone(),
+                    generator: weight.generator.clone(),
+                    pinned:    weight.pinned,
+                })
+            })
+            .collect();
 
-        output.push_str("}\n");
-        output
+        GraphSnapshot { nodes, edges }
     }
 
     pub fn graph(&self) -> &Graph<Node, Edge, Directed> {
@@ -138,6 +1498,14 @@ impl GraphStore {
         self.graph.edge_weight(index)
     }
 
+    /// Mutable access for fields no index covers, such as `pinned`.
+    ///
+    /// The relation and endpoints feed the prerequisite order; replace an
+    /// edge by removing and re-adding it instead.
+    pub fn edge_weight_mut(&mut self, index: EdgeIndex) -> Option<&mut Edge> {
+        self.graph.edge_weight_mut(index)
+    }
+
     pub fn prerequisite_edges(&self) -> usize {
         self.graph
             .edge_references()
@@ -152,27 +1520,1231 @@ impl GraphStore {
             .count()
     }
 
-    pub fn is_prerequisite_dag(&self) -> bool {
-        let mut check_graph = Graph::<(), (), Directed>::new();
-        let mut mapping = HashMap::new();
+    pub fn related_edges(&self) -> usize {
+        self.graph
+            .edge_references()
+            .filter(|edge_ref| matches!(edge_ref.weight().relation, Relation::RelatedTo))
+            .count()
+    }
+
+    /// Whether `edge` is a prerequisite joining nodes of different namespaces.
+    pub fn is_cross_namespace_prereq(&self, edge: &Edge) -> bool {
+        matches!(edge.relation, Relation::PrerequisiteFor)
+            && match (self.node(edge.from), self.node(edge.to)) {
+                (Some(from), Some(to)) => from.namespace != to.namespace,
+                _ => false,
+            }
+    }
 
-        for node_index in self.graph.node_indices() {
-            let mapped = check_graph.add_node(());
-            mapping.insert(node_index, mapped);
+    /// A teaching order: every node after all of its prerequisites.
+    ///
+    /// Only prerequisite edges constrain the order; among nodes that are
+    /// free to come next, lower levels go first, then normalized text.
+    pub fn prerequisite_topo_order(&self) -> Result<Vec<Uuid>, CycleInfo> {
+        let order = self.teaching_order_within(|_| true);
+        if order.len() == self.graph.node_count() {
+            return Ok(order);
         }
 
-        for edge in self
+        let prerequisites = EdgeFiltered::from_fn(&self.graph, |edge| {
+            matches!(edge.weight().relation, Relation::PrerequisiteFor)
+        });
+        let mut on_cycle: Vec<_> = algo::tarjan_scc(&prerequisites)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || prerequisites
+                        .edges(component[0])
+                        .any(|edge| edge.target() == component[0])
+            })
+            .flatten()
+            .map(|index| self.teaching_key(index))
+            .collect();
+        on_cycle.sort_unstable();
+        Err(CycleInfo {
+            nodes: on_cycle.into_iter().map(|(_, _, id, _)| id).collect(),
+        })
+    }
+
+    /// Every node `id` transitively depends on through prerequisites, in
+    /// teaching order.
+    ///
+    /// Nodes on a prerequisite cycle cannot be ordered, so they come last,
+    /// by level then text.
+    pub fn required_before(&self, id: &Uuid) -> Result<Vec<Uuid>, UnknownNode> {
+        self.prerequisite_closure(id, Direction::Incoming)
+    }
+
+    /// Every node that transitively depends on `id` through prerequisites,
+    /// ordered like [`Self::required_before`].
+    pub fn unlocked_by(&self, id: &Uuid) -> Result<Vec<Uuid>, UnknownNode> {
+        self.prerequisite_closure(id, Direction::Outgoing)
+    }
+
+    fn prerequisite_closure(
+        &self,
+        id: &Uuid,
+        direction: Direction,
+    ) -> Result<Vec<Uuid>, UnknownNode> {
+        let start = self.find_by_id(id).ok_or(UnknownNode(*id))?;
+        let mut closure: HashSet<NodeIndex> = self
+            .ranked_reach(start, direction, |_| true)
+            .into_iter()
+            .collect();
+        closure.remove(&start);
+
+        let mut order = self.teaching_order_within(|index| closure.contains(&index));
+        if order.len() < closure.len() {
+            let ordered: HashSet<Uuid> = order.iter().copied().collect();
+            let mut rest: Vec<_> = closure
+                .iter()
+                .map(|&index| self.teaching_key(index))
+                .filter(|(_, _, id, _)| !ordered.contains(id))
+                .collect();
+            rest.sort_unstable();
+            order.extend(rest.into_iter().map(|(_, _, id, _)| id));
+        }
+        Ok(order)
+    }
+
+    /// Sort key choosing among nodes free to come next: level, then
+    /// normalized text.
+    fn teaching_key(&self, index: NodeIndex) -> (u8, String, Uuid, NodeIndex) {
+        let node = &self.graph[index];
+        (node.level, normalize_text(&node.text), node.id, index)
+    }
+
+    /// Kahn's algorithm over the prerequisite edges among nodes `within`
+    /// accepts; nodes on or behind a cycle are left out.
+    fn teaching_order_within(&self, within: impl Fn(NodeIndex) -> bool) -> Vec<Uuid> {
+        let prerequisites = EdgeFiltered::from_fn(&self.graph, |edge| {
+            matches!(edge.weight().relation, Relation::PrerequisiteFor)
+                && within(edge.source())
+                && within(edge.target())
+        });
+        let mut indegree: HashMap<NodeIndex, usize> = HashMap::new();
+        for edge in prerequisites.edge_references() {
+            *indegree.entry(edge.target()).or_default() += 1;
+        }
+
+        let mut ready: BTreeSet<_> = self
             .graph
-            .edge_references()
-            .filter(|edge| matches!(edge.weight().relation, Relation::PrerequisiteFor))
-        {
-            if let (Some(&from), Some(&to)) =
-                (mapping.get(&edge.source()), mapping.get(&edge.target()))
-            {
-                check_graph.add_edge(from, to, ());
+            .node_indices()
+            .filter(|&index| within(index) && !indegree.contains_key(&index))
+            .map(|index| self.teaching_key(index))
+            .collect();
+        let mut order = Vec::new();
+        while let Some((_, _, id, index)) = ready.pop_first() {
+            order.push(id);
+            for edge in prerequisites.edges(index) {
+                let Some(degree) = indegree.get_mut(&edge.target()) else {
+                    continue;
+                };
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.insert(self.teaching_key(edge.target()));
+                }
+            }
+        }
+        order
+    }
+
+    /// The longest prerequisite chain, first prerequisite first; `None`
+    /// when the prerequisites are cyclic.
+    ///
+    /// Ties go to whichever chain ends, and then steps back, through the
+    /// node earliest in [`Self::prerequisite_topo_order`]. A node with no
+    /// prerequisite edges is a chain of one.
+    pub fn longest_prerequisite_path(&self) -> Option<Vec<Uuid>> {
+        let order = self.prerequisite_topo_order().ok()?;
+        let mut best: HashMap<NodeIndex, (usize, Option<NodeIndex>)> = HashMap::new();
+        let mut end: Option<(usize, NodeIndex)> = None;
+        for id in &order {
+            let index = self.id_index[id];
+            let (length, _) = *best.entry(index).or_insert((1, None));
+            if end.is_none_or(|(longest, _)| length > longest) {
+                end = Some((length, index));
+            }
+            for edge in self.graph.edges(index) {
+                if matches!(edge.weight().relation, Relation::PrerequisiteFor) {
+                    let next = best.entry(edge.target()).or_insert((1, None));
+                    if length + 1 > next.0 {
+                        *next = (length + 1, Some(index));
+                    }
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut cursor = end.map(|(_, index)| index);
+        while let Some(index) = cursor {
+            path.push(self.graph[index].id);
+            cursor = best[&index].1;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Levels implied by prerequisite depth without applying them, as
+    /// `(id, old, new)` for each node that would change.
+    ///
+    /// A node's depth is the number of nodes before it on its longest
+    /// prerequisite chain, clamped to `max_level`. Supports and related
+    /// edges do not count, and nodes on or behind a cycle are left out.
+    pub fn level_changes(&self, max_level: u8) -> Vec<(Uuid, u8, u8)> {
+        let mut depth: HashMap<NodeIndex, usize> = HashMap::new();
+        let mut changes = Vec::new();
+        for id in self.teaching_order_within(|_| true) {
+            let index = self.id_index[&id];
+            let level = depth.get(&index).copied().unwrap_or_default();
+            for edge in self.graph.edges(index) {
+                if matches!(edge.weight().relation, Relation::PrerequisiteFor) {
+                    let next = depth.entry(edge.target()).or_default();
+                    *next = (*next).max(level + 1);
+                }
+            }
+            let old = self.graph[index].level;
+            let new = level.min(max_level.into()) as u8;
+            if old != new {
+                changes.push((id, old, new));
+            }
+        }
+        changes
+    }
+
+    /// Apply [`Self::level_changes`] and return what changed.
+    pub fn recompute_levels(&mut self, max_level: u8) -> Vec<(Uuid, u8, u8)> {
+        let changes = self.level_changes(max_level);
+        for (id, _, new) in &changes {
+            let index = self.id_index[id];
+            self.graph[index].level = *new;
+        }
+        changes
+    }
+
+    pub fn is_prerequisite_dag(&self) -> bool {
+        !self.prereq_cyclic
+    }
+}
+
+/// Jaccard similarity of the distinct words of two texts, the score
+/// [`GraphStore::find_similar`] ranks by.
+pub fn text_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (text_tokens(a), text_tokens(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// Distinct words of the normalized text, split at anything but letters
+/// and digits.
+fn text_tokens(text: &str) -> HashSet<String> {
+    normalize_text(text)
+        .split(|char: char| !char.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Move `index` entries under `keys` from `from` to `to`, dropping keys
+/// left without nodes when `to` is `None`.
+fn move_entries<'a>(
+    index: &mut HashMap<String, HashSet<NodeIndex>>,
+    keys: impl Iterator<Item = &'a str>,
+    from: NodeIndex,
+    to: Option<NodeIndex>,
+) {
+    for key in keys {
+        let Some(nodes) = index.get_mut(key) else {
+            continue;
+        };
+        nodes.remove(&from);
+        match to {
+            Some(to) => {
+                nodes.insert(to);
+            }
+            None if nodes.is_empty() => {
+                index.remove(key);
+            }
+            None => {}
+        }
+    }
+}
+
+/// Order nodes by normalized text, then id so equal texts stay stable.
+fn sort_by_text(nodes: &mut [&Node]) {
+    nodes.sort_by_cached_key(|node| (normalize_text(&node.text), node.id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Granularity, NodeKind};
+
+    fn node(text: String) -> Node {
+        Node {
+            id:          Uuid::new_v4(),
+            kind:        NodeKind::Concept,
+            granularity: Granularity::Sentence,
+            level:       0,
+            text:        text.into(),
+            tags:        None,
+            generator:   None,
+            namespace:   Some("recursion".to_string()),
+            topic:       None,
+            pinned:      false,
+        }
+    }
+
+    #[test]
+    fn from_snapshot_round_trips_and_reports_every_problem() {
+        let mut store = GraphStore::new();
+        let first = store.add_node(node("Base cases stop the descent.".to_string()));
+        let second = store.add_node(node("Recursive cases shrink the input.".to_string()));
+        store.add_edge(Edge {
+            from:      first,
+            to:        second,
+            relation:  Relation::PrerequisiteFor,
+            rationale: "Base cases come first.".to_string(),
+            generator: None,
+            pinned:    false,
+        });
+
+        let rebuilt = GraphStore::from_snapshot(store.snapshot()).expect("consistent snapshot");
+        assert_eq!((rebuilt.node_count(), rebuilt.edge_count()), (2, 1));
+
+        let mut broken = store.snapshot();
+        let duplicate = broken.nodes[0].clone();
+        broken.nodes.push(duplicate.clone());
+        let stray = Uuid::new_v4();
+        broken.edges[0].to = stray;
+        let problems = GraphStore::from_snapshot(broken).expect_err("broken snapshot");
+        assert_eq!(
+            problems,
+            vec![
+                SnapshotProblem::DuplicateNode(duplicate.id),
+                SnapshotProblem::UnknownEndpoint {
+                    from:    duplicate.id,
+                    to:      stray,
+                    missing: stray,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn save_and_load_round_trip_relations_tags_and_rationales() {
+        let mut store = GraphStore::new();
+        let mut tagged = node("Base cases stop the descent.".to_string());
+        tagged.tags = Some(vec!["termination".to_string(), "proofs".to_string()]);
+        let first = store.add_node(tagged);
+        let second = store.add_node(node("Recursive cases shrink the input.".to_string()));
+        let mut outcome = node("I can trace a recursive call.".to_string());
+        outcome.kind = NodeKind::LearningOutcome;
+        let third = store.add_node(outcome);
+        for (from, to, relation, rationale) in [
+            (first, second, Relation::PrerequisiteFor, "Stop first.\nThen shrink."),
+            (second, third, Relation::Supports, "Shrinking is what a trace shows."),
+            (first, third, Relation::RelatedTo, "Both end the recursion."),
+        ] {
+            store.add_edge(Edge {
+                from,
+                to,
+                relation,
+                rationale: rationale.to_string(),
+                generator: None,
+                pinned: false,
+            });
+        }
+
+        let path = std::env::temp_dir().join(format!("weaver-store-{}.json", Uuid::new_v4()));
+        store.save(&path).expect("save graph");
+        let loaded = GraphStore::load(&path).expect("load graph");
+        std::fs::remove_file(&path).ok();
+
+        let (saved, reloaded) = (store.snapshot(), loaded.snapshot());
+        assert_eq!(serde_json::to_value(&saved).unwrap(), serde_json::to_value(&reloaded).unwrap());
+        assert_eq!(
+            loaded.find_by_text(Some("recursion"), "base cases stop the descent"),
+            store.find_by_text(Some("recursion"), "base cases stop the descent")
+        );
+        assert_eq!(loaded.find_by_id(&saved.nodes[2].id), Some(third));
+    }
+
+    #[test]
+    fn load_rejects_unknown_endpoints_and_bad_json() {
+        let mut store = GraphStore::new();
+        let first = store.add_node(node("Base cases stop the descent.".to_string()));
+        let second = store.add_node(node("Recursive cases shrink the input.".to_string()));
+        store.add_edge(Edge {
+            from:      first,
+            to:        second,
+            relation:  Relation::Supports,
+            rationale: "Base cases come first.".to_string(),
+            generator: None,
+            pinned:    false,
+        });
+        let mut broken = store.snapshot();
+        broken.nodes.remove(1);
+
+        let path = std::env::temp_dir().join(format!("weaver-store-{}.json", Uuid::new_v4()));
+        std::fs::write(&path, serde_json::to_string(&broken).unwrap()).unwrap();
+        let err = GraphStore::load(&path).expect_err("dangling edge");
+        assert!(matches!(&err, LoadError::Invalid(problems) if problems.len() == 1), "{err}");
+
+        std::fs::write(&path, "{\"nodes\": [").unwrap();
+        let err = GraphStore::load(&path).expect_err("truncated JSON");
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(err, LoadError::Format(_)), "{err}");
+    }
+
+    #[test]
+    fn removing_a_chain_link_keeps_indexes_and_exports_consistent() {
+        let mut store = GraphStore::new();
+        let texts = [
+            "Values have types.",
+            "Variables name values.",
+            "Loops repeat statements.",
+            "Recursion replaces loops.",
+        ];
+        let chain: Vec<NodeIndex> = texts
+            .iter()
+            .map(|text| store.add_node(node(text.to_string())))
+            .collect();
+        for pair in chain.windows(2) {
+            store.add_edge(Edge {
+                from:      pair[0],
+                to:        pair[1],
+                relation:  Relation::PrerequisiteFor,
+                rationale: "Each step builds on the last.".to_string(),
+                generator: None,
+                pinned:    false,
+            });
+        }
+        let ids: Vec<Uuid> = chain
+            .iter()
+            .map(|&index| store.node(index).unwrap().id)
+            .collect();
+
+        let (removed, edges) = store.remove_node(&ids[1]).expect("node exists");
+        assert_eq!(removed.text.as_ref(), texts[1]);
+        assert_eq!(edges.len(), 2);
+        assert_eq!((store.node_count(), store.edge_count()), (3, 1));
+        assert!(store.is_prerequisite_dag());
+        assert_eq!(store.find_by_id(&ids[1]), None);
+        assert_eq!(store.find_by_text(Some("recursion"), texts[1]), None);
+        for (id, text) in [(ids[0], texts[0]), (ids[2], texts[2]), (ids[3], texts[3])] {
+            let index = store.find_by_id(&id).expect("kept node indexed");
+            assert_eq!(store.find_by_text(Some("recursion"), text), Some(index));
+            assert_eq!(store.node(index).unwrap().id, id);
+        }
+        let inventory: HashSet<Uuid> = store.inventory().iter().map(|entry| entry.0).collect();
+        assert_eq!(inventory, HashSet::from([ids[0], ids[2], ids[3]]));
+
+        let snapshot = store.snapshot();
+        assert_eq!((snapshot.edges[0].from, snapshot.edges[0].to), (ids[2], ids[3]));
+        let dot = crate::export::render_dot(&snapshot);
+        assert!(!dot.contains(&ids[1].to_string()) && dot.contains(&ids[3].to_string()));
+
+        assert_eq!(store.remove_node(&ids[1]).map(|_| ()), Err(UnknownNode(ids[1])));
+    }
+
+    #[test]
+    fn remove_edge_takes_only_the_matching_parallel_edge() {
+        let mut store = GraphStore::new();
+        let first = store.add_node(node("Base cases stop the descent.".to_string()));
+        let second = store.add_node(node("Recursive cases shrink the input.".to_string()));
+        for relation in [Relation::PrerequisiteFor, Relation::RelatedTo] {
+            store.add_edge(Edge {
+                from: first,
+                to: second,
+                relation,
+                rationale: "Both describe recursion.".to_string(),
+                generator: None,
+                pinned: false,
+            });
+        }
+        let (from, to) = (store.node(first).unwrap().id, store.node(second).unwrap().id);
+
+        assert!(store.remove_edge(&from, &to, Relation::PrerequisiteFor));
+        assert!(!store.remove_edge(&from, &to, Relation::PrerequisiteFor));
+        assert!(!store.remove_edge(&to, &from, Relation::RelatedTo));
+        assert_eq!(store.edge_count(), 1);
+        assert!(store.has_edge_between(first, second, &Relation::RelatedTo));
+
+        let related = store
+            .find_edge(first, second, &Relation::RelatedTo)
+            .expect("parallel edge survives");
+        let removed = store.remove_edge_at(related).expect("edge at index");
+        assert_eq!(removed.relation, Relation::RelatedTo);
+        assert_eq!(store.edge_count(), 0);
+    }
+
+    #[test]
+    fn update_node_text_moves_the_text_index_entry() {
+        let mut store = GraphStore::new();
+        let mut tagged = node("Base cases stop teh descent.".to_string());
+        tagged.tags = Some(vec!["termination".to_string()]);
+        tagged.level = 2;
+        let first = store.add_node(tagged);
+        let second = store.add_node(node("Recursive cases shrink the input.".to_string()));
+        store.add_edge(Edge {
+            from:      first,
+            to:        second,
+            relation:  Relation::PrerequisiteFor,
+            rationale: "Base cases come first.".to_string(),
+            generator: None,
+            pinned:    false,
+        });
+        let (id, other) = (store.node(first).unwrap().id, store.node(second).unwrap().id);
+
+        store
+            .update_node_text(&id, "  Base cases stop the   descent. ")
+            .expect("new text is free");
+        let updated = store.node(first).expect("node kept");
+        assert_eq!(updated.text.as_ref(), "Base cases stop the descent.");
+        assert_eq!((updated.id, updated.level), (id, 2));
+        assert_eq!(updated.tags, Some(vec!["termination".to_string()]));
+        assert_eq!(store.edge_count(), 1);
+        assert_eq!(
+            store.find_by_text(Some("recursion"), "base cases STOP the descent."),
+            Some(first)
+        );
+        assert_eq!(store.find_by_text(Some("recursion"), "Base cases stop teh descent."), None);
+
+        assert_eq!(
+            store.update_node_text(&id, "recursive cases  shrink the input."),
+            Err(UpdateTextError::DuplicateText(other))
+        );
+        assert_eq!(store.update_node_text(&id, "   "), Err(UpdateTextError::EmptyText));
+        let stray = Uuid::new_v4();
+        assert_eq!(
+            store.update_node_text(&stray, "Anything."),
+            Err(UpdateTextError::NotFound(stray))
+        );
+        store
+            .update_node_text(&id, "BASE cases stop the descent.")
+            .expect("recasing keeps its own key");
+    }
+
+    #[test]
+    fn tag_index_follows_adds_and_removals() {
+        let mut store = GraphStore::new();
+        let tagged = |text: &str, tags: &[&str]| {
+            let mut node = node(text.to_string());
+            node.tags = Some(tags.iter().map(|tag| tag.to_string()).collect());
+            node
+        };
+        let first = store.add_node(tagged("Base cases stop the descent.", &["proofs", "base"]));
+        store.add_node(node("Untagged nodes are not indexed.".to_string()));
+        store.add_node(tagged("Recursive cases shrink the input.", &["proofs"]));
+        store.add_node(tagged("Traces show each call.", &["proofs", "tracing"]));
+        assert_eq!(
+            store.tags_in_use(),
+            vec![
+                ("base".to_string(), 1),
+                ("proofs".to_string(), 3),
+                ("tracing".to_string(), 1)
+            ]
+        );
+
+        // Removing the first node moves the last one into its slot.
+        let id = store.node(first).unwrap().id;
+        store.remove_node(&id).expect("node exists");
+        let texts: Vec<&str> = store
+            .find_by_tag("proofs")
+            .iter()
+            .map(|node| node.text.as_ref())
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Traces show each call.",
+                "Recursive cases shrink the input."
+            ]
+        );
+        assert_eq!(store.find_by_tag("tracing")[0].text.as_ref(), "Traces show each call.");
+        assert!(store.find_by_tag("base").is_empty());
+        assert_eq!(
+            store.tags_in_use(),
+            vec![("proofs".to_string(), 2), ("tracing".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn nodes_group_by_level_in_text_order() {
+        let mut store = GraphStore::new();
+        for (level, text) in [
+            (2, "Recursion replaces loops."),
+            (0, "values have types."),
+            (3, "I can trace a recursive call."),
+            (1, "Loops repeat statements."),
+            (0, "Variables name values."),
+            (2, "base cases stop recursion."),
+        ] {
+            let mut leveled = node(text.to_string());
+            leveled.level = level;
+            store.add_node(leveled);
+        }
+
+        let levels = store.nodes_by_level();
+        let texts = |nodes: &[&Node]| -> Vec<String> {
+            nodes.iter().map(|node| node.text.to_string()).collect()
+        };
+        assert_eq!(levels.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert_eq!(texts(&levels[&0]), vec!["values have types.", "Variables name values."]);
+        assert_eq!(
+            texts(&levels[&2]),
+            vec!["base cases stop recursion.", "Recursion replaces loops."]
+        );
+        assert_eq!(texts(&store.nodes_at_level(2)), texts(&levels[&2]));
+        assert_eq!(store.nodes_at_level(3).len(), 1);
+        assert!(store.nodes_at_level(4).is_empty());
+    }
+
+    #[test]
+    fn topo_order_respects_prerequisites_then_level_and_text() {
+        let mut store = GraphStore::new();
+        let mut add = |level: u8, text: &str| {
+            let mut leveled = node(text.to_string());
+            leveled.level = level;
+            store.add_node(leveled)
+        };
+        let recursion = add(1, "Recursion replaces loops.");
+        let loops = add(2, "Loops repeat statements.");
+        let values = add(0, "Values have types.");
+        let alone = add(0, "Comments explain code.");
+        let outcome = add(3, "I can trace a recursive call.");
+        for (from, to, relation) in [
+            (loops, recursion, Relation::PrerequisiteFor),
+            (values, loops, Relation::PrerequisiteFor),
+            (recursion, values, Relation::Supports),
+            (recursion, outcome, Relation::PrerequisiteFor),
+        ] {
+            store.add_edge(Edge {
+                from,
+                to,
+                relation,
+                rationale: "Order matters.".to_string(),
+                generator: None,
+                pinned: false,
+            });
+        }
+        let id = |index: NodeIndex| store.node(index).unwrap().id;
+
+        let order = store
+            .prerequisite_topo_order()
+            .expect("acyclic prerequisites");
+        assert_eq!(order, vec![id(alone), id(values), id(loops), id(recursion), id(outcome)]);
+        let cycle_ids = vec![id(values), id(recursion), id(loops)];
+
+        store.add_edge(Edge {
+            from:      recursion,
+            to:        values,
+            relation:  Relation::PrerequisiteFor,
+            rationale: "Closes a cycle.".to_string(),
+            generator: None,
+            pinned:    false,
+        });
+        let cycle = store.prerequisite_topo_order().expect_err("cycle");
+        assert_eq!(cycle.nodes, cycle_ids);
+    }
+
+    #[test]
+    fn inventory_shares_the_stored_text() {
+        let mut store = GraphStore::new();
+        let index = store.add_node(node("Base cases stop the descent.".to_string()));
+
+        let inventory = store.inventory();
+
+        let stored = &store.node(index).expect("node stored").text;
+        assert!(Arc::ptr_eq(stored, &inventory[0].3));
+        assert_eq!(
+            serde_json::to_value(store.snapshot().nodes[0].text.clone()).unwrap(),
+            serde_json::json!("Base cases stop the descent.")
+        );
+    }
+
+    #[test]
+    fn interner_holds_one_copy_per_distinct_string() {
+        let mut store = GraphStore::new();
+        for i in 0..10_000 {
+            store.add_node(node(format!("concept number {i} is lowercase.")));
+        }
+        // Re-adding the same text (a duplicate the adder would reject) still
+        // shares the existing copy.
+        store.add_node(node("concept number 0 is lowercase.".to_string()));
+
+        // Each lowercase text doubles as its own normalized key, plus one
+        // namespace string.
+        assert_eq!(store.interner().len(), 10_001);
+    }
+
+    impl GraphStore {
+        /// A store around `graph` with none of the checks `add_node` and
+        /// `add_edge` make, so tests can build broken graphs.
+        fn unchecked(graph: Graph<Node, Edge, Directed>) -> Self {
+            let mut store = GraphStore::new();
+            for index in graph.node_indices() {
+                let node = &graph[index];
+                let key = store.intern_key(node.namespace.as_deref(), &node.text);
+                store.text_index.insert(key, index);
+                store.id_index.insert(node.id, index);
+                store.topo_rank.push(0);
+                store.token_counts.push(0);
+            }
+            store.graph = graph;
+            store.prereq_cyclic = true;
+            store.refresh_prerequisite_order();
+            store
+        }
+    }
+
+    fn prerequisite(from: NodeIndex, to: NodeIndex) -> Edge {
+        Edge {
+            from,
+            to,
+            relation: Relation::PrerequisiteFor,
+            rationale: String::new(),
+            generator: None,
+            pinned: false,
+        }
+    }
+
+    fn scattered_store(nodes: usize) -> GraphStore {
+        let mut store = GraphStore::new();
+        for index in 0..nodes {
+            store.add_node(node(format!("Concept number {index}.")));
+        }
+        store
+    }
+
+    #[test]
+    fn neighbor_queries_follow_relation_and_direction() {
+        let mut store = scattered_store(4);
+        let [base, loops, recursion, trace] = [0, 1, 2, 3].map(NodeIndex::new);
+        for (from, to, relation, rationale) in [
+            (base, recursion, Relation::PrerequisiteFor, "Stop first."),
+            (loops, recursion, Relation::PrerequisiteFor, "Iterate first."),
+            (recursion, trace, Relation::PrerequisiteFor, "Recurse, then trace."),
+            (loops, trace, Relation::Supports, "Loops trace the same way."),
+            (base, loops, Relation::RelatedTo, "Both terminate."),
+        ] {
+            store.add_edge(Edge {
+                rationale: rationale.to_string(),
+                relation,
+                ..prerequisite(from, to)
+            });
+        }
+        let ids = [base, loops, recursion, trace].map(|index| store.graph[index].id);
+        let texts = |found: Vec<(&Node, &str)>| -> Vec<(String, String)> {
+            found
+                .into_iter()
+                .map(|(node, rationale)| (node.text.to_string(), rationale.to_string()))
+                .collect()
+        };
+        let pair = |node: usize, rationale: &str| {
+            (format!("Concept number {node}."), rationale.to_string())
+        };
+
+        assert_eq!(
+            texts(store.prerequisites_of(&ids[2])),
+            vec![pair(0, "Stop first."), pair(1, "Iterate first.")]
+        );
+        assert_eq!(texts(store.dependents_of(&ids[2])), vec![pair(3, "Recurse, then trace.")]);
+        assert_eq!(texts(store.supporters_of(&ids[3])), vec![pair(1, "Loops trace the same way.")]);
+        assert!(store.dependents_of(&ids[3]).is_empty());
+        assert!(store.prerequisites_of(&ids[1]).is_empty());
+        assert!(store.supporters_of(&Uuid::new_v4()).is_empty());
+    }
+
+    #[test]
+    fn ancestor_subgraph_keeps_prerequisites_and_their_supporters() {
+        let mut store = scattered_store(7);
+        let [
+            first,
+            second,
+            outcome,
+            supporter,
+            before_supporter,
+            after,
+            unrelated,
+        ] = [0, 1, 2, 3, 4, 5, 6].map(NodeIndex::new);
+        for (from, to, relation) in [
+            (first, second, Relation::PrerequisiteFor),
+            (second, outcome, Relation::PrerequisiteFor),
+            (supporter, second, Relation::Supports),
+            (before_supporter, supporter, Relation::PrerequisiteFor),
+            (outcome, after, Relation::PrerequisiteFor),
+            (unrelated, first, Relation::RelatedTo),
+        ] {
+            store.add_edge(Edge {
+                relation,
+                ..prerequisite(from, to)
+            });
+        }
+        let id = |index: NodeIndex| store.graph[index].id;
+
+        let subgraph = store.ancestor_subgraph(&id(outcome)).expect("known node");
+        let snapshot = subgraph.snapshot();
+        let kept: Vec<Uuid> = snapshot.nodes.iter().map(|node| node.id).collect();
+        assert_eq!(kept, vec![id(first), id(second), id(outcome), id(supporter)]);
+        assert_eq!((subgraph.prerequisite_edges(), subgraph.supports_edges()), (2, 1));
+        assert!(crate::export::render_dot(&snapshot).contains("Concept number 3."));
+
+        let alone = store.ancestor_subgraph(&id(first)).expect("known node");
+        assert_eq!((alone.node_count(), alone.edge_count()), (1, 0));
+        let missing = Uuid::new_v4();
+        assert_eq!(store.ancestor_subgraph(&missing).map(|_| ()), Err(UnknownNode(missing)));
+    }
+
+    #[test]
+    fn fragmented_graphs_split_into_components() {
+        let mut store = scattered_store(6);
+        for (from, to, relation) in [
+            (0, 1, Relation::PrerequisiteFor),
+            (2, 1, Relation::Supports),
+            (4, 3, Relation::RelatedTo),
+        ] {
+            store.add_edge(Edge {
+                relation,
+                ..prerequisite(NodeIndex::new(from), NodeIndex::new(to))
+            });
+        }
+        let id = |index: usize| store.graph[NodeIndex::new(index)].id;
+
+        assert_eq!(
+            store.connected_components(),
+            vec![vec![id(0), id(1), id(2)], vec![id(3), id(4)], vec![id(5)]]
+        );
+        assert!(!store.is_connected());
+
+        store.add_edge(prerequisite(NodeIndex::new(1), NodeIndex::new(3)));
+        store.add_edge(prerequisite(NodeIndex::new(5), NodeIndex::new(0)));
+        assert_eq!(store.connected_components().len(), 1);
+        assert!(store.is_connected() && GraphStore::new().is_connected());
+    }
+
+    #[test]
+    fn a_barbell_hangs_on_its_handle() {
+        let mut store = scattered_store(7);
+        for (from, to) in [
+            (0, 1),
+            (1, 2),
+            (0, 2),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (5, 6),
+            (4, 6),
+        ] {
+            store.add_edge(prerequisite(NodeIndex::new(from), NodeIndex::new(to)));
+        }
+        let id = |store: &GraphStore, index: usize| store.graph[NodeIndex::new(index)].id;
+
+        assert_eq!(
+            store.articulation_points(),
+            vec![(id(&store, 3), 3), (id(&store, 2), 2), (id(&store, 4), 2)]
+        );
+        assert_eq!(
+            store.bridges(),
+            vec![
+                (id(&store, 2), id(&store, 3)),
+                (id(&store, 3), id(&store, 4))
+            ]
+        );
+
+        store.add_edge(Edge {
+            relation: Relation::Supports,
+            ..prerequisite(NodeIndex::new(3), NodeIndex::new(4))
+        });
+        assert_eq!(store.bridges(), vec![(id(&store, 2), id(&store, 3))]);
+        assert!(GraphStore::new().articulation_points().is_empty());
+    }
+
+    #[test]
+    fn find_similar_scores_shared_words() {
+        let mut store = GraphStore::new();
+        let trace =
+            store.add_node(node("Students trace data flow through recursive helpers.".to_string()));
+        let base = store.add_node(node("Base cases stop the descent.".to_string()));
+        let helpers = store.add_node(node("Recursive helpers carry state.".to_string()));
+
+        let found =
+            store.find_similar("Students trace the data flow through recursive helpers.", 0.5);
+        assert_eq!(found, vec![(trace, 7.0 / 8.0)]);
+        let found = store.find_similar("RECURSIVE HELPERS, carry state", 0.0);
+        assert_eq!(found, vec![(helpers, 1.0), (trace, 2.0 / 9.0)]);
+        assert!(
+            store
+                .find_similar("Queues hold waiting tasks.", 0.1)
+                .is_empty()
+        );
+
+        let id = store.node(trace).expect("node exists").id;
+        store.remove_node(&id).expect("node exists");
+        assert_eq!(store.find_similar("recursive helpers", 0.1), vec![(trace, 0.5)]);
+        let id = store.node(base).expect("node exists").id;
+        store
+            .update_node_text(&id, "Base cases stop recursive helpers.")
+            .expect("new text is free");
+        assert_eq!(store.find_similar("the descent", 0.1), vec![]);
+        assert_eq!(store.find_similar("recursive helpers", 0.3), vec![(trace, 0.5), (base, 0.4)]);
+    }
+
+    #[test]
+    fn merge_from_folds_shared_nodes_and_skips_bad_edges() {
+        let mut store = scattered_store(4);
+        for (from, to) in [(0, 1), (2, 3)] {
+            store.add_edge(prerequisite(NodeIndex::new(from), NodeIndex::new(to)));
+        }
+        let id = |store: &GraphStore, index: usize| store.graph[NodeIndex::new(index)].id;
+
+        let mut other = GraphStore::new();
+        other.add_node(store.graph[NodeIndex::new(0)].clone());
+        for index in 2..6 {
+            other.add_node(node(format!("Concept number {index}.")));
+        }
+        for (from, to, relation) in [
+            (1, 2, Relation::PrerequisiteFor),
+            (2, 3, Relation::PrerequisiteFor),
+            (3, 1, Relation::PrerequisiteFor),
+            (3, 4, Relation::Supports),
+        ] {
+            other.add_edge(Edge {
+                relation,
+                ..prerequisite(NodeIndex::new(from), NodeIndex::new(to))
+            });
+        }
+
+        let report = store.merge_from(&other);
+        assert_eq!((report.nodes_added, report.nodes_matched), (2, 1));
+        assert_eq!(
+            report.nodes_remapped,
+            vec![
+                (id(&other, 1), id(&store, 2)),
+                (id(&other, 2), id(&store, 3))
+            ]
+        );
+        assert_eq!(report.edges_added, 2);
+        assert_eq!(
+            report.edges_skipped,
+            vec![
+                SkippedEdge {
+                    from:     id(&store, 2),
+                    to:       id(&store, 3),
+                    relation: Relation::PrerequisiteFor,
+                    reason:   SkipReason::Duplicate,
+                },
+                SkippedEdge {
+                    from:     id(&other, 3),
+                    to:       id(&store, 2),
+                    relation: Relation::PrerequisiteFor,
+                    reason:   SkipReason::Cycle,
+                },
+            ]
+        );
+        assert_eq!((store.node_count(), store.edge_count()), (6, 4));
+        assert!(store.find_by_id(&id(&other, 4)).is_some());
+        assert!(store.is_prerequisite_dag());
+    }
+
+    #[test]
+    fn longest_prerequisite_path_breaks_ties_by_teaching_order() {
+        let mut store = scattered_store(6);
+        assert_eq!(GraphStore::new().longest_prerequisite_path(), Some(Vec::new()));
+        assert_eq!(store.longest_prerequisite_path().map(|path| path.len()), Some(1));
+
+        for (from, to) in [(1, 3), (0, 3), (3, 4), (2, 4), (5, 2)] {
+            store.add_edge(prerequisite(NodeIndex::new(from), NodeIndex::new(to)));
+        }
+        store.add_edge(Edge {
+            relation: Relation::Supports,
+            ..prerequisite(NodeIndex::new(4), NodeIndex::new(1))
+        });
+        let id = |index: usize| store.graph[NodeIndex::new(index)].id;
+        assert_eq!(store.longest_prerequisite_path(), Some(vec![id(0), id(3), id(4)]));
+
+        store.add_edge(prerequisite(NodeIndex::new(4), NodeIndex::new(0)));
+        assert_eq!(store.longest_prerequisite_path(), None);
+    }
+
+    #[test]
+    fn closures_list_a_diamond_once_in_teaching_order() {
+        let mut store = scattered_store(6);
+        for (from, to) in [(4, 0), (0, 2), (0, 1), (2, 3), (1, 3)] {
+            store.add_edge(prerequisite(NodeIndex::new(from), NodeIndex::new(to)));
+        }
+        store.add_edge(Edge {
+            relation: Relation::Supports,
+            ..prerequisite(NodeIndex::new(5), NodeIndex::new(3))
+        });
+        let id = |index: usize| store.graph[NodeIndex::new(index)].id;
+
+        assert_eq!(store.required_before(&id(3)), Ok(vec![id(4), id(0), id(1), id(2)]));
+        assert_eq!(store.unlocked_by(&id(0)), Ok(vec![id(1), id(2), id(3)]));
+        assert_eq!(store.unlocked_by(&id(3)), Ok(Vec::new()));
+        let missing = Uuid::new_v4();
+        assert_eq!(store.required_before(&missing), Err(UnknownNode(missing)));
+    }
+
+    #[test]
+    fn recomputed_levels_follow_prerequisite_depth() {
+        let mut store = scattered_store(4);
+        store.add_edge(prerequisite(NodeIndex::new(0), NodeIndex::new(1)));
+        store.add_edge(prerequisite(NodeIndex::new(1), NodeIndex::new(2)));
+        store.add_edge(Edge {
+            relation: Relation::Supports,
+            ..prerequisite(NodeIndex::new(2), NodeIndex::new(3))
+        });
+        let id = |store: &GraphStore, index: usize| store.graph[NodeIndex::new(index)].id;
+        let expected = vec![(id(&store, 1), 0, 1), (id(&store, 2), 0, 2)];
+
+        assert_eq!(store.level_changes(5), expected);
+        assert_eq!(store.graph[NodeIndex::new(2)].level, 0);
+        assert_eq!(store.recompute_levels(5), expected);
+        let levels: Vec<u8> = (0..4)
+            .map(|index| store.graph[NodeIndex::new(index)].level)
+            .collect();
+        assert_eq!(levels, vec![0, 1, 2, 0]);
+        assert!(store.recompute_levels(5).is_empty());
+        assert_eq!(store.level_changes(1), vec![(id(&store, 2), 2, 1)]);
+    }
+
+    #[test]
+    fn statistics_cover_empty_single_and_mixed_graphs() {
+        assert_eq!(GraphStore::new().statistics(), GraphStatistics::default());
+
+        let mut store = scattered_store(1);
+        let single = store.statistics();
+        assert_eq!((single.nodes, single.concepts, single.isolated_nodes), (1, 1, 1));
+        assert_eq!((single.density, single.max_degree), (0.0, 0));
+
+        let mut outcome = node("I can trace a loop.".to_string());
+        outcome.kind = NodeKind::LearningOutcome;
+        outcome.pinned = true;
+        store.add_node(outcome);
+        store.add_node(node("Concept number 1.".to_string()));
+        store.add_node(node("Concept number 2.".to_string()));
+        for (from, to, relation) in [
+            (0, 2, Relation::PrerequisiteFor),
+            (0, 1, Relation::Supports),
+            (2, 1, Relation::Supports),
+            (0, 2, Relation::RelatedTo),
+        ] {
+            store.add_edge(Edge {
+                relation,
+                ..prerequisite(NodeIndex::new(from), NodeIndex::new(to))
+            });
+        }
+        let mixed = store.statistics();
+        assert_eq!(
+            mixed,
+            GraphStatistics {
+                nodes:                    4,
+                concepts:                 3,
+                learning_outcomes:        1,
+                pinned_nodes:             1,
+                isolated_nodes:           1,
+                edges:                    4,
+                prerequisite_edges:       1,
+                supports_edges:           2,
+                related_edges:            1,
+                pinned_edges:             0,
+                density:                  4.0 / 12.0,
+                mean_prerequisite_degree: 0.25,
+                mean_supports_degree:     0.5,
+                mean_related_degree:      0.5,
+                max_degree:               3,
+            }
+        );
+    }
+
+    #[test]
+    fn degrees_count_by_relation_and_direction() {
+        let mut store = scattered_store(4);
+        for (from, to, relation) in [
+            (0, 1, Relation::PrerequisiteFor),
+            (0, 2, Relation::PrerequisiteFor),
+            (2, 1, Relation::Supports),
+            (0, 1, Relation::Supports),
+            (3, 0, Relation::RelatedTo),
+        ] {
+            store.add_edge(Edge {
+                relation,
+                ..prerequisite(NodeIndex::new(from), NodeIndex::new(to))
+            });
+        }
+        let id = |index: usize| store.graph[NodeIndex::new(index)].id;
+
+        assert_eq!(store.out_degree(&id(0), Some(Relation::PrerequisiteFor)), 2);
+        assert_eq!(store.out_degree(&id(0), Some(Relation::Supports)), 1);
+        assert_eq!(store.out_degree(&id(0), None), 4);
+        assert_eq!(store.in_degree(&id(0), None), 1);
+        assert_eq!(store.in_degree(&id(1), Some(Relation::Supports)), 2);
+        assert_eq!(store.in_degree(&id(1), Some(Relation::PrerequisiteFor)), 1);
+        assert_eq!(store.out_degree(&id(1), None), 0);
+        assert_eq!(store.out_degree(&id(3), Some(Relation::RelatedTo)), 1);
+        assert_eq!(store.in_degree(&id(3), Some(Relation::RelatedTo)), 1);
+        assert_eq!(store.in_degree(&Uuid::new_v4(), None), 0);
+
+        let table = store.degree_table();
+        assert_eq!(
+            table[0],
+            (
+                id(0),
+                DegreeInfo {
+                    prerequisites_in:  0,
+                    prerequisites_out: 2,
+                    supports_in:       0,
+                    supports_out:      1,
+                    related:           1,
+                }
+            )
+        );
+        assert_eq!(table[1].1.supports_in, 2);
+        assert_eq!(
+            table[3].1,
+            DegreeInfo {
+                related: 1,
+                ..DegreeInfo::default()
+            }
+        );
+    }
+
+    #[test]
+    fn validate_reports_each_kind_of_corruption() {
+        assert!(scattered_store(3).validate().is_clean());
+
+        let mut graph = Graph::new();
+        let mut raised = node("Level one concept.".to_string());
+        raised.level = 1;
+        let mut outcome = node("I can name a concept.".to_string());
+        outcome.kind = NodeKind::LearningOutcome;
+        let [raised, outcome, blank, low] = [
+            raised,
+            outcome,
+            node("   ".to_string()),
+            node("Level zero concept.".to_string()),
+        ]
+        .map(|node| graph.add_node(node));
+        for (from, to) in [(outcome, low), (raised, low), (low, raised)] {
+            graph.add_edge(from, to, prerequisite(from, to));
+        }
+        graph.add_edge(
+            low,
+            blank,
+            Edge {
+                relation: Relation::Supports,
+                ..prerequisite(blank, raised)
+            },
+        );
+        let mut store = GraphStore::unchecked(graph);
+        let id = |index: NodeIndex| store.graph[index].id;
+        let (raised_id, outcome_id, blank_id, low_id) =
+            (id(raised), id(outcome), id(blank), id(low));
+
+        let ghost = store.intern_key(None, "ghost");
+        store.text_index.insert(ghost, raised);
+        let key = store
+            .lookup_key(Some("recursion"), "Level zero concept.")
+            .expect("interned");
+        store.text_index.remove(&key);
+        store.id_index.remove(&blank_id);
+        let stray = Uuid::new_v4();
+        store.id_index.insert(stray, low);
+
+        let issues = store.validate().issues;
+        let expected = [
+            IntegrityIssue::DanglingEndpoint {
+                edge: EdgeIndex::new(3),
+            },
+            IntegrityIssue::StaleTextEntry {
+                text:  "ghost".to_string(),
+                index: raised,
+            },
+            IntegrityIssue::StaleIdEntry {
+                id:    stray,
+                index: low,
+            },
+            IntegrityIssue::MissingIdEntry { id: blank_id },
+            IntegrityIssue::EmptyText { id: blank_id },
+            IntegrityIssue::MissingTextEntry { id: low_id },
+            IntegrityIssue::PrerequisiteFromOutcome {
+                from: outcome_id,
+                to:   low_id,
+            },
+            IntegrityIssue::PrerequisiteLevelDecrease {
+                from:       raised_id,
+                to:         low_id,
+                from_level: 1,
+                to_level:   0,
+            },
+        ];
+        for issue in &expected {
+            assert!(issues.contains(issue), "{issue} in {issues:?}");
+        }
+        let Some(IntegrityIssue::PrerequisiteCycle { nodes }) = issues.last() else {
+            panic!("cycle reported last: {issues:?}");
+        };
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes.contains(&raised_id) && nodes.contains(&low_id));
+        assert_eq!(issues.len(), expected.len() + 1);
+    }
+
+    #[test]
+    fn incremental_order_matches_a_full_search() {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut store = scattered_store(60);
+        for round in 0..600 {
+            let count = store.node_count();
+            let from = NodeIndex::new(rng.random_range(0..count));
+            let to = NodeIndex::new(rng.random_range(0..count));
+            let expected = store.prereq_path_within(from, to, usize::MAX);
+            assert_eq!(store.prereq_cycle_path(from, to), expected, "round {round}");
+            if expected.is_none() || round % 97 == 0 {
+                store.add_edge(prerequisite(from, to));
             }
+            if round % 41 == 0 {
+                let id = store.graph[NodeIndex::new(rng.random_range(0..count))].id;
+                store.remove_node(&id).expect("node exists");
+                store.add_node(node(format!("Replacement {round}.")));
+            }
+            let dag = !algo::is_cyclic_directed(&EdgeFiltered::from_fn(&store.graph, |edge| {
+                matches!(edge.weight().relation, Relation::PrerequisiteFor)
+            }));
+            assert_eq!(store.is_prerequisite_dag(), dag, "round {round}");
         }
+    }
 
-        !algo::is_cyclic_directed(&check_graph)
+    /// Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn cycle_checks_at_five_thousand_nodes() {
+        use std::time::{Duration, Instant};
+
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut store = scattered_store(5_000);
+        let (mut incremental, mut full) = (Duration::ZERO, Duration::ZERO);
+        for _ in 0..20_000 {
+            let from = NodeIndex::new(rng.random_range(0..5_000));
+            let to = NodeIndex::new(rng.random_range(0..5_000));
+            let started = Instant::now();
+            let cycle = store.prereq_cycle_path(from, to).is_some();
+            incremental += started.elapsed();
+            let started = Instant::now();
+            assert_eq!(store.prereq_path_within(from, to, usize::MAX).is_some(), cycle);
+            full += started.elapsed();
+            if !cycle {
+                store.add_edge(prerequisite(from, to));
+            }
+        }
+        eprintln!("incremental {incremental:?}, full search {full:?}");
     }
 }
@@ -0,0 +1,217 @@
+//! Reading human-authored nodes from CSV, and graphs from DOT in [`dot`].
+//!
+//! Rows are `kind,level,text,tags`, with tags separated by `;`. Fields may
+//! be double-quoted, with `""` for a literal quote, so texts can contain
+//! commas. A first row starting with `kind` is a header and is skipped.
+
+use std::fmt;
+
+use crate::model::{GeneratorInfo, Granularity, NodeKind, NodeProposal};
+
+pub mod dot;
+
+/// A problem at one place in the CSV file; lines and columns count from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportError {
+    pub line:    usize,
+    pub column:  usize,
+    pub message: String,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// A parsed row and the line it came from.
+#[derive(Debug, Clone)]
+pub struct ImportedNode {
+    pub line:     usize,
+    pub proposal: NodeProposal,
+}
+
+/// Parse every row, reporting all malformed rows rather than the first.
+pub fn parse_node_csv(contents: &str) -> Result<Vec<ImportedNode>, Vec<ImportError>> {
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut first_row = true;
+    for (index, line) in contents.lines().enumerate() {
+        let number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = match split_fields(line) {
+            Ok(fields) => fields,
+            Err((column, message)) => {
+                errors.push(ImportError {
+                    line: number,
+                    column,
+                    message,
+                });
+                continue;
+            }
+        };
+        let header = first_row
+            && fields
+                .first()
+                .is_some_and(|(_, kind)| kind.trim().eq_ignore_ascii_case("kind"));
+        first_row = false;
+        if header {
+            continue;
+        }
+        match parse_row(&fields, line.chars().count() + 1) {
+            Ok(proposal) => nodes.push(ImportedNode {
+                line: number,
+                proposal,
+            }),
+            Err((column, message)) => errors.push(ImportError {
+                line: number,
+                column,
+                message,
+            }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(nodes)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Column and message of a problem within one line.
+type RowError = (usize, String);
+
+fn parse_row(fields: &[(usize, String)], end_column: usize) -> Result<NodeProposal, RowError> {
+    let field = |index: usize, name: &str| {
+        fields
+            .get(index)
+            .map(|(column, value)| (*column, value.trim()))
+            .ok_or_else(|| (end_column, format!("missing {name} column")))
+    };
+    if let Some((column, _)) = fields.get(4) {
+        return Err((*column, format!("expected 4 columns, found {}", fields.len())));
+    }
+
+    let (column, kind) = field(0, "kind")?;
+    let kind = match kind
+        .to_ascii_lowercase()
+        .replace(['_', '-', ' '], "")
+        .as_str()
+    {
+        "concept" => NodeKind::Concept,
+        "lo" | "learningoutcome" => NodeKind::LearningOutcome,
+        _ => {
+            return Err((
+                column,
+                format!("unknown kind '{kind}' (expected concept or learning_outcome)"),
+            ));
+        }
+    };
+    let (column, level) = field(1, "level")?;
+    let level = level
+        .parse()
+        .map_err(|_| (column, format!("invalid level '{level}': expected 0 to 255")))?;
+    let (column, text) = field(2, "text")?;
+    if text.is_empty() {
+        return Err((column, "text is empty".to_string()));
+    }
+    let tags: Vec<String> = fields
+        .get(3)
+        .map(|(_, tags)| {
+            tags.split(';')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(NodeProposal {
+        kind,
+        granularity: Granularity::Sentence,
+        level,
+        text: text.to_string(),
+        tags: (!tags.is_empty()).then_some(tags),
+        generator: Some(GeneratorInfo::manual()),
+        namespace: None,
+        topic: None,
+    })
+}
+
+/// Split one line into fields, each with the column it starts at.
+fn split_fields(line: &str) -> Result<Vec<(usize, String)>, RowError> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().enumerate().peekable();
+    loop {
+        let start = chars
+            .peek()
+            .map_or(line.chars().count(), |(index, _)| *index)
+            + 1;
+        let mut value = String::new();
+        if chars.next_if(|(_, char)| *char == '"').is_some() {
+            loop {
+                match chars.next() {
+                    Some((_, '"')) if chars.next_if(|(_, char)| *char == '"').is_some() => {
+                        value.push('"');
+                    }
+                    Some((_, '"')) => break,
+                    Some((_, char)) => value.push(char),
+                    None => return Err((start, "unterminated quoted field".to_string())),
+                }
+            }
+            match chars.peek() {
+                None | Some((_, ',')) => {}
+                Some((index, _)) => {
+                    return Err((index + 1, "expected ',' after quoted field".to_string()));
+                }
+            }
+        } else {
+            while let Some((_, char)) = chars.next_if(|(_, char)| *char != ',') {
+                value.push(char);
+            }
+        }
+        fields.push((start, value));
+        if chars.next().is_none() {
+            return Ok(fields);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_become_proposals_after_an_optional_header() {
+        let csv = "kind,level,text,tags\nconcept,1,Loops repeat statements.,implementation; \
+                   tests\n\nlearning_outcome,2,\"I can write a loop, then test it.\",\n";
+        let nodes = parse_node_csv(csv).expect("valid CSV");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].line, 2);
+        assert_eq!(nodes[0].proposal.kind, NodeKind::Concept);
+        assert_eq!(
+            nodes[0].proposal.tags,
+            Some(vec!["implementation".to_string(), "tests".to_string()])
+        );
+        assert_eq!(nodes[1].line, 4);
+        assert_eq!(nodes[1].proposal.kind, NodeKind::LearningOutcome);
+        assert_eq!(nodes[1].proposal.text, "I can write a loop, then test it.");
+        assert_eq!(nodes[1].proposal.tags, None);
+    }
+
+    #[test]
+    fn errors_name_line_and_column() {
+        let csv = "concept,high,Loops repeat.\nsubject,1,Loops repeat.\nconcept,1,\"open\n";
+        let errors = parse_node_csv(csv).expect_err("malformed rows");
+        let found: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            found,
+            vec![
+                "line 1, column 9: invalid level 'high': expected 0 to 255",
+                "line 2, column 1: unknown kind 'subject' (expected concept or learning_outcome)",
+                "line 3, column 11: unterminated quoted field",
+            ]
+        );
+    }
+}
@@ -0,0 +1,415 @@
+//! Reading graphs from the DOT subset that [`render_dot`] writes.
+//!
+//! Node statements carry the text in `label` and an optional `kind`; edge
+//! labels read `relation: rationale`. Nodes whose id is a UUID keep it, so
+//! a Weaver export round-trips; other ids get a fresh UUID and, without a
+//! label, use the id as their text. An unlabelled edge is a prerequisite.
+//!
+//! [`render_dot`]: crate::export::render_dot
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{
+    export::verify::{Keyword, Token, VerifyError, tokenize},
+    graph::{GraphSnapshot, SnapshotEdge},
+    model::{GeneratorInfo, Granularity, Node, NodeKind, Relation},
+};
+
+/// Parse a DOT document into a snapshot.
+///
+/// A syntax error stops parsing; bad attribute values are collected so
+/// every malformed statement is reported.
+pub fn parse_dot(source: &str) -> Result<GraphSnapshot, Vec<VerifyError>> {
+    let mut reader = DotReader {
+        tokens:   tokenize(source).map_err(|err| vec![err])?,
+        position: 0,
+        nodes:    Vec::new(),
+        ids:      HashMap::new(),
+        edges:    Vec::new(),
+        errors:   Vec::new(),
+    };
+    if let Err(err) = reader.graph() {
+        reader.errors.push(err);
+    }
+    if !reader.errors.is_empty() {
+        return Err(reader.errors);
+    }
+
+    let nodes = reader
+        .nodes
+        .into_iter()
+        .map(|pending| Node {
+            id:          pending.id,
+            kind:        pending.kind,
+            granularity: Granularity::Sentence,
+            level:       0,
+            text:        pending.label.unwrap_or(pending.name).into(),
+            tags:        None,
+            generator:   Some(GeneratorInfo::manual()),
+            namespace:   None,
+            topic:       None,
+            pinned:      false,
+        })
+        .collect();
+    Ok(GraphSnapshot {
+        nodes,
+        edges: reader.edges,
+    })
+}
+
+/// A node seen so far; edges may mention it before its statement.
+struct PendingNode {
+    id:    Uuid,
+    name:  String,
+    label: Option<String>,
+    kind:  NodeKind,
+}
+
+/// `name = value` pairs with the line each name sits on.
+type Attributes = Vec<(usize, String, String)>;
+
+struct DotReader {
+    tokens:   Vec<(usize, Token)>,
+    position: usize,
+    nodes:    Vec<PendingNode>,
+    ids:      HashMap<String, usize>,
+    edges:    Vec<SnapshotEdge>,
+    errors:   Vec<VerifyError>,
+}
+
+impl DotReader {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |(line, _)| *line)
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, expected: &str) -> VerifyError {
+        let found = self
+            .peek()
+            .map_or_else(|| "end of input".to_string(), Token::to_string);
+        VerifyError::new(self.line(), format!("expected {expected}, found {found}"))
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), VerifyError> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&expected.to_string()))
+        }
+    }
+
+    fn id(&mut self) -> Result<String, VerifyError> {
+        match self.peek() {
+            Some(Token::Id(id)) => {
+                let id = unescape(id);
+                self.position += 1;
+                Ok(id)
+            }
+            _ => Err(self.error("an identifier")),
+        }
+    }
+
+    fn graph(&mut self) -> Result<(), VerifyError> {
+        self.eat(&Token::Keyword(Keyword::Strict));
+        if !self.eat(&Token::Keyword(Keyword::Digraph)) {
+            return Err(self.error("'digraph'"));
+        }
+        if matches!(self.peek(), Some(Token::Id(_))) {
+            self.position += 1;
+        }
+        self.expect(&Token::LBrace)?;
+        self.stmt_list()?;
+        self.expect(&Token::RBrace)?;
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.error("end of input")),
+        }
+    }
+
+    fn stmt_list(&mut self) -> Result<(), VerifyError> {
+        while !matches!(self.peek(), Some(Token::RBrace) | None) {
+            self.stmt()?;
+            self.eat(&Token::Semi);
+        }
+        Ok(())
+    }
+
+    fn stmt(&mut self) -> Result<(), VerifyError> {
+        match self.peek() {
+            Some(Token::Keyword(Keyword::Graph | Keyword::Node | Keyword::Edge)) => {
+                self.position += 1;
+                self.attr_list().map(|_| ())
+            }
+            Some(Token::Keyword(Keyword::Subgraph) | Token::LBrace) => {
+                if self.eat(&Token::Keyword(Keyword::Subgraph))
+                    && matches!(self.peek(), Some(Token::Id(_)))
+                {
+                    self.position += 1;
+                }
+                self.expect(&Token::LBrace)?;
+                self.stmt_list()?;
+                self.expect(&Token::RBrace)
+            }
+            Some(Token::Id(_))
+                if self.tokens.get(self.position + 1).map(|(_, t)| t) == Some(&Token::Equals) =>
+            {
+                self.position += 2;
+                self.id().map(|_| ())
+            }
+            _ => {
+                let mut chain = vec![self.id()?];
+                while self.eat(&Token::Arrow) {
+                    chain.push(self.id()?);
+                }
+                let attributes = if matches!(self.peek(), Some(Token::LBracket)) {
+                    self.attr_list()?
+                } else {
+                    Vec::new()
+                };
+                if let [name] = chain.as_slice() {
+                    self.node_stmt(name, attributes);
+                } else {
+                    self.edge_stmt(&chain, &attributes);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn attr_list(&mut self) -> Result<Attributes, VerifyError> {
+        let mut attributes = Vec::new();
+        self.expect(&Token::LBracket)?;
+        loop {
+            while !matches!(self.peek(), Some(Token::RBracket)) {
+                let line = self.line();
+                let name = self.id()?;
+                self.expect(&Token::Equals)?;
+                attributes.push((line, name, self.id()?));
+                if !self.eat(&Token::Comma) {
+                    self.eat(&Token::Semi);
+                }
+            }
+            self.expect(&Token::RBracket)?;
+            if !self.eat(&Token::LBracket) {
+                return Ok(attributes);
+            }
+        }
+    }
+
+    /// Index of the node named `name`, declaring it on first mention.
+    fn node(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.ids.get(name) {
+            return index;
+        }
+        self.nodes.push(PendingNode {
+            id:    Uuid::parse_str(name).unwrap_or_else(|_| Uuid::new_v4()),
+            name:  name.to_string(),
+            label: None,
+            kind:  NodeKind::Concept,
+        });
+        self.ids.insert(name.to_string(), self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    fn node_stmt(&mut self, name: &str, attributes: Attributes) {
+        let index = self.node(name);
+        for (line, attribute, value) in attributes {
+            match attribute.as_str() {
+                "label" => self.nodes[index].label = Some(value),
+                "kind" => match parse_kind(&value) {
+                    Some(kind) => self.nodes[index].kind = kind,
+                    None => self.errors.push(VerifyError::new(
+                        line,
+                        format!("unknown kind '{value}' (expected concept or learning_outcome)"),
+                    )),
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn edge_stmt(&mut self, chain: &[String], attributes: &Attributes) {
+        let label = attributes
+            .iter()
+            .find(|(_, attribute, _)| attribute == "label")
+            .map(|(line, _, value)| (*line, value.as_str()));
+        let (relation, rationale) = match label {
+            None => (Relation::PrerequisiteFor, String::new()),
+            Some((line, label)) => {
+                let (name, rationale) = label.split_once(':').unwrap_or((label, ""));
+                match parse_relation(name.trim()) {
+                    Some(relation) => (relation, rationale.trim().to_string()),
+                    None => {
+                        self.errors.push(VerifyError::new(
+                            line,
+                            format!(
+                                "unknown relation '{}' in edge label (expected prerequisite_for, \
+                                 supports, or related_to)",
+                                name.trim()
+                            ),
+                        ));
+                        return;
+                    }
+                }
+            }
+        };
+        for pair in chain.windows(2) {
+            let (from, to) = (self.node(&pair[0]), self.node(&pair[1]));
+            let (from, to) = (self.nodes[from].id, self.nodes[to].id);
+            let (from, to) = relation.canonical_endpoints(from, to);
+            self.edges.push(SnapshotEdge {
+                from,
+                to,
+                relation: relation.clone(),
+                rationale: rationale.clone(),
+                generator: Some(GeneratorInfo::manual()),
+                pinned: false,
+            });
+        }
+    }
+}
+
+fn parse_kind(value: &str) -> Option<NodeKind> {
+    match value
+        .to_ascii_lowercase()
+        .replace(['_', '-', ' '], "")
+        .as_str()
+    {
+        "concept" => Some(NodeKind::Concept),
+        "lo" | "learningoutcome" => Some(NodeKind::LearningOutcome),
+        _ => None,
+    }
+}
+
+fn parse_relation(name: &str) -> Option<Relation> {
+    match name.to_ascii_lowercase().as_str() {
+        "prerequisite_for" | "prerequisite" | "prereq" => Some(Relation::PrerequisiteFor),
+        "supports" => Some(Relation::Supports),
+        "related_to" | "related" => Some(Relation::RelatedTo),
+        _ => None,
+    }
+}
+
+/// Undo DOT string escapes; line-break escapes become spaces.
+fn unescape(raw: &str) -> String {
+    let mut text = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            text.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'l' | 'r') => text.push(' '),
+            Some('\n') => {}
+            Some(other) => text.push(other),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{export::render_dot, graph::GraphStore};
+
+    #[test]
+    fn weaver_dot_round_trips() {
+        let concept = |text: &str| Node {
+            id:          Uuid::new_v4(),
+            kind:        NodeKind::Concept,
+            granularity: Granularity::Sentence,
+            level:       0,
+            text:        text.into(),
+            tags:        None,
+            generator:   None,
+            namespace:   None,
+            topic:       None,
+            pinned:      false,
+        };
+        let values = concept("Values have \"types\".");
+        let loops = concept("Loops repeat statements.");
+        let recursion = concept("Recursion replaces loops.");
+        let edge = |from: &Node, to: &Node, relation: Relation, rationale: &str| {
+            let (from, to) = relation.canonical_endpoints(from.id, to.id);
+            SnapshotEdge {
+                from,
+                to,
+                relation,
+                rationale: rationale.to_string(),
+                generator: None,
+                pinned: false,
+            }
+        };
+        let snapshot = GraphSnapshot {
+            edges: vec![
+                edge(&values, &loops, Relation::PrerequisiteFor, "Loops count values: often."),
+                edge(&loops, &recursion, Relation::Supports, ""),
+                edge(&recursion, &values, Relation::RelatedTo, "Both use values."),
+            ],
+            nodes: vec![values, loops, recursion],
+        };
+
+        let exported = render_dot(&snapshot);
+        let imported = parse_dot(&exported).expect("weaver DOT parses");
+        let store = GraphStore::from_snapshot(imported.clone()).expect("consistent graph");
+        assert_eq!((store.node_count(), store.edge_count()), (3, 3));
+        assert_eq!(imported.nodes[0].text.as_ref(), "Values have \"types\".");
+        assert_eq!(imported.edges[0].rationale, "Loops count values: often.");
+        assert_eq!(render_dot(&imported), exported);
+    }
+
+    #[test]
+    fn hand_drawn_files_infer_nodes_and_report_bad_lines() {
+        let source = "digraph course {\n  a [label=\"Sets.\", kind=learning_outcome];\n  a -> b \
+                      -> c;\n  c -> a [label=\"supports: Practice.\"];\n}\n";
+        let snapshot = parse_dot(source).expect("hand-drawn DOT parses");
+        assert_eq!(snapshot.nodes.len(), 3);
+        assert_eq!(snapshot.nodes[0].kind, NodeKind::LearningOutcome);
+        assert_eq!(snapshot.nodes[1].text.as_ref(), "b");
+        let relations: Vec<_> = snapshot.edges.iter().map(|e| e.relation.clone()).collect();
+        assert_eq!(
+            relations,
+            vec![
+                Relation::PrerequisiteFor,
+                Relation::PrerequisiteFor,
+                Relation::Supports
+            ]
+        );
+
+        let source = "digraph {\n  a [kind=topic];\n  a -> b [label=\"follows: x\"];\n}\n";
+        let errors: Vec<String> = parse_dot(source)
+            .expect_err("bad values")
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "line 2: unknown kind 'topic' (expected concept or learning_outcome)",
+                "line 3: unknown relation 'follows' in edge label (expected prerequisite_for, \
+                 supports, or related_to)",
+            ]
+        );
+        let errors = parse_dot("digraph {\n  a -> ;\n}\n").expect_err("syntax error");
+        assert_eq!(errors[0].line, 2);
+    }
+}
@@ -0,0 +1,31 @@
+//! Weaver builds learning networks of concept and learning-outcome nodes.
+//!
+//! The binary drives the actor pipeline; the [`validation`] functions can
+//! also be used directly to check proposals against weaver's rules.
+
+pub mod adder;
+pub mod audit;
+pub mod diff;
+pub mod edge_synth;
+pub mod export;
+pub mod graph;
+pub mod import;
+pub mod llm;
+pub mod manifest;
+pub mod model;
+pub mod node_synth;
+pub mod progress;
+pub mod read_view;
+pub mod shell;
+pub mod summary;
+pub mod text_utils;
+pub mod trace;
+pub mod validation;
+pub mod viz;
+
+pub use adder::RationalePolicy;
+pub use model::{NodeRules, TagRegistry};
+pub use validation::{
+    BatchState, MemoryContext, Rejection, ValidEdge, ValidNode, ValidationContext, validate_edge,
+    validate_node,
+};
@@ -1,4 +1,11 @@
-use std::env;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
 use async_openai::{
     Client,
@@ -10,9 +17,17 @@ use async_openai::{
 };
 use schemars::{JsonSchema, schema_for};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use thiserror::Error;
+use tracing::{Span, warn};
 
-use crate::model::{EdgeProposal, InventoryEntry, NodeKind, NodeProposal};
+use crate::{
+    model::{
+        EdgeProposal, GeneratorInfo, InventoryEntry, NodeKind, NodeProposal, NodeRules,
+        RoundFeedback,
+    },
+    trace,
+};
 
 /// Errors surfaced when interacting with the LLM backend.
 #[derive(Debug, Error)]
@@ -25,13 +40,216 @@ pub enum LlmError {
     RequestFailed(String),
     #[error("failed to parse LLM response: {0}")]
     InvalidResponse(String),
+    #[error("LLM response omitted required fields: {0}")]
+    SchemaDrift(String),
+}
+
+/// Boxed future returned by backend trait methods.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Model and endpoint selection for one LLM backend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmSettings {
+    pub model:    String,
+    pub base_url: Option<String>,
+}
+
+impl LlmSettings {
+    /// Read `OPENAI_MODEL` and `OPENAI_BASE_URL`, defaulting the model.
+    pub fn from_env() -> Self {
+        Self {
+            model:    env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string()),
+            base_url: env::var("OPENAI_BASE_URL").ok(),
+        }
+    }
+}
+
+/// Token counts reported by the backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens:     u32,
+    pub completion_tokens: u32,
+}
+
+impl TokenUsage {
+    pub fn add(&mut self, other: TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+    }
+}
+
+/// Wall-clock time of one backend request.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequestLatency {
+    pub model:      String,
+    /// `nodes` or `edges`.
+    pub kind:       &'static str,
+    pub elapsed_ms: f64,
+    /// The backend answered; a failed request still counts its wait.
+    pub ok:         bool,
+}
+
+/// Latencies shared by every client of a run; cloning shares the log.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyLog(Arc<Mutex<Vec<RequestLatency>>>);
+
+impl LatencyLog {
+    pub fn record(&self, latency: RequestLatency) {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(latency);
+    }
+
+    /// Every latency recorded so far, in completion order.
+    pub fn entries(&self) -> Vec<RequestLatency> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+/// Optional fields omitted by more than this fraction of items are reported.
+const SPARSE_FIELD_THRESHOLD: f64 = 0.9;
+
+/// Which schema fields the raw response objects actually contained.
+///
+/// Computed on the untyped JSON, before serde defaults can mask a missing
+/// field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SchemaAudit {
+    pub items:            usize,
+    /// Number of items missing each schema property.
+    pub omitted:          BTreeMap<String, usize>,
+    /// Required properties missing from at least one item.
+    pub missing_required: BTreeSet<String>,
+}
+
+impl SchemaAudit {
+    /// Audit `payload[key]`, an array of objects, against an item schema.
+    pub fn of_payload(payload: &Value, key: &str, item_schema: &Value) -> Self {
+        let properties = item_schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .map(|properties| properties.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let required: BTreeSet<&str> = item_schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|fields| fields.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut audit = SchemaAudit {
+            omitted: properties.iter().map(|field| (field.clone(), 0)).collect(),
+            ..SchemaAudit::default()
+        };
+        let items = payload.get(key).and_then(Value::as_array);
+        for item in items.into_iter().flatten() {
+            audit.items += 1;
+            for field in &properties {
+                if item.get(field).is_none() {
+                    *audit.omitted.entry(field.clone()).or_default() += 1;
+                    if required.contains(field.as_str()) {
+                        audit.missing_required.insert(field.clone());
+                    }
+                }
+            }
+        }
+        audit
+    }
+
+    /// Fraction of items missing `field`, or 0 when no items were seen.
+    pub fn omission_rate(&self, field: &str) -> f64 {
+        match (self.items, self.omitted.get(field)) {
+            (0, _) | (_, None) => 0.0,
+            (items, Some(&omitted)) => omitted as f64 / items as f64,
+        }
+    }
+
+    /// Optional fields omitted by almost every item.
+    pub fn sparse_fields(&self) -> Vec<&str> {
+        self.omitted
+            .keys()
+            .filter(|field| !self.missing_required.contains(*field))
+            .filter(|field| self.omission_rate(field) > SPARSE_FIELD_THRESHOLD)
+            .map(String::as_str)
+            .collect()
+    }
+
+    pub fn merge(&mut self, other: &SchemaAudit) {
+        self.items += other.items;
+        for (field, omitted) in &other.omitted {
+            *self.omitted.entry(field.clone()).or_default() += omitted;
+        }
+        self.missing_required
+            .extend(other.missing_required.iter().cloned());
+    }
+
+    /// Warn about sparse fields and, when `strict`, fail on missing
+    /// required fields.
+    pub fn enforce(&self, payload: &str, strict: bool) -> Result<(), LlmError> {
+        for field in self.sparse_fields() {
+            warn!(payload, field, rate = self.omission_rate(field), "llm.schema_field_sparse");
+        }
+        if self.missing_required.is_empty() {
+            return Ok(());
+        }
+
+        let fields = self
+            .missing_required
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if strict {
+            return Err(LlmError::SchemaDrift(format!("{payload}: {fields}")));
+        }
+        warn!(payload, fields = %fields, "llm.schema_required_missing");
+        Ok(())
+    }
+}
+
+/// Proposals from one backend request along with what it cost.
+#[derive(Debug, Clone, Default)]
+pub struct NodeResponse {
+    pub proposals: Vec<NodeProposal>,
+    pub usage:     TokenUsage,
+    pub audit:     SchemaAudit,
+}
+
+/// Edge proposals from one LLM request and the audit of its raw response.
+#[derive(Debug, Clone, Default)]
+pub struct EdgeResponse {
+    pub proposals: Vec<EdgeProposal>,
+    pub audit:     SchemaAudit,
+}
+
+/// Source of node proposals, implemented by [`LlmClient`] and test doubles.
+pub trait NodeBackend: Send + Sync + fmt::Debug {
+    /// Model identifier recorded in generation metadata.
+    fn model_name(&self) -> &str;
+
+    /// `topic` is set in multi-topic runs; `feedback` describes earlier
+    /// rounds and is empty on the first one.
+    fn propose_nodes<'a>(
+        &'a self,
+        concepts: usize,
+        learning_outcomes: usize,
+        topic: Option<&'a str>,
+        feedback: &'a RoundFeedback,
+    ) -> BoxFuture<'a, Result<NodeResponse, LlmError>>;
 }
 
 /// Client used by generators to reach the LLM backend.
 #[derive(Debug, Clone)]
 pub struct LlmClient {
-    client: Client<OpenAIConfig>,
-    model:  String,
+    client:        Client<OpenAIConfig>,
+    model:         String,
+    provider:      String,
+    strict_schema: bool,
+    node_rules:    NodeRules,
+    latency_log:   Option<LatencyLog>,
 }
 
 impl LlmClient {
@@ -40,44 +258,128 @@ impl LlmClient {
             return Err(LlmError::Disabled);
         }
 
+        Self::with_settings(LlmSettings::from_env())
+    }
+
+    /// Build a client for explicit settings; the API key still comes from
+    /// `OPENAI_API_KEY`.
+    pub fn with_settings(settings: LlmSettings) -> Result<Self, LlmError> {
         let api_key = env::var("OPENAI_API_KEY").map_err(|_| LlmError::MissingApiKey)?;
-        let base_url = env::var("OPENAI_BASE_URL").ok();
-        let model = env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+
+        let provider = settings
+            .base_url
+            .as_deref()
+            .map(provider_from_url)
+            .unwrap_or_else(|| "openai".to_string());
 
         let mut openai_config = OpenAIConfig::new().with_api_key(api_key);
-        if let Some(url) = base_url {
+        if let Some(url) = settings.base_url {
             openai_config = openai_config.with_api_base(url);
         }
 
         let client = Client::with_config(openai_config);
 
-        Ok(Self { client, model })
+        Ok(Self {
+            client,
+            model: settings.model,
+            provider,
+            strict_schema: false,
+            node_rules: NodeRules::default(),
+            latency_log: None,
+        })
+    }
+
+    /// Fail requests whose responses omit required schema fields instead of
+    /// only warning.
+    pub fn with_strict_schema(mut self, strict: bool) -> Self {
+        self.strict_schema = strict;
+        self
+    }
+
+    /// Ask for node levels and tags within `rules`.
+    pub fn with_node_rules(mut self, rules: NodeRules) -> Self {
+        self.node_rules = rules;
+        self
+    }
+
+    /// Record each request's latency in `log`.
+    pub fn with_latency_log(mut self, log: Option<LatencyLog>) -> Self {
+        self.latency_log = log;
+        self
+    }
+
+    fn record_latency(&self, kind: &'static str, started: Instant, ok: bool) {
+        if let Some(log) = &self.latency_log {
+            log.record(RequestLatency {
+                model: self.model.clone(),
+                kind,
+                elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+                ok,
+            });
+        }
+    }
+
+    /// Audit the raw response, then deserialize it.
+    fn parse_audited<T: serde::de::DeserializeOwned>(
+        &self,
+        content: &str,
+        key: &str,
+        item_schema: schemars::Schema,
+    ) -> Result<(T, SchemaAudit), LlmError> {
+        let raw: Value = serde_json::from_str(content)
+            .map_err(|err| LlmError::InvalidResponse(err.to_string()))?;
+        let item_schema = serde_json::to_value(&item_schema)
+            .map_err(|err| LlmError::InvalidResponse(err.to_string()))?;
+
+        let audit = SchemaAudit::of_payload(&raw, key, &item_schema);
+        audit.enforce(key, self.strict_schema)?;
+
+        let parsed = serde_json::from_value(raw)
+            .map_err(|err| LlmError::InvalidResponse(err.to_string()))?;
+        Ok((parsed, audit))
+    }
+
+    fn generator_info(&self, system_prompt: &str) -> GeneratorInfo {
+        GeneratorInfo {
+            model:       self.model.clone(),
+            provider:    self.provider.clone(),
+            prompt_hash: prompt_hash(system_prompt),
+        }
     }
 
     pub async fn generate_nodes(
         &self,
         concepts: usize,
         learning_outcomes: usize,
-    ) -> Result<Vec<NodeProposal>, LlmError> {
-        let system_prompt = r#"You produce placeholder educational nodes for a learning network.
-Rules:
-- Emit pure JSON matching the provided schema exactly.
-- Each node is a standalone statement that can be understood without citations.
-- "kind" must be either "Concept" or "LearningOutcome".
-- "granularity" must be "Sentence".
-- Avoid duplicates; vary vocabulary.
-- Learning outcomes MUST start with "I can " or "Students can ".
-- Match the requested counts for each node type."#;
+        topic: Option<&str>,
+        feedback: &RoundFeedback,
+    ) -> Result<NodeResponse, LlmError> {
+        let system_prompt = node_system_prompt(&self.node_rules);
 
+        let accepted = feedback
+            .inventory
+            .iter()
+            .map(|(_, kind, _, text, _)| format!("{kind:?}: {text}"))
+            .collect();
         let user_prompt = format!(
-            "Produce exactly {concepts} Concept nodes and {learning_outcomes} LearningOutcome \
-             nodes. Return ONLY JSON that satisfies the schema.",
+            "{topic}Produce exactly {concepts} Concept nodes and {learning_outcomes} \
+             LearningOutcome nodes with levels from 0 to {max_level}. {tags} Return ONLY JSON \
+             that satisfies the schema.{feedback}",
+            max_level = self.node_rules.max_level,
+            tags = match self.node_rules.allowed_tags.tags() {
+                [] => "Leave tags empty.".to_string(),
+                tags => format!("Tags must come from: {}.", tags.join(", ")),
+            },
+            topic = topic
+                .map(|topic| format!("Topic: {topic}. Every node must belong to this topic.\n"))
+                .unwrap_or_default(),
             concepts = concepts,
-            learning_outcomes = learning_outcomes
+            learning_outcomes = learning_outcomes,
+            feedback = feedback_section("Already accepted nodes", accepted, &feedback.rejections)
         );
 
         let system_message = ChatCompletionRequestSystemMessageArgs::default()
-            .content(system_prompt)
+            .content(system_prompt.as_str())
             .build()
             .map_err(|err| LlmError::RequestFailed(err.to_string()))?;
         let user_message = ChatCompletionRequestUserMessageArgs::default()
@@ -106,12 +408,19 @@ Rules:
             .build()
             .map_err(|err| LlmError::RequestFailed(err.to_string()))?;
 
-        let response = self
-            .client
-            .chat()
-            .create(request)
-            .await
-            .map_err(|err| LlmError::RequestFailed(err.to_string()))?;
+        let started = Instant::now();
+        let response = self.client.chat().create(request).await;
+        self.record_latency("nodes", started, response.is_ok());
+        let response = response.map_err(|err| LlmError::RequestFailed(err.to_string()))?;
+
+        let usage = response
+            .usage
+            .as_ref()
+            .map(|usage| TokenUsage {
+                prompt_tokens:     usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+            })
+            .unwrap_or_default();
 
         let content = response
             .choices
@@ -119,17 +428,32 @@ Rules:
             .and_then(|choice| choice.message.content.clone())
             .ok_or_else(|| LlmError::InvalidResponse("missing content".into()))?;
 
-        let batch: NodeBatchPayload = serde_json::from_str(&content)
-            .map_err(|err| LlmError::InvalidResponse(err.to_string()))?;
+        let (batch, audit): (NodeBatchPayload, _) =
+            self.parse_audited(&content, "nodes", schema_for!(NodeProposal))?;
+
+        let generator = self.generator_info(&system_prompt);
+        let proposals = batch
+            .nodes
+            .into_iter()
+            .map(|node| NodeProposal {
+                generator: Some(generator.clone()),
+                ..node
+            })
+            .collect();
 
-        Ok(batch.nodes)
+        Ok(NodeResponse {
+            proposals,
+            usage,
+            audit,
+        })
     }
 
     pub async fn generate_edges(
         &self,
         inventory: &[InventoryEntry],
         target_edges: usize,
-    ) -> Result<Vec<EdgeProposal>, LlmError> {
+        feedback: &RoundFeedback,
+    ) -> Result<EdgeResponse, LlmError> {
         let inventory_items: Vec<InventoryItem> = inventory
             .iter()
             .map(|(id, kind, level, text, tags)| InventoryItem {
@@ -146,16 +470,24 @@ Rules:
         let system_prompt = r#"You propose placeholder edges among existing nodes.
 Rules:
 - Emit pure JSON matching the provided schema exactly.
-- Edge kinds: "PrerequisiteFor" or "Supports" (use exact casing).
+- Edge kinds: "PrerequisiteFor", "Supports" or "RelatedTo" (use exact casing).
+- Use "RelatedTo" only for mutually related nodes; its direction is ignored.
 - Use from_id and to_id copied exactly from the provided inventory of UUIDs.
 - For "PrerequisiteFor", prefer foundational → advanced concepts or concept → learning outcome.
 - Include a concise rationale string for every edge.
 - Aim for the requested number of edges; it is OK to return fewer but avoid duplicates."#;
 
+        let accepted = feedback
+            .edges
+            .iter()
+            .map(|(from, to, relation)| format!("{from} {relation:?} {to}"))
+            .collect();
         let user_prompt = format!(
             "Accepted nodes (JSON array):\n{}\nRequested edge count: {}\nReturn ONLY JSON that \
-             satisfies the schema.",
-            inventory_json, target_edges
+             satisfies the schema.{}",
+            inventory_json,
+            target_edges,
+            feedback_section("Already accepted edges", accepted, &feedback.rejections)
         );
 
         let system_message = ChatCompletionRequestSystemMessageArgs::default()
@@ -188,12 +520,20 @@ Rules:
             .build()
             .map_err(|err| LlmError::RequestFailed(err.to_string()))?;
 
-        let response = self
-            .client
-            .chat()
-            .create(request)
-            .await
-            .map_err(|err| LlmError::RequestFailed(err.to_string()))?;
+        let started = Instant::now();
+        let response = self.client.chat().create(request).await;
+        self.record_latency("edges", started, response.is_ok());
+        let response = response.map_err(|err| LlmError::RequestFailed(err.to_string()))?;
+
+        if let Some(usage) = &response.usage {
+            trace::record_usage(
+                &Span::current(),
+                TokenUsage {
+                    prompt_tokens:     usage.prompt_tokens,
+                    completion_tokens: usage.completion_tokens,
+                },
+            );
+        }
 
         let content = response
             .choices
@@ -201,19 +541,118 @@ Rules:
             .and_then(|choice| choice.message.content.clone())
             .ok_or_else(|| LlmError::InvalidResponse("missing content".into()))?;
 
-        let batch: EdgeBatchPayload = serde_json::from_str(&content)
-            .map_err(|err| LlmError::InvalidResponse(err.to_string()))?;
+        let (batch, audit): (EdgeBatchPayload, _) =
+            self.parse_audited(&content, "edges", schema_for!(EdgeProposal))?;
+
+        let generator = self.generator_info(system_prompt);
+        Ok(EdgeResponse {
+            proposals: batch
+                .edges
+                .into_iter()
+                .map(|edge| EdgeProposal {
+                    generator: Some(generator.clone()),
+                    ..edge
+                })
+                .collect(),
+            audit,
+        })
+    }
+}
+
+impl NodeBackend for LlmClient {
+    fn model_name(&self) -> &str {
+        &self.model
+    }
 
-        Ok(batch.edges)
+    fn propose_nodes<'a>(
+        &'a self,
+        concepts: usize,
+        learning_outcomes: usize,
+        topic: Option<&'a str>,
+        feedback: &'a RoundFeedback,
+    ) -> BoxFuture<'a, Result<NodeResponse, LlmError>> {
+        Box::pin(self.generate_nodes(concepts, learning_outcomes, topic, feedback))
     }
 }
 
+/// System prompt for node generation, naming the configured learning
+/// outcome openings.
+fn node_system_prompt(rules: &NodeRules) -> String {
+    let quoted: Vec<String> = rules
+        .allowed_lo_prefixes
+        .iter()
+        .map(|prefix| format!("\"{} \"", prefix.trim()))
+        .collect();
+    let prefix_rule = match quoted.split_last() {
+        None => String::new(),
+        Some((only, [])) => format!("- Learning outcomes MUST start with {only}.\n"),
+        Some((last, rest)) => {
+            format!("- Learning outcomes MUST start with {} or {last}.\n", rest.join(", "))
+        }
+    };
+    format!(
+        r#"You produce placeholder educational nodes for a learning network.
+Rules:
+- Emit pure JSON matching the provided schema exactly.
+- Each node is a standalone statement that can be understood without citations.
+- "kind" must be either "Concept" or "LearningOutcome".
+- "granularity" must be "Sentence".
+- Avoid duplicates; vary vocabulary.
+{prefix_rule}- Match the requested counts for each node type."#
+    )
+}
+
+/// User-prompt suffix describing earlier rounds; empty on the first round.
+fn feedback_section(
+    heading: &str,
+    accepted: Vec<String>,
+    rejections: &[(String, usize)],
+) -> String {
+    let mut section = String::new();
+    if !accepted.is_empty() {
+        section.push_str(&format!("\n{heading} (do not propose these again):\n"));
+        for line in accepted {
+            section.push_str(&format!("- {line}\n"));
+        }
+    }
+    if !rejections.is_empty() {
+        section.push_str(
+            "\nEarlier proposals were rejected for these reasons; avoid repeating them:\n",
+        );
+        for (reason, count) in rejections {
+            section.push_str(&format!("- {reason} ({count}x)\n"));
+        }
+    }
+    section
+}
+
+/// Host of an API base URL, used as the provider name.
+fn provider_from_url(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    without_scheme
+        .split(['/', ':'])
+        .next()
+        .filter(|host| !host.is_empty())
+        .unwrap_or(url)
+        .to_string()
+}
+
+/// FNV-1a hash of a prompt, stable across runs and toolchains.
+fn prompt_hash(prompt: &str) -> String {
+    let hash = prompt
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{hash:016x}")
+}
+
 #[derive(Debug, Clone, Serialize)]
 struct InventoryItem {
     id:    uuid::Uuid,
     kind:  NodeKind,
     level: u8,
-    text:  String,
+    text:  Arc<str>,
     tags:  Option<Vec<String>>,
 }
 
@@ -226,3 +665,100 @@ struct NodeBatchPayload {
 struct EdgeBatchPayload {
     edges: Vec<EdgeProposal>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_schema() -> Value {
+        serde_json::to_value(schema_for!(NodeProposal)).unwrap()
+    }
+
+    #[test]
+    fn audit_counts_omitted_fields_per_item() {
+        let payload: Value = serde_json::from_str(
+            r#"{"nodes": [
+                {"kind": "Concept", "granularity": "Sentence", "level": 0, "text": "A."},
+                {"kind": "Concept", "granularity": "Sentence", "level": 1, "text": "B.", "tags": []},
+                {"kind": "Concept", "granularity": "Sentence", "text": "C."}
+            ]}"#,
+        )
+        .unwrap();
+
+        let audit = SchemaAudit::of_payload(&payload, "nodes", &node_schema());
+
+        assert_eq!(audit.items, 3);
+        assert_eq!(audit.omitted["tags"], 2);
+        assert_eq!(audit.omitted["level"], 1);
+        assert_eq!(audit.omitted["text"], 0);
+        assert!((audit.omission_rate("tags") - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(audit.missing_required, BTreeSet::from(["level".to_string()]));
+        assert!(audit.enforce("nodes", false).is_ok());
+        assert!(matches!(
+            audit.enforce("nodes", true),
+            Err(LlmError::SchemaDrift(message)) if message == "nodes: level"
+        ));
+    }
+
+    #[test]
+    fn audit_flags_optional_fields_omitted_by_nearly_every_item() {
+        let item = r#"{"kind": "Concept", "granularity": "Sentence", "level": 0, "text": "A."}"#;
+        let payload: Value =
+            serde_json::from_str(&format!(r#"{{"nodes": [{}]}}"#, [item; 10].join(","))).unwrap();
+
+        let audit = SchemaAudit::of_payload(&payload, "nodes", &node_schema());
+
+        assert_eq!(audit.sparse_fields(), vec!["tags"]);
+        assert!(audit.missing_required.is_empty());
+        assert!(audit.enforce("nodes", true).is_ok());
+    }
+
+    #[test]
+    fn feedback_section_lists_accepted_items_and_rejection_reasons() {
+        assert!(feedback_section("Already accepted nodes", Vec::new(), &[]).is_empty());
+
+        let section = feedback_section(
+            "Already accepted nodes",
+            vec!["Concept: Loops repeat work.".to_string()],
+            &[("duplicate node within batch".to_string(), 3)],
+        );
+        assert!(section.contains("Already accepted nodes (do not propose these again):"));
+        assert!(section.contains("- Concept: Loops repeat work.\n"));
+        assert!(section.contains("- duplicate node within batch (3x)\n"));
+    }
+
+    #[test]
+    fn node_prompt_names_the_configured_outcome_prefixes() {
+        let prompt = node_system_prompt(&NodeRules::default());
+        assert!(
+            prompt
+                .contains("- Learning outcomes MUST start with \"I can \" or \"Students can \".\n")
+        );
+
+        let rules = NodeRules {
+            allowed_lo_prefixes: vec![
+                "I can".to_string(),
+                "Students can ".to_string(),
+                "Students will be able to".to_string(),
+            ],
+            ..NodeRules::default()
+        };
+        assert!(node_system_prompt(&rules).contains(
+            "MUST start with \"I can \", \"Students can \" or \"Students will be able to \".\n"
+        ));
+
+        let any_opening = NodeRules {
+            allowed_lo_prefixes: Vec::new(),
+            ..NodeRules::default()
+        };
+        let prompt = node_system_prompt(&any_opening);
+        assert!(!prompt.contains("MUST start with"));
+        assert!(prompt.contains("vary vocabulary.\n- Match the requested counts"));
+    }
+
+    #[test]
+    fn provider_is_host_of_base_url() {
+        assert_eq!(provider_from_url("http://localhost:11434/v1"), "localhost");
+        assert_eq!(provider_from_url("https://api.example.com/v1"), "api.example.com");
+    }
+}
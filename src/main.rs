@@ -1,123 +1,1588 @@
-mod adder;
-mod edge_synth;
-mod graph;
-mod llm;
-mod model;
-mod node_synth;
-mod summary;
-mod viz;
-
-use std::{env, fmt, path::PathBuf};
-
-use adder::{AddEdges, AddNodes, ExportDot, GraphAdder, Inventory, Summarize};
-use edge_synth::{EdgeGenerator, EdgeGeneratorConfig, GenerateEdges};
-use graph::GraphStore;
-use kameo::Actor;
-use node_synth::{GenerateNodes, NodeGenerator, NodeGeneratorConfig};
-use tokio::sync::mpsc;
-use tracing::info;
-use viz::Viz;
+use std::{
+    collections::HashMap,
+    env, fmt,
+    future::IntoFuture,
+    io::IsTerminal,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use kameo::{Actor, actor::ActorRef, error::SendError};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tracing::{Instrument, Span, info, info_span};
+use uuid::Uuid;
+use weaver::{
+    adder::{
+        AddEdges, AddNodes, AdderConfig, GraphAdder, RationalePolicy, RationaleRules,
+        RejectionStats, SplitEdges, SplitNode, Summarize, TopoOrder,
+    },
+    audit::AuditLog,
+    diff::{DiffNode, diff_graphs},
+    edge_synth::{EdgeGenerator, EdgeGeneratorConfig, GenerateEdges},
+    export::{
+        CSV_FILES, DotExportOptions, ExportFormat, ExportRequest, STDOUT_PATH, is_stdout,
+        render_json, write_exports,
+    },
+    graph::{CycleInfo, GraphSnapshot, GraphStore, LoadError},
+    import::{ImportedNode, dot::parse_dot, parse_node_csv},
+    llm::{LatencyLog, LlmSettings, TokenUsage},
+    manifest::{
+        ArtifactRecord, ArtifactStatus, MANIFEST_VERSION, PhaseTiming, RunManifest, StoredRun,
+        config_hash, git_describe, sha256_hex,
+    },
+    model::{
+        ALLOWED_TAGS, Decision, EdgeProposal, InventoryEntry, LO_PREFIXES, MAX_NODE_LEVEL,
+        NodeKind, NodeProposal, NodeRules, RoundFeedback, TagRegistry, short_id,
+    },
+    node_synth::{
+        GenerateNodes, GenerationMetadata, NodeBatch, NodeGenerator, NodeGeneratorConfig,
+        split_evenly,
+    },
+    progress::ProgressRenderer,
+    read_view::ReadHandle,
+    summary::{RejectionReport, ResumeCounts, RunReport, Summary, Timings},
+    trace::{LogFormat, LogLevel, LogSettings},
+    validation::{DryRun, validate_snapshot},
+    viz::{Event, Phase, Viz, VizTarget, fan_out},
+};
 
 type DynError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
+/// Set by `--output json` or a `-` export path, which keep stdout for
+/// machine-readable output.
+static HUMAN_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// `println!` for human-readable output; goes to stderr once stdout is taken.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if HUMAN_TO_STDERR.load(Ordering::Relaxed) {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+/// A failure, by category; each category exits with its own status.
 #[derive(Debug)]
-struct CliError(String);
+enum CliError {
+    /// Bad flags, config file, or environment.
+    Usage(String),
+    /// An LLM backend or generator could not produce proposals.
+    Backend(String),
+    /// A graph broke an invariant, such as a prerequisite cycle under
+    /// `--strict`.
+    Invariant(String),
+    /// A file could not be read, parsed, or written.
+    Io(String),
+    /// Anything else, such as a viewer required by `--viz-required`.
+    Failed(String),
+}
+
+impl CliError {
+    fn message(&self) -> &str {
+        match self {
+            CliError::Usage(message)
+            | CliError::Backend(message)
+            | CliError::Invariant(message)
+            | CliError::Io(message)
+            | CliError::Failed(message) => message,
+        }
+    }
+
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Failed(_) => 1,
+            CliError::Usage(_) => 2,
+            CliError::Backend(_) => 3,
+            CliError::Invariant(_) => 4,
+            CliError::Io(_) => 5,
+        }
+    }
+}
 
 impl fmt::Display for CliError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        f.write_str(self.message())
     }
 }
 
 impl std::error::Error for CliError {}
 
-#[derive(Debug, Clone)]
+/// Top-level command selected on the command line.
+#[derive(Debug)]
+enum Command {
+    Run(Box<RunConfig>),
+    Verify(VerifyArgs),
+    Summary(SummaryArgs),
+    Validate(ValidateArgs),
+    Diff(DiffArgs),
+    Shell(ShellArgs),
+    Split(SplitArgs),
+    /// Print this help text and exit.
+    Help(String),
+    Version,
+}
+
+/// Arguments of `run verify`.
+#[derive(Debug)]
+struct VerifyArgs {
+    manifest: PathBuf,
+}
+
+/// Arguments of `summary`.
+#[derive(Debug)]
+struct SummaryArgs {
+    input:  PathBuf,
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `validate`.
+#[derive(Debug)]
+struct ValidateArgs {
+    input:     PathBuf,
+    max_level: u8,
+}
+
+/// Arguments of `shell`.
+#[derive(Debug)]
+struct ShellArgs {
+    input:        Option<PathBuf>,
+    namespace:    Option<String>,
+    viz:          VizTarget,
+    viz_explicit: bool,
+}
+
+/// Arguments of `graph split`.
+#[derive(Debug)]
+struct SplitArgs {
+    input:    PathBuf,
+    /// Id or id prefix of the node to split.
+    node:     String,
+    into:     Vec<String>,
+    /// `None` asks at the prompt, or takes the first replacement when
+    /// stdin is not a terminal.
+    edges_to: Option<SplitEdges>,
+    /// Where the split graph is written; defaults to `input`.
+    output:   Option<PathBuf>,
+}
+
+/// Arguments of `diff`.
+#[derive(Debug)]
+struct DiffArgs {
+    old:    PathBuf,
+    new:    PathBuf,
+    output: OutputFormat,
+}
+
+/// Settings for `mvp run`, from built-in defaults, then `weaver.toml`, then
+/// flags.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 struct RunConfig {
-    topic:             String,
-    concepts:          usize,
-    learning_outcomes: usize,
-    target_edges:      usize,
-    use_llm:           bool,
-    export_dot:        Option<PathBuf>,
+    topic:                String,
+    /// Further topics generated into the same graph after `topic`.
+    topics:               Vec<String>,
+    concepts:             usize,
+    learning_outcomes:    usize,
+    target_edges:         usize,
+    /// Generate-and-add passes; later passes only fill what is still missing.
+    rounds:               usize,
+    use_llm:              bool,
+    export_dot:           Option<PathBuf>,
+    export_lms:           Option<PathBuf>,
+    export_json:          Option<PathBuf>,
+    export_graphml:       Option<PathBuf>,
+    export_mermaid:       Option<PathBuf>,
+    export_cytoscape:     Option<PathBuf>,
+    export_gexf:          Option<PathBuf>,
+    /// Directory receiving `nodes.csv` and `edges.csv`.
+    export_csv:           Option<PathBuf>,
+    /// Shape nodes by kind and cluster them by level in the DOT export.
+    dot_style:            bool,
+    /// Cut DOT labels to this many characters.
+    dot_label_chars:      Option<usize>,
+    rationale_policy:     RationalePolicy,
+    explain:              bool,
+    max_nodes:            Option<usize>,
+    max_edges:            Option<usize>,
+    /// Supports edges each learning outcome needs before coverage is fine.
+    min_supports:         usize,
+    /// Word overlap at which a node counts as a near-duplicate; `None`
+    /// rejects exact duplicates only.
+    similarity_threshold: Option<f64>,
+    /// Fewest words an edge rationale may have.
+    min_rationale_words:  usize,
+    node_models:          Vec<LlmSettings>,
+    group_by_generator:   bool,
+    viz:                  VizTarget,
+    /// `--viz` was given rather than defaulted.
+    #[serde(skip_deserializing)]
+    viz_explicit:         bool,
+    viz_required:         bool,
+    progress:             bool,
+    manifest:             Option<PathBuf>,
+    output:               OutputFormat,
+    strict_schema:        bool,
+    /// Seed for fallback generation and node ids; runs with equal seeds match.
+    seed:                 Option<u64>,
+    deadline_secs:        Option<u64>,
+    check_exports:        bool,
+    /// Namespace for accepted nodes; defaults to the topic.
+    namespace:            Option<String>,
+    export_namespace:     Option<String>,
+    /// Reuse the run stored here when its config hash matches.
+    if_absent:            Option<PathBuf>,
+    /// Chrome trace-event file for the run's spans.
+    trace_file:           Option<PathBuf>,
+    /// JSONL file receiving every proposal and its decision.
+    audit_log:            Option<PathBuf>,
+    /// Stderr log level; `None` falls back to `RUST_LOG`.
+    log_level:            Option<LogLevel>,
+    log_format:           LogFormat,
+    /// Print the merged configuration instead of running.
+    #[serde(skip)]
+    print_config:         bool,
+    /// Decide proposals without adding them, exporting, or storing the run.
+    dry_run:              bool,
+    /// Fail when the finished graph's prerequisites are not a DAG.
+    strict:               bool,
+    /// Highest node level generated and accepted.
+    max_level:            u8,
+    /// Openings learning outcomes must start with.
+    lo_prefixes:          Vec<String>,
+    /// Tags nodes may carry; others are dropped.
+    allowed_tags:         Vec<String>,
+    /// Report phase durations and LLM request latency.
+    timings:              bool,
+    /// Print a numbered order that respects every prerequisite.
+    teaching_order:       bool,
+    /// CSV of human-authored nodes added before any generator runs.
+    import_nodes:         Option<PathBuf>,
+    /// Graph export that `mvp resume` continues from.
+    #[serde(skip_deserializing)]
+    resume:               Option<PathBuf>,
 }
 
-fn usage() -> &'static str {
-    "Usage: weaver mvp run [--topic TEXT] [--concepts N] [--los N] [--edges N] [--use-llm \
-     true|false] [--export-dot PATH]"
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            topic:                "Design Recipe".to_string(),
+            topics:               Vec::new(),
+            concepts:             25,
+            learning_outcomes:    5,
+            target_edges:         40,
+            rounds:               1,
+            use_llm:              false,
+            export_dot:           None,
+            export_lms:           None,
+            export_json:          None,
+            export_graphml:       None,
+            export_mermaid:       None,
+            export_cytoscape:     None,
+            export_gexf:          None,
+            export_csv:           None,
+            dot_style:            false,
+            dot_label_chars:      None,
+            rationale_policy:     RationalePolicy::Off,
+            explain:              false,
+            max_nodes:            None,
+            max_edges:            None,
+            min_supports:         AdderConfig::default().min_supports,
+            similarity_threshold: None,
+            min_rationale_words:  0,
+            node_models:          Vec::new(),
+            group_by_generator:   false,
+            strict_schema:        false,
+            seed:                 None,
+            deadline_secs:        None,
+            check_exports:        false,
+            namespace:            None,
+            export_namespace:     None,
+            if_absent:            None,
+            trace_file:           None,
+            audit_log:            None,
+            log_level:            None,
+            log_format:           LogFormat::Pretty,
+            viz:                  VizTarget::Spawn,
+            viz_explicit:         false,
+            viz_required:         false,
+            progress:             false,
+            manifest:             None,
+            output:               OutputFormat::Text,
+            print_config:         false,
+            dry_run:              false,
+            strict:               false,
+            max_level:            MAX_NODE_LEVEL,
+            lo_prefixes:          LO_PREFIXES
+                .iter()
+                .map(|prefix| prefix.to_string())
+                .collect(),
+            allowed_tags:         ALLOWED_TAGS.iter().map(|tag| tag.to_string()).collect(),
+            timings:              false,
+            teaching_order:       false,
+            import_nodes:         None,
+            resume:               None,
+        }
+    }
 }
 
-fn parse_args() -> Result<RunConfig, CliError> {
-    let mut args = env::args().skip(1);
+/// RunConfig fields that only affect presentation or output locations.
+///
+/// They are dropped before hashing, so changing them never forces a
+/// regeneration under `--if-absent`.
+const NON_SEMANTIC_FIELDS: &[&str] = &[
+    "export_dot",
+    "export_lms",
+    "export_json",
+    "export_graphml",
+    "export_mermaid",
+    "export_cytoscape",
+    "export_gexf",
+    "export_csv",
+    "dot_style",
+    "dot_label_chars",
+    "explain",
+    "group_by_generator",
+    "viz",
+    "viz_explicit",
+    "viz_required",
+    "progress",
+    "manifest",
+    "output",
+    "check_exports",
+    "export_namespace",
+    "if_absent",
+    "trace_file",
+    "audit_log",
+    "log_level",
+    "log_format",
+    "strict",
+    "timings",
+    "teaching_order",
+];
 
-    let Some(command) = args.next() else {
-        return Err(CliError(usage().to_string()));
+/// Config file read when `--config` is not given, if it exists.
+const DEFAULT_CONFIG_PATH: &str = "weaver.toml";
+
+/// Load the config file named by `--config`, or `weaver.toml` if present.
+///
+/// Keys the file leaves out keep their built-in defaults.
+fn load_config_file(args: &[String]) -> Result<RunConfig, CliError> {
+    let explicit = args
+        .iter()
+        .position(|arg| arg == "--config")
+        .map(|index| args.get(index + 1).filter(|value| !value.starts_with("--")));
+    let path = match explicit {
+        Some(Some(path)) => PathBuf::from(path),
+        Some(None) => {
+            return Err(CliError::Usage(format!(
+                "missing value for --config (usage: {})",
+                flag_usage(RUN_FLAGS, "--config")
+            )));
+        }
+        None if std::path::Path::new(DEFAULT_CONFIG_PATH).exists() => {
+            PathBuf::from(DEFAULT_CONFIG_PATH)
+        }
+        None => return Ok(RunConfig::default()),
     };
 
-    if command != "mvp" {
-        return Err(CliError(format!("unknown command '{command}'. {}", usage())));
+    let contents = std::fs::read_to_string(&path).map_err(|err| {
+        CliError::Usage(format!("failed to read config file {}: {err}", path.display()))
+    })?;
+    let mut config: RunConfig = toml::from_str(&contents)
+        .map_err(|err| CliError::Usage(format!("invalid config file {}: {err}", path.display())))?;
+    config.viz_explicit =
+        toml::from_str::<toml::Table>(&contents).is_ok_and(|table| table.contains_key("viz"));
+    Ok(config)
+}
+
+impl RunConfig {
+    fn namespace(&self) -> &str {
+        self.namespace.as_deref().unwrap_or(&self.topic)
     }
 
-    let Some(sub) = args.next() else {
-        return Err(CliError(format!("missing subcommand. {}", usage())));
-    };
+    fn node_rules(&self) -> NodeRules {
+        NodeRules {
+            max_level: self.max_level,
+            allowed_tags: TagRegistry::new(&self.allowed_tags),
+            allowed_lo_prefixes: self.lo_prefixes.clone(),
+            ..NodeRules::default()
+        }
+    }
+
+    fn rationale_rules(&self) -> RationaleRules {
+        RationaleRules {
+            min_words: self.min_rationale_words,
+            ..RationaleRules::default()
+        }
+    }
+
+    /// Every topic of the run, `topic` first.
+    fn all_topics(&self) -> Vec<&str> {
+        std::iter::once(self.topic.as_str())
+            .chain(self.topics.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Export flags whose path is `-`, in flag order.
+    fn stdout_exports(&self) -> Vec<&'static str> {
+        [
+            ("--export-dot", &self.export_dot),
+            ("--export-lms", &self.export_lms),
+            ("--export-json", &self.export_json),
+            ("--export-graphml", &self.export_graphml),
+            ("--export-mermaid", &self.export_mermaid),
+            ("--export-cytoscape", &self.export_cytoscape),
+            ("--export-gexf", &self.export_gexf),
+        ]
+        .into_iter()
+        .filter(|(_, path)| path.as_deref().is_some_and(is_stdout))
+        .map(|(flag, _)| flag)
+        .collect()
+    }
+
+    /// Hash of the fields that determine the generated graph.
+    ///
+    /// Input files count by their contents as well as their paths, so an
+    /// edited seed CSV or saved graph forces a regeneration. A file that
+    /// cannot be read hashes as `null` and fails later with a real error.
+    fn semantic_hash(&self) -> Result<String, serde_json::Error> {
+        let mut config = serde_json::to_value(self)?;
+        if let serde_json::Value::Object(fields) = &mut config {
+            for (field, path) in [
+                ("import_nodes", &self.import_nodes),
+                ("resume", &self.resume),
+            ] {
+                if let Some(path) = path {
+                    let digest = std::fs::read(path).ok().map(|bytes| sha256_hex(&bytes));
+                    fields.insert(format!("{field}_sha256"), digest.into());
+                }
+            }
+        }
+        Ok(config_hash(&config, NON_SEMANTIC_FIELDS))
+    }
+}
+
+/// A flag as shown in help, quoted next to errors about it.
+#[derive(Debug)]
+struct FlagHelp {
+    /// The flag and its value placeholder, such as `--concepts N`.
+    usage: &'static str,
+    about: &'static str,
+}
+
+impl FlagHelp {
+    fn name(&self) -> &'static str {
+        self.usage.split(' ').next().unwrap_or_default()
+    }
+}
+
+const fn flag(usage: &'static str, about: &'static str) -> FlagHelp {
+    FlagHelp { usage, about }
+}
+
+const RUN_FLAGS: &[FlagHelp] = &[
+    flag("--topic TEXT", "Topic to generate; repeat to merge several topics"),
+    flag("--topics-file PATH", "Read topics from PATH, one per line"),
+    flag(
+        "--import-nodes CSV",
+        "Add nodes from CSV rows kind,level,text,tags before generating",
+    ),
+    flag("--concepts N", "Concept nodes to generate"),
+    flag("--los N", "Learning-outcome nodes to generate"),
+    flag("--edges N", "Edges to generate"),
+    flag("--rounds N", "Generate-and-add passes; later passes fill what is missing"),
+    flag("--use-llm true|false", "Generate with the LLM instead of the fallback"),
+    flag("--node-model MODEL[@BASE_URL]", "Add a node model to the ensemble; repeatable"),
+    flag("--strict-schema", "Fail LLM responses that omit required fields"),
+    flag("--seed N", "Seed fallback generation and node ids"),
+    flag("--deadline SECONDS", "Stop generating after SECONDS"),
+    flag("--max-nodes N", "Cap on accepted nodes"),
+    flag("--max-edges N", "Cap on accepted edges"),
+    flag("--min-supports N", "Supports edges each learning outcome needs (default 1)"),
+    flag("--max-level N", "Highest node level (default 3)"),
+    flag(
+        "--similarity-threshold X",
+        "Reject nodes sharing at least X (0 to 1) of their words with a node",
+    ),
+    flag("--min-rationale-words N", "Reject edge rationales with fewer than N words"),
+    flag("--allowed-tags TAG,TAG,...", "Tags nodes may carry"),
+    flag(
+        "--lo-prefixes PREFIX,PREFIX,...",
+        "Openings learning outcomes must start with (default: I can, Students can)",
+    ),
+    flag("--namespace NAME", "Namespace for accepted nodes (default: the first topic)"),
+    flag("--check-rationales", "Warn about edge rationales that ignore their endpoints"),
+    flag("--strict-rationales", "Reject edges whose rationale ignores their endpoints"),
+    flag("--explain", "Print why proposals were rejected"),
+    flag("--group-by-generator", "Print acceptance counts per generator"),
+    flag("--export-dot PATH|-", "Write the graph as DOT; '-' is stdout"),
+    flag("--export-lms PATH|-", "Write the LMS outcome document"),
+    flag("--export-json PATH|-", "Write the graph as JSON"),
+    flag("--export-graphml PATH|-", "Write the graph as GraphML"),
+    flag("--export-mermaid PATH|-", "Write the graph as a Mermaid flowchart"),
+    flag("--export-cytoscape PATH|-", "Write the graph as Cytoscape.js JSON"),
+    flag("--export-gexf PATH|-", "Write the graph as GEXF for Gephi"),
+    flag("--export-csv DIR", "Write nodes.csv and edges.csv into DIR"),
+    flag("--dot-style", "Shape DOT nodes by kind and cluster them by level"),
+    flag("--dot-label-chars N", "Cut DOT labels to N characters"),
+    flag("--export-namespace NAME", "Export only nodes in namespace NAME"),
+    flag("--check-exports", "Re-read every export and fail if it does not parse"),
+    flag("--manifest PATH", "Write a run manifest with artifact hashes"),
+    flag("--if-absent PATH", "Reuse the run stored at PATH when the config is unchanged"),
+    flag("--output text|json", "Format of the end-of-run report"),
+    flag("--no-viz", "Do not start the viewer"),
+    flag("--viz spawn|save[:PATH]|off", "Where visualization goes"),
+    flag("--viz-required", "Fail when the requested viewer is unavailable"),
+    flag("--progress", "Show progress bars on a terminal"),
+    flag("--trace-file PATH", "Write the run's spans as a Chrome trace"),
+    flag("--audit-log PATH", "Append every proposal and its decision to PATH as JSONL"),
+    flag("--log-level error|warn|info|debug|trace", "Most verbose stderr log level"),
+    flag("--log-format pretty|json", "Stderr log line format"),
+    flag("--config PATH", "Read settings from PATH instead of weaver.toml"),
+    flag("--print-config", "Print the merged configuration and exit"),
+    flag("--dry-run", "Decide proposals without adding, exporting, or storing anything"),
+    flag("--strict", "Fail when the finished graph has a prerequisite cycle"),
+    flag("--timings", "Print phase durations and LLM request latency"),
+    flag("--teaching-order", "Print a numbered order that respects every prerequisite"),
+];
+
+const RESUME_FLAGS: &[FlagHelp] = &[flag(
+    "--input GRAPH",
+    "JSON graph export or DOT file to continue from",
+)];
+
+const SHELL_FLAGS: &[FlagHelp] = &[
+    flag("--input GRAPH", "Start from a JSON graph export or DOT file"),
+    flag("--namespace NAME", "Namespace for added nodes"),
+    flag("--no-viz", "Do not start the viewer"),
+    flag("--viz spawn|save[:PATH]|off", "Where visualization goes"),
+];
+
+const SUMMARY_FLAGS: &[FlagHelp] = &[
+    flag("--input GRAPH", "JSON graph export or DOT file to summarize"),
+    flag("--output PATH", "Also write the summary as JSON"),
+];
+
+const VALIDATE_FLAGS: &[FlagHelp] = &[
+    flag("--input GRAPH", "JSON graph export or DOT file to check"),
+    flag("--max-level N", "Highest node level (default 3)"),
+];
+
+const DIFF_FLAGS: &[FlagHelp] = &[flag("--output text|json", "Format of the comparison")];
+
+const SPLIT_FLAGS: &[FlagHelp] = &[
+    flag("--input GRAPH", "JSON graph export or DOT file holding the node"),
+    flag("--node ID", "Node to split; a unique id prefix is enough"),
+    flag("--into TEXT", "Replacement text; give it once per replacement"),
+    flag("--edges-to first|all|N", "Replacement that inherits the edges, or all of them"),
+    flag("--output PATH", "Write the split graph here instead of over --input"),
+];
+
+/// Width the environment list in `--help` wraps at, not counting its indent.
+const HELP_LINE_WIDTH: usize = 78;
+
+/// One entry of the command tree.
+struct Subcommand {
+    /// Words that select the subcommand.
+    name:     &'static str,
+    /// Positional arguments, shown after the flags.
+    operands: &'static str,
+    about:    &'static str,
+    flags:    &'static [FlagHelp],
+    /// Environment variables read in place of flags, listed in `--help`.
+    env:      &'static [(&'static str, EnvSetter)],
+    /// Extra paragraphs for `--help`.
+    notes:    &'static str,
+    parse:    fn(&[String]) -> Result<Command, CliError>,
+}
+
+const SUBCOMMANDS: &[Subcommand] = &[
+    Subcommand {
+        name:     "mvp run",
+        operands: "",
+        about:    "Generate a learning graph and export it",
+        flags:    RUN_FLAGS,
+        env:      ENV_VARS,
+        notes:    "Settings come from built-in defaults, then weaver.toml (or --config), then the \
+                   environment, then flags. Each variable takes the value of the flag it is named \
+                   after; flags without a value take true or false. RUST_LOG sets log filters \
+                   when --log-level is not given.",
+        parse:    parse_run_command,
+    },
+    Subcommand {
+        name:     "mvp resume",
+        operands: "",
+        about:    "Load a saved graph and generate what it is still missing",
+        flags:    RESUME_FLAGS,
+        env:      &[],
+        notes:    "Every 'mvp run' flag except --dry-run is accepted. --concepts, --los and \
+                   --edges count the finished graph, so only the shortfall is generated; \
+                   proposals duplicating the saved graph are rejected.",
+        parse:    parse_resume_command,
+    },
+    Subcommand {
+        name:     "run verify",
+        operands: " MANIFEST",
+        about:    "Check that a run's artifacts still match its manifest",
+        flags:    &[],
+        env:      &[],
+        notes:    "",
+        parse:    parse_verify_args,
+    },
+    Subcommand {
+        name:     "shell",
+        operands: "",
+        about:    "Add nodes and edges by hand from a line-oriented prompt",
+        flags:    SHELL_FLAGS,
+        env:      &[],
+        notes:    "Type 'help' at the prompt for its commands.",
+        parse:    parse_shell_args,
+    },
+    Subcommand {
+        name:     "summary",
+        operands: "",
+        about:    "Recompute summary statistics for a JSON graph export",
+        flags:    SUMMARY_FLAGS,
+        env:      &[],
+        notes:    "",
+        parse:    parse_summary_args,
+    },
+    Subcommand {
+        name:     "validate",
+        operands: "",
+        about:    "Re-check a JSON graph export against the adder's rules",
+        flags:    VALIDATE_FLAGS,
+        env:      &[],
+        notes:    "",
+        parse:    parse_validate_args,
+    },
+    Subcommand {
+        name:     "graph split",
+        operands: "",
+        about:    "Replace one node with several, re-attaching its edges",
+        flags:    SPLIT_FLAGS,
+        env:      &[],
+        notes:    "Each --into text is checked like a new proposal. Without --edges-to the \
+                   replacement for the node's edges is asked for at the prompt, or is the first \
+                   when stdin is not a terminal. A DOT --input needs --output, which is always \
+                   written as JSON.",
+        parse:    parse_split_args,
+    },
+    Subcommand {
+        name:     "diff",
+        operands: " OLD.json NEW.json",
+        about:    "Compare two JSON graph exports",
+        flags:    DIFF_FLAGS,
+        env:      &[],
+        notes:    "",
+        parse:    parse_diff_args,
+    },
+];
+
+const EXIT_STATUS_HELP: &str = "Exit status: 0 success, 1 other failure, 2 bad arguments, 3 LLM \
+                                backend failure, 4 graph invariant failure, 5 file I/O failure, \
+                                130 interrupted.";
+
+impl Subcommand {
+    /// Number of leading arguments that name this subcommand, if they do.
+    fn matches(&self, args: &[String]) -> Option<usize> {
+        let words: Vec<&str> = self.name.split(' ').collect();
+        (args.len() >= words.len() && args.iter().zip(&words).all(|(arg, word)| arg == word))
+            .then_some(words.len())
+    }
+
+    fn help(&self) -> String {
+        let flags = if self.flags.is_empty() {
+            ""
+        } else {
+            " [FLAGS]"
+        };
+        let mut help = format!(
+            "Usage: weaver {}{flags}{}\n\n{}.\n\nFlags:\n",
+            self.name, self.operands, self.about
+        );
+        let help_flag = flag("--help", "Print this help");
+        let width = self
+            .flags
+            .iter()
+            .chain([&help_flag])
+            .map(|flag| flag.usage.len())
+            .max()
+            .unwrap_or(0);
+        for flag in self.flags.iter().chain([&help_flag]) {
+            help.push_str(&format!("  {:width$}  {}\n", flag.usage, flag.about));
+        }
+        if !self.env.is_empty() {
+            help.push_str("\nEnvironment:\n");
+            let mut line = String::new();
+            for (name, _) in self.env {
+                if !line.is_empty() && line.len() + 1 + name.len() > HELP_LINE_WIDTH {
+                    help.push_str(&format!("  {line}\n"));
+                    line.clear();
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(name);
+            }
+            help.push_str(&format!("  {line}\n"));
+        }
+        if !self.notes.is_empty() {
+            help.push_str(&format!("\n{}\n", self.notes));
+        }
+        help
+    }
+}
+
+/// Whichever subcommand `args` names, with the number of words naming it.
+fn find_subcommand(args: &[String]) -> Option<(&'static Subcommand, usize)> {
+    SUBCOMMANDS
+        .iter()
+        .find_map(|sub| sub.matches(args).map(|words| (sub, words)))
+}
+
+/// Help for `weaver --help`.
+fn top_help() -> String {
+    let width = SUBCOMMANDS
+        .iter()
+        .map(|sub| sub.name.len())
+        .max()
+        .unwrap_or(0);
+    let mut help = "Usage: weaver COMMAND [ARGS]\n\nCommands:\n".to_string();
+    for sub in SUBCOMMANDS {
+        help.push_str(&format!("  {:width$}  {}\n", sub.name, sub.about));
+    }
+    help.push_str(
+        "\nRun 'weaver COMMAND --help' for its flags; 'weaver --version' prints the version.\n\n",
+    );
+    help.push_str(EXIT_STATUS_HELP);
+    help.push('\n');
+    help
+}
 
-    if sub != "run" {
-        return Err(CliError(format!("unknown subcommand '{sub}'. {}", usage())));
+/// Usage fragment for `flag` among `flags`, or empty when it has none.
+fn flag_usage(flags: &[FlagHelp], flag: &str) -> &'static str {
+    flags
+        .iter()
+        .find(|help| help.name() == flag)
+        .map_or("", |help| help.usage)
+}
+
+/// Argument errors collected while parsing, reported together.
+#[derive(Debug)]
+struct ArgErrors {
+    /// Flags of the subcommand being parsed, for usage fragments.
+    flags:  &'static [FlagHelp],
+    errors: Vec<String>,
+}
+
+impl ArgErrors {
+    fn new(flags: &'static [FlagHelp]) -> Self {
+        Self {
+            flags,
+            errors: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    fn push(&mut self, message: impl Into<String>) {
+        self.errors.push(message.into());
+    }
+
+    fn flag(&mut self, flag: &str, message: impl fmt::Display) {
+        let usage = flag_usage(self.flags, flag);
+        self.errors.push(format!("{message} (usage: {usage})"));
+    }
+
+    fn into_result<T>(self, value: T) -> Result<T, CliError> {
+        match self.errors.as_slice() {
+            [] => Ok(value),
+            [single] => Err(CliError::Usage(single.clone())),
+            many => {
+                let mut message = format!("{} argument errors:", many.len());
+                for error in many {
+                    message.push_str(&format!("\n  - {error}"));
+                }
+                Err(CliError::Usage(message))
+            }
+        }
     }
+}
 
-    let mut config = RunConfig {
-        topic:             "Design Recipe".to_string(),
-        concepts:          25,
-        learning_outcomes: 5,
-        target_edges:      40,
-        use_llm:           false,
-        export_dot:        None,
+fn parse_command(args: Vec<String>) -> Result<Command, CliError> {
+    match args.first().map(String::as_str) {
+        None => return Err(CliError::Usage("missing command".to_string())),
+        Some("--help" | "-h" | "help") => return Ok(Command::Help(top_help())),
+        Some("--version" | "-V") => return Ok(Command::Version),
+        _ => {}
+    }
+    let Some((sub, words)) = find_subcommand(&args) else {
+        return Err(unknown_command(&args));
     };
+    let rest = &args[words..];
+    if rest.iter().any(|arg| arg == "--help" || arg == "-h") {
+        return Ok(Command::Help(sub.help()));
+    }
+    (sub.parse)(rest)
+}
+
+/// Error for a command line that names no subcommand.
+fn unknown_command(args: &[String]) -> CliError {
+    let first = args[0].as_str();
+    let expected: Vec<&str> = SUBCOMMANDS
+        .iter()
+        .filter_map(|sub| sub.name.strip_prefix(first)?.strip_prefix(' '))
+        .collect();
+    CliError::Usage(match args.get(1) {
+        _ if expected.is_empty() => format!("unknown command '{first}'"),
+        Some(sub) => {
+            format!("unknown subcommand '{sub}' for '{first}' (expected {})", expected.join(", "))
+        }
+        None => format!("missing subcommand for '{first}' (expected {})", expected.join(", ")),
+    })
+}
+
+fn parse_run_command(args: &[String]) -> Result<Command, CliError> {
+    parse_run_args(args, |name| env::var(name).ok()).map(|config| Command::Run(Box::new(config)))
+}
+
+/// `mvp resume` takes `--input` plus the flags of `mvp run`.
+fn parse_resume_command(args: &[String]) -> Result<Command, CliError> {
+    let mut errors = ArgErrors::new(RESUME_FLAGS);
+    let mut input = None;
+    let mut rest = Vec::new();
+    let mut args = args.iter().cloned().peekable();
+    while let Some(arg) = args.next() {
+        if arg == "--input" {
+            input = required(&mut errors, &arg, args.next_if(|next| !next.starts_with("--")));
+        } else {
+            rest.push(arg);
+        }
+    }
+    let mut config = parse_run_args(&rest, |name| env::var(name).ok())?;
+    match input {
+        Some(input) => config.resume = Some(PathBuf::from(input)),
+        None if errors.is_empty() => errors.flag("--input", "missing --input"),
+        None => {}
+    }
+    if config.dry_run {
+        errors.push("--dry-run cannot resume a saved graph");
+    }
+    errors.into_result(Command::Run(Box::new(config)))
+}
+
+/// Parse the flags after `mvp run`, layering defaults, `weaver.toml`,
+/// `WEAVER_*` variables from `env`, then flags.
+fn parse_run_args(
+    args: &[String],
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<RunConfig, CliError> {
+    let remaining = args.to_vec();
+    let mut config = load_config_file(&remaining)?;
+    let mut args = remaining.into_iter().peekable();
+    let mut replaced_node_models = false;
+    let mut topics: Vec<String> = Vec::new();
+    let mut errors = ArgErrors::new(RUN_FLAGS);
+    apply_env(&mut config, env, &mut errors);
 
     while let Some(flag) = args.next() {
+        let mut value = || args.next_if(|next| !next.starts_with("--"));
         match flag.as_str() {
             "--topic" => {
-                config.topic = args
-                    .next()
-                    .ok_or_else(|| CliError(format!("missing value for --topic. {}", usage())))?;
+                if let Some(topic) = required(&mut errors, &flag, value()) {
+                    topics.push(topic);
+                }
+            }
+            "--topics-file" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    match std::fs::read_to_string(&path) {
+                        Ok(contents) => topics.extend(parse_topics_file(&contents)),
+                        Err(err) => {
+                            errors.flag(&flag, format!("failed to read topics file {path}: {err}"))
+                        }
+                    }
+                }
+            }
+            "--import-nodes" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.import_nodes = Some(PathBuf::from(path));
+                }
             }
             "--concepts" => {
-                config.concepts = parse_usize(args.next(), "--concepts")?;
+                if let Some(count) = parse_usize(&mut errors, &flag, value()) {
+                    config.concepts = count;
+                }
             }
             "--los" => {
-                config.learning_outcomes = parse_usize(args.next(), "--los")?;
+                if let Some(count) = parse_usize(&mut errors, &flag, value()) {
+                    config.learning_outcomes = count;
+                }
             }
             "--edges" => {
-                config.target_edges = parse_usize(args.next(), "--edges")?;
+                if let Some(count) = parse_usize(&mut errors, &flag, value()) {
+                    config.target_edges = count;
+                }
+            }
+            "--rounds" => {
+                if let Some(count) = parse_usize(&mut errors, &flag, value()) {
+                    config.rounds = count;
+                }
             }
             "--use-llm" => {
-                let value = args
-                    .next()
-                    .ok_or_else(|| CliError(format!("missing value for --use-llm. {}", usage())))?;
-                config.use_llm = parse_bool(&value)
-                    .ok_or_else(|| CliError(format!("invalid boolean '{value}' for --use-llm")))?;
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    match parse_bool(&raw) {
+                        Some(use_llm) => config.use_llm = use_llm,
+                        None => errors.flag(&flag, format!("invalid boolean '{raw}' for {flag}")),
+                    }
+                }
             }
             "--export-dot" => {
-                let value = args.next().ok_or_else(|| {
-                    CliError(format!("missing value for --export-dot. {}", usage()))
-                })?;
-                config.export_dot = Some(PathBuf::from(value));
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.export_dot = Some(PathBuf::from(path));
+                }
+            }
+            "--export-json" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.export_json = Some(PathBuf::from(path));
+                }
+            }
+            "--export-lms" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.export_lms = Some(PathBuf::from(path));
+                }
+            }
+            "--export-graphml" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.export_graphml = Some(PathBuf::from(path));
+                }
+            }
+            "--export-mermaid" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.export_mermaid = Some(PathBuf::from(path));
+                }
+            }
+            "--export-cytoscape" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.export_cytoscape = Some(PathBuf::from(path));
+                }
+            }
+            "--export-gexf" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.export_gexf = Some(PathBuf::from(path));
+                }
+            }
+            "--export-csv" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    if path == STDOUT_PATH {
+                        errors.flag(&flag, "--export-csv writes two files and needs a directory");
+                    } else {
+                        config.export_csv = Some(PathBuf::from(path));
+                    }
+                }
+            }
+            "--dot-style" => {
+                config.dot_style = true;
+            }
+            "--dot-label-chars" => {
+                if let Some(limit) = parse_usize(&mut errors, &flag, value()) {
+                    config.dot_label_chars = Some(limit);
+                }
+            }
+            "--check-rationales" => {
+                if config.rationale_policy == RationalePolicy::Off {
+                    config.rationale_policy = RationalePolicy::Warn;
+                }
+            }
+            "--strict-rationales" => {
+                config.rationale_policy = RationalePolicy::Strict;
+            }
+            "--explain" => {
+                config.explain = true;
+            }
+            "--teaching-order" => {
+                config.teaching_order = true;
+            }
+            "--dry-run" => {
+                config.dry_run = true;
+            }
+            "--strict" => {
+                config.strict = true;
+            }
+            "--timings" => {
+                config.timings = true;
+            }
+            "--max-nodes" => {
+                if let Some(limit) = parse_usize(&mut errors, &flag, value()) {
+                    config.max_nodes = Some(limit);
+                }
+            }
+            "--min-supports" => {
+                if let Some(minimum) = parse_usize(&mut errors, &flag, value()) {
+                    config.min_supports = minimum;
+                }
+            }
+            "--max-edges" => {
+                if let Some(limit) = parse_usize(&mut errors, &flag, value()) {
+                    config.max_edges = Some(limit);
+                }
+            }
+            "--max-level" => {
+                if let Some(level) = parse_level(&mut errors, &flag, value()) {
+                    config.max_level = level;
+                }
+            }
+            "--similarity-threshold" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    match raw.parse::<f64>() {
+                        Ok(threshold) => config.similarity_threshold = Some(threshold),
+                        Err(_) => errors.flag(&flag, format!("invalid number '{raw}' for {flag}")),
+                    }
+                }
+            }
+            "--min-rationale-words" => {
+                if let Some(words) = parse_usize(&mut errors, &flag, value()) {
+                    config.min_rationale_words = words;
+                }
+            }
+            "--allowed-tags" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    config.allowed_tags = comma_list(&raw);
+                }
+            }
+            "--lo-prefixes" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    config.lo_prefixes = comma_list(&raw);
+                }
+            }
+            "--node-model" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    // Models named on the command line replace the file's list.
+                    if !replaced_node_models {
+                        config.node_models.clear();
+                        replaced_node_models = true;
+                    }
+                    config.node_models.push(parse_llm_settings(&raw));
+                }
+            }
+            "--config" => {
+                // Read before the other flags by `load_config_file`.
+                value();
+            }
+            "--print-config" => {
+                config.print_config = true;
+            }
+            "--group-by-generator" => {
+                config.group_by_generator = true;
+            }
+            "--seed" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    match raw.parse::<u64>() {
+                        Ok(seed) => config.seed = Some(seed),
+                        Err(_) => errors.flag(&flag, format!("invalid integer '{raw}' for {flag}")),
+                    }
+                }
+            }
+            "--strict-schema" => {
+                config.strict_schema = true;
+            }
+            "--check-exports" => {
+                config.check_exports = true;
+            }
+            "--namespace" => {
+                if let Some(namespace) = required(&mut errors, &flag, value()) {
+                    config.namespace = Some(namespace);
+                }
+            }
+            "--export-namespace" => {
+                if let Some(namespace) = required(&mut errors, &flag, value()) {
+                    config.export_namespace = Some(namespace);
+                }
+            }
+            "--no-viz" => {
+                config.viz = VizTarget::Off;
+            }
+            "--viz" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    match parse_viz_target(&raw) {
+                        Some(target) => {
+                            config.viz = target;
+                            config.viz_explicit = true;
+                        }
+                        None => errors.flag(&flag, format!("invalid viz target '{raw}'")),
+                    }
+                }
+            }
+            "--viz-required" => {
+                config.viz_required = true;
+            }
+            "--progress" => {
+                config.progress = true;
+            }
+            "--trace-file" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.trace_file = Some(PathBuf::from(path));
+                }
+            }
+            "--audit-log" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.audit_log = Some(PathBuf::from(path));
+                }
+            }
+            "--log-level" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    match parse_log_level(&raw) {
+                        Some(level) => config.log_level = Some(level),
+                        None => errors.flag(&flag, format!("invalid log level '{raw}'")),
+                    }
+                }
+            }
+            "--log-format" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    match raw.as_str() {
+                        "pretty" => config.log_format = LogFormat::Pretty,
+                        "json" => config.log_format = LogFormat::Json,
+                        _ => errors.flag(&flag, format!("invalid log format '{raw}'")),
+                    }
+                }
+            }
+            "--if-absent" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.if_absent = Some(PathBuf::from(path));
+                }
+            }
+            "--manifest" => {
+                if let Some(path) = required(&mut errors, &flag, value()) {
+                    config.manifest = Some(PathBuf::from(path));
+                }
+            }
+            "--output" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    match raw.as_str() {
+                        "text" => config.output = OutputFormat::Text,
+                        "json" => config.output = OutputFormat::Json,
+                        _ => errors.flag(&flag, format!("invalid output format '{raw}'")),
+                    }
+                }
+            }
+            "--deadline" => {
+                if let Some(seconds) = parse_usize(&mut errors, &flag, value()) {
+                    config.deadline_secs = Some(seconds as u64);
+                }
             }
             other => {
-                return Err(CliError(format!("unknown flag '{other}'. {}", usage())));
+                errors.push(format!("unknown flag '{other}'"));
             }
         }
     }
 
-    Ok(config)
+    // Topics named on the command line replace the file's and environment's.
+    let mut topics = topics.into_iter();
+    if let Some(first) = topics.next() {
+        config.topic = first;
+        config.topics = topics.collect();
+    }
+
+    validate_config(&config, &mut errors);
+    errors.into_result(config)
 }
 
-fn parse_usize(value: Option<String>, flag: &str) -> Result<usize, CliError> {
-    let Some(raw) = value else {
-        return Err(CliError(format!("missing value for {flag}. {}", usage())));
-    };
-    raw.parse::<usize>()
-        .map_err(|_| CliError(format!("invalid integer '{raw}' for {flag}")))
+/// One topic per line; blank lines and lines starting with `#` are skipped.
+fn parse_topics_file(contents: &str) -> impl Iterator<Item = String> + '_ {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+}
+
+/// Sets one `RunConfig` field from an environment value, or says what was
+/// expected.
+type EnvSetter = fn(&mut RunConfig, &str) -> Result<(), &'static str>;
+
+/// Environment variables read between `weaver.toml` and flags; each is named
+/// after the flag it stands in for.
+const ENV_VARS: &[(&str, EnvSetter)] = &[
+    ("WEAVER_TOPIC", |config, raw| {
+        config.topic = raw.to_string();
+        Ok(())
+    }),
+    ("WEAVER_CONCEPTS", |config, raw| {
+        config.concepts = env_usize(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_LOS", |config, raw| {
+        config.learning_outcomes = env_usize(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_EDGES", |config, raw| {
+        config.target_edges = env_usize(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_ROUNDS", |config, raw| {
+        config.rounds = env_usize(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_USE_LLM", |config, raw| {
+        config.use_llm = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_IMPORT_NODES", |config, raw| {
+        config.import_nodes = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_NODE_MODELS", |config, raw| {
+        config.node_models = comma_list(raw)
+            .iter()
+            .map(|model| parse_llm_settings(model))
+            .collect();
+        Ok(())
+    }),
+    ("WEAVER_STRICT_SCHEMA", |config, raw| {
+        config.strict_schema = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_EXPORT_DOT", |config, raw| {
+        config.export_dot = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_EXPORT_LMS", |config, raw| {
+        config.export_lms = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_EXPORT_JSON", |config, raw| {
+        config.export_json = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_EXPORT_GRAPHML", |config, raw| {
+        config.export_graphml = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_EXPORT_MERMAID", |config, raw| {
+        config.export_mermaid = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_EXPORT_CYTOSCAPE", |config, raw| {
+        config.export_cytoscape = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_EXPORT_GEXF", |config, raw| {
+        config.export_gexf = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_EXPORT_CSV", |config, raw| {
+        if raw == STDOUT_PATH {
+            return Err("expected a directory, not '-'");
+        }
+        config.export_csv = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_DOT_STYLE", |config, raw| {
+        config.dot_style = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_DOT_LABEL_CHARS", |config, raw| {
+        config.dot_label_chars = Some(env_usize(raw)?);
+        Ok(())
+    }),
+    ("WEAVER_MAX_NODES", |config, raw| {
+        config.max_nodes = Some(env_usize(raw)?);
+        Ok(())
+    }),
+    ("WEAVER_MAX_EDGES", |config, raw| {
+        config.max_edges = Some(env_usize(raw)?);
+        Ok(())
+    }),
+    ("WEAVER_MIN_SUPPORTS", |config, raw| {
+        config.min_supports = env_usize(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_MAX_LEVEL", |config, raw| {
+        config.max_level = raw.parse().map_err(|_| "expected 0 to 255")?;
+        Ok(())
+    }),
+    ("WEAVER_SIMILARITY_THRESHOLD", |config, raw| {
+        config.similarity_threshold = Some(raw.parse().map_err(|_| "expected a number")?);
+        Ok(())
+    }),
+    ("WEAVER_MIN_RATIONALE_WORDS", |config, raw| {
+        config.min_rationale_words = env_usize(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_ALLOWED_TAGS", |config, raw| {
+        config.allowed_tags = comma_list(raw);
+        Ok(())
+    }),
+    ("WEAVER_LO_PREFIXES", |config, raw| {
+        config.lo_prefixes = comma_list(raw);
+        Ok(())
+    }),
+    ("WEAVER_CHECK_RATIONALES", |config, raw| {
+        if !env_bool(raw)? {
+            config.rationale_policy = RationalePolicy::Off;
+        } else if config.rationale_policy == RationalePolicy::Off {
+            config.rationale_policy = RationalePolicy::Warn;
+        }
+        Ok(())
+    }),
+    ("WEAVER_STRICT_RATIONALES", |config, raw| {
+        if env_bool(raw)? {
+            config.rationale_policy = RationalePolicy::Strict;
+        } else if config.rationale_policy == RationalePolicy::Strict {
+            config.rationale_policy = RationalePolicy::Warn;
+        }
+        Ok(())
+    }),
+    ("WEAVER_EXPLAIN", |config, raw| {
+        config.explain = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_GROUP_BY_GENERATOR", |config, raw| {
+        config.group_by_generator = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_CHECK_EXPORTS", |config, raw| {
+        config.check_exports = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_DRY_RUN", |config, raw| {
+        config.dry_run = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_STRICT", |config, raw| {
+        config.strict = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_TIMINGS", |config, raw| {
+        config.timings = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_TEACHING_ORDER", |config, raw| {
+        config.teaching_order = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_SEED", |config, raw| {
+        config.seed = Some(raw.parse().map_err(|_| "expected a non-negative integer")?);
+        Ok(())
+    }),
+    ("WEAVER_DEADLINE", |config, raw| {
+        config.deadline_secs = Some(raw.parse().map_err(|_| "expected a number of seconds")?);
+        Ok(())
+    }),
+    ("WEAVER_NAMESPACE", |config, raw| {
+        config.namespace = Some(raw.to_string());
+        Ok(())
+    }),
+    ("WEAVER_EXPORT_NAMESPACE", |config, raw| {
+        config.export_namespace = Some(raw.to_string());
+        Ok(())
+    }),
+    ("WEAVER_VIZ", |config, raw| {
+        config.viz = parse_viz_target(raw).ok_or("expected spawn, save[:PATH] or off")?;
+        config.viz_explicit = true;
+        Ok(())
+    }),
+    ("WEAVER_VIZ_REQUIRED", |config, raw| {
+        config.viz_required = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_PROGRESS", |config, raw| {
+        config.progress = env_bool(raw)?;
+        Ok(())
+    }),
+    ("WEAVER_OUTPUT", |config, raw| {
+        config.output = match raw {
+            "text" => OutputFormat::Text,
+            "json" => OutputFormat::Json,
+            _ => return Err("expected text or json"),
+        };
+        Ok(())
+    }),
+    ("WEAVER_MANIFEST", |config, raw| {
+        config.manifest = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_IF_ABSENT", |config, raw| {
+        config.if_absent = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_TRACE_FILE", |config, raw| {
+        config.trace_file = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_AUDIT_LOG", |config, raw| {
+        config.audit_log = Some(PathBuf::from(raw));
+        Ok(())
+    }),
+    ("WEAVER_LOG_LEVEL", |config, raw| {
+        config.log_level =
+            Some(parse_log_level(raw).ok_or("expected error, warn, info, debug or trace")?);
+        Ok(())
+    }),
+    ("WEAVER_LOG_FORMAT", |config, raw| {
+        config.log_format = match raw {
+            "pretty" => LogFormat::Pretty,
+            "json" => LogFormat::Json,
+            _ => return Err("expected pretty or json"),
+        };
+        Ok(())
+    }),
+];
+
+fn env_usize(raw: &str) -> Result<usize, &'static str> {
+    raw.parse().map_err(|_| "expected a non-negative integer")
+}
+
+fn env_bool(raw: &str) -> Result<bool, &'static str> {
+    parse_bool(raw).ok_or("expected true or false")
+}
+
+/// Split `A,B,...`, dropping blank entries.
+fn comma_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn apply_env(config: &mut RunConfig, env: impl Fn(&str) -> Option<String>, errors: &mut ArgErrors) {
+    for (name, set) in ENV_VARS {
+        let Some(raw) = env(name) else {
+            continue;
+        };
+        if let Err(expected) = set(config, &raw) {
+            errors.push(format!("invalid value '{raw}' for {name}: {expected}"));
+        }
+    }
+}
+
+/// Semantic checks over a fully parsed configuration.
+fn validate_config(config: &RunConfig, errors: &mut ArgErrors) {
+    if config.concepts == 0 && config.learning_outcomes == 0 {
+        errors.push("--concepts 0 and --los 0 together leave nothing to generate");
+    }
+    if config.max_nodes == Some(0) {
+        errors.flag("--max-nodes", "--max-nodes must be at least 1");
+    }
+    if config.max_edges == Some(0) {
+        errors.flag("--max-edges", "--max-edges must be at least 1");
+    }
+    if config.dot_label_chars == Some(0) {
+        errors.flag("--dot-label-chars", "--dot-label-chars must be at least 1");
+    }
+    if config
+        .similarity_threshold
+        .is_some_and(|threshold| !(0.0..=1.0).contains(&threshold))
+    {
+        errors.flag("--similarity-threshold", "--similarity-threshold must be between 0 and 1");
+    }
+    if config.viz_required && !config.viz_explicit {
+        errors.flag("--viz", "--viz-required needs an explicit --viz target");
+    }
+    if config.rounds == 0 {
+        errors.flag("--rounds", "--rounds must be at least 1");
+    }
+    if config.deadline_secs == Some(0) {
+        errors.flag("--deadline", "--deadline must be at least 1 second");
+    }
+    let stdout_exports = config.stdout_exports();
+    if stdout_exports.len() > 1 {
+        errors.push(format!(
+            "only one export can write to stdout ('-'), got {}",
+            stdout_exports.join(", ")
+        ));
+    }
+    if let Some(flag) = stdout_exports.first()
+        && config.output == OutputFormat::Json
+    {
+        errors.push(format!("{flag} - and --output json both write to stdout"));
+    }
+    for settings in &config.node_models {
+        if settings.model.is_empty() {
+            errors.flag("--node-model", "--node-model needs a model name before '@'");
+        }
+    }
+}
+
+fn required(errors: &mut ArgErrors, flag: &str, value: Option<String>) -> Option<String> {
+    if value.is_none() {
+        errors.flag(flag, format!("missing value for {flag}"));
+    }
+    value
+}
+
+fn parse_usize(errors: &mut ArgErrors, flag: &str, value: Option<String>) -> Option<usize> {
+    let raw = required(errors, flag, value)?;
+    match raw.parse::<usize>() {
+        Ok(parsed) => Some(parsed),
+        Err(_) => {
+            errors.flag(flag, format!("invalid integer '{raw}' for {flag}"));
+            None
+        }
+    }
+}
+
+fn parse_level(errors: &mut ArgErrors, flag: &str, value: Option<String>) -> Option<u8> {
+    let raw = required(errors, flag, value)?;
+    match raw.parse::<u8>() {
+        Ok(parsed) => Some(parsed),
+        Err(_) => {
+            errors.flag(flag, format!("invalid level '{raw}' for {flag}: expected 0 to 255"));
+            None
+        }
+    }
+}
+
+/// Parse `MODEL[@BASE_URL]`.
+fn parse_llm_settings(value: &str) -> LlmSettings {
+    match value.split_once('@') {
+        Some((model, base_url)) => LlmSettings {
+            model:    model.to_string(),
+            base_url: Some(base_url.to_string()),
+        },
+        None => LlmSettings {
+            model:    value.to_string(),
+            base_url: None,
+        },
+    }
+}
+
+/// Default file written by `--viz save`.
+const DEFAULT_RECORDING_PATH: &str = "weaver.rrd";
+
+fn parse_viz_target(value: &str) -> Option<VizTarget> {
+    match value.split_once(':') {
+        Some(("save", path)) if !path.is_empty() => Some(VizTarget::Save(PathBuf::from(path))),
+        Some(_) => None,
+        None => match value {
+            "spawn" => Some(VizTarget::Spawn),
+            "save" => Some(VizTarget::Save(PathBuf::from(DEFAULT_RECORDING_PATH))),
+            "off" | "false" => Some(VizTarget::Off),
+            "true" => Some(VizTarget::Spawn),
+            _ => None,
+        },
+    }
+}
+
+fn parse_log_level(value: &str) -> Option<LogLevel> {
+    match value.to_ascii_lowercase().as_str() {
+        "error" => Some(LogLevel::Error),
+        "warn" => Some(LogLevel::Warn),
+        "info" => Some(LogLevel::Info),
+        "debug" => Some(LogLevel::Debug),
+        "trace" => Some(LogLevel::Trace),
+        _ => None,
+    }
 }
 
 fn parse_bool(value: &str) -> Option<bool> {
@@ -128,107 +1593,1550 @@ fn parse_bool(value: &str) -> Option<bool> {
     }
 }
 
+/// Exit status for a failed command.
+///
+/// Errors that are not a [`CliError`] count as I/O failures when they come
+/// from the filesystem and as general failures otherwise.
+fn exit_code(err: &(dyn std::error::Error + 'static)) -> i32 {
+    if let Some(err) = err.downcast_ref::<CliError>() {
+        err.exit_code()
+    } else if err.is::<std::io::Error>() {
+        CliError::Io(String::new()).exit_code()
+    } else {
+        CliError::Failed(String::new()).exit_code()
+    }
+}
+
 #[tokio::main]
-async fn main() -> Result<(), DynError> {
-    let config = match parse_args() {
-        Ok(cfg) => cfg,
+async fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match parse_command(args.clone()) {
+        Ok(command) => command,
         Err(err) => {
+            let help = find_subcommand(&args).map_or("weaver --help".to_string(), |(sub, _)| {
+                format!("weaver {} --help", sub.name)
+            });
             eprintln!("{err}");
-            eprintln!("{}", usage());
-            std::process::exit(1);
+            eprintln!("Run '{help}' for usage.");
+            std::process::exit(err.exit_code());
         }
     };
 
-    run_mvp(config).await
+    if let Err(err) = run_command(command).await {
+        eprintln!("error: {err}");
+        std::process::exit(exit_code(&*err));
+    }
 }
 
-async fn run_mvp(config: RunConfig) -> Result<(), DynError> {
-    info!(topic = %config.topic, use_llm = config.use_llm, "starting run");
-
-    let (event_tx, event_rx) = mpsc::unbounded_channel();
-    tokio::spawn(async move {
-        Viz::new(true).run(event_rx).await;
-    });
-
-    let graph_store = GraphStore::new();
-    let adder_ref = GraphAdder::spawn(GraphAdder::with_event_sender(graph_store, Some(event_tx)));
-
-    let node_generator_ref = NodeGenerator::spawn(NodeGenerator::new(NodeGeneratorConfig {
-        use_llm:                   config.use_llm,
-        default_concepts:          config.concepts,
-        default_learning_outcomes: config.learning_outcomes,
-    }));
+async fn run_command(command: Command) -> Result<(), DynError> {
+    match command {
+        Command::Run(config) if config.print_config => {
+            print!("{}", toml::to_string_pretty(&*config)?);
+            Ok(())
+        }
+        Command::Run(config) => run(*config).await,
+        Command::Verify(args) => verify_manifest(&args),
+        Command::Summary(args) => summarize_file(&args),
+        Command::Validate(args) => validate_file(&args),
+        Command::Diff(args) => diff_files(&args),
+        Command::Shell(args) => run_shell(args).await,
+        Command::Split(args) => split_graph(args).await,
+        Command::Help(help) => {
+            print!("{help}");
+            Ok(())
+        }
+        Command::Version => {
+            println!("weaver {}", env!("CARGO_PKG_VERSION"));
+            Ok(())
+        }
+    }
+}
 
-    let nodes = node_generator_ref
-        .ask(GenerateNodes {
-            concepts:          config.concepts,
-            learning_outcomes: config.learning_outcomes,
-        })
-        .await
-        .map_err(|err| -> DynError {
-            Box::new(CliError(format!("failed to generate nodes: {err}")))
+/// Run `mvp run` with logging and tracing installed.
+async fn run(config: RunConfig) -> Result<(), DynError> {
+    let log = LogSettings {
+        level:  config.log_level,
+        format: config.log_format,
+    };
+    let _trace =
+        weaver::trace::install(config.trace_file.as_deref(), log).map_err(|err| -> DynError {
+            Box::new(CliError::Io(format!("failed to create trace file: {err}")))
         })?;
-
-    let node_decisions = adder_ref
-        .ask(AddNodes(nodes))
-        .await
-        .map_err(|err| -> DynError { Box::new(CliError(format!("failed to add nodes: {err}"))) })?;
-
-    let accepted_nodes = node_decisions.iter().filter(|d| d.accepted).count();
-    let rejected_nodes = node_decisions.len() - accepted_nodes;
+    HUMAN_TO_STDERR.store(
+        config.output == OutputFormat::Json || !config.stdout_exports().is_empty(),
+        Ordering::Relaxed,
+    );
+    let span = info_span!("run", topic = %config.topic);
+    let status = run_mvp(config).instrument(span).await?;
+    drop(_trace);
+    if status == RunStatus::Interrupted {
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    }
+    Ok(())
+}
+
+fn parse_summary_args(args: &[String]) -> Result<Command, CliError> {
+    let mut args = args.iter().cloned().peekable();
+    let mut input = None;
+    let mut output = None;
+    let mut errors = ArgErrors::new(SUMMARY_FLAGS);
+    while let Some(flag) = args.next() {
+        let mut value = || args.next_if(|next| !next.starts_with("--"));
+        match flag.as_str() {
+            "--input" => input = required(&mut errors, &flag, value()).map(PathBuf::from),
+            "--output" => output = required(&mut errors, &flag, value()).map(PathBuf::from),
+            other => errors.push(format!("unknown flag '{other}'")),
+        }
+    }
+    if input.is_none() && errors.is_empty() {
+        errors.flag("--input", "missing --input");
+    }
+    errors.into_result(()).map(|()| {
+        Command::Summary(SummaryArgs {
+            input: input.unwrap_or_default(),
+            output,
+        })
+    })
+}
+
+fn parse_validate_args(args: &[String]) -> Result<Command, CliError> {
+    let mut args = args.iter().cloned().peekable();
+    let mut input = None;
+    let mut max_level = MAX_NODE_LEVEL;
+    let mut errors = ArgErrors::new(VALIDATE_FLAGS);
+    while let Some(flag) = args.next() {
+        let mut value = || args.next_if(|next| !next.starts_with("--"));
+        match flag.as_str() {
+            "--input" => input = required(&mut errors, &flag, value()).map(PathBuf::from),
+            "--max-level" => {
+                if let Some(level) = parse_level(&mut errors, &flag, value()) {
+                    max_level = level;
+                }
+            }
+            other => errors.push(format!("unknown flag '{other}'")),
+        }
+    }
+    if input.is_none() && errors.is_empty() {
+        errors.flag("--input", "missing --input");
+    }
+    errors.into_result(()).map(|()| {
+        Command::Validate(ValidateArgs {
+            input: input.unwrap_or_default(),
+            max_level,
+        })
+    })
+}
+
+fn parse_diff_args(args: &[String]) -> Result<Command, CliError> {
+    let mut args = args.iter().cloned().peekable();
+    let mut paths = Vec::new();
+    let mut output = OutputFormat::Text;
+    let mut errors = ArgErrors::new(DIFF_FLAGS);
+    while let Some(flag) = args.next() {
+        let mut value = || args.next_if(|next| !next.starts_with("--"));
+        match flag.as_str() {
+            "--output" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    match raw.as_str() {
+                        "text" => output = OutputFormat::Text,
+                        "json" => output = OutputFormat::Json,
+                        _ => errors.flag(&flag, format!("invalid output format '{raw}'")),
+                    }
+                }
+            }
+            other if other.starts_with("--") => errors.push(format!("unknown flag '{other}'")),
+            _ => paths.push(PathBuf::from(flag)),
+        }
+    }
+    if paths.len() != 2 && errors.is_empty() {
+        errors.push("usage: weaver diff OLD.json NEW.json [--output text|json]");
+    }
+    let mut paths = paths.into_iter();
+    errors.into_result(()).map(|()| {
+        Command::Diff(DiffArgs {
+            old: paths.next().unwrap_or_default(),
+            new: paths.next().unwrap_or_default(),
+            output,
+        })
+    })
+}
+
+fn parse_shell_args(args: &[String]) -> Result<Command, CliError> {
+    let mut args = args.iter().cloned().peekable();
+    let mut shell = ShellArgs {
+        input:        None,
+        namespace:    None,
+        viz:          VizTarget::Spawn,
+        viz_explicit: false,
+    };
+    let mut errors = ArgErrors::new(SHELL_FLAGS);
+    while let Some(flag) = args.next() {
+        let mut value = || args.next_if(|next| !next.starts_with("--"));
+        match flag.as_str() {
+            "--input" => shell.input = required(&mut errors, &flag, value()).map(PathBuf::from),
+            "--namespace" => shell.namespace = required(&mut errors, &flag, value()),
+            "--no-viz" => shell.viz = VizTarget::Off,
+            "--viz" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    match parse_viz_target(&raw) {
+                        Some(target) => {
+                            shell.viz = target;
+                            shell.viz_explicit = true;
+                        }
+                        None => errors.flag(&flag, format!("invalid viz target '{raw}'")),
+                    }
+                }
+            }
+            other => errors.push(format!("unknown flag '{other}'")),
+        }
+    }
+    errors.into_result(Command::Shell(shell))
+}
+
+fn parse_split_args(args: &[String]) -> Result<Command, CliError> {
+    let mut args = args.iter().cloned().peekable();
+    let (mut input, mut node, mut edges_to, mut output) = (None, None, None, None);
+    let mut into = Vec::new();
+    let mut errors = ArgErrors::new(SPLIT_FLAGS);
+    while let Some(flag) = args.next() {
+        let mut value = || args.next_if(|next| !next.starts_with("--"));
+        match flag.as_str() {
+            "--input" => input = required(&mut errors, &flag, value()).map(PathBuf::from),
+            "--node" => node = required(&mut errors, &flag, value()),
+            "--into" => into.extend(required(&mut errors, &flag, value())),
+            "--edges-to" => {
+                if let Some(raw) = required(&mut errors, &flag, value()) {
+                    match parse_split_edges(&raw) {
+                        Some(target) => edges_to = Some(target),
+                        None => errors.flag(&flag, format!("invalid edge target '{raw}'")),
+                    }
+                }
+            }
+            "--output" => output = required(&mut errors, &flag, value()).map(PathBuf::from),
+            other => errors.push(format!("unknown flag '{other}'")),
+        }
+    }
+    if errors.is_empty() {
+        if input.is_none() {
+            errors.flag("--input", "missing --input");
+        }
+        if node.is_none() {
+            errors.flag("--node", "missing --node");
+        }
+        if into.len() < 2 {
+            errors.flag("--into", "a split needs at least two --into texts");
+        }
+        if input.as_deref().is_some_and(is_dot_file) && output.is_none() {
+            errors.flag("--output", "a DOT --input needs --output");
+        }
+    }
+    errors.into_result(()).map(|()| {
+        Command::Split(SplitArgs {
+            input: input.unwrap_or_default(),
+            node: node.unwrap_or_default(),
+            into,
+            edges_to,
+            output,
+        })
+    })
+}
+
+/// `first`, `all`, or a replacement counted from 1.
+fn parse_split_edges(raw: &str) -> Option<SplitEdges> {
+    match raw {
+        "first" => Some(SplitEdges::First),
+        "all" => Some(SplitEdges::All),
+        _ => match raw.parse::<usize>() {
+            Ok(position) if position > 0 => Some(SplitEdges::Replacement(position - 1)),
+            _ => None,
+        },
+    }
+}
+
+fn parse_verify_args(args: &[String]) -> Result<Command, CliError> {
+    match args {
+        [manifest] if !manifest.starts_with("--") => Ok(Command::Verify(VerifyArgs {
+            manifest: PathBuf::from(manifest),
+        })),
+        _ => Err(CliError::Usage("usage: weaver run verify MANIFEST".to_string())),
+    }
+}
+
+/// Whether `input` names a DOT file rather than a JSON export.
+fn is_dot_file(input: &std::path::Path) -> bool {
+    input
+        .extension()
+        .is_some_and(|extension| extension == "dot" || extension == "gv")
+}
+
+fn read_snapshot(input: &std::path::Path) -> Result<GraphSnapshot, DynError> {
+    let contents = std::fs::read_to_string(input).map_err(|err| -> DynError {
+        Box::new(CliError::Io(format!("failed to read {}: {err}", input.display())))
+    })?;
+    if is_dot_file(input) {
+        return parse_dot(&contents).map_err(|errors| -> DynError {
+            let mut message = format!("{} is not a readable DOT graph:", input.display());
+            for error in errors {
+                message.push_str(&format!("\n  line {}: {}", error.line, error.message));
+            }
+            Box::new(CliError::Io(message))
+        });
+    }
+    serde_json::from_str(&contents).map_err(|err| -> DynError {
+        Box::new(CliError::Io(format!("{} is not a JSON graph export: {err}", input.display())))
+    })
+}
+
+/// Curate a graph by hand through the adder, with viewer events as in a run.
+async fn run_shell(args: ShellArgs) -> Result<(), DynError> {
+    let store = match &args.input {
+        Some(path) => read_store(path)?,
+        None => GraphStore::new(),
+    };
+    let (event_tx, event_rx) = mpsc::unbounded_channel();
+    let viz = Viz::new(args.viz);
+    if let Some(warning) = viz.status().warning()
+        && args.viz_explicit
+    {
+        eprintln!("warning: {warning}");
+    }
+    let viz_task = tokio::spawn(viz.run(event_rx));
+
+    let adder = GraphAdder::with_event_sender(store, Some(event_tx)).with_config(AdderConfig {
+        namespace: args.namespace.clone(),
+        ..AdderConfig::default()
+    });
+    let view = adder.read_handle();
+    let adder = GraphAdder::spawn(adder);
+    let stdin = tokio::io::stdin();
+    let mut shell = weaver::shell::Shell::new(adder.clone(), view)
+        .with_topic(args.namespace)
+        .with_prompt(std::io::stdin().is_terminal());
+    let result = shell
+        .run(tokio::io::BufReader::new(stdin), &mut std::io::stdout())
+        .await;
+
+    adder.stop_gracefully().await.ok();
+    adder.wait_for_shutdown().await;
+    viz_task.await.ok();
+    result.map_err(|err| -> DynError { Box::new(CliError::Io(format!("shell I/O failed: {err}"))) })
+}
+
+/// Parse `--import-nodes`, reporting every malformed row.
+fn read_imports(path: &std::path::Path) -> Result<Vec<ImportedNode>, DynError> {
+    let contents = std::fs::read_to_string(path).map_err(|err| -> DynError {
+        Box::new(CliError::Io(format!("failed to read {}: {err}", path.display())))
+    })?;
+    parse_node_csv(&contents).map_err(|problems| -> DynError {
+        let mut message = format!("{} has {} problem(s):", path.display(), problems.len());
+        for problem in problems {
+            message.push_str(&format!("\n  - {problem}"));
+        }
+        Box::new(CliError::Io(message))
+    })
+}
+
+/// Load an exported JSON graph into a store, rebuilding its indexes.
+/// Split one node of a saved graph and write the result back out.
+async fn split_graph(args: SplitArgs) -> Result<(), DynError> {
+    let store = read_store(&args.input)?;
+    let digits = args.node.replace('-', "");
+    let mut matches = store
+        .snapshot()
+        .nodes
+        .into_iter()
+        .filter(|node| node.id.simple().to_string().starts_with(&digits));
+    let node = match (matches.next(), matches.next()) {
+        (Some(node), None) => node,
+        (None, _) => {
+            return Err(Box::new(CliError::Usage(format!(
+                "no node id starts with '{}'",
+                args.node
+            ))));
+        }
+        (Some(_), Some(_)) => {
+            return Err(Box::new(CliError::Usage(format!(
+                "'{}' matches more than one node id",
+                args.node
+            ))));
+        }
+    };
+    let edge_count = store
+        .find_by_id(&node.id)
+        .map_or(0, |index| store.incident_edges(index).len());
+    let edges_to = match args.edges_to {
+        Some(edges_to) => edges_to,
+        None if edge_count > 0 && std::io::stdin().is_terminal() => {
+            ask_split_edges(&args.into, edge_count)?
+        }
+        None => SplitEdges::First,
+    };
+
+    let adder = GraphAdder::with_event_sender(store, None);
+    let view = adder.read_handle();
+    let adder = GraphAdder::spawn(adder);
+    let outcome = adder
+        .ask(SplitNode {
+            node_id: node.id,
+            into: args.into,
+            edges_to,
+        })
+        .await;
+    adder.stop_gracefully().await.ok();
+    adder.wait_for_shutdown().await;
+    let outcome = outcome.map_err(|err| -> DynError {
+        Box::new(CliError::Failed(format!("adder failed: {err}")))
+    })?;
+
+    if !outcome.decision.accepted {
+        print_rejections("replacement", &outcome.replacements);
+        let reason = outcome.decision.reason.unwrap_or_default();
+        return Err(Box::new(CliError::Invariant(format!(
+            "cannot split {}: {reason}",
+            short_id(&node.id)
+        ))));
+    }
+    let snapshot = view.load().snapshot.clone();
+    say!("Split {} \"{}\" into:", short_id(&node.id), node.text);
+    for id in &outcome.ids {
+        if let Some(replacement) = snapshot.nodes.iter().find(|node| node.id == *id) {
+            say!("  {} {}", short_id(id), replacement.text);
+        }
+    }
+    let moved = outcome.edges.iter().filter(|d| d.accepted).count();
+    say!(
+        "Edges re-attached: {moved} / {} (rejected {})",
+        outcome.edges.len(),
+        outcome.edges.len() - moved
+    );
+    print_rejections("edge", &outcome.edges);
+
+    let output = args.output.unwrap_or(args.input);
+    std::fs::write(&output, render_json(&snapshot)).map_err(|err| -> DynError {
+        Box::new(CliError::Io(format!("failed to write {}: {err}", output.display())))
+    })?;
+    say!("Graph written to {}", output.display());
+    Ok(())
+}
+
+/// Ask which replacement inherits the split node's `edge_count` edges.
+fn ask_split_edges(into: &[String], edge_count: usize) -> Result<SplitEdges, DynError> {
+    println!("The node has {edge_count} edge(s). Re-attach them to:");
+    for (position, text) in into.iter().enumerate() {
+        println!("  {}) {text}", position + 1);
+    }
+    loop {
+        print!("Replacement number, or 'all' [1]: ");
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer)? == 0 {
+            return Ok(SplitEdges::First);
+        }
+        match answer.trim() {
+            "" => return Ok(SplitEdges::First),
+            raw => match parse_split_edges(raw) {
+                Some(SplitEdges::Replacement(position)) if position >= into.len() => {}
+                Some(edges_to) => return Ok(edges_to),
+                None => {}
+            },
+        }
+        println!("Enter 1 to {} or 'all'.", into.len());
+    }
+}
+
+fn read_store(input: &std::path::Path) -> Result<GraphStore, DynError> {
+    let loaded = if is_dot_file(input) {
+        GraphStore::from_snapshot(read_snapshot(input)?).map_err(LoadError::Invalid)
+    } else {
+        GraphStore::load(input)
+    };
+    loaded.map_err(|err| -> DynError {
+        Box::new(match err {
+            LoadError::Io(err) => {
+                CliError::Io(format!("failed to read {}: {err}", input.display()))
+            }
+            LoadError::Format(err) => {
+                CliError::Io(format!("{} is not a JSON graph export: {err}", input.display()))
+            }
+            LoadError::Invalid(problems) => {
+                let mut message = format!("{} has {} problem(s):", input.display(), problems.len());
+                for problem in problems {
+                    message.push_str(&format!("\n  - {problem}"));
+                }
+                CliError::Invariant(message)
+            }
+        })
+    })
+}
+
+/// Re-check an exported JSON graph against the adder's rules.
+fn validate_file(args: &ValidateArgs) -> Result<(), DynError> {
+    let ValidateArgs { input, max_level } = args;
+    let max_level = *max_level;
+    let snapshot = read_snapshot(input)?;
+    let (nodes, edges) = (snapshot.nodes.len(), snapshot.edges.len());
+    let violations = validate_snapshot(
+        &snapshot,
+        RationalePolicy::Off,
+        &NodeRules {
+            max_level,
+            ..NodeRules::default()
+        },
+    );
+    for violation in &violations {
+        say!("VIOLATION {violation}");
+    }
+    if !violations.is_empty() {
+        return Err(Box::new(CliError::Invariant(format!(
+            "{} violation(s) in {}",
+            violations.len(),
+            input.display()
+        ))));
+    }
+    say!("{}: {nodes} nodes and {edges} edges pass validation", input.display());
+    Ok(())
+}
+
+/// Compare two JSON graph exports and print what changed.
+fn diff_files(args: &DiffArgs) -> Result<(), DynError> {
+    let DiffArgs { old, new, output } = args;
+    let diff = diff_graphs(&read_snapshot(old)?, &read_snapshot(new)?);
+    if *output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+    if diff.is_empty() {
+        println!("No differences between {} and {}", old.display(), new.display());
+        return Ok(());
+    }
+
+    let describe = |node: &DiffNode| format!("{:?} L{}: {}", node.kind, node.level, node.text);
+    println!(
+        "Nodes: {} added, {} removed, {} changed",
+        diff.added_nodes.len(),
+        diff.removed_nodes.len(),
+        diff.changed_nodes.len()
+    );
+    for node in &diff.added_nodes {
+        println!("  + {}", describe(node));
+    }
+    for node in &diff.removed_nodes {
+        println!("  - {}", describe(node));
+    }
+    for change in &diff.changed_nodes {
+        let (old, new) = (&change.old, &change.new);
+        let fields: Vec<String> = change
+            .fields
+            .iter()
+            .map(|field| match field.as_str() {
+                "kind" => format!("kind {:?} -> {:?}", old.kind, new.kind),
+                "level" => format!("level {} -> {}", old.level, new.level),
+                "tags" => format!("tags [{}] -> [{}]", old.tags.join(", "), new.tags.join(", ")),
+                _ => format!("text was '{}'", old.text),
+            })
+            .collect();
+        println!("  ~ {}: {}", new.text, fields.join("; "));
+    }
     println!(
-        "Nodes accepted: {} / {} (rejected {})",
-        accepted_nodes,
-        node_decisions.len(),
-        rejected_nodes
+        "Edges:{}",
+        if diff.edges.is_empty() {
+            " unchanged"
+        } else {
+            ""
+        }
     );
+    for group in &diff.edges {
+        println!(
+            "  {:?}: {} added, {} removed",
+            group.relation,
+            group.added.len(),
+            group.removed.len()
+        );
+        for edge in &group.added {
+            println!("    + {} -> {}", edge.from, edge.to);
+        }
+        for edge in &group.removed {
+            println!("    - {} -> {}", edge.from, edge.to);
+        }
+    }
+    Ok(())
+}
+
+/// Recompute and print summary statistics for an exported JSON graph.
+fn summarize_file(args: &SummaryArgs) -> Result<(), DynError> {
+    let SummaryArgs { input, output } = args;
+    let store = read_store(input)?;
+    let summary = Summary::from_store(&store);
+    print_summary(&RunConfig::default(), &summary);
+    if let Some(path) = output {
+        std::fs::write(path, serde_json::to_string_pretty(&summary)?)?;
+        say!("Summary written to {}", path.display());
+    }
+    Ok(())
+}
 
-    let inventory = adder_ref.ask(Inventory).await.map_err(|err| -> DynError {
-        Box::new(CliError(format!("failed to fetch inventory: {err}")))
+/// Re-hash the artifacts recorded in a manifest and report drift.
+fn verify_manifest(args: &VerifyArgs) -> Result<(), DynError> {
+    let path = &args.manifest;
+    let manifest = RunManifest::read(path).map_err(|err| -> DynError {
+        Box::new(CliError::Io(format!("failed to read manifest {}: {err}", path.display())))
     })?;
 
+    let mut drifted = 0;
+    for (artifact, status) in manifest.verify() {
+        match status {
+            ArtifactStatus::Intact => say!("OK       {}", artifact.path.display()),
+            ArtifactStatus::Modified { actual } => {
+                drifted += 1;
+                say!(
+                    "MODIFIED {} (expected {}, found {actual})",
+                    artifact.path.display(),
+                    artifact.sha256
+                );
+            }
+            ArtifactStatus::Unreadable(err) => {
+                drifted += 1;
+                say!("MISSING  {} ({err})", artifact.path.display());
+            }
+        }
+    }
+
+    if drifted > 0 {
+        return Err(Box::new(CliError::Failed(format!(
+            "{drifted} of {} artifact(s) drifted",
+            manifest.artifacts.len()
+        ))));
+    }
+    say!("All {} artifact(s) match the manifest", manifest.artifacts.len());
+    Ok(())
+}
+
+/// Format of the end-of-run report on stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Accepted proposals in one `--rounds` iteration.
+#[derive(Debug, Clone, Copy, Default)]
+struct RoundReport {
+    nodes_accepted: usize,
+    nodes_rejected: usize,
+    edges_accepted: usize,
+    edges_rejected: usize,
+}
+
+/// Concepts, outcomes and edges still missing from the configured targets.
+fn shortfall(config: &RunConfig, snapshot: &GraphSnapshot) -> (usize, usize, usize) {
+    let concepts = snapshot
+        .nodes
+        .iter()
+        .filter(|node| matches!(node.kind, NodeKind::Concept))
+        .count();
+    let outcomes = snapshot.nodes.len() - concepts;
+    (
+        config.concepts.saturating_sub(concepts),
+        config.learning_outcomes.saturating_sub(outcomes),
+        config.target_edges.saturating_sub(snapshot.edges.len()),
+    )
+}
+
+/// Fraction of the remaining budget node generation may spend, leaving the
+/// rest for edge generation.
+const NODE_GENERATION_SHARE: f64 = 0.5;
+
+/// Overall `--deadline` budget, measured from the start of the run.
+///
+/// Only generation phases are time-boxed; adding, summarizing and exporting
+/// always run so a partial graph is still reported.
+#[derive(Debug, Clone, Copy)]
+struct RunDeadline {
+    started: Instant,
+    budget:  Option<Duration>,
+}
+
+impl RunDeadline {
+    fn new(budget: Option<Duration>) -> Self {
+        Self {
+            started: Instant::now(),
+            budget,
+        }
+    }
+
+    fn remaining(&self) -> Option<Duration> {
+        self.budget
+            .map(|budget| budget.saturating_sub(self.started.elapsed()))
+    }
+
+    /// Timeout for a phase allowed `fraction` of the remaining budget.
+    fn share(&self, fraction: f64) -> Option<Duration> {
+        self.remaining()
+            .map(|remaining| remaining.mul_f64(fraction))
+    }
+
+    fn exceeded(&self) -> bool {
+        self.remaining()
+            .is_some_and(|remaining| remaining.is_zero())
+    }
+}
+
+/// Announces run phases on the event stream and records their durations.
+struct PhaseClock {
+    events:  UnboundedSender<Event>,
+    started: Option<Instant>,
+    timings: Vec<PhaseTiming>,
+    span:    Span,
+}
+
+impl PhaseClock {
+    fn new(events: UnboundedSender<Event>) -> Self {
+        Self {
+            events,
+            started: None,
+            timings: Vec::new(),
+            span: Span::none(),
+        }
+    }
+
+    /// Span of the phase in progress, for work done on its behalf.
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+
+    fn start(&mut self, phase: Phase, expected: usize) {
+        self.started = Some(Instant::now());
+        self.span = info_span!("phase", name = %phase);
+        let _ = self.events.send(Event::PhaseStarted { phase, expected });
+    }
+
+    /// Time a step that is not a viewer phase, such as exporting.
+    fn record(&mut self, label: &str, started: Instant) {
+        self.timings.push(PhaseTiming {
+            phase:      label.to_string(),
+            elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+        });
+    }
+
+    fn finish(&mut self, phase: Phase, detail: String) {
+        if let Some(started) = self.started.take() {
+            self.timings.push(PhaseTiming {
+                phase:      phase.to_string(),
+                elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+            });
+        }
+        self.span = Span::none();
+        let _ = self.events.send(Event::PhaseFinished { phase, detail });
+    }
+}
+
+/// Exit status after Ctrl-C, matching the shell convention for SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// How a run ended when it did not fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunStatus {
+    Completed,
+    Interrupted,
+}
+
+/// Ctrl-C state shared with the signal task.
+///
+/// Adder phases check it only before they start, so a batch the adder has
+/// begun is always finished. Generator phases are abandoned as soon as it
+/// is set; generators hold no graph state.
+#[derive(Default)]
+struct Interrupt {
+    requested: AtomicBool,
+    notify:    tokio::sync::Notify,
+}
+
+impl Interrupt {
+    fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Resolve once Ctrl-C has been pressed.
+    async fn requested(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_requested() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Listen for Ctrl-C: the first sets the returned interrupt, the second
+/// exits immediately.
+fn watch_interrupts() -> Arc<Interrupt> {
+    let interrupt = Arc::new(Interrupt::default());
+    let shared = Arc::clone(&interrupt);
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        shared.requested.store(true, Ordering::SeqCst);
+        shared.notify.notify_waiters();
+        eprintln!(
+            "Interrupted: stopping generation and exporting the partial graph (Ctrl-C again to \
+             quit now)"
+        );
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+    });
+    interrupt
+}
+
+/// Where a run's proposals are decided.
+///
+/// Under `--dry-run` a [`DryRun`] stands in for the adder: the same rules
+/// decide, but no events are emitted and no graph store is touched.
+enum Target {
+    Adder {
+        adder: ActorRef<GraphAdder>,
+        view:  ReadHandle,
+    },
+    DryRun(Box<DryRun>),
+}
+
+impl Target {
+    async fn add_nodes(
+        &mut self,
+        proposals: Vec<NodeProposal>,
+        span: Span,
+    ) -> Result<Vec<Decision>, DynError> {
+        match self {
+            Target::Adder { adder, .. } => adder
+                .ask(AddNodes(proposals))
+                .into_future()
+                .instrument(span)
+                .await
+                .map_err(|err| -> DynError {
+                    Box::new(CliError::Failed(format!("failed to add nodes: {err}")))
+                }),
+            Target::DryRun(dry_run) => Ok(dry_run.add_nodes(proposals)),
+        }
+    }
+
+    async fn add_edges(
+        &mut self,
+        proposals: Vec<EdgeProposal>,
+        span: Span,
+    ) -> Result<Vec<Decision>, DynError> {
+        match self {
+            Target::Adder { adder, .. } => adder
+                .ask(AddEdges(proposals))
+                .into_future()
+                .instrument(span)
+                .await
+                .map_err(|err| -> DynError {
+                    Box::new(CliError::Failed(format!("failed to add edges: {err}")))
+                }),
+            Target::DryRun(dry_run) => Ok(dry_run.add_edges(proposals)),
+        }
+    }
+
+    fn snapshot(&self) -> GraphSnapshot {
+        match self {
+            Target::Adder { view, .. } => view.load().snapshot.clone(),
+            Target::DryRun(dry_run) => dry_run.snapshot(),
+        }
+    }
+
+    fn inventory(&self) -> Vec<InventoryEntry> {
+        match self {
+            Target::Adder { view, .. } => view.load().inventory.clone(),
+            Target::DryRun(dry_run) => dry_run.inventory(),
+        }
+    }
+
+    async fn summary(&self) -> Result<Summary, DynError> {
+        match self {
+            Target::Adder { adder, .. } => adder.ask(Summarize).await.map_err(|err| -> DynError {
+                Box::new(CliError::Failed(format!("failed to compute summary: {err}")))
+            }),
+            Target::DryRun(dry_run) => Ok(dry_run.summary()),
+        }
+    }
+
+    async fn rejection_stats(&mut self) -> Result<RejectionReport, DynError> {
+        match self {
+            Target::Adder { adder, .. } => adder
+                .ask(RejectionStats { reset: false })
+                .await
+                .map_err(|err| -> DynError {
+                    Box::new(CliError::Failed(format!("failed to collect rejections: {err}")))
+                }),
+            Target::DryRun(dry_run) => Ok(dry_run.rejection_stats(false)),
+        }
+    }
+
+    async fn teaching_order(&self) -> Result<Result<Vec<Uuid>, CycleInfo>, DynError> {
+        match self {
+            Target::Adder { adder, .. } => match adder.ask(TopoOrder).await {
+                Ok(order) => Ok(Ok(order)),
+                Err(SendError::HandlerError(cycle)) => Ok(Err(cycle)),
+                Err(err) => Err(Box::new(CliError::Failed(format!(
+                    "failed to compute teaching order: {err}"
+                )))),
+            },
+            Target::DryRun(dry_run) => GraphStore::from_snapshot(dry_run.snapshot())
+                .map(|store| store.prerequisite_topo_order())
+                .map_err(|problems| -> DynError {
+                    Box::new(CliError::Invariant(format!(
+                        "dry-run graph has {} problem(s)",
+                        problems.len()
+                    )))
+                }),
+        }
+    }
+
+    async fn shutdown(self) {
+        if let Target::Adder { adder, .. } = self {
+            adder.stop_gracefully().await.ok();
+            adder.wait_for_shutdown().await;
+        }
+    }
+}
+
+async fn run_mvp(config: RunConfig) -> Result<RunStatus, DynError> {
+    info!(topic = %config.topic, use_llm = config.use_llm, "starting run");
+    let config_hash = config.semantic_hash()?;
+    if let Some(path) = config.if_absent.as_ref().filter(|_| !config.dry_run) {
+        match StoredRun::reusable(path, &config_hash) {
+            Ok(stored) => return reuse_stored_run(&config, stored).await,
+            Err(reason) if path.exists() => {
+                say!("Regenerating {}: {reason}", path.display());
+            }
+            Err(_) => {}
+        }
+    }
+
+    let imported = match &config.import_nodes {
+        Some(path) => read_imports(path)?,
+        None => Vec::new(),
+    };
+
+    let (event_tx, event_rx) = mpsc::unbounded_channel();
+    let (viz_tx, viz_rx) = mpsc::unbounded_channel();
+    let viz = Viz::new(if config.dry_run {
+        VizTarget::Off
+    } else {
+        config.viz.clone()
+    });
+    let viz_status = viz.status().clone();
+    if let Some(warning) = viz_status.warning() {
+        // The default viewer is best-effort; only a requested one is worth a
+        // warning.
+        if config.viz_explicit {
+            eprintln!("warning: {warning}");
+        } else {
+            info!(error = viz_status.error.as_deref(), "{warning}");
+        }
+        if config.viz_required {
+            let detail = viz_status.error.as_deref().unwrap_or("unknown error");
+            return Err(Box::new(CliError::Failed(format!(
+                "--viz-required: visualization unavailable ({detail})"
+            ))));
+        }
+    }
+    let viz_task = tokio::spawn(viz.run(viz_rx));
+    let mut sinks = vec![viz_tx];
+    let mut progress_task = None;
+    if config.progress && std::io::stdout().is_terminal() {
+        let (progress_tx, progress_rx) = mpsc::unbounded_channel();
+        sinks.push(progress_tx);
+        progress_task = Some(tokio::spawn(ProgressRenderer::terminal().run(progress_rx)));
+    }
+    let fan_out_task = tokio::spawn(fan_out(event_rx, sinks));
+    let interrupt = watch_interrupts();
+    let stop_requested = |before: Phase| {
+        let stop = interrupt.is_requested();
+        if stop {
+            say!("Interrupted: skipping {before} and later phases");
+        }
+        stop
+    };
+    let mut phases = PhaseClock::new(event_tx.clone());
+    let latency_log = config.timings.then(LatencyLog::default);
+    let deadline = RunDeadline::new(config.deadline_secs.map(Duration::from_secs));
+
+    let adder_config = AdderConfig {
+        rationale_policy:       config.rationale_policy,
+        max_nodes:              config.max_nodes,
+        max_edges:              config.max_edges,
+        namespace:              Some(config.namespace().to_string()),
+        id_seed:                config.seed,
+        node_rules:             config.node_rules(),
+        require_edge_rationale: true,
+        rationale_rules:        config.rationale_rules(),
+        atomic_threshold:       None,
+        similarity_threshold:   config.similarity_threshold,
+        undo_depth:             0,
+        min_supports:           config.min_supports,
+    };
+    // `mvp resume` starts from the saved graph, whose texts and ids are
+    // indexed again so duplicates of it are rejected.
+    let store = match &config.resume {
+        Some(path) => read_store(path)?,
+        None => GraphStore::new(),
+    };
+    let existing = (store.node_count(), store.edge_count());
+    let mut target = if config.dry_run {
+        Target::DryRun(Box::new(DryRun::new(adder_config)))
+    } else {
+        let mut adder =
+            GraphAdder::with_event_sender(store, Some(event_tx.clone())).with_config(adder_config);
+        if let Some(path) = &config.audit_log {
+            let log = AuditLog::open(path).map_err(|err| -> DynError {
+                Box::new(CliError::Io(format!(
+                    "failed to open audit log {}: {err}",
+                    path.display()
+                )))
+            })?;
+            adder = adder.with_audit_log(log);
+        }
+        let view = adder.read_handle();
+        Target::Adder {
+            adder: GraphAdder::spawn(adder),
+            view,
+        }
+    };
+
+    let node_generator_ref = NodeGenerator::spawn(NodeGenerator::new(NodeGeneratorConfig {
+        use_llm: config.use_llm,
+        default_concepts: config.concepts,
+        default_learning_outcomes: config.learning_outcomes,
+        ensemble: config.node_models.clone(),
+        strict_schema: config.strict_schema,
+        seed: config.seed,
+        node_rules: config.node_rules(),
+        latency_log: latency_log.clone(),
+    }));
     let edge_generator_ref = EdgeGenerator::spawn(EdgeGenerator::new(EdgeGeneratorConfig {
         use_llm:              config.use_llm,
         default_target_edges: config.target_edges,
+        strict_schema:        config.strict_schema,
+        seed:                 config.seed,
+        latency_log:          latency_log.clone(),
     }));
 
-    let edges = edge_generator_ref
-        .ask(GenerateEdges {
-            inventory:    inventory.clone(),
-            target_edges: config.target_edges,
-        })
-        .await
-        .map_err(|err| -> DynError {
-            Box::new(CliError(format!("failed to generate edges: {err}")))
-        })?;
+    let mut report = RunReport::new(&config.topic);
+    let mut rounds: Vec<RoundReport> = Vec::new();
+    let mut node_rejections = Vec::new();
+    let mut edge_rejections = Vec::new();
+    let mut rationale_warnings = Vec::new();
+    let mut token_usage = TokenUsage::default();
+    let mut deadline_exceeded = false;
 
-    let edge_decisions = adder_ref
-        .ask(AddEdges(edges))
-        .await
-        .map_err(|err| -> DynError { Box::new(CliError(format!("failed to add edges: {err}"))) })?;
+    // Imported nodes count toward --concepts and --los, so generators only
+    // fill what the file leaves missing and edges can reach them.
+    let mut import_rejections = Vec::new();
+    if !imported.is_empty() {
+        let (lines, proposals): (Vec<usize>, Vec<NodeProposal>) = imported
+            .into_iter()
+            .map(|node| (node.line, node.proposal))
+            .unzip();
+        let decisions = target.add_nodes(proposals, Span::current()).await?;
+        let accepted = decisions.iter().filter(|d| d.accepted).count();
+        say!("Imported nodes: {accepted} / {} accepted", decisions.len());
+        import_rejections = lines
+            .into_iter()
+            .zip(&decisions)
+            .filter(|(_, decision)| !decision.accepted)
+            .map(|(line, decision)| {
+                format!("line {line}: {}", decision.reason.as_deref().unwrap_or("rejected"))
+            })
+            .collect();
+        report.record_nodes(&decisions);
+    }
+    let seeded = config.resume.is_some() || config.import_nodes.is_some();
 
-    let accepted_edges = edge_decisions.iter().filter(|d| d.accepted).count();
-    let rejected_edges = edge_decisions.len() - accepted_edges;
-    println!(
-        "Edges accepted: {} / {} (rejected {})",
-        accepted_edges,
-        edge_decisions.len(),
-        rejected_edges
-    );
+    for round in 0..config.rounds {
+        let (needed_concepts, needed_outcomes, needed_edges) =
+            shortfall(&config, &target.snapshot());
+        if needed_concepts + needed_outcomes + needed_edges == 0 || deadline_exceeded {
+            break;
+        }
+        if needed_concepts + needed_outcomes > 0 && stop_requested(Phase::GeneratingNodes) {
+            break;
+        }
+        if config.rounds > 1 {
+            say!("Round {}:", round + 1);
+        }
+        // Follow-up rounds and seeded graphs see what is already in the
+        // graph and why the previous round's proposals were turned down.
+        let feedback = |target: &Target, rejections: &Vec<(String, usize)>| {
+            (round > 0 || seeded).then(|| RoundFeedback {
+                inventory:  target.inventory(),
+                edges:      target
+                    .snapshot()
+                    .edges
+                    .iter()
+                    .map(|edge| (edge.from, edge.to, edge.relation.clone()))
+                    .collect(),
+                rejections: rejections.clone(),
+            })
+        };
+        let mut round_report = RoundReport::default();
 
-    let summary = adder_ref.ask(Summarize).await.map_err(|err| -> DynError {
-        Box::new(CliError(format!("failed to compute summary: {err}")))
-    })?;
+        if needed_concepts + needed_outcomes > 0 {
+            phases.start(Phase::GeneratingNodes, needed_concepts + needed_outcomes);
+            // Each topic asks for its share of the shortfall, one after
+            // another, and the batches go to the adder together so duplicates
+            // across topics are caught in one namespace.
+            let topics = config.all_topics();
+            let multi_topic = topics.len() > 1;
+            let concept_shares = split_evenly(needed_concepts, topics.len());
+            let outcome_shares = split_evenly(needed_outcomes, topics.len());
+            let timeout = deadline
+                .share(NODE_GENERATION_SHARE)
+                .map(|timeout| timeout / topics.len() as u32);
+            let mut node_batch = NodeBatch {
+                proposals: Vec::new(),
+                metadata:  GenerationMetadata::default(),
+            };
+            let mut interrupted = false;
+            for (index, topic) in topics.iter().enumerate() {
+                let (concepts, learning_outcomes) = (concept_shares[index], outcome_shares[index]);
+                if concepts + learning_outcomes == 0 {
+                    continue;
+                }
+                let request = node_generator_ref
+                    .ask(GenerateNodes {
+                        concepts,
+                        learning_outcomes,
+                        timeout,
+                        span: phases.span(),
+                        feedback: feedback(&target, &node_rejections),
+                        topic: multi_topic.then(|| topic.to_string()),
+                    })
+                    .into_future()
+                    .instrument(phases.span());
+                tokio::select! {
+                    reply = request => node_batch.merge(reply.map_err(|err| -> DynError {
+                        Box::new(CliError::Backend(format!("failed to generate nodes: {err}")))
+                    })?),
+                    () = interrupt.requested() => {
+                        interrupted = true;
+                        break;
+                    }
+                }
+            }
+            if interrupted {
+                phases.finish(Phase::GeneratingNodes, "interrupted".to_string());
+                break;
+            }
 
-    println!(
+            let node_requests: usize = node_batch
+                .metadata
+                .contributions
+                .iter()
+                .map(|contribution| contribution.requests)
+                .sum();
+            phases.finish(
+                Phase::GeneratingNodes,
+                format!("{} proposals, {node_requests} LLM requests", node_batch.proposals.len()),
+            );
+            deadline_exceeded |= node_batch.metadata.deadline_exceeded;
+
+            for contribution in &node_batch.metadata.contributions {
+                token_usage.add(contribution.usage);
+                match &contribution.error {
+                    None => say!(
+                        "Node model {}: {} nodes ({} duplicates, {} prompt + {} completion tokens)",
+                        contribution.model,
+                        contribution.contributed,
+                        contribution.duplicates,
+                        contribution.usage.prompt_tokens,
+                        contribution.usage.completion_tokens
+                    ),
+                    Some(err) => say!("Node model {}: failed ({err})", contribution.model),
+                }
+            }
+
+            phases.start(Phase::AddingNodes, node_batch.proposals.len());
+            let node_decisions = target
+                .add_nodes(node_batch.proposals, phases.span())
+                .await?;
+
+            round_report.nodes_accepted = node_decisions.iter().filter(|d| d.accepted).count();
+            round_report.nodes_rejected = node_decisions.len() - round_report.nodes_accepted;
+            phases.finish(
+                Phase::AddingNodes,
+                format!(
+                    "{} accepted, {} rejected",
+                    round_report.nodes_accepted, round_report.nodes_rejected
+                ),
+            );
+            say!(
+                "Nodes accepted: {} / {} (rejected {})",
+                round_report.nodes_accepted,
+                node_decisions.len(),
+                round_report.nodes_rejected
+            );
+            if config.explain {
+                print_rejections("node", &node_decisions);
+            }
+            node_rejections = RoundFeedback::digest(&node_decisions);
+            report.record_nodes(&node_decisions);
+        }
+
+        let inventory = target.inventory();
+        if needed_edges > 0 && !inventory.is_empty() {
+            if stop_requested(Phase::GeneratingEdges) {
+                rounds.push(round_report);
+                break;
+            }
+            phases.start(Phase::GeneratingEdges, needed_edges);
+            let request = edge_generator_ref
+                .ask(GenerateEdges {
+                    inventory,
+                    target_edges: needed_edges,
+                    timeout: deadline.share(1.0),
+                    span: phases.span(),
+                    feedback: feedback(&target, &edge_rejections),
+                })
+                .into_future()
+                .instrument(phases.span());
+            let edge_batch = tokio::select! {
+                reply = request => reply.map_err(|err| -> DynError {
+                    Box::new(CliError::Backend(format!("failed to generate edges: {err}")))
+                })?,
+                () = interrupt.requested() => {
+                    phases.finish(Phase::GeneratingEdges, "interrupted".to_string());
+                    rounds.push(round_report);
+                    break;
+                }
+            };
+
+            let edges = edge_batch.proposals;
+            phases.finish(Phase::GeneratingEdges, format!("{} proposals", edges.len()));
+            deadline_exceeded |= deadline.exceeded();
+
+            phases.start(Phase::AddingEdges, edges.len());
+            let edge_decisions = target.add_edges(edges, phases.span()).await?;
+
+            round_report.edges_accepted = edge_decisions.iter().filter(|d| d.accepted).count();
+            round_report.edges_rejected = edge_decisions.len() - round_report.edges_accepted;
+            phases.finish(
+                Phase::AddingEdges,
+                format!(
+                    "{} accepted, {} rejected",
+                    round_report.edges_accepted, round_report.edges_rejected
+                ),
+            );
+            say!(
+                "Edges accepted: {} / {} (rejected {})",
+                round_report.edges_accepted,
+                edge_decisions.len(),
+                round_report.edges_rejected
+            );
+            if config.explain {
+                print_rejections("edge", &edge_decisions);
+            }
+            rationale_warnings.extend(
+                edge_decisions
+                    .iter()
+                    .flat_map(|decision| decision.warnings.iter().cloned()),
+            );
+            edge_rejections = RoundFeedback::digest(&edge_decisions);
+            report.record_edges(&edge_decisions);
+        }
+
+        rounds.push(round_report);
+        // Another round would see the same graph and the same feedback.
+        if round_report.nodes_accepted + round_report.edges_accepted == 0 {
+            break;
+        }
+    }
+    if deadline_exceeded {
+        say!("Deadline exceeded: generation was cut short and results are partial");
+    }
+    let was_interrupted = interrupt.is_requested();
+
+    if !rationale_warnings.is_empty() {
+        say!("Rationale warnings: {}", rationale_warnings.len());
+        for warning in rationale_warnings {
+            say!("  {warning}");
+        }
+    }
+
+    let summarizing = Instant::now();
+    let summary = target.summary().await?;
+    report.rejections = target.rejection_stats().await?;
+    phases.record("Summarizing", summarizing);
+
+    print_summary(&config, &summary);
+    print_rejection_table(&report.rejections);
+    if config.teaching_order {
+        print_teaching_order(&target.snapshot(), target.teaching_order().await?);
+    }
+    if config.resume.is_some() {
+        let counts = ResumeCounts {
+            existing_nodes: existing.0,
+            existing_edges: existing.1,
+            added_nodes:    summary.total_nodes.saturating_sub(existing.0),
+            added_edges:    summary.total_edges.saturating_sub(existing.1),
+        };
+        say!(
+            "Resumed graph: {} nodes and {} edges already present, {} nodes and {} edges added",
+            counts.existing_nodes,
+            counts.existing_edges,
+            counts.added_nodes,
+            counts.added_edges
+        );
+        report.resumed = Some(counts);
+    }
+    if !import_rejections.is_empty() {
+        say!("Rejected imports ({}):", import_rejections.len());
+        for rejection in &import_rejections {
+            say!("  {rejection}");
+        }
+    }
+    if config.rounds > 1 {
+        say!("Rounds:");
+        for (round, report) in rounds.iter().enumerate() {
+            say!(
+                "  {}: nodes {} accepted / {} rejected, edges {} accepted / {} rejected",
+                round + 1,
+                report.nodes_accepted,
+                report.nodes_rejected,
+                report.edges_accepted,
+                report.edges_rejected
+            );
+        }
+    }
+
+    let mut failed_exports = 0;
+    if config.dry_run {
+        say!("Dry run: nothing was added, exported, or stored");
+    } else {
+        let snapshot = target.snapshot();
+        let exporting = Instant::now();
+        let (artifacts, export_failures) =
+            write_artifacts(snapshot.clone(), export_requests(&config)).await;
+        phases.record("Exporting", exporting);
+        failed_exports += export_failures;
+
+        if config.manifest.is_some() || config.if_absent.is_some() {
+            let mut generators = Vec::new();
+            for generator in snapshot
+                .nodes
+                .iter()
+                .filter_map(|node| node.generator.as_ref())
+                .chain(
+                    snapshot
+                        .edges
+                        .iter()
+                        .filter_map(|edge| edge.generator.as_ref()),
+                )
+            {
+                if !generators.contains(generator) {
+                    generators.push(generator.clone());
+                }
+            }
+            let manifest = RunManifest {
+                version: MANIFEST_VERSION,
+                crate_version: env!("CARGO_PKG_VERSION").to_string(),
+                git_describe: git_describe(),
+                config: serde_json::to_value(&config)?,
+                config_hash: Some(config_hash),
+                generators,
+                seed: config.seed,
+                token_usage,
+                phases: phases.timings.clone(),
+                deadline_exceeded,
+                viz: Some(viz_status.clone()),
+                artifacts,
+                summary: summary.clone(),
+            };
+            if let Some(path) = &config.manifest {
+                match manifest.write(path) {
+                    Ok(()) => say!("Manifest written to {}", path.display()),
+                    Err(err) => {
+                        eprintln!("failed to write manifest to {}: {err}", path.display());
+                        failed_exports += 1;
+                    }
+                }
+            }
+            // A partial graph must not be reused as if the run had finished.
+            if let Some(path) = config.if_absent.as_ref().filter(|_| !was_interrupted) {
+                let stored = StoredRun {
+                    manifest,
+                    graph: snapshot,
+                };
+                if let Err(err) = stored.write(path) {
+                    eprintln!("failed to store run at {}: {err}", path.display());
+                    failed_exports += 1;
+                }
+            }
+        }
+    }
+
+    if was_interrupted {
+        // An abandoned request may still be running inside a generator.
+        edge_generator_ref.kill();
+        node_generator_ref.kill();
+    } else {
+        edge_generator_ref.stop_gracefully().await.ok();
+        node_generator_ref.stop_gracefully().await.ok();
+    }
+    target.shutdown().await;
+    if config.timings {
+        let latencies = latency_log.map(|log| log.entries()).unwrap_or_default();
+        report.timings = Some(Timings::new(&phases.timings, &latencies));
+    }
+    // Every event sender is gone once the clock is dropped, so the viewer and
+    // progress tasks drain what is queued and finish.
+    drop(phases);
+    drop(event_tx);
+    fan_out_task.await.ok();
+    viz_task.await.ok();
+    if let Some(task) = progress_task {
+        task.await.ok();
+    }
+
+    report.deadline_exceeded = deadline_exceeded;
+    report.interrupted = was_interrupted;
+    report.dry_run = config.dry_run;
+    report.summary = summary;
+    print_report(&config, &report)?;
+    if failed_exports > 0 {
+        return Err(Box::new(CliError::Io(format!("{failed_exports} export(s) failed"))));
+    }
+    check_strict(&config, &report.summary)?;
+
+    Ok(if was_interrupted {
+        RunStatus::Interrupted
+    } else {
+        RunStatus::Completed
+    })
+}
+
+/// Skip generation: reprint the stored summary and rewrite requested exports.
+async fn reuse_stored_run(config: &RunConfig, stored: StoredRun) -> Result<RunStatus, DynError> {
+    if let Some(path) = &config.if_absent {
+        say!("Reusing {}: configuration unchanged", path.display());
+    }
+    print_summary(config, &stored.manifest.summary);
+
+    let (_, failed_exports) = write_artifacts(stored.graph, export_requests(config)).await;
+    let mut report = RunReport::new(&config.topic);
+    report.reused = true;
+    report.deadline_exceeded = stored.manifest.deadline_exceeded;
+    report.summary = stored.manifest.summary;
+    print_report(config, &report)?;
+    if failed_exports > 0 {
+        return Err(Box::new(CliError::Io(format!("{failed_exports} export(s) failed"))));
+    }
+    check_strict(config, &report.summary)?;
+    Ok(RunStatus::Completed)
+}
+
+/// Under `--strict`, fail when the graph's prerequisites contain a cycle.
+fn check_strict(config: &RunConfig, summary: &Summary) -> Result<(), CliError> {
+    if config.strict && !summary.prerequisite_dag_ok {
+        return Err(CliError::Invariant("--strict: prerequisite edges form a cycle".to_string()));
+    }
+    Ok(())
+}
+
+/// Print the JSON report to stdout under `--output json`.
+fn print_report(config: &RunConfig, report: &RunReport) -> Result<(), DynError> {
+    if config.output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(report)?);
+    } else if let Some(timings) = &report.timings {
+        print_timings(timings);
+    }
+    Ok(())
+}
+
+fn print_timings(timings: &Timings) {
+    say!("Timings:");
+    let width = timings
+        .phases
+        .iter()
+        .map(|timing| timing.phase.len())
+        .max()
+        .unwrap_or(0);
+    for timing in &timings.phases {
+        say!("  {:width$}  {:>10.1} ms", timing.phase, timing.elapsed_ms);
+    }
+    for stats in &timings.llm {
+        say!(
+            "  LLM {} requests: {} ({} failed), min {:.1} ms, median {:.1} ms, max {:.1} ms",
+            stats.kind,
+            stats.requests,
+            stats.failed,
+            stats.min_ms,
+            stats.median_ms,
+            stats.max_ms
+        );
+    }
+}
+
+fn print_teaching_order(snapshot: &GraphSnapshot, order: Result<Vec<Uuid>, CycleInfo>) {
+    let texts: HashMap<Uuid, &str> = snapshot
+        .nodes
+        .iter()
+        .map(|node| (node.id, node.text.as_ref()))
+        .collect();
+    let text = |id: &Uuid| texts.get(id).copied().unwrap_or_default();
+    match order {
+        Ok(order) => {
+            say!("Teaching order:");
+            for (position, id) in order.iter().enumerate() {
+                say!("  {}. {}", position + 1, text(id));
+            }
+        }
+        Err(cycle) => {
+            say!("Teaching order unavailable: {cycle}:");
+            for id in &cycle.nodes {
+                say!("  {} {}", short_id(id), text(id));
+            }
+        }
+    }
+}
+
+/// Print accepted and rejected counts with the rejections by category,
+/// largest first.
+fn print_rejection_table(report: &RejectionReport) {
+    say!("Rejections:");
+    for (subject, counts) in [("nodes", &report.nodes), ("edges", &report.edges)] {
+        say!("  {subject}: {} accepted, {} rejected", counts.accepted, counts.rejected);
+        for (category, count) in counts.ranked() {
+            say!("    {count:>5}  {}", category.label());
+        }
+    }
+    let unknown_tags = report.top_unknown_tags(5);
+    if !unknown_tags.is_empty() {
+        say!("  most dropped unknown tags:");
+        for (tag, count) in unknown_tags {
+            say!("    {count:>5}  {tag}");
+        }
+    }
+}
+
+fn print_summary(config: &RunConfig, summary: &Summary) {
+    say!(
         "Nodes: {} (concept={}, learning_outcome={})",
-        summary.total_nodes, summary.concepts, summary.learning_outcomes
+        summary.total_nodes,
+        summary.concepts,
+        summary.learning_outcomes
     );
-    println!(
-        "Edges: {} (prerequisite_for={}, supports={})",
-        summary.total_edges, summary.prerequisite_edges, summary.supports_edges
+    say!(
+        "Edges: {} (prerequisite_for={}, supports={}, related_to={})",
+        summary.total_edges,
+        summary.prerequisite_edges,
+        summary.supports_edges,
+        summary.related_edges
     );
-    println!(
+    say!(
         "Prerequisite DAG: {}",
         if summary.prerequisite_dag_ok {
             "OK"
@@ -237,24 +3145,582 @@ async fn run_mvp(config: RunConfig) -> Result<(), DynError> {
         }
     );
 
+    if summary.pinned_nodes + summary.pinned_edges > 0 {
+        say!("Pinned: {} nodes, {} edges", summary.pinned_nodes, summary.pinned_edges);
+    }
+
+    if let Some(remaining) = summary.remaining_node_capacity {
+        say!("Remaining node capacity: {remaining}");
+    }
+    if let Some(remaining) = summary.remaining_edge_capacity {
+        say!("Remaining edge capacity: {remaining}");
+    }
+    if summary.namespaces.len() > 1 || summary.cross_namespace_prerequisites > 0 {
+        say!("Namespaces:");
+        for stats in &summary.namespaces {
+            say!(
+                "  {}: {} nodes, {} internal edges, {} cross-namespace prerequisites",
+                stats.namespace.as_deref().unwrap_or("(none)"),
+                stats.nodes,
+                stats.internal_edges,
+                stats.outgoing_cross_prerequisites
+            );
+        }
+    }
+
+    if config.group_by_generator {
+        say!("Acceptance by generator:");
+        for stats in &summary.generators {
+            for (subject, counts) in [("nodes", &stats.nodes), ("edges", &stats.edges)] {
+                if counts.proposed == 0 {
+                    continue;
+                }
+                say!(
+                    "  {} {subject}: {} / {} accepted ({:.0}%)",
+                    stats.generator,
+                    counts.accepted,
+                    counts.proposed,
+                    counts.acceptance_rate() * 100.0
+                );
+                for (reason, count) in &counts.rejection_reasons {
+                    say!("    {count} x {reason}");
+                }
+            }
+        }
+    }
+
     if !summary.top_learning_outcomes.is_empty() {
-        println!("Top learning outcomes by incoming supports:");
+        say!("Top learning outcomes by incoming supports:");
         for entry in summary.top_learning_outcomes.iter().take(5) {
-            println!("  {} ({} supports) - {}", entry.id, entry.supports, entry.text);
+            say!("  {} ({} supports) - {}", entry.id, entry.supports, entry.text);
         }
     }
 
-    if let Some(path) = &config.export_dot {
-        let dot = adder_ref.ask(ExportDot).await.map_err(|err| -> DynError {
-            Box::new(CliError(format!("failed to export DOT: {err}")))
-        })?;
-        tokio::fs::write(path, dot).await?;
-        println!("DOT graph written to {}", path.display());
+    // Coverage gaps always go to stderr so they show even when stdout is piped.
+    if !summary.coverage_ok {
+        eprintln!(
+            "warning: {} learning outcome(s) are below the supports minimum:",
+            summary.under_supported.len()
+        );
+        for entry in &summary.under_supported {
+            eprintln!("warning:   {} ({} supports) - {}", entry.id, entry.supports, entry.text);
+        }
+    }
+}
+
+/// Write every requested export and hash the ones that succeeded.
+///
+/// Returns the manifest records and the number of failed artifacts.
+async fn write_artifacts(
+    snapshot: GraphSnapshot,
+    requests: Vec<ExportRequest>,
+) -> (Vec<ArtifactRecord>, usize) {
+    let mut failed_exports = 0;
+    let mut artifacts = Vec::new();
+    if requests.is_empty() {
+        return (artifacts, failed_exports);
+    }
+
+    for outcome in write_exports(snapshot, requests).await {
+        let elapsed_ms = outcome.elapsed.as_secs_f64() * 1000.0;
+        match &outcome.error {
+            // Nothing is left to hash once the artifact has gone to stdout.
+            None if is_stdout(&outcome.path) => {
+                say!("{} written to stdout ({elapsed_ms:.1} ms)", outcome.format.label());
+            }
+            None => {
+                say!(
+                    "{} written to {} ({elapsed_ms:.1} ms)",
+                    outcome.format.label(),
+                    outcome.path.display()
+                );
+                match ArtifactRecord::from_file(outcome.format.label(), &outcome.path, elapsed_ms) {
+                    Ok(record) => artifacts.push(record),
+                    Err(err) => {
+                        eprintln!(
+                            "failed to hash {} for the manifest: {err}",
+                            outcome.path.display()
+                        )
+                    }
+                }
+            }
+            Some(err) => {
+                failed_exports += 1;
+                eprintln!(
+                    "failed to write {} to {}: {err}",
+                    outcome.format.label(),
+                    outcome.path.display()
+                );
+            }
+        }
     }
 
-    edge_generator_ref.stop_gracefully().await.ok();
-    node_generator_ref.stop_gracefully().await.ok();
-    adder_ref.stop_gracefully().await.ok();
+    (artifacts, failed_exports)
+}
 
-    Ok(())
+fn print_rejections(subject: &str, decisions: &[Decision]) {
+    for (index, decision) in decisions.iter().enumerate() {
+        if decision.accepted {
+            continue;
+        }
+        let reason = decision.reason.as_deref().unwrap_or("rejected");
+        match &decision.explanation {
+            Some(explanation) => say!("  {subject} #{index}: {reason} ({explanation})"),
+            None => say!("  {subject} #{index}: {reason}"),
+        }
+    }
+}
+
+fn export_requests(config: &RunConfig) -> Vec<ExportRequest> {
+    [
+        (ExportFormat::Dot, &config.export_dot),
+        (ExportFormat::Lms, &config.export_lms),
+        (ExportFormat::Json, &config.export_json),
+        (ExportFormat::Graphml, &config.export_graphml),
+        (ExportFormat::Mermaid, &config.export_mermaid),
+        (ExportFormat::Cytoscape, &config.export_cytoscape),
+        (ExportFormat::Gexf, &config.export_gexf),
+    ]
+    .into_iter()
+    .filter_map(|(format, path)| Some((format, path.clone()?)))
+    .chain(
+        config
+            .export_csv
+            .iter()
+            .flat_map(|directory| CSV_FILES.map(|(format, name)| (format, directory.join(name)))),
+    )
+    .map(|(format, path)| ExportRequest {
+        format,
+        path,
+        check: config.check_exports,
+        namespace: config.export_namespace.clone(),
+        topic: Some(config.topic.clone()),
+        dot: DotExportOptions {
+            color_by_kind:    config.dot_style,
+            cluster_by_level: config.dot_style,
+            max_label_chars:  config.dot_label_chars,
+        },
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::OnceLock;
+
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    /// Empty config file shared by the parse tests.
+    fn empty_config() -> &'static std::path::Path {
+        static PATH: OnceLock<PathBuf> = OnceLock::new();
+        PATH.get_or_init(|| {
+            let path =
+                std::env::temp_dir().join(format!("weaver-empty-{}.toml", std::process::id()));
+            std::fs::write(&path, "").expect("write empty config");
+            path
+        })
+    }
+
+    /// `args` plus `--config` naming an empty file unless it names its own,
+    /// so no test reads `./weaver.toml` from the working directory.
+    fn isolated(mut args: Vec<String>) -> Vec<String> {
+        if !args.iter().any(|arg| arg == "--config") {
+            args.push("--config".to_string());
+            args.push(empty_config().display().to_string());
+        }
+        args
+    }
+
+    fn parse_args(args: Vec<String>) -> Result<RunConfig, CliError> {
+        match parse_command(isolated(args))? {
+            Command::Run(config) => Ok(*config),
+            other => panic!("expected mvp run, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn help_and_version_come_from_the_command_tree() {
+        let Command::Help(help) = parse_args_help("--help") else {
+            panic!("top-level help");
+        };
+        for sub in SUBCOMMANDS {
+            assert!(help.contains(sub.name), "{} is listed", sub.name);
+        }
+        let Command::Help(help) = parse_args_help("mvp run --concepts 3 --help") else {
+            panic!("run help");
+        };
+        for flag in RUN_FLAGS {
+            assert!(help.contains(flag.usage), "{} is documented", flag.usage);
+        }
+        assert!(
+            matches!(parse_args_help("diff -h"), Command::Help(help) if help.contains("OLD.json"))
+        );
+        assert!(matches!(parse_args_help("--version"), Command::Version));
+
+        let err = parse_command(args("mvp walk")).unwrap_err();
+        assert_eq!(err.message(), "unknown subcommand 'walk' for 'mvp' (expected run, resume)");
+        let err = parse_command(args("explode")).unwrap_err();
+        assert_eq!(err.message(), "unknown command 'explode'");
+        let err = parse_command(args("summary --output")).unwrap_err();
+        assert!(err.message().contains("(usage: --output PATH)"), "{}", err.message());
+    }
+
+    fn parse_args_help(line: &str) -> Command {
+        parse_command(args(line)).expect("valid command line")
+    }
+
+    #[test]
+    fn all_argument_errors_are_reported_together() {
+        let err = parse_args(args("mvp run --concepts many --bogus --max-nodes 0 --topic"))
+            .expect_err("command line has mistakes");
+
+        assert!(err.message().starts_with("4 argument errors:"), "{}", err.message());
+        assert!(
+            err.message()
+                .contains("invalid integer 'many' for --concepts (usage: --concepts N)")
+        );
+        assert!(err.message().contains("unknown flag '--bogus'"));
+        assert!(err.message().contains("--max-nodes must be at least 1"));
+        assert!(err.message().contains("missing value for --topic"));
+    }
+
+    #[test]
+    fn viz_targets_parse_and_required_needs_explicit_target() {
+        let config = parse_args(args("mvp run --viz save --viz-required")).expect("valid flags");
+        assert_eq!(config.viz, VizTarget::Save(PathBuf::from(DEFAULT_RECORDING_PATH)));
+        let config = parse_args(args("mvp run --viz save:out.rrd")).expect("valid flags");
+        assert_eq!(config.viz, VizTarget::Save(PathBuf::from("out.rrd")));
+
+        for line in ["mvp run --no-viz", "mvp run --viz false"] {
+            assert_eq!(parse_args(args(line)).expect("valid flags").viz, VizTarget::Off);
+        }
+
+        let err = parse_args(args("mvp run --viz-required --viz nowhere"))
+            .expect_err("target is invalid");
+        assert!(err.message().contains("invalid viz target 'nowhere'"));
+        let err = parse_args(args("mvp run --viz-required")).expect_err("target is implicit");
+        assert!(
+            err.message()
+                .contains("--viz-required needs an explicit --viz target")
+        );
+    }
+
+    #[test]
+    fn config_file_sits_between_defaults_and_flags() {
+        let dir = std::env::temp_dir().join(format!("weaver-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let path = dir.join("weaver.toml");
+        std::fs::write(&path, "topic = \"Recursion\"\nconcepts = 7\nlearning_outcomes = 2\n")
+            .expect("write config");
+
+        let line = format!("mvp run --config {} --concepts 9", path.display());
+        let config = parse_args(args(&line)).expect("valid config");
+        assert_eq!(config.topic, "Recursion");
+        assert_eq!(config.concepts, 9);
+        assert_eq!(config.learning_outcomes, 2);
+        assert_eq!(config.target_edges, RunConfig::default().target_edges);
+
+        std::fs::write(&path, "topik = \"Typo\"\n").expect("write config");
+        let err = parse_args(args(&line)).expect_err("unknown key");
+        assert!(err.message().contains("unknown field `topik`"), "{}", err.message());
+        assert!(err.message().contains(&path.display().to_string()), "{}", err.message());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn allowed_tags_come_from_the_flag_or_config_file() {
+        let default = parse_args(args("mvp run")).expect("defaults");
+        assert_eq!(default.node_rules().allowed_tags, TagRegistry::default());
+
+        let config = parse_args(args("mvp run --allowed-tags Lab,,safety,lab")).expect("tags");
+        assert_eq!(config.node_rules().allowed_tags.tags(), ["lab", "safety"]);
+
+        let dir = std::env::temp_dir().join(format!("weaver-tags-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let path = dir.join("weaver.toml");
+        std::fs::write(&path, "allowed_tags = [\"stoichiometry\"]\n").expect("write config");
+        let config =
+            parse_args(args(&format!("mvp run --config {}", path.display()))).expect("config tags");
+        assert_eq!(config.node_rules().allowed_tags.tags(), ["stoichiometry"]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn repeated_topics_and_topics_file_merge_in_order() {
+        let dir = std::env::temp_dir().join(format!("weaver-topics-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let path = dir.join("topics.txt");
+        std::fs::write(&path, "# course units\nClosures\n\n  Iterators  \n").expect("write topics");
+
+        let line = format!("mvp run --topic Recursion --topics-file {}", path.display());
+        let config = parse_args(args(&line)).expect("valid topics");
+        assert_eq!(config.all_topics(), vec!["Recursion", "Closures", "Iterators"]);
+        assert_eq!(config.namespace(), "Recursion");
+
+        let config = parse_args(args("mvp run")).expect("defaults");
+        assert_eq!(config.all_topics(), vec!["Design Recipe"]);
+
+        let err =
+            parse_args(args("mvp run --topics-file missing-topics.txt")).expect_err("missing");
+        assert!(
+            err.message()
+                .contains("failed to read topics file missing-topics.txt"),
+            "{}",
+            err.message()
+        );
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn environment_sits_between_defaults_and_flags() {
+        let env = |name: &str| match name {
+            "WEAVER_TOPIC" => Some("Closures".to_string()),
+            "WEAVER_CONCEPTS" => Some("12".to_string()),
+            "WEAVER_USE_LLM" => Some("false".to_string()),
+            "WEAVER_EXPORT_DOT" => Some("env.dot".to_string()),
+            _ => None,
+        };
+        let config = parse_run_args(&isolated(args("--concepts 4")), env).expect("valid env");
+        assert_eq!(config.topic, "Closures");
+        assert_eq!(config.concepts, 4);
+        assert_eq!(config.learning_outcomes, RunConfig::default().learning_outcomes);
+        assert_eq!(config.export_dot, Some(PathBuf::from("env.dot")));
+
+        let env = |name: &str| (name == "WEAVER_EDGES").then(|| "lots".to_string());
+        let err = parse_run_args(&isolated(Vec::new()), env).expect_err("bad env value");
+        assert_eq!(
+            err.message(),
+            "invalid value 'lots' for WEAVER_EDGES: expected a non-negative integer"
+        );
+
+        let env = |name: &str| {
+            match name {
+                "WEAVER_MAX_LEVEL" => Some("2"),
+                "WEAVER_ALLOWED_TAGS" => Some("recursion, lists"),
+                "WEAVER_STRICT_RATIONALES" => Some("yes"),
+                "WEAVER_STRICT" => Some("true"),
+                "WEAVER_LOG_LEVEL" => Some("debug"),
+                "WEAVER_IMPORT_NODES" => Some("seed.csv"),
+                _ => None,
+            }
+            .map(str::to_string)
+        };
+        let config =
+            parse_run_args(&isolated(args("--allowed-tags loops")), env).expect("valid env");
+        assert_eq!(config.max_level, 2);
+        assert_eq!(config.allowed_tags, ["loops"]);
+        assert_eq!(config.rationale_policy, RationalePolicy::Strict);
+        assert!(config.strict);
+        assert_eq!(config.log_level, Some(LogLevel::Debug));
+        assert_eq!(config.import_nodes, Some(PathBuf::from("seed.csv")));
+
+        let env = |name: &str| (name == "WEAVER_TIMINGS").then(|| "sometimes".to_string());
+        let err = parse_run_args(&isolated(Vec::new()), env).expect_err("bad env boolean");
+        assert_eq!(
+            err.message(),
+            "invalid value 'sometimes' for WEAVER_TIMINGS: expected true or false"
+        );
+
+        let help = SUBCOMMANDS[0].help();
+        for (name, _) in ENV_VARS {
+            assert!(help.contains(name), "{name} is documented");
+        }
+    }
+
+    #[tokio::test]
+    async fn interrupt_wakes_waiters_and_stays_set() {
+        let interrupt = Arc::new(Interrupt::default());
+        let waiter = tokio::spawn({
+            let interrupt = Arc::clone(&interrupt);
+            async move { interrupt.requested().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!interrupt.is_requested());
+
+        interrupt.requested.store(true, Ordering::SeqCst);
+        interrupt.notify.notify_waiters();
+        tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .expect("waiter wakes")
+            .expect("waiter finishes");
+        // Later phases see the request without waiting.
+        interrupt.requested().await;
+    }
+
+    #[test]
+    fn missing_value_does_not_swallow_next_flag() {
+        let err = parse_args(args("mvp run --export-dot --explain --concepts 0 --los 0"))
+            .expect_err("export path is missing");
+
+        assert!(err.message().contains("missing value for --export-dot"));
+        assert!(err.message().contains("leave nothing to generate"));
+        assert!(!err.message().contains("unknown flag"));
+    }
+
+    #[test]
+    fn min_supports_comes_from_the_flag_or_environment() {
+        let config = parse_run_args(&isolated(Vec::new()), |_| None).expect("defaults");
+        assert_eq!(config.min_supports, 1);
+        let config = parse_run_args(&isolated(args("--min-supports 3")), |_| None).expect("flag");
+        assert_eq!(config.min_supports, 3);
+        let env = |name: &str| (name == "WEAVER_MIN_SUPPORTS").then(|| "0".to_string());
+        assert_eq!(
+            parse_run_args(&isolated(Vec::new()), env)
+                .expect("env")
+                .min_supports,
+            0
+        );
+        let err = parse_run_args(&isolated(args("--min-supports some")), |_| None).unwrap_err();
+        assert!(
+            err.message()
+                .contains("invalid integer 'some' for --min-supports")
+        );
+    }
+
+    #[test]
+    fn dot_flags_reach_the_dot_export_request() {
+        let config = parse_run_args(
+            &isolated(args(
+                "--export-dot g.dot --export-json g.json --dot-style --dot-label-chars 20",
+            )),
+            |_| None,
+        )
+        .expect("dot flags");
+        let requests = export_requests(&config);
+        let styled = DotExportOptions {
+            color_by_kind:    true,
+            cluster_by_level: true,
+            max_label_chars:  Some(20),
+        };
+        assert!(requests.iter().all(|request| request.dot == styled));
+
+        let config =
+            parse_run_args(&isolated(args("--export-dot g.dot")), |_| None).expect("plain dot");
+        assert_eq!(export_requests(&config)[0].dot, DotExportOptions::default());
+        let err = parse_run_args(&isolated(args("--dot-label-chars 0")), |_| None).unwrap_err();
+        assert!(
+            err.message()
+                .contains("--dot-label-chars must be at least 1"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn graph_split_needs_a_node_and_two_replacements() {
+        let mut raw = args("graph split --input g.json --node 7a19 --edges-to 2 --into");
+        raw.push("Loops repeat statements.".to_string());
+        raw.push("--into".to_string());
+        raw.push("Loops stop at a condition.".to_string());
+        let Command::Split(split) = parse_command(raw).expect("valid split") else {
+            panic!("expected graph split");
+        };
+        assert_eq!(split.node, "7a19");
+        assert_eq!(split.into.len(), 2);
+        assert_eq!(split.edges_to, Some(SplitEdges::Replacement(1)));
+        assert_eq!(split.output, None);
+
+        let err =
+            parse_command(args("graph split --input g.dot --node 7a19 --into One. --edges-to 0"))
+                .unwrap_err();
+        assert!(err.message().contains("invalid edge target '0'"), "{}", err.message());
+        let err =
+            parse_command(args("graph split --input g.dot --node 7a19 --into One. --into Two."))
+                .unwrap_err();
+        assert!(err.message().contains("a DOT --input needs --output"), "{}", err.message());
+        let err = parse_command(args("graph split --input g.json --into One.")).unwrap_err();
+        assert!(err.message().contains("missing --node"), "{}", err.message());
+        assert!(err.message().contains("at least two --into texts"), "{}", err.message());
+    }
+
+    #[test]
+    fn valid_command_line_parses() {
+        let config = parse_args(args("mvp run --concepts 3 --los 1 --max-edges 5 --explain"))
+            .expect("valid arguments");
+        assert_eq!((config.concepts, config.learning_outcomes), (3, 1));
+        assert_eq!(config.max_edges, Some(5));
+        assert!(config.explain);
+    }
+
+    #[test]
+    fn dry_run_is_off_unless_asked_for() {
+        assert!(!parse_args(args("mvp run")).expect("defaults").dry_run);
+        assert!(
+            parse_args(args("mvp run --dry-run"))
+                .expect("dry run")
+                .dry_run
+        );
+    }
+
+    #[test]
+    fn log_level_and_format_parse() {
+        assert_eq!(parse_args(args("mvp run")).expect("defaults").log_level, None);
+        let config = parse_args(args("mvp run --log-level DEBUG --log-format json"))
+            .expect("valid log flags");
+        assert_eq!(config.log_level, Some(LogLevel::Debug));
+        assert_eq!(config.log_format, LogFormat::Json);
+        let err = parse_args(args("mvp run --log-level loud")).unwrap_err();
+        assert!(err.message().contains("invalid log level 'loud'"), "{err}");
+    }
+
+    #[test]
+    fn one_export_may_write_to_stdout() {
+        let config = parse_args(args("mvp run --export-json -")).expect("stdout export");
+        assert_eq!(config.stdout_exports(), vec!["--export-json"]);
+        let err = parse_args(args("mvp run --export-dot - --output json")).unwrap_err();
+        assert!(err.message().contains("--export-dot - and --output json"), "{err}");
+    }
+
+    #[test]
+    fn csv_export_writes_two_files_into_a_directory() {
+        let config = parse_args(args("mvp run --export-csv tables")).expect("csv directory");
+        let paths: Vec<PathBuf> = export_requests(&config)
+            .into_iter()
+            .map(|request| request.path)
+            .collect();
+        assert_eq!(paths, ["tables/nodes.csv", "tables/edges.csv"].map(PathBuf::from));
+        assert!(parse_args(args("mvp run --export-csv -")).is_err());
+    }
+
+    #[test]
+    fn similarity_threshold_must_lie_between_zero_and_one() {
+        assert_eq!(
+            parse_args(args("mvp run"))
+                .expect("defaults")
+                .similarity_threshold,
+            None
+        );
+        let config = parse_args(args("mvp run --similarity-threshold 0.85")).expect("in range");
+        assert_eq!(config.similarity_threshold, Some(0.85));
+        assert!(parse_args(args("mvp run --similarity-threshold 1.5")).is_err());
+    }
+
+    #[test]
+    fn min_rationale_words_reach_the_rationale_rules() {
+        let config = parse_args(args("mvp run --min-rationale-words 4")).expect("word count");
+        assert_eq!(config.rationale_rules().min_words, 4);
+    }
+
+    #[test]
+    fn lo_prefixes_split_on_commas_and_trim() {
+        let mut raw = args("mvp run --lo-prefixes");
+        raw.push("I can ,Students can ,Students will be able to ".to_string());
+        let config = parse_args(raw).expect("prefix list");
+        assert_eq!(
+            config.node_rules().allowed_lo_prefixes,
+            ["I can", "Students can", "Students will be able to"]
+        );
+    }
+
+    #[test]
+    fn output_format_is_text_or_json() {
+        assert_eq!(parse_args(args("mvp run")).expect("defaults").output, OutputFormat::Text);
+        let config = parse_args(args("mvp run --output json")).expect("valid output format");
+        assert_eq!(config.output, OutputFormat::Json);
+        let err = parse_args(args("mvp run --output yaml")).expect_err("unknown format");
+        assert!(err.message().contains("invalid output format 'yaml'"));
+    }
 }
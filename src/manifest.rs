@@ -0,0 +1,380 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    graph::GraphSnapshot, llm::TokenUsage, model::GeneratorInfo, summary::Summary, viz::VizStatus,
+};
+
+/// Schema version written into every manifest.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Reproducibility record written at the end of a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunManifest {
+    pub version:           u32,
+    pub crate_version:     String,
+    pub git_describe:      Option<String>,
+    /// Effective run configuration after argument parsing.
+    pub config:            serde_json::Value,
+    /// [`config_hash`] of `config`; runs with equal hashes build the same
+    /// graph.
+    #[serde(default)]
+    pub config_hash:       Option<String>,
+    pub generators:        Vec<GeneratorInfo>,
+    pub seed:              Option<u64>,
+    pub token_usage:       TokenUsage,
+    pub phases:            Vec<PhaseTiming>,
+    /// The run hit `--deadline` and its results are partial.
+    #[serde(default)]
+    pub deadline_exceeded: bool,
+    #[serde(default)]
+    pub viz:               Option<VizStatus>,
+    pub artifacts:         Vec<ArtifactRecord>,
+    pub summary:           Summary,
+}
+
+/// Wall-clock duration of one run phase.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PhaseTiming {
+    pub phase:      String,
+    pub elapsed_ms: f64,
+}
+
+/// An artifact written by the run, the hash of its contents, and how long
+/// it took to render and write.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ArtifactRecord {
+    pub kind:       String,
+    pub path:       PathBuf,
+    pub sha256:     String,
+    pub bytes:      u64,
+    /// Zero in manifests written before artifacts were timed.
+    #[serde(default)]
+    pub elapsed_ms: f64,
+}
+
+impl ArtifactRecord {
+    /// Hash a written file, recording its canonical path when available.
+    pub fn from_file(kind: impl Into<String>, path: &Path, elapsed_ms: f64) -> io::Result<Self> {
+        let contents = fs::read(path)?;
+        Ok(Self {
+            kind: kind.into(),
+            path: fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()),
+            sha256: sha256_hex(&contents),
+            bytes: contents.len() as u64,
+            elapsed_ms,
+        })
+    }
+}
+
+/// Outcome of re-hashing one recorded artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactStatus {
+    Intact,
+    Modified { actual: String },
+    Unreadable(String),
+}
+
+impl RunManifest {
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let manifest: Self = serde_json::from_str(&contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if manifest.version > MANIFEST_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "manifest version {} is newer than supported version {MANIFEST_VERSION}",
+                    manifest.version
+                ),
+            ));
+        }
+        Ok(manifest)
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, json)
+    }
+
+    /// Re-hash every recorded artifact, in manifest order.
+    pub fn verify(&self) -> Vec<(&ArtifactRecord, ArtifactStatus)> {
+        self.artifacts
+            .iter()
+            .map(|artifact| {
+                let status = match fs::read(&artifact.path) {
+                    Ok(contents) => {
+                        let actual = sha256_hex(&contents);
+                        if actual == artifact.sha256 {
+                            ArtifactStatus::Intact
+                        } else {
+                            ArtifactStatus::Modified { actual }
+                        }
+                    }
+                    Err(err) => ArtifactStatus::Unreadable(err.to_string()),
+                };
+                (artifact, status)
+            })
+            .collect()
+    }
+}
+
+/// A finished run's graph stored alongside the manifest that produced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredRun {
+    pub manifest: RunManifest,
+    pub graph:    GraphSnapshot,
+}
+
+impl StoredRun {
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let stored: Self = serde_json::from_str(&contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if stored.manifest.version > MANIFEST_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "manifest version {} is newer than supported version {MANIFEST_VERSION}",
+                    stored.manifest.version
+                ),
+            ));
+        }
+        Ok(stored)
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, json)
+    }
+
+    /// Load `path` if it holds a complete, consistent run with `config_hash`.
+    ///
+    /// The error explains why the stored run cannot stand in for a fresh one.
+    pub fn reusable(path: &Path, config_hash: &str) -> Result<Self, String> {
+        let stored = Self::read(path).map_err(|err| format!("cannot load: {err}"))?;
+        stored.validate()?;
+        let manifest = &stored.manifest;
+        if manifest.deadline_exceeded {
+            return Err("stored run was cut short by --deadline".to_string());
+        }
+        match manifest.config_hash.as_deref() {
+            Some(stored_hash) if stored_hash == config_hash => Ok(stored),
+            Some(_) => Err("configuration changed since the stored run".to_string()),
+            None => Err("stored run has no config hash".to_string()),
+        }
+    }
+
+    /// Check edge endpoints resolve and counts agree with the stored summary.
+    fn validate(&self) -> Result<(), String> {
+        let ids: std::collections::HashSet<_> =
+            self.graph.nodes.iter().map(|node| node.id).collect();
+        if ids.len() != self.graph.nodes.len() {
+            return Err("stored graph repeats a node id".to_string());
+        }
+        if let Some(edge) = self
+            .graph
+            .edges
+            .iter()
+            .find(|edge| !ids.contains(&edge.from) || !ids.contains(&edge.to))
+        {
+            return Err(format!("stored edge {} -> {} has a missing endpoint", edge.from, edge.to));
+        }
+        let summary = &self.manifest.summary;
+        if (summary.total_nodes, summary.total_edges)
+            != (self.graph.nodes.len(), self.graph.edges.len())
+        {
+            return Err("stored graph does not match its summary".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Hash of the run configuration with `ignored` top-level fields removed.
+///
+/// The canonical form is compact JSON with object keys sorted at every
+/// depth, so field order and non-semantic settings (output paths,
+/// verbosity) never change the hash.
+pub fn config_hash(config: &serde_json::Value, ignored: &[&str]) -> String {
+    let mut canonical = canonicalize(config);
+    if let serde_json::Value::Object(fields) = &mut canonical {
+        fields.retain(|key, _| !ignored.contains(&key.as_str()));
+    }
+    sha256_hex(canonical.to_string().as_bytes())
+}
+
+fn canonicalize(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(fields) => {
+            let mut keys: Vec<_> = fields.keys().collect();
+            keys.sort();
+            serde_json::Value::Object(
+                keys.into_iter()
+                    .map(|key| (key.clone(), canonicalize(&fields[key])))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(canonicalize).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// `git describe --always --dirty` for the working directory, if available.
+pub fn git_describe() -> Option<String> {
+    let output = Command::new("git")
+        .args(["describe", "--always", "--dirty"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let described = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!described.is_empty()).then_some(described)
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("weaver-manifest-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    fn manifest_with(artifacts: Vec<ArtifactRecord>) -> RunManifest {
+        RunManifest {
+            version: MANIFEST_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_describe: None,
+            config: serde_json::json!({ "topic": "Design Recipe" }),
+            config_hash: None,
+            generators: vec![GeneratorInfo::fallback()],
+            seed: None,
+            token_usage: TokenUsage::default(),
+            phases: vec![PhaseTiming {
+                phase:      "Adding nodes".to_string(),
+                elapsed_ms: 1.5,
+            }],
+            deadline_exceeded: false,
+            viz: None,
+            artifacts,
+            summary: Summary::empty(),
+        }
+    }
+
+    #[test]
+    fn manifest_round_trips_through_json() {
+        let dir = temp_dir();
+        let path = dir.join("manifest.json");
+        let artifact = dir.join("graph.dot");
+        fs::write(&artifact, "digraph weaver {}\n").expect("write artifact");
+        let manifest = manifest_with(vec![
+            ArtifactRecord::from_file("dot", &artifact, 2.25).expect("hash artifact"),
+        ]);
+
+        manifest.write(&path).expect("write manifest");
+        let loaded = RunManifest::read(&path).expect("read manifest");
+
+        assert_eq!(loaded.version, MANIFEST_VERSION);
+        assert_eq!(loaded.config, manifest.config);
+        assert_eq!(loaded.generators, manifest.generators);
+        assert_eq!(loaded.phases, manifest.phases);
+        assert_eq!(loaded.artifacts, manifest.artifacts);
+        assert_eq!(loaded.artifacts[0].elapsed_ms, 2.25);
+
+        let mut untimed = serde_json::to_value(&manifest.artifacts[0]).expect("serialize");
+        untimed
+            .as_object_mut()
+            .expect("record object")
+            .remove("elapsed_ms");
+        let untimed: ArtifactRecord = serde_json::from_value(untimed).expect("old record");
+        assert_eq!(untimed.elapsed_ms, 0.0);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn config_hash_ignores_key_order_and_non_semantic_fields() {
+        let base = serde_json::json!({ "topic": "T", "limits": { "a": 1, "b": 2 }, "out": "x" });
+        let reordered =
+            serde_json::json!({ "out": "y", "limits": { "b": 2, "a": 1 }, "topic": "T" });
+        assert_eq!(config_hash(&base, &["out"]), config_hash(&reordered, &["out"]));
+        assert_ne!(config_hash(&base, &[]), config_hash(&reordered, &[]));
+    }
+
+    #[test]
+    fn stored_run_is_reused_only_when_intact_and_unchanged() {
+        let dir = temp_dir();
+        let path = dir.join("run.json");
+        let mut manifest = manifest_with(Vec::new());
+        manifest.config_hash = Some("current".to_string());
+        let stored = StoredRun {
+            manifest,
+            graph: GraphSnapshot {
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            },
+        };
+        stored.write(&path).expect("write stored run");
+
+        assert!(StoredRun::reusable(&path, "current").is_ok());
+        let mismatch = StoredRun::reusable(&path, "other").expect_err("hash differs");
+        assert!(mismatch.contains("configuration changed"), "{mismatch}");
+
+        fs::write(&path, "{ \"manifest\": ").expect("corrupt");
+        let corrupt = StoredRun::reusable(&path, "current").expect_err("file is corrupt");
+        assert!(corrupt.starts_with("cannot load"), "{corrupt}");
+        let absent = StoredRun::reusable(&dir.join("absent.json"), "current");
+        assert!(absent.is_err());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn verify_reports_intact_modified_and_missing_artifacts() {
+        let dir = temp_dir();
+        let intact = dir.join("intact.dot");
+        let tampered = dir.join("tampered.dot");
+        let missing = dir.join("missing.dot");
+        for path in [&intact, &tampered, &missing] {
+            fs::write(path, "digraph weaver {}\n").expect("write artifact");
+        }
+        let manifest = manifest_with(
+            [&intact, &tampered, &missing]
+                .into_iter()
+                .map(|path| ArtifactRecord::from_file("dot", path, 0.0).expect("hash artifact"))
+                .collect(),
+        );
+
+        fs::write(&tampered, "digraph weaver { edited }\n").expect("tamper");
+        fs::remove_file(&missing).expect("remove");
+
+        let statuses: Vec<_> = manifest
+            .verify()
+            .into_iter()
+            .map(|(_, status)| status)
+            .collect();
+        assert_eq!(statuses[0], ArtifactStatus::Intact);
+        assert!(matches!(statuses[1], ArtifactStatus::Modified { .. }));
+        assert!(matches!(statuses[2], ArtifactStatus::Unreadable(_)));
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,9 +1,14 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
 use petgraph::graph::NodeIndex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub const MAX_NODE_LEVEL: u8 = 3;
+pub const MAX_TAGS_PER_NODE: usize = 3;
+/// Openings a learning outcome may start with, compared case-insensitively.
+pub const LO_PREFIXES: &[&str] = &["I can", "Students can"];
 pub const ALLOWED_TAGS: &[&str] = &[
     "design_recipe",
     "contract",
@@ -14,6 +19,68 @@ pub const ALLOWED_TAGS: &[&str] = &[
     "refactor",
 ];
 
+/// Tag vocabulary nodes may carry; other tags are dropped on acceptance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagRegistry(Vec<String>);
+
+impl TagRegistry {
+    /// Tags are trimmed and lowercased; blanks and repeats are skipped.
+    pub fn new<S: AsRef<str>>(tags: impl IntoIterator<Item = S>) -> Self {
+        let mut registry = Vec::new();
+        for tag in tags {
+            let tag = tag.as_ref().trim().to_lowercase();
+            if !tag.is_empty() && !registry.contains(&tag) {
+                registry.push(tag);
+            }
+        }
+        Self(registry)
+    }
+
+    /// `tag` must already be normalized.
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.iter().any(|allowed| allowed == tag)
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl Default for TagRegistry {
+    fn default() -> Self {
+        Self::new(ALLOWED_TAGS)
+    }
+}
+
+/// Bounds on node proposals, shared by the generators and the adder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeRules {
+    /// Highest accepted level.
+    pub max_level:               u8,
+    pub allowed_tags:            TagRegistry,
+    /// Openings learning outcome texts must start with; empty accepts any.
+    pub allowed_lo_prefixes:     Vec<String>,
+    /// Reject node texts holding more than one sentence.
+    pub require_single_sentence: bool,
+    /// Tags kept per node; later tags are dropped.
+    pub max_tags_per_node:       usize,
+}
+
+impl Default for NodeRules {
+    fn default() -> Self {
+        Self {
+            max_level:               MAX_NODE_LEVEL,
+            allowed_tags:            TagRegistry::default(),
+            allowed_lo_prefixes:     LO_PREFIXES
+                .iter()
+                .map(|prefix| prefix.to_string())
+                .collect(),
+            require_single_sentence: true,
+            max_tags_per_node:       MAX_TAGS_PER_NODE,
+        }
+    }
+}
+
 /// Type of node in the learning graph.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 pub enum NodeKind {
@@ -27,6 +94,44 @@ pub enum Granularity {
     Sentence,
 }
 
+/// Which generator produced a proposal, for comparing models.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct GeneratorInfo {
+    pub model:       String,
+    pub provider:    String,
+    /// Stable hash of the system prompt the proposal was generated from.
+    pub prompt_hash: String,
+}
+
+impl GeneratorInfo {
+    /// Sentinel recorded on proposals produced by the deterministic fallback.
+    pub fn fallback() -> Self {
+        Self {
+            model:       "fallback".to_string(),
+            provider:    "weaver".to_string(),
+            prompt_hash: String::new(),
+        }
+    }
+
+    /// Recorded on human-authored proposals, from `weaver shell` or
+    /// `--import-nodes`.
+    pub fn manual() -> Self {
+        Self {
+            model:       "manual".to_string(),
+            provider:    "shell".to_string(),
+            prompt_hash: String::new(),
+        }
+    }
+
+    /// Grouping key used in per-generator statistics.
+    pub fn label(generator: Option<&GeneratorInfo>) -> String {
+        match generator {
+            Some(info) => format!("{}/{}", info.provider, info.model),
+            None => "unknown".to_string(),
+        }
+    }
+}
+
 /// Fully validated node stored in the graph.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Node {
@@ -34,19 +139,49 @@ pub struct Node {
     pub kind:        NodeKind,
     pub granularity: Granularity,
     pub level:       u8,
-    pub text:        String,
+    /// Interned by the [`GraphStore`](crate::graph::GraphStore) holding it.
+    pub text:        Arc<str>,
     pub tags:        Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator:   Option<GeneratorInfo>,
+    /// Topic graph the node belongs to; text is deduplicated per namespace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace:   Option<String>,
+    /// Topic whose generation produced the node, in multi-topic runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic:       Option<String>,
+    /// Curated nodes survive [`ClearUnpinned`](crate::adder::ClearUnpinned).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned:      bool,
 }
 
 /// Flattened representation of a node used when sharing inventory across
 /// actors.
-pub type InventoryEntry = (Uuid, NodeKind, u8, String, Option<Vec<String>>);
+pub type InventoryEntry = (Uuid, NodeKind, u8, Arc<str>, Option<Vec<String>>);
 
 /// Relation type between two nodes.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 pub enum Relation {
     PrerequisiteFor,
     Supports,
+    /// Undirected association; stored with the smaller UUID as `from`.
+    RelatedTo,
+}
+
+impl Relation {
+    /// Whether `A -> B` and `B -> A` denote the same edge.
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, Relation::RelatedTo)
+    }
+
+    /// Endpoint order used for storage and duplicate checks.
+    pub fn canonical_endpoints(&self, from: Uuid, to: Uuid) -> (Uuid, Uuid) {
+        if self.is_symmetric() && to < from {
+            (to, from)
+        } else {
+            (from, to)
+        }
+    }
 }
 
 /// Fully validated edge stored in the graph.
@@ -56,6 +191,8 @@ pub struct Edge {
     pub to:        NodeIndex,
     pub relation:  Relation,
     pub rationale: String,
+    pub generator: Option<GeneratorInfo>,
+    pub pinned:    bool,
 }
 
 /// Proposed node emitted by a generator (LLM or fallback).
@@ -66,6 +203,18 @@ pub struct NodeProposal {
     pub level:       u8,
     pub text:        String,
     pub tags:        Option<Vec<String>>,
+    /// Filled in by the generator; never requested from the model.
+    #[serde(default, skip_serializing)]
+    #[schemars(skip)]
+    pub generator:   Option<GeneratorInfo>,
+    /// Assigned by the adder from the run topic when left unset.
+    #[serde(default, skip_serializing)]
+    #[schemars(skip)]
+    pub namespace:   Option<String>,
+    /// Filled in by the generator in multi-topic runs.
+    #[serde(default, skip_serializing)]
+    #[schemars(skip)]
+    pub topic:       Option<String>,
 }
 
 /// Proposed edge emitted by a generator (LLM or fallback).
@@ -75,14 +224,131 @@ pub struct EdgeProposal {
     pub from_id:   Uuid,
     pub to_id:     Uuid,
     pub rationale: String,
+    /// Filled in by the generator; never requested from the model.
+    #[serde(default, skip_serializing)]
+    #[schemars(skip)]
+    pub generator: Option<GeneratorInfo>,
+}
+
+/// Why a proposed tag was left off its node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagDropReason {
+    /// Not in the allowed tag list.
+    Unknown,
+    /// Past [`NodeRules::max_tags_per_node`].
+    OverLimit,
+    /// Repeats an earlier tag of the same proposal.
+    Duplicate,
+}
+
+/// A proposed tag, normalized, that was not stored on the node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DroppedTag {
+    pub tag:    String,
+    pub reason: TagDropReason,
 }
 
 /// Decision result returned by the GraphAdder for node and edge proposals.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, kameo::Reply)]
 pub struct Decision {
-    pub accepted:    bool,
-    pub reason:      Option<String>,
-    pub assigned_id: Option<Uuid>,
+    pub accepted:     bool,
+    pub reason:       Option<String>,
+    pub assigned_id:  Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings:     Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation:  Option<Explanation>,
+    /// Which proposal of its batch the decision answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context:      Option<ProposalContext>,
+    /// Tags of an accepted node proposal that were not stored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped_tags: Vec<DroppedTag>,
+}
+
+/// Position of a proposal in its batch and the fields that identify it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProposalContext {
+    pub index:   usize,
+    #[serde(flatten)]
+    pub subject: ProposalSubject,
+}
+
+/// Identifying fields echoed from a proposal.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "proposal", rename_all = "snake_case")]
+pub enum ProposalSubject {
+    /// The proposed text, cleaned.
+    Node { text: String },
+    Edge {
+        from:     Uuid,
+        to:       Uuid,
+        relation: Relation,
+    },
+}
+
+impl From<&NodeProposal> for ProposalSubject {
+    fn from(proposal: &NodeProposal) -> Self {
+        ProposalSubject::Node {
+            text: clean_text(&proposal.text),
+        }
+    }
+}
+
+impl From<&EdgeProposal> for ProposalSubject {
+    fn from(proposal: &EdgeProposal) -> Self {
+        ProposalSubject::Edge {
+            from:     proposal.from_id,
+            to:       proposal.to_id,
+            relation: proposal.relation.clone(),
+        }
+    }
+}
+
+/// Node reference carried inside an [`Explanation`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NodeRef {
+    pub id:   Uuid,
+    pub text: String,
+}
+
+/// Structured detail explaining why a proposal was rejected.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Explanation {
+    /// Existing prerequisite path from the proposed target back to the source.
+    CyclePath { path: Vec<NodeRef> },
+    /// Levels of the endpoints of a level-decreasing prerequisite.
+    LevelDecrease { from_level: u8, to_level: u8 },
+    /// Node that already holds the proposed text.
+    DuplicateNode { existing: Uuid },
+    /// Endpoints of the edge that already exists.
+    DuplicateEdge { from: Uuid, to: Uuid },
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Explanation::CyclePath { path } => {
+                let steps: Vec<_> = path
+                    .iter()
+                    .map(|node| format!("{} \"{}\"", short_id(&node.id), node.text))
+                    .collect();
+                write!(f, "existing prerequisite path {}", steps.join(" -> "))
+            }
+            Explanation::LevelDecrease {
+                from_level,
+                to_level,
+            } => write!(f, "from level {from_level} > to level {to_level}"),
+            Explanation::DuplicateNode { existing } => {
+                write!(f, "existing node {}", short_id(existing))
+            }
+            Explanation::DuplicateEdge { from, to } => {
+                write!(f, "existing edge {} -> {}", short_id(from), short_id(to))
+            }
+        }
+    }
 }
 
 impl Decision {
@@ -92,17 +358,93 @@ impl Decision {
             accepted: true,
             reason: None,
             assigned_id,
+            warnings: Vec::new(),
+            explanation: None,
+            context: None,
+            dropped_tags: Vec::new(),
         }
     }
 
     /// Construct a rejected decision with the provided reason.
     pub fn rejected(reason: impl Into<String>) -> Self {
         Self {
-            accepted:    false,
-            reason:      Some(reason.into()),
-            assigned_id: None,
+            accepted:     false,
+            reason:       Some(reason.into()),
+            assigned_id:  None,
+            warnings:     Vec::new(),
+            explanation:  None,
+            context:      None,
+            dropped_tags: Vec::new(),
         }
     }
+
+    /// Attach a non-fatal warning to the decision.
+    pub fn with_warning(mut self, warning: impl Into<String>) -> Self {
+        self.warnings.push(warning.into());
+        self
+    }
+
+    /// Attach structured detail explaining the decision.
+    pub fn with_explanation(mut self, explanation: Explanation) -> Self {
+        self.explanation = Some(explanation);
+        self
+    }
+
+    /// List the tags that were left off the accepted node.
+    pub fn with_dropped_tags(mut self, dropped_tags: Vec<DroppedTag>) -> Self {
+        self.dropped_tags = dropped_tags;
+        self
+    }
+
+    /// Record which proposal of its batch the decision answers.
+    pub fn with_context(mut self, index: usize, subject: impl Into<ProposalSubject>) -> Self {
+        self.context = Some(ProposalContext {
+            index,
+            subject: subject.into(),
+        });
+        self
+    }
+}
+
+/// Rejection reasons kept in a [`RoundFeedback`] digest.
+pub const MAX_FEEDBACK_REASONS: usize = 8;
+
+/// What earlier rounds of a run produced, handed back to the generators so
+/// follow-up proposals avoid what was already accepted or rejected.
+#[derive(Debug, Clone, Default)]
+pub struct RoundFeedback {
+    /// Nodes accepted so far.
+    pub inventory:  Vec<InventoryEntry>,
+    /// Edges accepted so far.
+    pub edges:      Vec<(Uuid, Uuid, Relation)>,
+    /// Distinct rejection reasons and their counts, most frequent first.
+    pub rejections: Vec<(String, usize)>,
+}
+
+impl RoundFeedback {
+    /// Collapse the rejected decisions into their most frequent reasons.
+    pub fn digest(decisions: &[Decision]) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for reason in decisions
+            .iter()
+            .filter(|decision| !decision.accepted)
+            .filter_map(|decision| decision.reason.as_deref())
+        {
+            *counts.entry(reason).or_default() += 1;
+        }
+        let mut digest: Vec<_> = counts
+            .into_iter()
+            .map(|(reason, count)| (reason.to_string(), count))
+            .collect();
+        digest.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        digest.truncate(MAX_FEEDBACK_REASONS);
+        digest
+    }
+}
+
+/// First eight hex digits of a UUID, for compact human-readable output.
+pub fn short_id(id: &Uuid) -> String {
+    id.simple().to_string()[..8].to_string()
 }
 
 /// Normalizes text for deduplication: trim, collapse whitespace, lowercase.
@@ -114,6 +456,13 @@ pub fn normalize_text(text: &str) -> String {
         .to_lowercase()
 }
 
+/// Deduplication key: normalized text scoped to a namespace.
+pub type TextKey = (Option<String>, String);
+
+pub fn text_key(namespace: Option<&str>, text: &str) -> TextKey {
+    (namespace.map(str::to_string), normalize_text(text))
+}
+
 /// Trim leading/trailing whitespace and collapse interior whitespace.
 pub fn clean_text(text: &str) -> String {
     text.split_whitespace()
@@ -124,7 +473,32 @@ pub fn clean_text(text: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::normalize_text;
+    use super::{Decision, Node, RoundFeedback, normalize_text};
+
+    #[test]
+    fn nodes_without_generator_deserialize_as_unknown() {
+        let json = r#"{"id":"00000000-0000-0000-0000-000000000001","kind":"Concept",
+            "granularity":"Sentence","level":0,"text":"Old node.","tags":null}"#;
+        let node: Node = serde_json::from_str(json).expect("legacy node parses");
+        assert!(node.generator.is_none());
+    }
+
+    #[test]
+    fn digest_counts_rejection_reasons_most_frequent_first() {
+        let decisions = vec![
+            Decision::rejected("edge rationale missing"),
+            Decision::accepted(None),
+            Decision::rejected("duplicate edge"),
+            Decision::rejected("duplicate edge"),
+        ];
+        assert_eq!(
+            RoundFeedback::digest(&decisions),
+            vec![
+                ("duplicate edge".to_string(), 2),
+                ("edge rationale missing".to_string(), 1),
+            ]
+        );
+    }
 
     #[test]
     fn normalize_text_lowercases_and_collapses_whitespace() {
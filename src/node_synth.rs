@@ -1,28 +1,59 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
 use kameo::{
     Actor,
     message::{Context, Message},
 };
-use tracing::warn;
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+use serde::Serialize;
+use tokio::{
+    task::JoinSet,
+    time::{Instant, timeout_at},
+};
+use tracing::{Instrument, Span, field, info_span, warn};
 
 use crate::{
-    llm::{LlmClient, LlmError},
-    model::{ALLOWED_TAGS, Granularity, MAX_NODE_LEVEL, NodeKind, NodeProposal},
+    llm::{LatencyLog, LlmClient, LlmError, LlmSettings, NodeBackend, SchemaAudit, TokenUsage},
+    model::{
+        GeneratorInfo, Granularity, NodeKind, NodeProposal, NodeRules, RoundFeedback, TagRegistry,
+        normalize_text,
+    },
+    trace,
 };
 
+/// Number of request rounds an ensemble gets to fill the requested counts.
+const MAX_GENERATION_ROUNDS: usize = 2;
+
 /// Configuration for generating node proposals.
 #[derive(Debug, Clone)]
 pub struct NodeGeneratorConfig {
-    pub use_llm:                   bool,
-    pub default_concepts:          usize,
+    pub use_llm: bool,
+    pub default_concepts: usize,
     pub default_learning_outcomes: usize,
+    /// Backends queried together; when non-empty these replace the
+    /// environment-configured client.
+    pub ensemble: Vec<LlmSettings>,
+    /// Reject responses that omit required schema fields.
+    pub strict_schema: bool,
+    /// Shuffles fallback word choice; `None` keeps the fixed order.
+    pub seed: Option<u64>,
+    /// Levels and tags proposals are given or asked for.
+    pub node_rules: NodeRules,
+    /// Receives the latency of every LLM request.
+    pub latency_log: Option<LatencyLog>,
 }
 
 impl Default for NodeGeneratorConfig {
     fn default() -> Self {
         Self {
-            use_llm:                   false,
-            default_concepts:          25,
+            use_llm: false,
+            default_concepts: 25,
             default_learning_outcomes: 5,
+            ensemble: Vec::new(),
+            strict_schema: false,
+            seed: None,
+            node_rules: NodeRules::default(),
+            latency_log: None,
         }
     }
 }
@@ -31,30 +62,278 @@ impl Default for NodeGeneratorConfig {
 pub struct GenerateNodes {
     pub concepts:          usize,
     pub learning_outcomes: usize,
+    /// Time allowed for LLM requests; proposals received before it runs
+    /// out are kept and the rest are abandoned.
+    pub timeout:           Option<Duration>,
+    /// Caller's span; generation spans nest under it.
+    pub span:              Span,
+    /// Earlier rounds of the run; `None` on the first. With feedback, zero
+    /// counts mean zero instead of the configured defaults.
+    pub feedback:          Option<RoundFeedback>,
+    /// Topic of this batch in a multi-topic run; stamped on every proposal.
+    pub topic:             Option<String>,
+}
+
+/// Node proposals together with how they were produced.
+#[derive(Debug, Clone, kameo::Reply)]
+pub struct NodeBatch {
+    pub proposals: Vec<NodeProposal>,
+    pub metadata:  GenerationMetadata,
+}
+
+impl NodeBatch {
+    /// Append another batch, folding its contributions into this batch's
+    /// entries for the same model.
+    pub fn merge(&mut self, other: NodeBatch) {
+        self.proposals.extend(other.proposals);
+        self.metadata.used_fallback |= other.metadata.used_fallback;
+        self.metadata.deadline_exceeded |= other.metadata.deadline_exceeded;
+        for contribution in other.metadata.contributions {
+            let Some(existing) = self
+                .metadata
+                .contributions
+                .iter_mut()
+                .find(|existing| existing.model == contribution.model)
+            else {
+                self.metadata.contributions.push(contribution);
+                continue;
+            };
+            existing.requests += contribution.requests;
+            existing.contributed += contribution.contributed;
+            existing.duplicates += contribution.duplicates;
+            existing.usage.add(contribution.usage);
+            existing.schema.merge(&contribution.schema);
+            existing.error = existing.error.take().or(contribution.error);
+        }
+    }
+}
+
+/// Provenance of a generated batch.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GenerationMetadata {
+    pub contributions:     Vec<ModelContribution>,
+    pub used_fallback:     bool,
+    /// Generation stopped early because the request timeout elapsed.
+    pub deadline_exceeded: bool,
+}
+
+/// What a single backend contributed to a batch.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ModelContribution {
+    pub model:       String,
+    pub requests:    usize,
+    /// Proposals kept after cross-model deduplication.
+    pub contributed: usize,
+    /// Proposals dropped because another model (or this one) already
+    /// produced the same normalized text.
+    pub duplicates:  usize,
+    pub usage:       TokenUsage,
+    /// Field omissions observed in this backend's raw responses.
+    pub schema:      SchemaAudit,
+    pub error:       Option<String>,
 }
 
 /// Actor responsible for producing node proposals via LLM or deterministic
 /// fallback.
 #[derive(Debug, Actor)]
 pub struct NodeGenerator {
-    config: NodeGeneratorConfig,
-    llm:    Option<LlmClient>,
+    config:   NodeGeneratorConfig,
+    backends: Vec<Arc<dyn NodeBackend>>,
 }
 
 impl NodeGenerator {
     pub fn new(config: NodeGeneratorConfig) -> Self {
-        let llm = match LlmClient::new(config.use_llm) {
-            Ok(client) => Some(client),
-            Err(LlmError::Disabled) => None,
-            Err(err) => {
-                warn!(error = %err, "node_generator.llm_unavailable");
-                None
-            }
+        let clients = if config.ensemble.is_empty() {
+            vec![LlmClient::new(config.use_llm)]
+        } else {
+            config
+                .ensemble
+                .iter()
+                .cloned()
+                .map(LlmClient::with_settings)
+                .collect()
         };
-        Self { config, llm }
+
+        let mut backends: Vec<Arc<dyn NodeBackend>> = Vec::new();
+        for client in clients {
+            match client {
+                Ok(client) => backends.push(Arc::new(
+                    client
+                        .with_strict_schema(config.strict_schema)
+                        .with_node_rules(config.node_rules.clone())
+                        .with_latency_log(config.latency_log.clone()),
+                )),
+                Err(LlmError::Disabled) => {}
+                Err(err) => warn!(error = %err, "node_generator.llm_unavailable"),
+            }
+        }
+
+        Self::with_backends(config, backends)
+    }
+
+    /// Create a generator over explicit backends.
+    pub fn with_backends(config: NodeGeneratorConfig, backends: Vec<Arc<dyn NodeBackend>>) -> Self {
+        Self { config, backends }
+    }
+
+    /// Query every backend concurrently and pool their proposals.
+    ///
+    /// Requested counts are split evenly across backends. Proposals are
+    /// deduplicated by normalized text in backend order, and any shortfall
+    /// left by failed backends or duplicates is redistributed across the
+    /// backends that are still healthy. Once `deadline` passes, in-flight
+    /// requests are aborted and no further rounds are started. Texts already
+    /// in the feedback inventory count as duplicates.
+    async fn generate_from_backends(
+        backends: Vec<Arc<dyn NodeBackend>>,
+        concepts: usize,
+        learning_outcomes: usize,
+        deadline: Option<Instant>,
+        topic: Option<Arc<str>>,
+        feedback: Arc<RoundFeedback>,
+    ) -> NodeBatch {
+        let mut contributions: Vec<ModelContribution> = backends
+            .iter()
+            .map(|backend| ModelContribution {
+                model: backend.model_name().to_string(),
+                ..ModelContribution::default()
+            })
+            .collect();
+        let mut healthy: Vec<usize> = (0..backends.len()).collect();
+        let mut seen: HashSet<String> = feedback
+            .inventory
+            .iter()
+            .map(|(_, _, _, text, _)| normalize_text(text))
+            .collect();
+        let mut proposals = Vec::new();
+        let mut needed_concepts = concepts;
+        let mut needed_outcomes = learning_outcomes;
+        let mut deadline_exceeded = false;
+
+        for round in 0..MAX_GENERATION_ROUNDS {
+            if (needed_concepts == 0 && needed_outcomes == 0) || healthy.is_empty() {
+                break;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                deadline_exceeded = true;
+                break;
+            }
+
+            let round_span = info_span!("round", round);
+            let concept_shares = split_evenly(needed_concepts, healthy.len());
+            let outcome_shares = split_evenly(needed_outcomes, healthy.len());
+
+            let mut requests = JoinSet::new();
+            for (slot, &backend_index) in healthy.iter().enumerate() {
+                let (share_c, share_lo) = (concept_shares[slot], outcome_shares[slot]);
+                if share_c == 0 && share_lo == 0 {
+                    continue;
+                }
+                let backend = Arc::clone(&backends[backend_index]);
+                let feedback = Arc::clone(&feedback);
+                let topic = topic.clone();
+                contributions[backend_index].requests += 1;
+                let request_span = info_span!(
+                    parent: &round_span,
+                    "llm.request",
+                    model = backend.model_name(),
+                    kind = "nodes",
+                    prompt_tokens = field::Empty,
+                    completion_tokens = field::Empty,
+                );
+                requests.spawn(
+                    async move {
+                        let result = backend
+                            .propose_nodes(share_c, share_lo, topic.as_deref(), &feedback)
+                            .await;
+                        if let Ok(response) = &result {
+                            trace::record_usage(&Span::current(), response.usage);
+                        }
+                        (backend_index, result)
+                    }
+                    .instrument(request_span),
+                );
+            }
+
+            let mut results = Vec::new();
+            async {
+                loop {
+                    let joined = match deadline {
+                        Some(deadline) => match timeout_at(deadline, requests.join_next()).await {
+                            Ok(joined) => joined,
+                            Err(_) => {
+                                warn!(pending = requests.len(), "node_generator.deadline_exceeded");
+                                requests.abort_all();
+                                deadline_exceeded = true;
+                                break;
+                            }
+                        },
+                        None => requests.join_next().await,
+                    };
+                    let Some(joined) = joined else {
+                        break;
+                    };
+                    match joined {
+                        Ok(result) => results.push(result),
+                        Err(err) => warn!(error = %err, "node_generator.request_panicked"),
+                    }
+                }
+            }
+            .instrument(round_span)
+            .await;
+            results.sort_by_key(|(backend_index, _)| *backend_index);
+
+            for (backend_index, result) in results {
+                let contribution = &mut contributions[backend_index];
+                match result {
+                    Ok(response) => {
+                        contribution.usage.add(response.usage);
+                        contribution.schema.merge(&response.audit);
+                        for proposal in response.proposals {
+                            let needed = match proposal.kind {
+                                NodeKind::Concept => &mut needed_concepts,
+                                NodeKind::LearningOutcome => &mut needed_outcomes,
+                            };
+                            if *needed == 0 {
+                                continue;
+                            }
+                            if !seen.insert(normalize_text(&proposal.text)) {
+                                contribution.duplicates += 1;
+                                continue;
+                            }
+                            *needed -= 1;
+                            contribution.contributed += 1;
+                            proposals.push(proposal);
+                        }
+                    }
+                    Err(err) => {
+                        warn!(model = %contribution.model, error = %err, "node_generator.llm_failed");
+                        contribution.error = Some(err.to_string());
+                        healthy.retain(|&index| index != backend_index);
+                    }
+                }
+            }
+        }
+
+        NodeBatch {
+            proposals,
+            metadata: GenerationMetadata {
+                contributions,
+                used_fallback: false,
+                deadline_exceeded,
+            },
+        }
     }
 
-    fn fallback_nodes(concepts: usize, learning_outcomes: usize) -> Vec<NodeProposal> {
+    /// Template proposals; with a `topic`, every text names it so topics
+    /// sharing a run do not collide.
+    fn fallback_nodes(
+        concepts: usize,
+        learning_outcomes: usize,
+        seed: Option<u64>,
+        topic: Option<&str>,
+        rules: &NodeRules,
+    ) -> Vec<NodeProposal> {
         const CONCEPT_SUBJECTS: [&str; 6] = [
             "Students",
             "Learners",
@@ -108,53 +387,121 @@ impl NodeGenerator {
         ];
         const MAX_TAGS: usize = 3;
 
+        // Shuffling each list keeps every index combination distinct.
+        let mut rng = seed.map(StdRng::seed_from_u64);
+        let mut shuffled = |words: &[&'static str]| {
+            let mut words = words.to_vec();
+            if let Some(rng) = rng.as_mut() {
+                words.shuffle(rng);
+            }
+            words
+        };
+        let concept_subjects = shuffled(&CONCEPT_SUBJECTS);
+        let concept_verbs = shuffled(&CONCEPT_VERBS);
+        let concept_objects = shuffled(&CONCEPT_OBJECTS);
+        let concept_purposes = shuffled(&CONCEPT_PURPOSES);
+        let lo_verbs = shuffled(&LO_VERBS);
+        let lo_objects = shuffled(&LO_OBJECTS);
+        let lo_contexts = shuffled(&LO_CONTEXTS);
+
         let mut proposals = Vec::with_capacity(concepts + learning_outcomes);
 
         for i in 0..concepts {
-            let subject = CONCEPT_SUBJECTS[i % CONCEPT_SUBJECTS.len()];
-            let verb = CONCEPT_VERBS[(i / CONCEPT_SUBJECTS.len()) % CONCEPT_VERBS.len()];
-            let object = CONCEPT_OBJECTS
+            let subject = concept_subjects[i % CONCEPT_SUBJECTS.len()];
+            let verb = concept_verbs[(i / CONCEPT_SUBJECTS.len()) % CONCEPT_VERBS.len()];
+            let object = concept_objects
                 [(i / (CONCEPT_SUBJECTS.len() * CONCEPT_VERBS.len())) % CONCEPT_OBJECTS.len()];
-            let purpose = CONCEPT_PURPOSES[(i
+            let purpose = concept_purposes[(i
                 / (CONCEPT_SUBJECTS.len() * CONCEPT_VERBS.len() * CONCEPT_OBJECTS.len()))
                 % CONCEPT_PURPOSES.len()];
-            let sentence = format!("{subject} {verb} {object} to {purpose}.");
-            let level = (i % ((MAX_NODE_LEVEL as usize) + 1)) as u8;
-            let tags = Self::fallback_tags(i, 1, MAX_TAGS);
+            let sentence = in_topic(format!("{subject} {verb} {object} to {purpose}"), topic);
+            let level = (i % (rules.max_level as usize + 1)) as u8;
+            let tags = Self::fallback_tags(i, 1, MAX_TAGS, &rules.allowed_tags);
             proposals.push(NodeProposal {
                 kind: NodeKind::Concept,
                 granularity: Granularity::Sentence,
                 level,
                 text: sentence,
                 tags,
+                generator: Some(GeneratorInfo::fallback()),
+                namespace: None,
+                topic: None,
             });
         }
 
+        // Outcomes open with the first configured prefix so they pass validation.
+        let opening = rules
+            .allowed_lo_prefixes
+            .first()
+            .map_or("I can", |prefix| prefix.trim());
         for i in 0..learning_outcomes {
-            let verb = LO_VERBS[i % LO_VERBS.len()];
-            let object = LO_OBJECTS[(i / LO_VERBS.len()) % LO_OBJECTS.len()];
+            let verb = lo_verbs[i % LO_VERBS.len()];
+            let object = lo_objects[(i / LO_VERBS.len()) % LO_OBJECTS.len()];
             let context =
-                LO_CONTEXTS[(i / (LO_VERBS.len() * LO_OBJECTS.len())) % LO_CONTEXTS.len()];
-            let sentence = format!("I can {verb} {object} {context}.");
-            let mut level = MAX_NODE_LEVEL.saturating_sub(1) + (i as u8 % 2);
-            if level > MAX_NODE_LEVEL {
-                level = MAX_NODE_LEVEL;
-            }
-            let tags = Self::fallback_tags(concepts + i, 2, MAX_TAGS);
+                lo_contexts[(i / (LO_VERBS.len() * LO_OBJECTS.len())) % LO_CONTEXTS.len()];
+            let sentence = in_topic(format!("{opening} {verb} {object} {context}"), topic);
+            let max_level = rules.max_level;
+            let level = (max_level.saturating_sub(1) + (i as u8 % 2)).min(max_level);
+            let tags = Self::fallback_tags(concepts + i, 2, MAX_TAGS, &rules.allowed_tags);
             proposals.push(NodeProposal {
                 kind: NodeKind::LearningOutcome,
                 granularity: Granularity::Sentence,
                 level,
                 text: sentence,
                 tags,
+                generator: Some(GeneratorInfo::fallback()),
+                namespace: None,
+                topic: None,
             });
         }
 
         proposals
     }
 
-    fn fallback_tags(seed: usize, desired: usize, max_tags: usize) -> Option<Vec<String>> {
-        if desired == 0 {
+    /// Fallback proposals that skip texts already in the feedback inventory.
+    fn fallback_excluding(
+        concepts: usize,
+        learning_outcomes: usize,
+        seed: Option<u64>,
+        topic: Option<&str>,
+        rules: &NodeRules,
+        feedback: &RoundFeedback,
+    ) -> Vec<NodeProposal> {
+        let known: HashSet<String> = feedback
+            .inventory
+            .iter()
+            .map(|(_, _, _, text, _)| normalize_text(text))
+            .collect();
+        let (mut needed_concepts, mut needed_outcomes) = (concepts, learning_outcomes);
+        Self::fallback_nodes(
+            concepts + known.len(),
+            learning_outcomes + known.len(),
+            seed,
+            topic,
+            rules,
+        )
+        .into_iter()
+        .filter(|proposal| !known.contains(&normalize_text(&proposal.text)))
+        .filter(|proposal| {
+            let needed = match proposal.kind {
+                NodeKind::Concept => &mut needed_concepts,
+                NodeKind::LearningOutcome => &mut needed_outcomes,
+            };
+            let keep = *needed > 0;
+            *needed = needed.saturating_sub(1);
+            keep
+        })
+        .collect()
+    }
+
+    fn fallback_tags(
+        seed: usize,
+        desired: usize,
+        max_tags: usize,
+        allowed: &TagRegistry,
+    ) -> Option<Vec<String>> {
+        let allowed = allowed.tags();
+        if desired == 0 || allowed.is_empty() {
             return None;
         }
         let mut tags = Vec::new();
@@ -162,7 +509,7 @@ impl NodeGenerator {
             if tags.len() >= max_tags {
                 break;
             }
-            let tag = ALLOWED_TAGS[(seed + offset) % ALLOWED_TAGS.len()].to_string();
+            let tag = allowed[(seed + offset) % allowed.len()].clone();
             if !tags.contains(&tag) {
                 tags.push(tag);
             }
@@ -171,40 +518,503 @@ impl NodeGenerator {
     }
 }
 
+/// Finish a fallback sentence, naming `topic` when there is one.
+fn in_topic(sentence: String, topic: Option<&str>) -> String {
+    match topic.map(|topic| topic.trim_end_matches(['.', '!', '?'])) {
+        Some(topic) if !topic.is_empty() => format!("{sentence} in {topic}."),
+        _ => format!("{sentence}."),
+    }
+}
+
+/// Split `total` into `parts` near-equal shares, front-loading the remainder.
+pub fn split_evenly(total: usize, parts: usize) -> Vec<usize> {
+    if parts == 0 {
+        return Vec::new();
+    }
+    (0..parts)
+        .map(|part| total / parts + usize::from(part < total % parts))
+        .collect()
+}
+
 impl Message<GenerateNodes> for NodeGenerator {
-    type Reply = Vec<NodeProposal>;
+    type Reply = NodeBatch;
 
     fn handle(
         &mut self,
         msg: GenerateNodes,
         _ctx: &mut Context<Self, Self::Reply>,
     ) -> impl std::future::Future<Output = Self::Reply> + Send {
-        let llm = self.llm.clone();
-        let concepts = if msg.concepts == 0 {
+        let backends = self.backends.clone();
+        let literal = msg.feedback.is_some();
+        let concepts = if msg.concepts == 0 && !literal {
             self.config.default_concepts
         } else {
             msg.concepts
         };
-        let learning_outcomes = if msg.learning_outcomes == 0 {
+        let learning_outcomes = if msg.learning_outcomes == 0 && !literal {
             self.config.default_learning_outcomes
         } else {
             msg.learning_outcomes
         };
 
+        let deadline = msg.timeout.map(|timeout| Instant::now() + timeout);
+        let span = msg.span;
+        let seed = self.config.seed;
+        let rules = self.config.node_rules.clone();
+        let feedback = Arc::new(msg.feedback.unwrap_or_default());
+        let topic = msg.topic;
+
         async move {
-            if let Some(client) = llm {
-                match client.generate_nodes(concepts, learning_outcomes).await {
-                    Ok(nodes) if !nodes.is_empty() => return nodes,
-                    Ok(_) => {
-                        warn!("node_generator.llm_returned_empty_batch");
-                    }
-                    Err(err) => {
-                        warn!(error = %err, "node_generator.llm_failed");
-                    }
+            let mut batch = NodeBatch {
+                proposals: Vec::new(),
+                metadata:  GenerationMetadata::default(),
+            };
+            if !backends.is_empty() {
+                batch = NodeGenerator::generate_from_backends(
+                    backends,
+                    concepts,
+                    learning_outcomes,
+                    deadline,
+                    topic.as_deref().map(Arc::from),
+                    Arc::clone(&feedback),
+                )
+                .await;
+                if batch.proposals.is_empty() {
+                    warn!("node_generator.llm_returned_empty_batch");
+                }
+            }
+
+            if batch.proposals.is_empty() {
+                batch.proposals = NodeGenerator::fallback_excluding(
+                    concepts,
+                    learning_outcomes,
+                    seed,
+                    topic.as_deref(),
+                    &rules,
+                    &feedback,
+                );
+                batch.metadata.used_fallback = true;
+            }
+            if topic.is_some() {
+                for proposal in &mut batch.proposals {
+                    proposal.topic = topic.clone();
                 }
             }
+            batch
+        }
+        .instrument(span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, sync::Mutex};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::llm::{BoxFuture, NodeResponse};
+
+    type CannedResponse = Result<Vec<NodeProposal>, LlmError>;
+
+    #[derive(Debug)]
+    struct MockBackend {
+        model:     String,
+        responses: Mutex<VecDeque<CannedResponse>>,
+        requests:  Mutex<Vec<(usize, usize)>>,
+        latency:   Duration,
+    }
+
+    impl MockBackend {
+        fn new(model: &str, responses: Vec<CannedResponse>) -> Arc<Self> {
+            Self::slow(model, responses, Duration::ZERO)
+        }
+
+        fn slow(model: &str, responses: Vec<CannedResponse>, latency: Duration) -> Arc<Self> {
+            Arc::new(Self {
+                model: model.to_string(),
+                responses: Mutex::new(responses.into()),
+                requests: Mutex::new(Vec::new()),
+                latency,
+            })
+        }
+
+        fn requests(&self) -> Vec<(usize, usize)> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    impl NodeBackend for MockBackend {
+        fn model_name(&self) -> &str {
+            &self.model
+        }
+
+        fn propose_nodes<'a>(
+            &'a self,
+            concepts: usize,
+            learning_outcomes: usize,
+            _topic: Option<&'a str>,
+            _feedback: &'a RoundFeedback,
+        ) -> BoxFuture<'a, Result<NodeResponse, LlmError>> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((concepts, learning_outcomes));
+            let response = self
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| Ok(Vec::new()));
+            let usage = TokenUsage {
+                prompt_tokens:     10,
+                completion_tokens: 5,
+            };
+            let latency = self.latency;
+            Box::pin(async move {
+                tokio::time::sleep(latency).await;
+                response.map(|proposals| NodeResponse {
+                    proposals,
+                    usage,
+                    audit: SchemaAudit::default(),
+                })
+            })
+        }
+    }
+
+    fn concepts(texts: &[&str]) -> CannedResponse {
+        Ok(texts
+            .iter()
+            .map(|text| NodeProposal {
+                kind:        NodeKind::Concept,
+                granularity: Granularity::Sentence,
+                level:       0,
+                text:        text.to_string(),
+                tags:        None,
+                generator:   None,
+                namespace:   None,
+                topic:       None,
+            })
+            .collect())
+    }
+
+    #[test]
+    fn seeded_fallback_is_reproducible_and_seed_dependent() {
+        let texts = |seed| -> Vec<String> {
+            NodeGenerator::fallback_nodes(30, 6, seed, None, &NodeRules::default())
+                .into_iter()
+                .map(|proposal| proposal.text)
+                .collect()
+        };
+
+        assert_eq!(texts(Some(7)), texts(Some(7)));
+        assert_ne!(texts(Some(7)), texts(Some(8)));
+        let unique: HashSet<_> = texts(Some(7)).into_iter().collect();
+        assert_eq!(unique.len(), 36);
+        assert!(texts(None)[0].starts_with("Students map"));
+    }
+
+    #[test]
+    fn fallback_levels_stay_under_the_ceiling() {
+        for max_level in [0, 5] {
+            let rules = NodeRules {
+                max_level,
+                ..NodeRules::default()
+            };
+            let proposals = NodeGenerator::fallback_nodes(12, 4, None, None, &rules);
+            assert!(proposals.iter().all(|proposal| proposal.level <= max_level));
+            assert!(proposals.iter().any(|proposal| proposal.level == max_level));
+        }
+    }
+
+    #[test]
+    fn fallback_outcomes_open_with_the_first_configured_prefix() {
+        let rules = NodeRules {
+            allowed_lo_prefixes: vec!["Students will be able to ".to_string()],
+            ..NodeRules::default()
+        };
+        let proposals = NodeGenerator::fallback_nodes(2, 3, None, None, &rules);
+        assert!(
+            proposals
+                .iter()
+                .filter(|proposal| proposal.kind == NodeKind::LearningOutcome)
+                .all(|proposal| proposal.text.starts_with("Students will be able to "))
+        );
+    }
+
+    #[test]
+    fn fallback_skips_texts_already_accepted() {
+        let first_round = NodeGenerator::fallback_nodes(4, 1, Some(3), None, &NodeRules::default());
+        let feedback = RoundFeedback {
+            inventory: first_round
+                .iter()
+                .map(|proposal| {
+                    (
+                        Uuid::new_v4(),
+                        proposal.kind.clone(),
+                        proposal.level,
+                        proposal.text.as_str().into(),
+                        None,
+                    )
+                })
+                .collect(),
+            ..RoundFeedback::default()
+        };
+
+        let next_round = NodeGenerator::fallback_excluding(
+            4,
+            0,
+            Some(3),
+            None,
+            &NodeRules::default(),
+            &feedback,
+        );
+        assert_eq!(next_round.len(), 4);
+        assert!(next_round.iter().all(|proposal| {
+            matches!(proposal.kind, NodeKind::Concept)
+                && first_round
+                    .iter()
+                    .all(|earlier| earlier.text != proposal.text)
+        }));
+    }
+
+    #[tokio::test]
+    async fn topic_batches_are_stamped_and_fallback_names_the_topic() {
+        let generator = NodeGenerator::spawn(NodeGenerator::new(NodeGeneratorConfig::default()));
+        let ask = |topic: &str| {
+            generator.ask(GenerateNodes {
+                concepts:          2,
+                learning_outcomes: 1,
+                timeout:           None,
+                span:              Span::none(),
+                feedback:          None,
+                topic:             Some(topic.to_string()),
+            })
+        };
+        let recursion = ask("Recursion.").await.expect("generate");
+        let testing = ask("Testing").await.expect("generate");
+
+        assert!(recursion.metadata.used_fallback);
+        for proposal in &recursion.proposals {
+            assert_eq!(proposal.topic.as_deref(), Some("Recursion."));
+            assert!(proposal.text.ends_with(" in Recursion."), "{}", proposal.text);
+        }
+        assert!(recursion.proposals.iter().all(|proposal| {
+            testing
+                .proposals
+                .iter()
+                .all(|other| other.text != proposal.text)
+        }));
+    }
+
+    #[tokio::test]
+    async fn failed_backend_share_is_redistributed() {
+        let healthy = MockBackend::new(
+            "healthy",
+            vec![
+                concepts(&["Idea one matters.", "Idea two matters."]),
+                concepts(&["Idea three matters.", "Idea four matters."]),
+            ],
+        );
+        let failing =
+            MockBackend::new("failing", vec![Err(LlmError::RequestFailed("boom".to_string()))]);
+
+        let batch = NodeGenerator::generate_from_backends(
+            vec![healthy.clone(), failing.clone()],
+            4,
+            0,
+            None,
+            None,
+            Arc::default(),
+        )
+        .await;
+
+        assert_eq!(batch.proposals.len(), 4);
+        assert_eq!(healthy.requests(), vec![(2, 0), (2, 0)]);
+        assert_eq!(failing.requests(), vec![(2, 0)]);
+
+        let [healthy_stats, failing_stats] = &batch.metadata.contributions[..] else {
+            panic!("expected one contribution per backend");
+        };
+        assert_eq!(healthy_stats.contributed, 4);
+        assert_eq!(healthy_stats.usage.prompt_tokens, 20);
+        assert_eq!(failing_stats.contributed, 0);
+        assert!(failing_stats.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn duplicates_across_models_are_dropped_and_refilled() {
+        let first = MockBackend::new(
+            "first",
+            vec![
+                concepts(&["Shared idea one.", "Shared idea two."]),
+                concepts(&["Fresh idea five."]),
+            ],
+        );
+        let second = MockBackend::new(
+            "second",
+            vec![concepts(&["Shared idea  TWO.", "Unique idea three."])],
+        );
+
+        let batch = NodeGenerator::generate_from_backends(
+            vec![first.clone(), second.clone()],
+            4,
+            0,
+            None,
+            None,
+            Arc::default(),
+        )
+        .await;
+
+        let texts: Vec<_> = batch.proposals.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Shared idea one.",
+                "Shared idea two.",
+                "Unique idea three.",
+                "Fresh idea five.",
+            ]
+        );
+        assert_eq!(first.requests(), vec![(2, 0), (1, 0)]);
+        assert_eq!(second.requests(), vec![(2, 0)]);
+
+        let stats = &batch.metadata.contributions;
+        assert_eq!((stats[0].contributed, stats[0].duplicates), (3, 0));
+        assert_eq!((stats[1].contributed, stats[1].duplicates), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn deadline_aborts_slow_backends_and_keeps_partial_results() {
+        let fast =
+            MockBackend::new("fast", vec![concepts(&["Quick idea one.", "Quick idea two."])]);
+        let slow = MockBackend::slow(
+            "slow",
+            vec![concepts(&["Late idea three.", "Late idea four."])],
+            Duration::from_secs(30),
+        );
+        let deadline = Instant::now() + Duration::from_millis(50);
+
+        let batch = NodeGenerator::generate_from_backends(
+            vec![fast.clone(), slow.clone()],
+            4,
+            0,
+            Some(deadline),
+            None,
+            Arc::default(),
+        )
+        .await;
+
+        assert!(batch.metadata.deadline_exceeded);
+        let texts: Vec<_> = batch.proposals.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(texts, vec!["Quick idea one.", "Quick idea two."]);
+        assert_eq!(fast.requests(), vec![(2, 0)], "no refill round after the deadline");
+        assert!(Instant::now() < deadline + Duration::from_secs(5));
+    }
+
+    #[test]
+    fn split_evenly_front_loads_remainder() {
+        assert_eq!(split_evenly(5, 2), vec![3, 2]);
+        assert_eq!(split_evenly(1, 3), vec![1, 0, 0]);
+        assert!(split_evenly(4, 0).is_empty());
+    }
+
+    /// Span name, parent span name and the fields recorded on it.
+    type CapturedSpan = (String, Option<String>, Vec<String>);
+
+    #[derive(Clone, Default)]
+    struct SpanCapture(Arc<Mutex<Vec<CapturedSpan>>>);
+
+    struct FieldNames<'a>(&'a mut Vec<String>);
+
+    impl tracing::field::Visit for FieldNames<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, _: &dyn std::fmt::Debug) {
+            self.0.push(field.name().to_string());
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for SpanCapture
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let parent = ctx
+                .span(id)
+                .and_then(|span| span.parent())
+                .map(|parent| parent.name().to_string());
+            let mut fields = Vec::new();
+            attrs.record(&mut FieldNames(&mut fields));
+            let name = attrs.metadata().name().to_string();
+            self.0.lock().unwrap().push((name, parent, fields));
+        }
+
+        fn on_record(
+            &self,
+            id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let Some(name) = ctx.span(id).map(|span| span.name()) else {
+                return;
+            };
+            let mut spans = self.0.lock().unwrap();
+            if let Some((_, _, fields)) = spans.iter_mut().rev().find(|(n, ..)| n == name) {
+                values.record(&mut FieldNames(fields));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn generation_spans_nest_under_the_calling_phase() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let capture = SpanCapture::default();
+        let _default =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+        let backend = MockBackend::new("scripted", vec![concepts(&["Spans nest cleanly."])]);
+        let generator = NodeGenerator::spawn(NodeGenerator::with_backends(
+            NodeGeneratorConfig::default(),
+            vec![backend],
+        ));
+        let run = info_span!("run", topic = "Tracing");
+        let phase = info_span!(parent: &run, "phase", name = "Generating nodes");
+        generator
+            .ask(GenerateNodes {
+                concepts:          1,
+                learning_outcomes: 0,
+                timeout:           None,
+                span:              phase,
+                feedback:          None,
+                topic:             None,
+            })
+            .await
+            .expect("generate");
+
+        let spans = capture.0.lock().unwrap().clone();
+        let parent_of = |name: &str| {
+            spans
+                .iter()
+                .find(|(span, ..)| span == name)
+                .unwrap_or_else(|| panic!("missing span {name}: {spans:?}"))
+                .1
+                .clone()
+        };
+        assert_eq!(parent_of("phase").as_deref(), Some("run"));
+        assert_eq!(parent_of("round").as_deref(), Some("phase"));
+        assert_eq!(parent_of("llm.request").as_deref(), Some("round"));
 
-            NodeGenerator::fallback_nodes(concepts, learning_outcomes)
+        let (_, _, fields) = spans
+            .iter()
+            .find(|(name, ..)| name == "llm.request")
+            .unwrap();
+        for field in ["model", "kind", "prompt_tokens", "completion_tokens"] {
+            assert!(fields.iter().any(|recorded| recorded == field), "{field} in {fields:?}");
         }
     }
 }
@@ -0,0 +1,244 @@
+use std::{io::Write, time::Duration};
+
+use indicatif::{ProgressBar, ProgressStyle};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::viz::{Event, Phase};
+
+/// Running tallies shown by the progress display.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgressCounters {
+    pub phase:            Option<Phase>,
+    pub nodes_accepted:   usize,
+    pub nodes_rejected:   usize,
+    pub edges_accepted:   usize,
+    pub edges_rejected:   usize,
+    pub completed_phases: Vec<Phase>,
+}
+
+enum Backend {
+    Terminal(Option<ProgressBar>),
+    Plain(Box<dyn Write + Send>),
+}
+
+/// Event consumer that renders run progress for terminal runs.
+///
+/// The terminal backend draws an indicatif spinner during generation phases
+/// and a bar while proposals are added; the plain backend prints only the
+/// completed-phase lines and is used for tests and non-interactive output.
+pub struct ProgressRenderer {
+    backend:  Backend,
+    counters: ProgressCounters,
+}
+
+impl ProgressRenderer {
+    pub fn terminal() -> Self {
+        Self {
+            backend:  Backend::Terminal(None),
+            counters: ProgressCounters::default(),
+        }
+    }
+
+    pub fn plain(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            backend:  Backend::Plain(Box::new(writer)),
+            counters: ProgressCounters::default(),
+        }
+    }
+
+    pub fn counters(&self) -> &ProgressCounters {
+        &self.counters
+    }
+
+    pub async fn run(mut self, mut rx: UnboundedReceiver<Event>) {
+        while let Some(event) = rx.recv().await {
+            self.handle_event(&event);
+        }
+        if let Backend::Terminal(Some(bar)) = &self.backend {
+            bar.finish_and_clear();
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::PhaseStarted { phase, expected } => self.start_phase(*phase, *expected),
+            Event::PhaseFinished { phase, detail } => self.finish_phase(*phase, detail),
+            Event::NodeAccepted { .. } => {
+                self.counters.nodes_accepted += 1;
+                self.advance();
+            }
+            Event::NodeRejected { .. } => {
+                self.counters.nodes_rejected += 1;
+                self.advance();
+            }
+            Event::EdgeAccepted { .. } => {
+                self.counters.edges_accepted += 1;
+                self.advance();
+            }
+            Event::EdgeRejected { .. } => {
+                self.counters.edges_rejected += 1;
+                self.advance();
+            }
+            Event::NodeUpdated { .. }
+            | Event::NodeRemoved { .. }
+            | Event::EdgeRemoved { .. }
+            | Event::SummaryLine { .. }
+            | Event::CapacityReached { .. }
+            | Event::CoverageWarning { .. }
+            | Event::BatchStarted { .. }
+            | Event::BatchCompleted { .. } => {}
+        }
+    }
+
+    fn start_phase(&mut self, phase: Phase, expected: usize) {
+        self.counters.phase = Some(phase);
+        let Backend::Terminal(slot) = &mut self.backend else {
+            return;
+        };
+
+        let bar = if phase.is_generation() {
+            let bar = ProgressBar::new_spinner()
+                .with_style(
+                    ProgressStyle::with_template("{spinner} {msg} [{elapsed}]")
+                        .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+                )
+                .with_message(phase.to_string());
+            bar.enable_steady_tick(Duration::from_millis(120));
+            bar
+        } else {
+            ProgressBar::new(expected as u64).with_style(
+                ProgressStyle::with_template("{msg} [{bar:30}] {pos}/{len}")
+                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
+            )
+        };
+        if let Some(previous) = slot.replace(bar) {
+            previous.finish_and_clear();
+        }
+        self.advance_message();
+    }
+
+    fn finish_phase(&mut self, phase: Phase, detail: &str) {
+        self.counters.completed_phases.push(phase);
+        if self.counters.phase == Some(phase) {
+            self.counters.phase = None;
+        }
+
+        let line = format!("done: {phase} ({detail})");
+        match &mut self.backend {
+            Backend::Terminal(slot) => match slot.take() {
+                Some(bar) => {
+                    let elapsed = bar.elapsed();
+                    bar.finish_and_clear();
+                    bar.println(format!("{line} in {:.1}s", elapsed.as_secs_f64()));
+                }
+                None => println!("{line}"),
+            },
+            Backend::Plain(writer) => {
+                let _ = writeln!(writer, "{line}");
+            }
+        }
+    }
+
+    fn advance(&mut self) {
+        if let Backend::Terminal(Some(bar)) = &self.backend {
+            bar.inc(1);
+        }
+        self.advance_message();
+    }
+
+    fn advance_message(&self) {
+        let Backend::Terminal(Some(bar)) = &self.backend else {
+            return;
+        };
+        let Some(phase) = self.counters.phase else {
+            return;
+        };
+        let (accepted, rejected) = match phase {
+            Phase::AddingNodes => (self.counters.nodes_accepted, self.counters.nodes_rejected),
+            Phase::AddingEdges => (self.counters.edges_accepted, self.counters.edges_rejected),
+            Phase::GeneratingNodes | Phase::GeneratingEdges => return,
+        };
+        bar.set_message(format!("{phase}: {accepted} accepted, {rejected} rejected"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::model::{NodeKind, Relation};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn scripted_events_update_counters_and_phase_lines() {
+        let buffer = SharedBuffer::default();
+        let mut renderer = ProgressRenderer::plain(buffer.clone());
+
+        let script = vec![
+            Event::PhaseStarted {
+                phase:    Phase::GeneratingNodes,
+                expected: 0,
+            },
+            Event::PhaseFinished {
+                phase:  Phase::GeneratingNodes,
+                detail: "3 proposals".to_string(),
+            },
+            Event::PhaseStarted {
+                phase:    Phase::AddingNodes,
+                expected: 3,
+            },
+            Event::NodeAccepted {
+                id:           Uuid::new_v4(),
+                kind:         NodeKind::Concept,
+                level:        0,
+                tags:         None,
+                text:         "A.".into(),
+                dropped_tags: Vec::new(),
+            },
+            Event::NodeAccepted {
+                id:           Uuid::new_v4(),
+                kind:         NodeKind::Concept,
+                level:        0,
+                tags:         None,
+                text:         "B.".into(),
+                dropped_tags: Vec::new(),
+            },
+            Event::NodeRejected {
+                text:   "B.".to_string(),
+                reason: "duplicate node within batch".to_string(),
+            },
+            Event::EdgeRejected {
+                relation: Relation::Supports,
+                reason:   "edge rationale missing".to_string(),
+            },
+        ];
+        for event in &script {
+            renderer.handle_event(event);
+        }
+
+        let counters = renderer.counters();
+        assert_eq!(counters.phase, Some(Phase::AddingNodes));
+        assert_eq!((counters.nodes_accepted, counters.nodes_rejected), (2, 1));
+        assert_eq!((counters.edges_accepted, counters.edges_rejected), (0, 1));
+        assert_eq!(counters.completed_phases, vec![Phase::GeneratingNodes]);
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output, "done: Generating nodes (3 proposals)\n");
+    }
+}
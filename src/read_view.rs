@@ -0,0 +1,79 @@
+use std::{collections::HashMap, sync::Arc};
+
+use arc_swap::ArcSwap;
+use uuid::Uuid;
+
+use crate::{
+    graph::{GraphSnapshot, GraphStore},
+    model::{InventoryEntry, Relation},
+    summary::Summary,
+};
+
+/// Immutable, derived view of the graph as of the last completed batch.
+#[derive(Debug)]
+pub struct GraphReadView {
+    pub snapshot:  GraphSnapshot,
+    pub inventory: Vec<InventoryEntry>,
+    /// Outgoing edges keyed by source node.
+    pub adjacency: HashMap<Uuid, Vec<(Uuid, Relation)>>,
+    /// Graph statistics and coverage, without the adder's capacity or
+    /// generator fields.
+    pub summary:   Summary,
+}
+
+impl GraphReadView {
+    /// The view of `store`, checking coverage against `min_supports`.
+    pub fn from_store(store: &GraphStore, min_supports: usize) -> Self {
+        let snapshot = store.snapshot();
+        let mut adjacency: HashMap<Uuid, Vec<(Uuid, Relation)>> = HashMap::new();
+        for edge in &snapshot.edges {
+            adjacency
+                .entry(edge.from)
+                .or_default()
+                .push((edge.to, edge.relation.clone()));
+        }
+
+        let mut summary = Summary::from_store(store);
+        summary.check_coverage(store, min_supports);
+        Self {
+            inventory: store.inventory(),
+            summary,
+            adjacency,
+            snapshot,
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.snapshot.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.snapshot.edges.len()
+    }
+}
+
+/// Shared handle for reading the graph without messaging the adder.
+///
+/// The adder publishes a fresh [`GraphReadView`] after every mutation batch,
+/// so readers see the last completed batch and never a partially applied one.
+#[derive(Debug, Clone)]
+pub struct ReadHandle {
+    view: Arc<ArcSwap<GraphReadView>>,
+}
+
+impl ReadHandle {
+    pub fn new(view: GraphReadView) -> Self {
+        Self {
+            view: Arc::new(ArcSwap::from_pointee(view)),
+        }
+    }
+
+    /// Current view; holding the returned Arc pins that version.
+    pub fn load(&self) -> Arc<GraphReadView> {
+        self.view.load_full()
+    }
+
+    pub(crate) fn publish(&self, view: GraphReadView) {
+        self.view.store(Arc::new(view));
+    }
+}
@@ -0,0 +1,533 @@
+//! Line-oriented shell for curating a graph by hand.
+//!
+//! Every command goes through the same [`GraphAdder`] as generated
+//! proposals, so validation, duplicate detection and viewer events are
+//! unchanged; rejections are printed with their reason.
+
+use std::{io::Write, path::PathBuf};
+
+use kameo::actor::ActorRef;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+use uuid::Uuid;
+
+use crate::{
+    adder::{AddEdges, AddNodes, GraphAdder, Summarize},
+    export::{CSV_FILES, DotExportOptions, ExportFormat, ExportRequest, is_stdout, write_exports},
+    model::{
+        Decision, EdgeProposal, GeneratorInfo, Granularity, NodeKind, NodeProposal, Relation,
+        short_id,
+    },
+    read_view::ReadHandle,
+};
+
+const HELP: &str = "\
+Commands:
+  add-concept \"TEXT\" [--level N] [--tags TAG,TAG]   Propose a concept node
+  add-lo \"TEXT\" [--level N] [--tags TAG,TAG]        Propose a learning outcome
+  link FROM TO prereq|supports|related \"RATIONALE\"  Propose an edge; ids may be prefixes
+  list                                              Print nodes and edges
+  summary                                           Print graph statistics
+  export FORMAT PATH                                Write dot, json, lms, graphml, mermaid,
+                                                    cytoscape, or gexf; '-' is stdout
+  export csv DIR                                    Write nodes.csv and edges.csv into DIR
+  history                                           Print earlier commands
+  !N                                                Repeat command N from history
+  help                                              Print this help
+  quit                                              Leave the shell";
+
+/// Whether the shell keeps reading after a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Continue,
+    Quit,
+}
+
+/// Curation shell over a running adder.
+pub struct Shell {
+    adder:   ActorRef<GraphAdder>,
+    view:    ReadHandle,
+    /// Recorded by LMS exports.
+    topic:   Option<String>,
+    history: Vec<String>,
+    prompt:  bool,
+}
+
+impl Shell {
+    pub fn new(adder: ActorRef<GraphAdder>, view: ReadHandle) -> Self {
+        Self {
+            adder,
+            view,
+            topic: None,
+            history: Vec::new(),
+            prompt: false,
+        }
+    }
+
+    pub fn with_topic(mut self, topic: Option<String>) -> Self {
+        self.topic = topic;
+        self
+    }
+
+    /// Print a prompt before each line, for interactive input.
+    pub fn with_prompt(mut self, prompt: bool) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    /// Run commands from `input` until `quit` or end of input.
+    pub async fn run(
+        &mut self,
+        input: impl AsyncBufRead + Unpin,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let mut lines = input.lines();
+        loop {
+            if self.prompt {
+                write!(out, "weaver> ")?;
+                out.flush()?;
+            }
+            let Some(line) = lines.next_line().await? else {
+                break;
+            };
+            if self.execute(&line, out).await? == Step::Quit {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Run one command line, printing its result or error to `out`.
+    pub async fn execute(&mut self, line: &str, out: &mut impl Write) -> std::io::Result<Step> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(Step::Continue);
+        }
+        let line = match line.strip_prefix('!') {
+            Some(number) => match number
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| self.history.get(number.checked_sub(1)?))
+            {
+                Some(earlier) => {
+                    writeln!(out, "{earlier}")?;
+                    earlier.clone()
+                }
+                None => {
+                    writeln!(out, "error: no command {number} in history")?;
+                    return Ok(Step::Continue);
+                }
+            },
+            None => line.to_string(),
+        };
+        if line != "history" {
+            self.history.push(line.clone());
+        }
+
+        match self.dispatch(&line, out).await {
+            Ok(step) => Ok(step),
+            Err(CommandError::Io(err)) => Err(err),
+            Err(CommandError::Invalid(message)) => {
+                writeln!(out, "error: {message}")?;
+                Ok(Step::Continue)
+            }
+        }
+    }
+
+    async fn dispatch(&mut self, line: &str, out: &mut impl Write) -> Result<Step, CommandError> {
+        let words = split_words(line)?;
+        let (command, args) = words
+            .split_first()
+            .ok_or_else(|| CommandError::Invalid("empty command".to_string()))?;
+        match command.as_str() {
+            "add-concept" => self.add_node(NodeKind::Concept, args, out).await?,
+            "add-lo" => self.add_node(NodeKind::LearningOutcome, args, out).await?,
+            "link" => self.link(args, out).await?,
+            "list" => self.list(out)?,
+            "summary" => self.summary(out).await?,
+            "export" => self.export(args, out).await?,
+            "history" => {
+                for (index, command) in self.history.iter().enumerate() {
+                    writeln!(out, "{:>4}  {command}", index + 1)?;
+                }
+            }
+            "help" => writeln!(out, "{HELP}")?,
+            "quit" | "exit" => return Ok(Step::Quit),
+            other => {
+                return Err(CommandError::Invalid(format!(
+                    "unknown command '{other}' (try 'help')"
+                )));
+            }
+        }
+        Ok(Step::Continue)
+    }
+
+    async fn add_node(
+        &self,
+        kind: NodeKind,
+        args: &[String],
+        out: &mut impl Write,
+    ) -> Result<(), CommandError> {
+        let mut text = None;
+        let mut level = 0;
+        let mut tags = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--level" => {
+                    let raw = args
+                        .next()
+                        .ok_or_else(|| invalid("missing value for --level"))?;
+                    level = raw
+                        .parse()
+                        .map_err(|_| invalid(format!("invalid level '{raw}'")))?;
+                }
+                "--tags" => {
+                    let raw = args
+                        .next()
+                        .ok_or_else(|| invalid("missing value for --tags"))?;
+                    tags = Some(
+                        raw.split(',')
+                            .map(str::trim)
+                            .filter(|tag| !tag.is_empty())
+                            .map(str::to_string)
+                            .collect(),
+                    );
+                }
+                flag if flag.starts_with("--") => {
+                    return Err(invalid(format!("unknown flag '{flag}'")));
+                }
+                _ if text.is_some() => return Err(invalid("quote text that contains spaces")),
+                _ => text = Some(arg.clone()),
+            }
+        }
+        let text = text.ok_or_else(|| invalid("missing node text"))?;
+
+        let proposal = NodeProposal {
+            kind,
+            granularity: Granularity::Sentence,
+            level,
+            text,
+            tags,
+            generator: Some(GeneratorInfo::manual()),
+            namespace: None,
+            topic: None,
+        };
+        let decisions = self
+            .adder
+            .ask(AddNodes(vec![proposal]))
+            .await
+            .map_err(|err| invalid(format!("adder unavailable: {err}")))?;
+        print_decisions(&decisions, out)
+    }
+
+    async fn link(&self, args: &[String], out: &mut impl Write) -> Result<(), CommandError> {
+        let [from, to, relation, rationale] = args else {
+            return Err(invalid("usage: link FROM TO prereq|supports|related \"RATIONALE\""));
+        };
+        let relation = match relation.as_str() {
+            "prereq" | "prerequisite" => Relation::PrerequisiteFor,
+            "supports" => Relation::Supports,
+            "related" => Relation::RelatedTo,
+            other => {
+                return Err(invalid(format!(
+                    "unknown relation '{other}' (expected prereq, supports or related)"
+                )));
+            }
+        };
+        let proposal = EdgeProposal {
+            relation,
+            from_id: self.resolve(from)?,
+            to_id: self.resolve(to)?,
+            rationale: rationale.clone(),
+            generator: Some(GeneratorInfo::manual()),
+        };
+        let decisions = self
+            .adder
+            .ask(AddEdges(vec![proposal]))
+            .await
+            .map_err(|err| invalid(format!("adder unavailable: {err}")))?;
+        print_decisions(&decisions, out)
+    }
+
+    /// The node whose id is or starts with `prefix`, hyphens ignored.
+    fn resolve(&self, prefix: &str) -> Result<Uuid, CommandError> {
+        let view = self.view.load();
+        let digits = prefix.replace('-', "");
+        let mut matches = view
+            .snapshot
+            .nodes
+            .iter()
+            .filter(|node| node.id.simple().to_string().starts_with(&digits));
+        match (matches.next(), matches.next()) {
+            (Some(node), None) => Ok(node.id),
+            (None, _) => Err(invalid(format!("no node id starts with '{prefix}'"))),
+            (Some(_), Some(_)) => Err(invalid(format!("'{prefix}' matches several node ids"))),
+        }
+    }
+
+    fn list(&self, out: &mut impl Write) -> Result<(), CommandError> {
+        let view = self.view.load();
+        let snapshot = &view.snapshot;
+        writeln!(out, "Nodes ({}):", snapshot.nodes.len())?;
+        for node in &snapshot.nodes {
+            let tags = node
+                .tags
+                .as_deref()
+                .map(|tags| format!(" [{}]", tags.join(", ")))
+                .unwrap_or_default();
+            writeln!(
+                out,
+                "  {} {:?} L{}{tags}: {}",
+                short_id(&node.id),
+                node.kind,
+                node.level,
+                node.text
+            )?;
+        }
+        writeln!(out, "Edges ({}):", snapshot.edges.len())?;
+        for edge in &snapshot.edges {
+            writeln!(
+                out,
+                "  {} {:?} {}: {}",
+                short_id(&edge.from),
+                edge.relation,
+                short_id(&edge.to),
+                edge.rationale
+            )?;
+        }
+        Ok(())
+    }
+
+    async fn summary(&self, out: &mut impl Write) -> Result<(), CommandError> {
+        let summary = self
+            .adder
+            .ask(Summarize)
+            .await
+            .map_err(|err| invalid(format!("adder unavailable: {err}")))?;
+        writeln!(
+            out,
+            "Nodes: {} (concept={}, learning_outcome={})",
+            summary.total_nodes, summary.concepts, summary.learning_outcomes
+        )?;
+        writeln!(
+            out,
+            "Edges: {} (prerequisite_for={}, supports={}, related_to={})",
+            summary.total_edges,
+            summary.prerequisite_edges,
+            summary.supports_edges,
+            summary.related_edges
+        )?;
+        writeln!(
+            out,
+            "Prerequisite DAG: {}",
+            if summary.prerequisite_dag_ok {
+                "OK"
+            } else {
+                "cycle detected"
+            }
+        )?;
+        Ok(())
+    }
+
+    async fn export(&self, args: &[String], out: &mut impl Write) -> Result<(), CommandError> {
+        let [format, path] = args else {
+            return Err(invalid(
+                "usage: export dot|json|lms|graphml|mermaid|cytoscape|gexf PATH, or export csv DIR",
+            ));
+        };
+        let path = PathBuf::from(path);
+        let targets = match format.as_str() {
+            "dot" => vec![(ExportFormat::Dot, path)],
+            "json" => vec![(ExportFormat::Json, path)],
+            "lms" => vec![(ExportFormat::Lms, path)],
+            "graphml" => vec![(ExportFormat::Graphml, path)],
+            "mermaid" => vec![(ExportFormat::Mermaid, path)],
+            "cytoscape" => vec![(ExportFormat::Cytoscape, path)],
+            "gexf" => vec![(ExportFormat::Gexf, path)],
+            "csv" if is_stdout(&path) => {
+                return Err(invalid("csv export writes two files and needs a directory"));
+            }
+            "csv" => CSV_FILES
+                .map(|(format, name)| (format, path.join(name)))
+                .into(),
+            other => return Err(invalid(format!("unknown export format '{other}'"))),
+        };
+        let requests = targets
+            .into_iter()
+            .map(|(format, path)| ExportRequest {
+                format,
+                path,
+                check: false,
+                namespace: None,
+                topic: self.topic.clone(),
+                dot: DotExportOptions::default(),
+            })
+            .collect();
+        let snapshot = self.view.load().snapshot.clone();
+        for outcome in write_exports(snapshot, requests).await {
+            match outcome.error {
+                None => writeln!(
+                    out,
+                    "{} written to {}",
+                    outcome.format.label(),
+                    outcome.path.display()
+                )?,
+                Some(err) => writeln!(
+                    out,
+                    "error: failed to write {} to {}: {err}",
+                    outcome.format.label(),
+                    outcome.path.display()
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+enum CommandError {
+    /// Reported to the user; the shell keeps going.
+    Invalid(String),
+    /// The output itself failed.
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(err: std::io::Error) -> Self {
+        CommandError::Io(err)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> CommandError {
+    CommandError::Invalid(message.into())
+}
+
+fn print_decisions(decisions: &[Decision], out: &mut impl Write) -> Result<(), CommandError> {
+    for decision in decisions {
+        match (decision.accepted, decision.assigned_id) {
+            (true, Some(id)) => writeln!(out, "accepted {}", short_id(&id))?,
+            (true, None) => writeln!(out, "accepted")?,
+            (false, _) => writeln!(
+                out,
+                "rejected: {}",
+                decision.reason.as_deref().unwrap_or("no reason given")
+            )?,
+        }
+        for warning in &decision.warnings {
+            writeln!(out, "warning: {warning}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Split on whitespace, keeping double-quoted words together.
+fn split_words(line: &str) -> Result<Vec<String>, CommandError> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&next) = chars.peek() {
+        if next.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut word = String::new();
+        if next == '"' {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => word.extend(chars.next()),
+                    Some(other) => word.push(other),
+                    None => return Err(invalid("unterminated quote")),
+                }
+            }
+        } else {
+            while let Some(&other) = chars.peek() {
+                if other.is_whitespace() {
+                    break;
+                }
+                word.push(other);
+                chars.next();
+            }
+        }
+        words.push(word);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use kameo::Actor;
+
+    use super::*;
+    use crate::graph::GraphStore;
+
+    async fn run(shell: &mut Shell, lines: &[&str]) -> String {
+        let mut out = Vec::new();
+        for line in lines {
+            shell
+                .execute(line, &mut out)
+                .await
+                .expect("writes to memory");
+        }
+        String::from_utf8(out).expect("utf-8 output")
+    }
+
+    #[test]
+    fn quoted_words_stay_together() {
+        let words = split_words(r#"add-lo "I can \"trace\" loops" --level 2"#).ok();
+        assert_eq!(
+            words,
+            Some(vec![
+                "add-lo".to_string(),
+                "I can \"trace\" loops".to_string(),
+                "--level".to_string(),
+                "2".to_string(),
+            ])
+        );
+        assert!(split_words("add-concept \"open").is_err());
+    }
+
+    #[tokio::test]
+    async fn commands_go_through_the_adder_and_print_rejections() {
+        let adder = GraphAdder::with_event_sender(GraphStore::new(), None);
+        let view = adder.read_handle();
+        let mut shell = Shell::new(GraphAdder::spawn(adder), view.clone());
+
+        let output = run(
+            &mut shell,
+            &[
+                r#"add-concept "Loops repeat statements." --level 1 --tags implementation"#,
+                r#"add-concept "loops repeat  statements.""#,
+                r#"add-lo "I can write a counting loop.""#,
+            ],
+        )
+        .await;
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].starts_with("accepted "), "{output}");
+        assert_eq!(lines[1], "rejected: duplicate node already present");
+        assert!(lines[2].starts_with("accepted "), "{output}");
+
+        let snapshot = view.load().snapshot.clone();
+        let id = |kind: NodeKind| {
+            let node = snapshot
+                .nodes
+                .iter()
+                .find(|node| node.kind == kind)
+                .unwrap();
+            short_id(&node.id)
+        };
+        let link = format!(
+            "link {} {} supports \"Loops are what the outcome practices\"",
+            id(NodeKind::Concept),
+            id(NodeKind::LearningOutcome)
+        );
+        let output = run(&mut shell, &[&link, "summary", "bogus", "!1"]).await;
+        assert!(output.starts_with("accepted\n"), "{output}");
+        assert!(output.contains("Edges: 1 (prerequisite_for=0, supports=1"), "{output}");
+        assert!(output.contains("error: unknown command 'bogus'"), "{output}");
+        assert!(output.ends_with("rejected: duplicate node already present\n"), "{output}");
+
+        let output = run(&mut shell, &["history"]).await;
+        assert!(output.contains("   5  summary"), "{output}");
+        assert_eq!(shell.execute("quit", &mut Vec::new()).await.ok(), Some(Step::Quit));
+    }
+}
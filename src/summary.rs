@@ -1,31 +1,416 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
     graph::GraphStore,
-    model::{NodeKind, Relation},
+    llm::RequestLatency,
+    manifest::PhaseTiming,
+    model::{Decision, NodeKind, Relation, TagDropReason},
 };
 
 /// Aggregate statistics about the current graph state.
-#[derive(Debug, Clone, Serialize, kameo::Reply)]
+#[derive(Debug, Clone, Serialize, Deserialize, kameo::Reply)]
 pub struct Summary {
-    pub total_nodes:           usize,
-    pub concepts:              usize,
-    pub learning_outcomes:     usize,
-    pub total_edges:           usize,
-    pub prerequisite_edges:    usize,
-    pub supports_edges:        usize,
-    pub prerequisite_dag_ok:   bool,
+    pub total_nodes: usize,
+    pub concepts: usize,
+    pub learning_outcomes: usize,
+    pub total_edges: usize,
+    pub prerequisite_edges: usize,
+    pub supports_edges: usize,
+    pub related_edges: usize,
+    pub pinned_nodes: usize,
+    pub pinned_edges: usize,
+    pub prerequisite_dag_ok: bool,
     pub top_learning_outcomes: Vec<TopLearningOutcome>,
+    /// No learning outcome is below the adder's supports minimum.
+    #[serde(default = "coverage_ok_default")]
+    pub coverage_ok: bool,
+    /// Learning outcomes below the supports minimum, fewest first.
+    #[serde(default)]
+    pub under_supported: Vec<TopLearningOutcome>,
+    /// Nodes that can still be accepted; `None` when uncapped.
+    pub remaining_node_capacity: Option<usize>,
+    /// Edges that can still be accepted; `None` when uncapped.
+    pub remaining_edge_capacity: Option<usize>,
+    /// Proposal outcomes grouped by the generator that produced them.
+    pub generators: Vec<GeneratorStats>,
+    /// Prerequisite edges whose endpoints sit in different namespaces.
+    pub cross_namespace_prerequisites: usize,
+    pub namespaces: Vec<NamespaceStats>,
+}
+
+/// Node and edge counts for a single namespace.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceStats {
+    pub namespace: Option<String>,
+    pub nodes: usize,
+    /// Edges with both endpoints in this namespace.
+    pub internal_edges: usize,
+    /// Prerequisite edges leaving this namespace.
+    pub outgoing_cross_prerequisites: usize,
+}
+
+/// Node and edge outcomes for proposals from a single generator.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GeneratorStats {
+    pub generator: String,
+    pub nodes:     AcceptanceStats,
+    pub edges:     AcceptanceStats,
+}
+
+/// Acceptance counts and a histogram of rejection reasons.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AcceptanceStats {
+    pub proposed:          usize,
+    pub accepted:          usize,
+    pub rejection_reasons: BTreeMap<String, usize>,
+}
+
+impl AcceptanceStats {
+    pub fn record(&mut self, decision: &Decision) {
+        self.proposed += 1;
+        if decision.accepted {
+            self.accepted += 1;
+        } else {
+            let reason = decision.reason.as_deref().unwrap_or("rejected");
+            *self
+                .rejection_reasons
+                .entry(reason.to_string())
+                .or_default() += 1;
+        }
+    }
+
+    /// Fraction of proposals accepted, or 0 when nothing was proposed.
+    pub fn acceptance_rate(&self) -> f64 {
+        if self.proposed == 0 {
+            0.0
+        } else {
+            self.accepted as f64 / self.proposed as f64
+        }
+    }
+}
+
+/// Why a proposal was rejected, grouped from its free-form reason.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionCategory {
+    Granularity,
+    EmptyText,
+    NotSingleSentence,
+    LearningOutcomePrefix,
+    LevelOutOfRange,
+    DuplicateNode,
+    NearDuplicateNode,
+    UnknownFromId,
+    UnknownToId,
+    SelfLoop,
+    DuplicateEdge,
+    PrerequisiteCycle,
+    PrerequisiteEndpoint,
+    LevelDecrease,
+    MissingRationale,
+    WeakRationale,
+    RationaleMismatch,
+    Capacity,
+    RolledBack,
+    Other,
+}
+
+impl RejectionCategory {
+    /// Category of a rejection reason produced by the adder or
+    /// [`crate::validation`].
+    pub fn of(reason: &str) -> Self {
+        const PREFIXES: &[(&str, RejectionCategory)] = &[
+            ("granularity must be", RejectionCategory::Granularity),
+            ("node text is empty", RejectionCategory::EmptyText),
+            ("node text must be a single sentence", RejectionCategory::NotSingleSentence),
+            ("learning outcomes must start with", RejectionCategory::LearningOutcomePrefix),
+            ("level must be between", RejectionCategory::LevelOutOfRange),
+            ("duplicate node", RejectionCategory::DuplicateNode),
+            ("near-duplicate of node", RejectionCategory::NearDuplicateNode),
+            ("unknown from_id", RejectionCategory::UnknownFromId),
+            ("unknown to_id", RejectionCategory::UnknownToId),
+            ("self-loops", RejectionCategory::SelfLoop),
+            ("duplicate edge", RejectionCategory::DuplicateEdge),
+            ("edge already exists", RejectionCategory::DuplicateEdge),
+            (
+                "edge would introduce a prerequisite cycle",
+                RejectionCategory::PrerequisiteCycle,
+            ),
+            ("prerequisite edges must originate", RejectionCategory::PrerequisiteEndpoint),
+            ("prerequisite edges must target", RejectionCategory::PrerequisiteEndpoint),
+            ("prerequisite edges must not decrease level", RejectionCategory::LevelDecrease),
+            ("edge rationale missing", RejectionCategory::MissingRationale),
+            ("edge rationale too", RejectionCategory::WeakRationale),
+            ("edge rationale is not", RejectionCategory::WeakRationale),
+            ("rationale does not reference", RejectionCategory::RationaleMismatch),
+            ("CapacityExceeded", RejectionCategory::Capacity),
+            ("batch rolled back", RejectionCategory::RolledBack),
+        ];
+        PREFIXES
+            .iter()
+            .find(|(prefix, _)| reason.starts_with(prefix))
+            .map_or(RejectionCategory::Other, |(_, category)| *category)
+    }
+
+    /// Short description for tables.
+    pub fn label(self) -> &'static str {
+        match self {
+            RejectionCategory::Granularity => "wrong granularity",
+            RejectionCategory::EmptyText => "empty text",
+            RejectionCategory::NotSingleSentence => "not single sentence",
+            RejectionCategory::LearningOutcomePrefix => "learning outcome prefix",
+            RejectionCategory::LevelOutOfRange => "level out of range",
+            RejectionCategory::DuplicateNode => "duplicate node",
+            RejectionCategory::NearDuplicateNode => "near-duplicate node",
+            RejectionCategory::UnknownFromId => "unknown from_id",
+            RejectionCategory::UnknownToId => "unknown to_id",
+            RejectionCategory::SelfLoop => "self-loop",
+            RejectionCategory::DuplicateEdge => "duplicate edge",
+            RejectionCategory::PrerequisiteCycle => "prerequisite cycle",
+            RejectionCategory::PrerequisiteEndpoint => "prerequisite endpoint kind",
+            RejectionCategory::LevelDecrease => "level decrease",
+            RejectionCategory::MissingRationale => "missing rationale",
+            RejectionCategory::WeakRationale => "weak rationale",
+            RejectionCategory::RationaleMismatch => "rationale mismatch",
+            RejectionCategory::Capacity => "capacity",
+            RejectionCategory::RolledBack => "batch rolled back",
+            RejectionCategory::Other => "other",
+        }
+    }
+}
+
+/// Accepted proposals and rejections by category for one kind of proposal.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RejectionCounts {
+    pub accepted:   usize,
+    pub rejected:   usize,
+    pub categories: BTreeMap<RejectionCategory, usize>,
+}
+
+impl RejectionCounts {
+    pub fn record(&mut self, decision: &Decision) {
+        if decision.accepted {
+            self.accepted += 1;
+        } else {
+            self.rejected += 1;
+            let category = RejectionCategory::of(decision.reason.as_deref().unwrap_or_default());
+            *self.categories.entry(category).or_default() += 1;
+        }
+    }
+
+    /// Categories by descending count, ties in declaration order.
+    pub fn ranked(&self) -> Vec<(RejectionCategory, usize)> {
+        let mut ranked: Vec<_> = self
+            .categories
+            .iter()
+            .map(|(category, count)| (*category, *count))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked
+    }
+}
+
+/// Rejection counts kept by the adder, answered to
+/// [`crate::adder::RejectionStats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, kameo::Reply)]
+pub struct RejectionReport {
+    pub nodes:        RejectionCounts,
+    pub edges:        RejectionCounts,
+    /// Times each unknown tag was dropped from an accepted node.
+    #[serde(default)]
+    pub unknown_tags: BTreeMap<String, usize>,
+}
+
+impl RejectionReport {
+    /// Count a node decision and the unknown tags its node lost.
+    pub fn record_node(&mut self, decision: &Decision) {
+        self.nodes.record(decision);
+        if !decision.accepted {
+            return;
+        }
+        for dropped in &decision.dropped_tags {
+            if dropped.reason == TagDropReason::Unknown {
+                *self.unknown_tags.entry(dropped.tag.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    /// At most `limit` unknown tags, most often dropped first, then by name.
+    pub fn top_unknown_tags(&self, limit: usize) -> Vec<(&str, usize)> {
+        let mut tags: Vec<(&str, usize)> = self
+            .unknown_tags
+            .iter()
+            .map(|(tag, count)| (tag.as_str(), *count))
+            .collect();
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        tags.truncate(limit);
+        tags
+    }
+}
+
+/// End-of-run report printed by `mvp run --output json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunReport {
+    pub topic:             String,
+    /// A stored run was reused, so no proposals were made.
+    pub reused:            bool,
+    pub deadline_exceeded: bool,
+    /// Ctrl-C stopped generation; the graph is partial.
+    pub interrupted:       bool,
+    /// `--dry-run`: decisions were made but nothing was added to a graph.
+    pub dry_run:           bool,
+    pub nodes:             AcceptanceStats,
+    pub edges:             AcceptanceStats,
+    pub node_decisions:    Vec<Decision>,
+    pub edge_decisions:    Vec<Decision>,
+    pub summary:           Summary,
+    /// Rejections by category over the whole run.
+    #[serde(default)]
+    pub rejections:        RejectionReport,
+    /// Set by `--timings`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings:           Option<Timings>,
+    /// Set by `mvp resume`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resumed:           Option<ResumeCounts>,
+}
+
+/// How much of a resumed graph was already there.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumeCounts {
+    pub existing_nodes: usize,
+    pub existing_edges: usize,
+    pub added_nodes:    usize,
+    pub added_edges:    usize,
+}
+
+impl RunReport {
+    pub fn new(topic: impl Into<String>) -> Self {
+        Self {
+            topic:             topic.into(),
+            reused:            false,
+            deadline_exceeded: false,
+            interrupted:       false,
+            dry_run:           false,
+            nodes:             AcceptanceStats::default(),
+            edges:             AcceptanceStats::default(),
+            node_decisions:    Vec::new(),
+            edge_decisions:    Vec::new(),
+            summary:           Summary::empty(),
+            rejections:        RejectionReport::default(),
+            timings:           None,
+            resumed:           None,
+        }
+    }
+
+    pub fn record_nodes(&mut self, decisions: &[Decision]) {
+        decisions
+            .iter()
+            .for_each(|decision| self.nodes.record(decision));
+        self.node_decisions.extend_from_slice(decisions);
+    }
+
+    pub fn record_edges(&mut self, decisions: &[Decision]) {
+        decisions
+            .iter()
+            .for_each(|decision| self.edges.record(decision));
+        self.edge_decisions.extend_from_slice(decisions);
+    }
+}
+
+/// Where a run spent its time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Timings {
+    /// Time per phase summed over rounds, in the order phases first ran.
+    pub phases: Vec<PhaseTiming>,
+    /// Backend request latency by request kind; empty without the LLM.
+    pub llm:    Vec<LatencyStats>,
+}
+
+/// Latency spread of one kind of backend request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub kind:      String,
+    pub requests:  usize,
+    pub failed:    usize,
+    pub min_ms:    f64,
+    pub median_ms: f64,
+    pub max_ms:    f64,
+}
+
+impl Timings {
+    pub fn new(phases: &[PhaseTiming], latencies: &[RequestLatency]) -> Self {
+        let mut totals: Vec<PhaseTiming> = Vec::new();
+        for timing in phases {
+            match totals.iter_mut().find(|total| total.phase == timing.phase) {
+                Some(total) => total.elapsed_ms += timing.elapsed_ms,
+                None => totals.push(timing.clone()),
+            }
+        }
+
+        let mut kinds: Vec<&str> = Vec::new();
+        for latency in latencies {
+            if !kinds.contains(&latency.kind) {
+                kinds.push(latency.kind);
+            }
+        }
+        let llm = kinds
+            .into_iter()
+            .map(|kind| {
+                let of_kind: Vec<&RequestLatency> = latencies
+                    .iter()
+                    .filter(|latency| latency.kind == kind)
+                    .collect();
+                let mut elapsed: Vec<f64> =
+                    of_kind.iter().map(|latency| latency.elapsed_ms).collect();
+                elapsed.sort_by(f64::total_cmp);
+                let middle = elapsed.len() / 2;
+                let median_ms = if elapsed.len().is_multiple_of(2) {
+                    (elapsed[middle - 1] + elapsed[middle]) / 2.0
+                } else {
+                    elapsed[middle]
+                };
+                LatencyStats {
+                    kind: kind.to_string(),
+                    requests: of_kind.len(),
+                    failed: of_kind.iter().filter(|latency| !latency.ok).count(),
+                    min_ms: elapsed[0],
+                    median_ms,
+                    max_ms: elapsed[elapsed.len() - 1],
+                }
+            })
+            .collect();
+
+        Self {
+            phases: totals,
+            llm,
+        }
+    }
+}
+
+fn namespace_entry<'a>(
+    namespaces: &'a mut BTreeMap<Option<String>, NamespaceStats>,
+    namespace: &Option<String>,
+) -> &'a mut NamespaceStats {
+    namespaces
+        .entry(namespace.clone())
+        .or_insert_with(|| NamespaceStats {
+            namespace: namespace.clone(),
+            ..NamespaceStats::default()
+        })
 }
 
 /// Lightweight view of a learning outcome ranked by inbound supports.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TopLearningOutcome {
     pub id:       Uuid,
-    pub text:     String,
+    pub text:     Arc<str>,
     pub supports: usize,
 }
 
@@ -33,38 +418,63 @@ impl Summary {
     /// Create an empty summary placeholder.
     pub fn empty() -> Self {
         Self {
-            total_nodes:           0,
-            concepts:              0,
-            learning_outcomes:     0,
-            total_edges:           0,
-            prerequisite_edges:    0,
-            supports_edges:        0,
-            prerequisite_dag_ok:   true,
+            total_nodes: 0,
+            concepts: 0,
+            learning_outcomes: 0,
+            total_edges: 0,
+            prerequisite_edges: 0,
+            supports_edges: 0,
+            related_edges: 0,
+            pinned_nodes: 0,
+            pinned_edges: 0,
+            prerequisite_dag_ok: true,
             top_learning_outcomes: Vec::new(),
+            coverage_ok: true,
+            under_supported: Vec::new(),
+            remaining_node_capacity: None,
+            remaining_edge_capacity: None,
+            generators: Vec::new(),
+            cross_namespace_prerequisites: 0,
+            namespaces: Vec::new(),
         }
     }
 
     /// Build a summary snapshot from the current graph store.
     pub fn from_store(store: &GraphStore) -> Self {
-        let mut summary = Summary::empty();
-
+        let statistics = store.statistics();
+        let mut summary = Summary {
+            total_nodes: statistics.nodes,
+            concepts: statistics.concepts,
+            learning_outcomes: statistics.learning_outcomes,
+            total_edges: statistics.edges,
+            prerequisite_edges: statistics.prerequisite_edges,
+            supports_edges: statistics.supports_edges,
+            related_edges: statistics.related_edges,
+            pinned_nodes: statistics.pinned_nodes,
+            pinned_edges: statistics.pinned_edges,
+            prerequisite_dag_ok: store.is_prerequisite_dag(),
+            ..Summary::empty()
+        };
+        let mut namespaces: BTreeMap<Option<String>, NamespaceStats> = BTreeMap::new();
         for index in store.node_indices() {
             if let Some(node) = store.node(index) {
-                summary.total_nodes += 1;
-                match node.kind {
-                    NodeKind::Concept => summary.concepts += 1,
-                    NodeKind::LearningOutcome => summary.learning_outcomes += 1,
-                }
+                namespace_entry(&mut namespaces, &node.namespace).nodes += 1;
             }
         }
 
-        summary.total_edges = store.edge_indices().count();
-        summary.prerequisite_edges = store.prerequisite_edges();
-        summary.supports_edges = store.supports_edges();
-        summary.prerequisite_dag_ok = store.is_prerequisite_dag();
-
         let mut support_counts: HashMap<Uuid, usize> = HashMap::new();
         for edge_index in store.edge_indices() {
+            if let Some(edge) = store.edge_weight(edge_index)
+                && let (Some(from), Some(to)) = (store.node(edge.from), store.node(edge.to))
+            {
+                if from.namespace == to.namespace {
+                    namespace_entry(&mut namespaces, &from.namespace).internal_edges += 1;
+                } else if store.is_cross_namespace_prereq(edge) {
+                    namespace_entry(&mut namespaces, &from.namespace)
+                        .outgoing_cross_prerequisites += 1;
+                    summary.cross_namespace_prerequisites += 1;
+                }
+            }
             if let Some(edge) = store.edge_weight(edge_index)
                 && matches!(edge.relation, Relation::Supports)
                 && let Some(node) = store.node(edge.to)
@@ -95,6 +505,75 @@ impl Summary {
         learning_outcomes.truncate(5);
 
         summary.top_learning_outcomes = learning_outcomes;
+        summary.namespaces = namespaces.into_values().collect();
         summary
     }
+
+    /// Fill in the learning outcomes backed by fewer than `minimum`
+    /// supports edges.
+    pub fn check_coverage(&mut self, store: &GraphStore, minimum: usize) {
+        self.under_supported = store
+            .under_supported(minimum)
+            .into_iter()
+            .map(|(node, supports)| TopLearningOutcome {
+                id: node.id,
+                text: node.text.clone(),
+                supports,
+            })
+            .collect();
+        self.coverage_ok = self.under_supported.is_empty();
+    }
+}
+
+/// Summaries saved before coverage was checked report it as fine.
+fn coverage_ok_default() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latency(kind: &'static str, elapsed_ms: f64, ok: bool) -> RequestLatency {
+        RequestLatency {
+            model: "test-model".to_string(),
+            kind,
+            elapsed_ms,
+            ok,
+        }
+    }
+
+    #[test]
+    fn timings_sum_phases_and_spread_latency_by_kind() {
+        let phase = |phase: &str, elapsed_ms| PhaseTiming {
+            phase: phase.to_string(),
+            elapsed_ms,
+        };
+        let timings = Timings::new(
+            &[
+                phase("Generating nodes", 30.0),
+                phase("Adding nodes", 2.0),
+                phase("Generating nodes", 10.0),
+            ],
+            &[
+                latency("nodes", 40.0, true),
+                latency("edges", 5.0, false),
+                latency("nodes", 10.0, true),
+                latency("nodes", 25.0, true),
+                latency("nodes", 20.0, true),
+            ],
+        );
+
+        assert_eq!(
+            timings.phases,
+            vec![phase("Generating nodes", 40.0), phase("Adding nodes", 2.0)]
+        );
+        let nodes = &timings.llm[0];
+        assert_eq!((nodes.kind.as_str(), nodes.requests, nodes.failed), ("nodes", 4, 0));
+        assert_eq!((nodes.min_ms, nodes.median_ms, nodes.max_ms), (10.0, 22.5, 40.0));
+        let edges = &timings.llm[1];
+        assert_eq!((edges.requests, edges.failed, edges.median_ms), (1, 1, 5.0));
+
+        assert!(Timings::new(&[], &[]).llm.is_empty());
+    }
 }
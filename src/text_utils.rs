@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+
+/// Words ignored when comparing the content of two pieces of text.
+const STOP_WORDS: &[&str] = &[
+    "a",
+    "about",
+    "across",
+    "after",
+    "all",
+    "also",
+    "an",
+    "and",
+    "any",
+    "are",
+    "as",
+    "at",
+    "be",
+    "because",
+    "before",
+    "being",
+    "between",
+    "both",
+    "but",
+    "by",
+    "can",
+    "each",
+    "for",
+    "from",
+    "has",
+    "have",
+    "helps",
+    "how",
+    "i",
+    "in",
+    "into",
+    "is",
+    "it",
+    "its",
+    "learners",
+    "more",
+    "must",
+    "not",
+    "of",
+    "on",
+    "or",
+    "other",
+    "so",
+    "students",
+    "such",
+    "than",
+    "that",
+    "the",
+    "their",
+    "them",
+    "then",
+    "these",
+    "they",
+    "this",
+    "those",
+    "through",
+    "to",
+    "understand",
+    "understanding",
+    "was",
+    "we",
+    "what",
+    "when",
+    "which",
+    "while",
+    "who",
+    "why",
+    "will",
+    "with",
+    "without",
+    "you",
+];
+
+/// Lowercased words of `text` with punctuation and stop words removed.
+pub fn content_words(text: &str) -> HashSet<String> {
+    words(text)
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Runs of capitalized words in `text`, longest first.
+///
+/// Single-word runs consisting only of stop words (e.g. a leading "The") are
+/// skipped so that sentence-initial capitalization does not count as a
+/// reference.
+pub fn capitalized_phrases(text: &str) -> Vec<String> {
+    let mut phrases = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+
+    for word in words(text) {
+        if word.chars().next().is_some_and(char::is_uppercase) {
+            current.push(word);
+        } else if !current.is_empty() {
+            phrases.push(current.join(" "));
+            current.clear();
+        }
+    }
+    if !current.is_empty() {
+        phrases.push(current.join(" "));
+    }
+
+    phrases.retain(|phrase| !content_words(phrase).is_empty());
+    phrases.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    phrases.dedup();
+    phrases
+}
+
+/// Returns true when `a` and `b` share at least one content word.
+pub fn shares_content_word(a: &str, b: &str) -> bool {
+    let left = content_words(a);
+    content_words(b).iter().any(|word| left.contains(word))
+}
+
+/// Checks that an edge rationale mentions both of its endpoints.
+///
+/// Returns a description of the mismatch when either endpoint shares no
+/// content word with the rationale, naming the longest capitalized phrase
+/// that matches neither endpoint as the likely stray reference.
+pub fn rationale_mismatch(rationale: &str, from_text: &str, to_text: &str) -> Option<String> {
+    let missing: Vec<&str> = [("from", from_text), ("to", to_text)]
+        .into_iter()
+        .filter(|(_, text)| !shares_content_word(rationale, text))
+        .map(|(side, _)| side)
+        .collect();
+
+    if missing.is_empty() {
+        return None;
+    }
+
+    let mut message = format!("rationale does not reference the {} node", missing.join(" or "));
+    if let Some(stray) = capitalized_phrases(rationale).into_iter().find(|phrase| {
+        !shares_content_word(phrase, from_text) && !shares_content_word(phrase, to_text)
+    }) {
+        message.push_str(&format!(" (mentions '{stray}')"));
+    }
+
+    Some(message)
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|ch: char| !(ch.is_alphanumeric() || ch == '\'' || ch == '-'))
+        .map(|word| word.trim_matches(|ch: char| ch == '\'' || ch == '-'))
+        .filter(|word| !word.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_words_drop_stop_words_and_punctuation() {
+        let words = content_words("The tests, of course, capture behavior!");
+        let mut sorted: Vec<_> = words.into_iter().collect();
+        sorted.sort();
+        assert_eq!(sorted, vec!["behavior", "capture", "course", "tests"]);
+    }
+
+    #[test]
+    fn capitalized_phrases_are_longest_first() {
+        let phrases = capitalized_phrases("The Design Recipe guides Recursion Base Cases work.");
+        assert_eq!(phrases, vec!["Recursion Base Cases", "The Design Recipe"]);
+    }
+
+    #[test]
+    fn rationale_mismatch_accepts_rationales_naming_both_endpoints() {
+        let result = rationale_mismatch(
+            "Method signatures shape the tests written first.",
+            "A method signature names parameter types.",
+            "Write tests before implementation.",
+        );
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn rationale_mismatch_names_stray_reference() {
+        let result = rationale_mismatch(
+            "Recursion Base Cases underpin each method signature.",
+            "A method signature names parameter types.",
+            "Write tests before implementation.",
+        )
+        .expect("to endpoint is not referenced");
+        assert!(result.contains("the to node"));
+        assert!(result.contains("'Recursion Base Cases'"));
+    }
+}
@@ -0,0 +1,126 @@
+//! Tracing setup and span conventions.
+//!
+//! Spans form the hierarchy `run` → `phase` → `round` → `llm.request`:
+//!
+//! - `run` (`topic`): one whole `mvp run`.
+//! - `phase` (`name`): one [`Phase`](crate::viz::Phase), named by its display
+//!   label.
+//! - `round` (`round`): one node-generation round, counted from zero.
+//! - `llm.request` (`model`, `kind`, `prompt_tokens`, `completion_tokens`): one
+//!   backend call. `kind` is `nodes` or `edges`. Token fields are recorded once
+//!   the response arrives.
+//! - `adder.batch` (`kind`, `size`): one `AddNodes`/`AddEdges` message.
+//! - `export.render` (`format`): rendering and writing one artifact.
+//!
+//! Span names are lowercase and dotted by component. Fields are named after
+//! the struct fields they come from. Actor messages that start generation
+//! carry the caller's span, so work on actor tasks nests under the phase
+//! that asked for it. `adder.batch` and `export.render` run on their own
+//! tasks and are root spans.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, IsTerminal},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::Span;
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry, layer::SubscriberExt, util::SubscriberInitExt,
+};
+
+use crate::llm::TokenUsage;
+
+/// Most verbose level written to the stderr log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// Filter directives for this level.
+    ///
+    /// Levels past `warn` apply to weaver only; dependencies such as the
+    /// HTTP client and the viewer stay at `warn`.
+    fn directives(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "warn,weaver=info",
+            LogLevel::Debug => "warn,weaver=debug",
+            LogLevel::Trace => "warn,weaver=trace",
+        }
+    }
+}
+
+/// Line format of the stderr log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Human-readable lines, colored on a terminal.
+    #[default]
+    Pretty,
+    /// One JSON object per event, with its fields and enclosing spans.
+    Json,
+}
+
+/// Stderr log configuration for [`install`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogSettings {
+    /// `None` defers to `RUST_LOG`; without either nothing is logged.
+    pub level:  Option<LogLevel>,
+    pub format: LogFormat,
+}
+
+/// Install the global subscriber.
+///
+/// Log lines go to stderr, filtered by `log.level` or else `RUST_LOG`, so
+/// stdout carries only the run's own output. With `trace_file`, spans are
+/// also written in Chrome trace-event format, which Perfetto can open. The
+/// trace is complete once the returned guard is dropped. A subscriber
+/// installed earlier is left in place.
+pub fn install(trace_file: Option<&Path>, log: LogSettings) -> io::Result<Option<FlushGuard>> {
+    let filter = match log.level {
+        Some(level) => Some(EnvFilter::new(level.directives())),
+        None => std::env::var_os("RUST_LOG").map(|_| EnvFilter::from_default_env()),
+    };
+    let fmt = filter.map(|filter| -> Box<dyn Layer<Registry> + Send + Sync> {
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(io::stderr)
+            .with_ansi(io::stderr().is_terminal());
+        match log.format {
+            LogFormat::Pretty => layer.with_filter(filter).boxed(),
+            LogFormat::Json => layer.json().with_filter(filter).boxed(),
+        }
+    });
+    let (chrome, guard) = match trace_file {
+        Some(path) => {
+            let (layer, guard) = ChromeLayerBuilder::new()
+                .writer(BufWriter::new(File::create(path)?))
+                .include_args(true)
+                .build();
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry()
+        .with(fmt)
+        .with(chrome)
+        .try_init()
+        .ok();
+    Ok(guard)
+}
+
+/// Fill the token fields of an `llm.request` span.
+pub(crate) fn record_usage(span: &Span, usage: TokenUsage) {
+    span.record("prompt_tokens", usage.prompt_tokens);
+    span.record("completion_tokens", usage.completion_tokens);
+}
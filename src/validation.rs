@@ -0,0 +1,1584 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use rand::{SeedableRng, rngs::StdRng};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    adder::{
+        AdderConfig, RationalePolicy, RationaleRules, UpdateNode, capacity_reason, next_node_id,
+    },
+    edge_synth::truncate_sentence,
+    graph::{GraphSnapshot, GraphStore, SnapshotEdge, text_similarity},
+    model::{
+        Decision, DroppedTag, EdgeProposal, Explanation, GeneratorInfo, Granularity,
+        InventoryEntry, Node, NodeKind, NodeProposal, NodeRef, NodeRules, ProposalSubject,
+        Relation, TagDropReason, TextKey, clean_text, text_key,
+    },
+    summary::{GeneratorStats, RejectionReport, Summary},
+    text_utils::rationale_mismatch,
+    viz::CapacityResource,
+};
+
+/// Read-only view of an existing graph that proposals are validated against.
+pub trait ValidationContext {
+    fn node_by_id(&self, id: &Uuid) -> Option<&Node>;
+
+    /// Look up a node by text within a namespace, compared after
+    /// normalization.
+    fn node_by_text(&self, namespace: Option<&str>, text: &str) -> Option<&Node>;
+
+    fn has_edge(&self, from: &Uuid, to: &Uuid, relation: &Relation) -> bool;
+
+    /// Existing prerequisite path from `to` back to `from`, if any.
+    ///
+    /// Adding a `from -> to` prerequisite edge closes a cycle exactly when
+    /// such a path exists.
+    fn prereq_cycle_path(&self, from: &Uuid, to: &Uuid) -> Option<Vec<Uuid>>;
+
+    /// The node in `namespace` whose words best match `text`, with its
+    /// [`text_similarity`] score, when that score is at least `threshold`.
+    fn most_similar(
+        &self,
+        namespace: Option<&str>,
+        text: &str,
+        threshold: f64,
+    ) -> Option<(&Node, f64)>;
+}
+
+/// Proposal texts and edge keys already seen in the current batch.
+#[derive(Debug, Default)]
+pub struct BatchState {
+    nodes: HashSet<TextKey>,
+    edges: HashSet<(Uuid, Uuid, Relation)>,
+}
+
+/// Why a proposal was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    pub reason:      String,
+    pub explanation: Option<Explanation>,
+}
+
+impl Rejection {
+    fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason:      reason.into(),
+            explanation: None,
+        }
+    }
+
+    fn explained(reason: impl Into<String>, explanation: Option<Explanation>) -> Self {
+        Self {
+            reason: reason.into(),
+            explanation,
+        }
+    }
+}
+
+impl From<Rejection> for Decision {
+    fn from(rejection: Rejection) -> Self {
+        let decision = Decision::rejected(rejection.reason);
+        match rejection.explanation {
+            Some(explanation) => decision.with_explanation(explanation),
+            None => decision,
+        }
+    }
+}
+
+/// A node proposal that passed validation, with cleaned text and tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidNode {
+    pub kind:         NodeKind,
+    pub granularity:  Granularity,
+    pub level:        u8,
+    pub text:         String,
+    pub tags:         Option<Vec<String>>,
+    pub generator:    Option<GeneratorInfo>,
+    pub namespace:    Option<String>,
+    pub topic:        Option<String>,
+    /// Names the proposed tags when none of them was allowed.
+    pub warning:      Option<String>,
+    pub dropped_tags: Vec<DroppedTag>,
+}
+
+impl ValidNode {
+    pub fn into_node(self, id: Uuid) -> Node {
+        Node {
+            id,
+            kind: self.kind,
+            granularity: self.granularity,
+            level: self.level,
+            text: self.text.into(),
+            tags: self.tags,
+            generator: self.generator,
+            namespace: self.namespace,
+            topic: self.topic,
+            pinned: false,
+        }
+    }
+}
+
+/// Edits from an [`UpdateNode`] that passed validation; `None` fields are
+/// left unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidUpdate {
+    pub text:    Option<String>,
+    pub level:   Option<u8>,
+    pub tags:    Option<Option<Vec<String>>>,
+    /// Names the proposed tags when none of them was allowed.
+    pub warning: Option<String>,
+}
+
+/// An edge proposal that passed validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidEdge {
+    pub relation:  Relation,
+    pub from:      Uuid,
+    pub to:        Uuid,
+    pub rationale: String,
+    pub generator: Option<GeneratorInfo>,
+    /// Rationale consistency warning raised under [`RationalePolicy::Warn`].
+    pub warning:   Option<String>,
+}
+
+/// Validate a node proposal against the graph and the rest of its batch.
+pub fn validate_node(
+    proposal: NodeProposal,
+    rules: &NodeRules,
+    batch: &mut BatchState,
+    context: &impl ValidationContext,
+) -> Result<ValidNode, Rejection> {
+    let NodeProposal {
+        kind,
+        granularity,
+        level,
+        text,
+        tags,
+        generator,
+        namespace,
+        topic,
+    } = proposal;
+
+    if granularity != Granularity::Sentence {
+        return Err(Rejection::new("granularity must be sentence"));
+    }
+
+    check_level(level, rules)?;
+    let cleaned_text = check_text(&kind, &text, rules)?;
+
+    let existing = context
+        .node_by_text(namespace.as_deref(), &cleaned_text)
+        .map(|node| Explanation::DuplicateNode { existing: node.id });
+
+    if !batch
+        .nodes
+        .insert(text_key(namespace.as_deref(), &cleaned_text))
+    {
+        return Err(Rejection::explained("duplicate node within batch", existing));
+    }
+
+    if existing.is_some() {
+        return Err(Rejection::explained("duplicate node already present", existing));
+    }
+
+    let (tags, dropped_tags) = sanitize_tags(tags, rules);
+    let warning = unknown_tags_warning(&tags, &dropped_tags);
+
+    Ok(ValidNode {
+        kind,
+        granularity,
+        level,
+        text: cleaned_text,
+        tags,
+        generator,
+        namespace,
+        topic,
+        warning,
+        dropped_tags,
+    })
+}
+
+/// Validate edits to the accepted `node` under the rules for new proposals.
+///
+/// New text must not match another node's; prerequisite levels are left to
+/// the caller, which can see the node's edges.
+pub fn validate_node_update(
+    node: &Node,
+    update: UpdateNode,
+    rules: &NodeRules,
+    context: &impl ValidationContext,
+) -> Result<ValidUpdate, Rejection> {
+    let UpdateNode {
+        text, level, tags, ..
+    } = update;
+    if let Some(level) = level {
+        check_level(level, rules)?;
+    }
+
+    let text = match text {
+        Some(text) => {
+            let cleaned = check_text(&node.kind, &text, rules)?;
+            if let Some(existing) = context.node_by_text(node.namespace.as_deref(), &cleaned)
+                && existing.id != node.id
+            {
+                return Err(Rejection::explained(
+                    "duplicate node already present",
+                    Some(Explanation::DuplicateNode {
+                        existing: existing.id,
+                    }),
+                ));
+            }
+            Some(cleaned)
+        }
+        None => None,
+    };
+
+    let mut warning = None;
+    let tags = tags.map(|tags| {
+        let (tags, dropped) = sanitize_tags(tags, rules);
+        warning = unknown_tags_warning(&tags, &dropped);
+        tags
+    });
+
+    Ok(ValidUpdate {
+        text,
+        level,
+        tags,
+        warning,
+    })
+}
+
+/// Reject a validated node whose words match an existing node's at
+/// `threshold` or above.
+pub fn check_similarity(
+    valid: &ValidNode,
+    threshold: f64,
+    context: &impl ValidationContext,
+) -> Result<(), Rejection> {
+    let Some((existing, score)) =
+        context.most_similar(valid.namespace.as_deref(), &valid.text, threshold)
+    else {
+        return Ok(());
+    };
+    Err(Rejection::explained(
+        format!(
+            "near-duplicate of node {} \"{}\" (similarity {score:.2})",
+            existing.id, existing.text
+        ),
+        Some(Explanation::DuplicateNode {
+            existing: existing.id,
+        }),
+    ))
+}
+
+fn check_level(level: u8, rules: &NodeRules) -> Result<(), Rejection> {
+    if level > rules.max_level {
+        return Err(Rejection::new(format!("level must be between 0 and {}", rules.max_level)));
+    }
+    Ok(())
+}
+
+/// Clean `text` and check it against the sentence and learning outcome
+/// prefix rules.
+fn check_text(kind: &NodeKind, text: &str, rules: &NodeRules) -> Result<String, Rejection> {
+    let cleaned_text = clean_text(text);
+    if cleaned_text.is_empty() {
+        return Err(Rejection::new("node text is empty after trimming"));
+    }
+
+    if rules.require_single_sentence && !is_single_sentence(&cleaned_text) {
+        return Err(Rejection::new("node text must be a single sentence"));
+    }
+
+    let prefixes = &rules.allowed_lo_prefixes;
+    if matches!(kind, NodeKind::LearningOutcome) && !prefixes.is_empty() {
+        let lowered = cleaned_text.to_lowercase();
+        if !prefixes
+            .iter()
+            .any(|prefix| lowered.starts_with(&format!("{} ", prefix.trim().to_lowercase())))
+        {
+            let quoted: Vec<String> = prefixes
+                .iter()
+                .map(|prefix| format!("'{}'", prefix.trim()))
+                .collect();
+            let listed = match quoted.split_last() {
+                Some((last, rest)) if !rest.is_empty() => {
+                    format!("{} or {last}", rest.join(", "))
+                }
+                _ => quoted.concat(),
+            };
+            return Err(Rejection::new(format!("learning outcomes must start with {listed}")));
+        }
+    }
+    Ok(cleaned_text)
+}
+
+/// Validate an edge proposal against the graph and the rest of its batch.
+///
+/// An empty rationale is rejected when `require_rationale` is set and
+/// skips the quality and consistency checks otherwise.
+pub fn validate_edge(
+    proposal: EdgeProposal,
+    policy: RationalePolicy,
+    require_rationale: bool,
+    rules: &RationaleRules,
+    batch: &mut BatchState,
+    context: &impl ValidationContext,
+) -> Result<ValidEdge, Rejection> {
+    let EdgeProposal {
+        relation,
+        from_id,
+        to_id,
+        rationale,
+        generator,
+    } = proposal;
+    let (from_id, to_id) = relation.canonical_endpoints(from_id, to_id);
+
+    if !batch.edges.insert((from_id, to_id, relation.clone())) {
+        return Err(Rejection::new("duplicate edge within batch"));
+    }
+
+    let Some(from_node) = context.node_by_id(&from_id) else {
+        return Err(Rejection::new(format!("unknown from_id {}", from_id)));
+    };
+
+    let Some(to_node) = context.node_by_id(&to_id) else {
+        return Err(Rejection::new(format!("unknown to_id {}", to_id)));
+    };
+
+    if from_id == to_id {
+        return Err(Rejection::new("self-loops are not allowed"));
+    }
+
+    if context.has_edge(&from_id, &to_id, &relation) {
+        return Err(Rejection::explained(
+            "edge already exists",
+            Some(Explanation::DuplicateEdge {
+                from: from_id,
+                to:   to_id,
+            }),
+        ));
+    }
+
+    let prerequisite = matches!(relation, Relation::PrerequisiteFor);
+
+    if prerequisite && let Some(path) = context.prereq_cycle_path(&from_id, &to_id) {
+        let path = path
+            .iter()
+            .filter_map(|id| context.node_by_id(id))
+            .map(|node| NodeRef {
+                id:   node.id,
+                text: truncate_sentence(&node.text),
+            })
+            .collect();
+        return Err(Rejection::explained(
+            "edge would introduce a prerequisite cycle",
+            Some(Explanation::CyclePath { path }),
+        ));
+    }
+
+    if prerequisite && !matches!(from_node.kind, NodeKind::Concept) {
+        return Err(Rejection::new("prerequisite edges must originate from a concept"));
+    }
+
+    if prerequisite && !matches!(to_node.kind, NodeKind::Concept | NodeKind::LearningOutcome) {
+        return Err(Rejection::new("prerequisite edges must target a concept or learning outcome"));
+    }
+
+    if prerequisite && from_node.level > to_node.level {
+        return Err(Rejection::explained(
+            "prerequisite edges must not decrease level",
+            Some(Explanation::LevelDecrease {
+                from_level: from_node.level,
+                to_level:   to_node.level,
+            }),
+        ));
+    }
+
+    let rationale = rationale.trim();
+    if rationale.is_empty() && require_rationale {
+        return Err(Rejection::new("edge rationale missing"));
+    }
+    if !rationale.is_empty() {
+        check_rationale(rationale, rules)?;
+    }
+
+    let mut warning = None;
+    if policy != RationalePolicy::Off
+        && !rationale.is_empty()
+        && let Some(mismatch) = rationale_mismatch(rationale, &from_node.text, &to_node.text)
+    {
+        if policy == RationalePolicy::Strict {
+            return Err(Rejection::new(mismatch));
+        }
+        warning = Some(mismatch);
+    }
+
+    Ok(ValidEdge {
+        relation,
+        from: from_id,
+        to: to_id,
+        rationale: rationale.to_string(),
+        generator,
+        warning,
+    })
+}
+
+/// Apply the word, length, and sentence rules to a trimmed rationale.
+fn check_rationale(rationale: &str, rules: &RationaleRules) -> Result<(), Rejection> {
+    let words = rationale.split_whitespace().count();
+    if words < rules.min_words {
+        return Err(Rejection::new(format!(
+            "edge rationale too short: {words} word(s), minimum {}",
+            rules.min_words
+        )));
+    }
+    let chars = rationale.chars().count();
+    if let Some(max_chars) = rules.max_chars
+        && chars > max_chars
+    {
+        return Err(Rejection::new(format!(
+            "edge rationale too long: {chars} characters, maximum {max_chars}"
+        )));
+    }
+    if rules.require_sentence && !is_single_sentence(rationale) {
+        return Err(Rejection::new(format!(
+            "edge rationale is not a complete sentence: '{}'",
+            truncate_sentence(rationale)
+        )));
+    }
+    Ok(())
+}
+
+/// Allowed tags, normalized, deduplicated, and capped, plus every other
+/// normalized tag and why it was dropped.
+fn sanitize_tags(
+    tags: Option<Vec<String>>,
+    rules: &NodeRules,
+) -> (Option<Vec<String>>, Vec<DroppedTag>) {
+    let mut result: Vec<String> = Vec::new();
+    let mut dropped: Vec<DroppedTag> = Vec::new();
+    let Some(tags) = tags else {
+        return (None, dropped);
+    };
+
+    for tag in tags {
+        let normalized = tag.trim().to_lowercase();
+        if normalized.is_empty() {
+            continue;
+        }
+        let reason = if result.contains(&normalized)
+            || dropped.iter().any(|dropped| dropped.tag == normalized)
+        {
+            TagDropReason::Duplicate
+        } else if !rules.allowed_tags.contains(&normalized) {
+            TagDropReason::Unknown
+        } else if result.len() == rules.max_tags_per_node {
+            TagDropReason::OverLimit
+        } else {
+            result.push(normalized);
+            continue;
+        };
+        dropped.push(DroppedTag {
+            tag: normalized,
+            reason,
+        });
+    }
+
+    ((!result.is_empty()).then_some(result), dropped)
+}
+
+/// Warning naming the unknown tags when they left the node with none.
+fn unknown_tags_warning(tags: &Option<Vec<String>>, dropped: &[DroppedTag]) -> Option<String> {
+    let unknown: Vec<&str> = dropped
+        .iter()
+        .filter(|dropped| dropped.reason == TagDropReason::Unknown)
+        .map(|dropped| dropped.tag.as_str())
+        .collect();
+    (tags.is_none() && !unknown.is_empty())
+        .then(|| format!("dropped tags outside the allowed list: {}", unknown.join(", ")))
+}
+
+fn is_single_sentence(text: &str) -> bool {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return false;
+    }
+
+    let mut ender_count = 0;
+    for ch in trimmed.chars() {
+        if matches!(ch, '.' | '!' | '?') {
+            ender_count += 1;
+        }
+    }
+
+    matches!(trimmed.chars().last(), Some('.') | Some('!') | Some('?')) && ender_count == 1
+}
+
+impl ValidationContext for GraphStore {
+    fn node_by_id(&self, id: &Uuid) -> Option<&Node> {
+        self.node(self.find_by_id(id)?)
+    }
+
+    fn node_by_text(&self, namespace: Option<&str>, text: &str) -> Option<&Node> {
+        self.node(self.find_by_text(namespace, text)?)
+    }
+
+    fn has_edge(&self, from: &Uuid, to: &Uuid, relation: &Relation) -> bool {
+        match (self.find_by_id(from), self.find_by_id(to)) {
+            (Some(from), Some(to)) => self.has_edge_between(from, to, relation),
+            _ => false,
+        }
+    }
+
+    fn prereq_cycle_path(&self, from: &Uuid, to: &Uuid) -> Option<Vec<Uuid>> {
+        let path =
+            GraphStore::prereq_cycle_path(self, self.find_by_id(from)?, self.find_by_id(to)?)?;
+        Some(
+            path.into_iter()
+                .filter_map(|index| self.node(index).map(|node| node.id))
+                .collect(),
+        )
+    }
+
+    fn most_similar(
+        &self,
+        namespace: Option<&str>,
+        text: &str,
+        threshold: f64,
+    ) -> Option<(&Node, f64)> {
+        self.find_similar(text, threshold)
+            .into_iter()
+            .filter_map(|(index, score)| Some((self.node(index)?, score)))
+            .find(|(node, _)| node.namespace.as_deref() == namespace)
+    }
+}
+
+/// Rule broken by a node or edge of an existing graph.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Violation {
+    #[error("node {id}: {reason}")]
+    Node { id: Uuid, reason: String },
+    #[error("edge {from} -{relation:?}-> {to}: {reason}")]
+    Edge {
+        from:     Uuid,
+        to:       Uuid,
+        relation: Relation,
+        reason:   String,
+    },
+}
+
+/// Replay a snapshot through the same node and edge rules the adder applies.
+///
+/// Nodes are checked before edges, each in snapshot order, and everything
+/// that passes joins the context before the next item is checked. So the
+/// later of two duplicates, and the edge that closes a prerequisite cycle,
+/// are the ones reported.
+pub fn validate_snapshot(
+    snapshot: &GraphSnapshot,
+    policy: RationalePolicy,
+    rules: &NodeRules,
+) -> Vec<Violation> {
+    let mut context = MemoryContext::new();
+    let mut batch = BatchState::default();
+    let mut violations = Vec::new();
+
+    for node in &snapshot.nodes {
+        let proposal = NodeProposal {
+            kind:        node.kind.clone(),
+            granularity: node.granularity.clone(),
+            level:       node.level,
+            text:        node.text.to_string(),
+            tags:        node.tags.clone(),
+            generator:   node.generator.clone(),
+            namespace:   node.namespace.clone(),
+            topic:       node.topic.clone(),
+        };
+        match validate_node(proposal, rules, &mut batch, &context) {
+            Ok(_) => context.add_node(node.clone()),
+            Err(rejection) => violations.push(Violation::Node {
+                id:     node.id,
+                reason: rejection.reason,
+            }),
+        }
+    }
+
+    for edge in &snapshot.edges {
+        let proposal = EdgeProposal {
+            relation:  edge.relation.clone(),
+            from_id:   edge.from,
+            to_id:     edge.to,
+            rationale: edge.rationale.clone(),
+            generator: edge.generator.clone(),
+        };
+        match validate_edge(
+            proposal,
+            policy,
+            true,
+            &RationaleRules::default(),
+            &mut batch,
+            &context,
+        ) {
+            Ok(valid) => context.add_edge(SnapshotEdge {
+                from:      valid.from,
+                to:        valid.to,
+                relation:  valid.relation,
+                rationale: valid.rationale,
+                generator: valid.generator,
+                pinned:    edge.pinned,
+            }),
+            Err(rejection) => violations.push(Violation::Edge {
+                from:     edge.from,
+                to:       edge.to,
+                relation: edge.relation.clone(),
+                reason:   rejection.reason,
+            }),
+        }
+    }
+
+    violations
+}
+
+/// Standalone [`ValidationContext`] for callers without a [`GraphStore`].
+#[derive(Debug, Clone, Default)]
+pub struct MemoryContext {
+    nodes:      HashMap<Uuid, Node>,
+    text_index: HashMap<TextKey, Uuid>,
+    edges:      Vec<SnapshotEdge>,
+}
+
+impl MemoryContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_snapshot(snapshot: GraphSnapshot) -> Self {
+        let mut context = Self::new();
+        for node in snapshot.nodes {
+            context.add_node(node);
+        }
+        context.edges = snapshot.edges;
+        context
+    }
+
+    pub fn add_node(&mut self, node: Node) {
+        self.text_index
+            .insert(text_key(node.namespace.as_deref(), &node.text), node.id);
+        self.nodes.insert(node.id, node);
+    }
+
+    pub fn add_edge(&mut self, edge: SnapshotEdge) {
+        self.edges.push(edge);
+    }
+}
+
+impl ValidationContext for MemoryContext {
+    fn node_by_id(&self, id: &Uuid) -> Option<&Node> {
+        self.nodes.get(id)
+    }
+
+    fn node_by_text(&self, namespace: Option<&str>, text: &str) -> Option<&Node> {
+        self.nodes
+            .get(self.text_index.get(&text_key(namespace, text))?)
+    }
+
+    fn most_similar(
+        &self,
+        namespace: Option<&str>,
+        text: &str,
+        threshold: f64,
+    ) -> Option<(&Node, f64)> {
+        self.nodes
+            .values()
+            .filter(|node| node.namespace.as_deref() == namespace)
+            .map(|node| (node, text_similarity(text, &node.text)))
+            .filter(|(_, score)| *score >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.id.cmp(&a.0.id)))
+    }
+
+    fn has_edge(&self, from: &Uuid, to: &Uuid, relation: &Relation) -> bool {
+        self.edges
+            .iter()
+            .any(|edge| edge.from == *from && edge.to == *to && edge.relation == *relation)
+    }
+
+    fn prereq_cycle_path(&self, from: &Uuid, to: &Uuid) -> Option<Vec<Uuid>> {
+        if from == to {
+            return Some(vec![*from]);
+        }
+
+        let mut queue = VecDeque::from([*to]);
+        let mut parent: HashMap<Uuid, Uuid> = HashMap::new();
+        let mut visited = HashSet::from([*to]);
+        while let Some(current) = queue.pop_front() {
+            if current == *from {
+                let mut path = vec![current];
+                let mut cursor = current;
+                while let Some(&previous) = parent.get(&cursor) {
+                    path.push(previous);
+                    cursor = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for edge in &self.edges {
+                if edge.from == current
+                    && matches!(edge.relation, Relation::PrerequisiteFor)
+                    && visited.insert(edge.to)
+                {
+                    parent.insert(edge.to, current);
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Proposals decided under the adder's rules without touching a graph.
+///
+/// Decisions match what the adder returns for the same proposals and
+/// config, ids included when seeded, but nothing is emitted and accepted
+/// items only join an in-memory context.
+#[derive(Debug)]
+pub struct DryRun {
+    config:          AdderConfig,
+    context:         MemoryContext,
+    /// Accepted node ids in acceptance order.
+    order:           Vec<Uuid>,
+    id_rng:          Option<StdRng>,
+    generator_stats: BTreeMap<String, GeneratorStats>,
+    rejection_stats: RejectionReport,
+}
+
+impl DryRun {
+    pub fn new(config: AdderConfig) -> Self {
+        Self {
+            id_rng: config.id_seed.map(StdRng::seed_from_u64),
+            config,
+            context: MemoryContext::new(),
+            order: Vec::new(),
+            generator_stats: BTreeMap::new(),
+            rejection_stats: RejectionReport::default(),
+        }
+    }
+
+    pub fn add_nodes(&mut self, proposals: Vec<NodeProposal>) -> Vec<Decision> {
+        let mut decisions = Vec::with_capacity(proposals.len());
+        let mut batch = BatchState::default();
+
+        for (index, mut proposal) in proposals.into_iter().enumerate() {
+            if proposal.namespace.is_none() {
+                proposal.namespace = self.config.namespace.clone();
+            }
+            let generator = GeneratorInfo::label(proposal.generator.as_ref());
+            let subject = ProposalSubject::from(&proposal);
+            let decision = self
+                .decide_node(proposal, &mut batch)
+                .with_context(index, subject);
+            self.generator_entry(generator).nodes.record(&decision);
+            self.rejection_stats.record_node(&decision);
+            decisions.push(decision);
+        }
+
+        decisions
+    }
+
+    fn decide_node(&mut self, proposal: NodeProposal, batch: &mut BatchState) -> Decision {
+        let valid = match validate_node(proposal, &self.config.node_rules, batch, &self.context) {
+            Ok(valid) => valid,
+            Err(rejection) => return rejection.into(),
+        };
+        if let Some(threshold) = self.config.similarity_threshold
+            && let Err(rejection) = check_similarity(&valid, threshold, &self.context)
+        {
+            return rejection.into();
+        }
+        let warning = valid.warning.clone();
+        let dropped_tags = valid.dropped_tags.clone();
+        if let Some(limit) = self.config.max_nodes
+            && self.order.len() >= limit
+        {
+            return Decision::rejected(capacity_reason(CapacityResource::Nodes, limit));
+        }
+
+        let node = valid.into_node(next_node_id(self.id_rng.as_mut()));
+        let id = node.id;
+        self.context.add_node(node);
+        self.order.push(id);
+        let decision = Decision::accepted(Some(id)).with_dropped_tags(dropped_tags);
+        match warning {
+            Some(warning) => decision.with_warning(warning),
+            None => decision,
+        }
+    }
+
+    pub fn add_edges(&mut self, proposals: Vec<EdgeProposal>) -> Vec<Decision> {
+        let mut decisions = Vec::with_capacity(proposals.len());
+        let mut batch = BatchState::default();
+
+        for (index, proposal) in proposals.into_iter().enumerate() {
+            let generator = GeneratorInfo::label(proposal.generator.as_ref());
+            let subject = ProposalSubject::from(&proposal);
+            let decision = self
+                .decide_edge(proposal, &mut batch)
+                .with_context(index, subject);
+            self.generator_entry(generator).edges.record(&decision);
+            self.rejection_stats.edges.record(&decision);
+            decisions.push(decision);
+        }
+
+        decisions
+    }
+
+    fn decide_edge(&mut self, proposal: EdgeProposal, batch: &mut BatchState) -> Decision {
+        let valid = match validate_edge(
+            proposal,
+            self.config.rationale_policy,
+            self.config.require_edge_rationale,
+            &self.config.rationale_rules,
+            batch,
+            &self.context,
+        ) {
+            Ok(valid) => valid,
+            Err(rejection) => return rejection.into(),
+        };
+        if let Some(limit) = self.config.max_edges
+            && self.context.edges.len() >= limit
+        {
+            return Decision::rejected(capacity_reason(CapacityResource::Edges, limit));
+        }
+
+        self.context.add_edge(SnapshotEdge {
+            from:      valid.from,
+            to:        valid.to,
+            relation:  valid.relation,
+            rationale: valid.rationale,
+            generator: valid.generator,
+            pinned:    false,
+        });
+        match valid.warning {
+            Some(warning) => Decision::accepted(None).with_warning(warning),
+            None => Decision::accepted(None),
+        }
+    }
+
+    fn generator_entry(&mut self, generator: String) -> &mut GeneratorStats {
+        self.generator_stats
+            .entry(generator.clone())
+            .or_insert_with(|| GeneratorStats {
+                generator,
+                ..GeneratorStats::default()
+            })
+    }
+
+    /// Everything accepted so far, nodes in acceptance order.
+    pub fn snapshot(&self) -> GraphSnapshot {
+        GraphSnapshot {
+            nodes: self
+                .order
+                .iter()
+                .filter_map(|id| self.context.nodes.get(id).cloned())
+                .collect(),
+            edges: self.context.edges.clone(),
+        }
+    }
+
+    pub fn inventory(&self) -> Vec<InventoryEntry> {
+        self.order
+            .iter()
+            .filter_map(|id| self.context.nodes.get(id))
+            .map(|node| {
+                (node.id, node.kind.clone(), node.level, node.text.clone(), node.tags.clone())
+            })
+            .collect()
+    }
+
+    /// The summary the adder would report for the same accepted items.
+    pub fn summary(&self) -> Summary {
+        let mut summary = GraphStore::from_snapshot(self.snapshot())
+            .map(|store| {
+                let mut summary = Summary::from_store(&store);
+                summary.check_coverage(&store, self.config.min_supports);
+                summary
+            })
+            .unwrap_or_else(|_| Summary::empty());
+        summary.remaining_node_capacity = self
+            .config
+            .max_nodes
+            .map(|limit| limit.saturating_sub(self.order.len()));
+        summary.remaining_edge_capacity = self
+            .config
+            .max_edges
+            .map(|limit| limit.saturating_sub(self.context.edges.len()));
+        summary.generators = self.generator_stats.values().cloned().collect();
+        summary
+    }
+
+    /// Rejection counts as the adder's [`crate::adder::RejectionStats`]
+    /// reports them.
+    pub fn rejection_stats(&mut self, reset: bool) -> RejectionReport {
+        if reset {
+            std::mem::take(&mut self.rejection_stats)
+        } else {
+            self.rejection_stats.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kameo::{Actor, actor::ActorRef};
+
+    use super::*;
+    use crate::{
+        adder::{AddEdges, AddNodes, AdderConfig, GraphAdder, Inventory, RejectionStats},
+        model::TagRegistry,
+    };
+
+    /// Either a spawned GraphAdder or a DryRun over the same config, so each
+    /// scenario can assert both paths agree.
+    enum Path {
+        Actor(ActorRef<GraphAdder>),
+        Pure(Box<DryRun>),
+    }
+
+    impl Path {
+        fn both(policy: RationalePolicy) -> [Path; 2] {
+            Self::configured(AdderConfig {
+                rationale_policy: policy,
+                ..AdderConfig::default()
+            })
+        }
+
+        fn configured(config: AdderConfig) -> [Path; 2] {
+            let adder =
+                GraphAdder::with_event_sender(GraphStore::new(), None).with_config(config.clone());
+            [
+                Path::Actor(GraphAdder::spawn(adder)),
+                Path::Pure(Box::new(DryRun::new(config))),
+            ]
+        }
+
+        async fn add_nodes(&mut self, proposals: Vec<NodeProposal>) -> Vec<Decision> {
+            match self {
+                Path::Actor(adder) => adder.ask(AddNodes(proposals)).await.expect("actor reply"),
+                Path::Pure(dry_run) => dry_run.add_nodes(proposals),
+            }
+        }
+
+        async fn add_edges(&mut self, proposals: Vec<EdgeProposal>) -> Vec<Decision> {
+            match self {
+                Path::Actor(adder) => adder.ask(AddEdges(proposals)).await.expect("actor reply"),
+                Path::Pure(dry_run) => dry_run.add_edges(proposals),
+            }
+        }
+    }
+
+    fn sample_concept(text: &str) -> NodeProposal {
+        NodeProposal {
+            kind:        NodeKind::Concept,
+            granularity: Granularity::Sentence,
+            level:       0,
+            text:        text.to_string(),
+            tags:        Some(vec!["tests".to_string()]),
+            generator:   None,
+            namespace:   None,
+            topic:       None,
+        }
+    }
+
+    fn ids(decisions: &[Decision]) -> Vec<Uuid> {
+        decisions.iter().filter_map(|d| d.assigned_id).collect()
+    }
+
+    /// Decision fields that do not depend on generated ids.
+    fn outcome(decision: &Decision) -> (bool, Option<String>, Vec<String>) {
+        (decision.accepted, decision.reason.clone(), decision.warnings.clone())
+    }
+
+    #[tokio::test]
+    async fn configured_tags_replace_the_default_vocabulary() {
+        let tagged = |tags: &[&str], text: &str| NodeProposal {
+            tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+            ..sample_concept(text)
+        };
+        for mut path in Path::configured(AdderConfig {
+            node_rules: NodeRules {
+                allowed_tags: TagRegistry::new(["Chemistry", "lab"]),
+                ..NodeRules::default()
+            },
+            ..AdderConfig::default()
+        }) {
+            let decisions = path
+                .add_nodes(vec![
+                    tagged(&["lab", "tests"], "Titration finds an unknown concentration."),
+                    tagged(&["tests", " Stub "], "Buffers resist changes in acidity."),
+                ])
+                .await;
+            assert!(decisions.iter().all(|decision| decision.accepted));
+            assert!(decisions[0].warnings.is_empty());
+            assert_eq!(
+                decisions[1].warnings,
+                vec!["dropped tags outside the allowed list: tests, stub".to_string()]
+            );
+
+            let inventory = match &path {
+                Path::Actor(adder) => adder.ask(Inventory).await.expect("inventory"),
+                Path::Pure(dry_run) => dry_run.inventory(),
+            };
+            let tags_of = |prefix: &str| {
+                inventory
+                    .iter()
+                    .find(|(_, _, _, text, _)| text.starts_with(prefix))
+                    .and_then(|(.., tags)| tags.clone())
+            };
+            assert_eq!(tags_of("Titration"), Some(vec!["lab".to_string()]));
+            assert_eq!(tags_of("Buffers"), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn dropped_tags_are_reported_with_their_reason() {
+        let tagged = |tags: &[&str], text: &str| NodeProposal {
+            tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+            ..sample_concept(text)
+        };
+        for mut path in Path::configured(AdderConfig {
+            node_rules: NodeRules {
+                max_tags_per_node: 2,
+                ..NodeRules::default()
+            },
+            ..AdderConfig::default()
+        }) {
+            let decisions = path
+                .add_nodes(vec![
+                    tagged(
+                        &[
+                            "tests",
+                            "Tests",
+                            "mocks",
+                            "purpose",
+                            "implementation",
+                            "mocks",
+                        ],
+                        "Stubs stand in for unwritten functions.",
+                    ),
+                    tagged(&["mocks", "tests"], "Stubs let tests run early."),
+                    tagged(&["mocks"], "Stubs let tests run early."),
+                ])
+                .await;
+            let dropped = |tag: &str, reason| DroppedTag {
+                tag: tag.to_string(),
+                reason,
+            };
+            assert_eq!(
+                decisions[0].dropped_tags,
+                vec![
+                    dropped("tests", TagDropReason::Duplicate),
+                    dropped("mocks", TagDropReason::Unknown),
+                    dropped("implementation", TagDropReason::OverLimit),
+                    dropped("mocks", TagDropReason::Duplicate),
+                ]
+            );
+            assert_eq!(decisions[1].dropped_tags, vec![dropped("mocks", TagDropReason::Unknown)]);
+            assert!(!decisions[2].accepted && decisions[2].dropped_tags.is_empty());
+
+            let report = match &mut path {
+                Path::Actor(adder) => adder
+                    .ask(RejectionStats { reset: false })
+                    .await
+                    .expect("rejection stats"),
+                Path::Pure(dry_run) => dry_run.rejection_stats(false),
+            };
+            assert_eq!(report.top_unknown_tags(5), vec![("mocks", 2)]);
+
+            let inventory = match &path {
+                Path::Actor(adder) => adder.ask(Inventory).await.expect("inventory"),
+                Path::Pure(dry_run) => dry_run.inventory(),
+            };
+            assert_eq!(inventory[0].4, Some(vec!["tests".to_string(), "purpose".to_string()]));
+        }
+    }
+
+    #[tokio::test]
+    async fn configured_level_ceiling_bounds_proposals() {
+        let at_level = |level, text: &str| NodeProposal {
+            level,
+            ..sample_concept(text)
+        };
+        for mut path in Path::configured(AdderConfig {
+            node_rules: NodeRules {
+                max_level: 5,
+                ..NodeRules::default()
+            },
+            ..AdderConfig::default()
+        }) {
+            let decisions = path
+                .add_nodes(vec![
+                    at_level(5, "Level five is the configured top."),
+                    at_level(6, "Level six is past the configured top."),
+                ])
+                .await;
+            let outcomes: Vec<_> = decisions.iter().map(outcome).collect();
+            assert_eq!(outcomes[0], (true, None, Vec::new()));
+            assert_eq!(
+                outcomes[1],
+                (false, Some("level must be between 0 and 5".to_string()), Vec::new())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn configured_outcome_prefixes_replace_the_defaults() {
+        let outcome_text = |text: &str| NodeProposal {
+            kind: NodeKind::LearningOutcome,
+            ..sample_concept(text)
+        };
+        let with_prefixes = |prefixes: &[&str]| AdderConfig {
+            node_rules: NodeRules {
+                allowed_lo_prefixes: prefixes.iter().map(|prefix| prefix.to_string()).collect(),
+                ..NodeRules::default()
+            },
+            ..AdderConfig::default()
+        };
+        let proposals = || {
+            vec![
+                outcome_text("Students will be able to trace a recursive call."),
+                outcome_text("STUDENTS WILL BE ABLE TO name a base case."),
+                outcome_text("I can trace a recursive call."),
+            ]
+        };
+
+        for mut path in Path::configured(with_prefixes(&["I can ", "Students will be able to "])) {
+            let decisions = path.add_nodes(proposals()).await;
+            assert!(decisions.iter().all(|decision| decision.accepted));
+        }
+
+        for mut path in Path::configured(with_prefixes(&["Students will be able to"])) {
+            let decisions = path.add_nodes(proposals()).await;
+            let outcomes: Vec<_> = decisions.iter().map(outcome).collect();
+            assert_eq!(
+                outcomes,
+                vec![
+                    (true, None, Vec::new()),
+                    (true, None, Vec::new()),
+                    (
+                        false,
+                        Some(
+                            "learning outcomes must start with 'Students will be able to'"
+                                .to_string()
+                        ),
+                        Vec::new()
+                    ),
+                ]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn configured_text_rules_change_acceptance() {
+        let outcome_text = |text: &str| NodeProposal {
+            kind: NodeKind::LearningOutcome,
+            ..sample_concept(text)
+        };
+        let proposals = || {
+            vec![
+                outcome_text("Learners will trace a recursive call."),
+                outcome_text("I can trace a recursive call."),
+                sample_concept("Recursion needs a base case. It also needs progress."),
+                NodeProposal {
+                    tags: Some(vec!["contract".to_string(), "tests".to_string()]),
+                    ..sample_concept("Contracts state what a function consumes.")
+                },
+            ]
+        };
+        let relaxed = AdderConfig {
+            node_rules: NodeRules {
+                allowed_lo_prefixes: vec!["Learners will".to_string(), "You can".to_string()],
+                require_single_sentence: false,
+                max_tags_per_node: 1,
+                ..NodeRules::default()
+            },
+            ..AdderConfig::default()
+        };
+        for mut path in Path::configured(relaxed) {
+            let decisions = path.add_nodes(proposals()).await;
+            let outcomes: Vec<_> = decisions.iter().map(outcome).collect();
+            assert_eq!(outcomes[0], (true, None, Vec::new()));
+            assert_eq!(
+                outcomes[1].1.as_deref(),
+                Some("learning outcomes must start with 'Learners will' or 'You can'")
+            );
+            assert!(decisions[2].accepted, "several sentences are allowed");
+            assert!(decisions[3].accepted);
+
+            let inventory = match &path {
+                Path::Actor(adder) => adder.ask(Inventory).await.expect("inventory"),
+                Path::Pure(dry_run) => dry_run.inventory(),
+            };
+            let tags = inventory
+                .iter()
+                .find(|(_, _, _, text, _)| text.starts_with("Contracts"))
+                .and_then(|(.., tags)| tags.clone());
+            assert_eq!(tags, Some(vec!["contract".to_string()]));
+        }
+
+        for mut path in Path::both(RationalePolicy::Off) {
+            let accepted: Vec<bool> = path
+                .add_nodes(proposals())
+                .await
+                .iter()
+                .map(|decision| decision.accepted)
+                .collect();
+            assert_eq!(accepted, [false, true, false, true]);
+        }
+
+        let any_opening = AdderConfig {
+            node_rules: NodeRules {
+                allowed_lo_prefixes: Vec::new(),
+                ..NodeRules::default()
+            },
+            ..AdderConfig::default()
+        };
+        for mut path in Path::configured(any_opening) {
+            let decisions = path
+                .add_nodes(vec![outcome_text("Trace a recursive call by hand.")])
+                .await;
+            assert!(decisions[0].accepted);
+        }
+    }
+
+    #[tokio::test]
+    async fn optional_rationale_admits_bare_edges() {
+        for require_edge_rationale in [true, false] {
+            for mut path in Path::configured(AdderConfig {
+                rationale_policy: RationalePolicy::Warn,
+                require_edge_rationale,
+                ..AdderConfig::default()
+            }) {
+                let nodes = path
+                    .add_nodes(vec![
+                        sample_concept("Lists hold items in order."),
+                        sample_concept("Sorting reorders the items of a list."),
+                    ])
+                    .await;
+                let ids = ids(&nodes);
+                let decisions = path
+                    .add_edges(vec![EdgeProposal {
+                        relation:  Relation::PrerequisiteFor,
+                        from_id:   ids[0],
+                        to_id:     ids[1],
+                        rationale: "  ".to_string(),
+                        generator: None,
+                    }])
+                    .await;
+                let expected = if require_edge_rationale {
+                    (false, Some("edge rationale missing".to_string()), Vec::new())
+                } else {
+                    (true, None, Vec::new())
+                };
+                assert_eq!(outcome(&decisions[0]), expected);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn rationale_rules_name_the_failed_check() {
+        let config = AdderConfig {
+            rationale_rules: RationaleRules {
+                min_words:        3,
+                max_chars:        Some(40),
+                require_sentence: true,
+            },
+            ..AdderConfig::default()
+        };
+        for mut path in Path::configured(config) {
+            let nodes = path
+                .add_nodes(vec![
+                    sample_concept("Lists hold items in order."),
+                    sample_concept("Sorting reorders the items of a list."),
+                    sample_concept("Searching finds an item in a list."),
+                    sample_concept("Indexing reads an item by position."),
+                ])
+                .await;
+            let ids = ids(&nodes);
+            let edge = |from: usize, to: usize, rationale: &str| EdgeProposal {
+                relation:  Relation::RelatedTo,
+                from_id:   ids[from],
+                to_id:     ids[to],
+                rationale: rationale.to_string(),
+                generator: None,
+            };
+            let decisions = path
+                .add_edges(vec![
+                    edge(0, 1, "Sorting needs lists."),
+                    edge(0, 2, " related. "),
+                    edge(0, 3, "   "),
+                    edge(1, 2, "Both walk every item of the list in order to finish."),
+                    edge(1, 3, "Both use positions"),
+                ])
+                .await;
+            let found: Vec<_> = decisions.iter().map(outcome).collect();
+            let rejected = |reason: &str| (false, Some(reason.to_string()), Vec::new());
+            assert_eq!(
+                found,
+                vec![
+                    (true, None, Vec::new()),
+                    rejected("edge rationale too short: 1 word(s), minimum 3"),
+                    rejected("edge rationale missing"),
+                    rejected("edge rationale too long: 52 characters, maximum 40"),
+                    rejected("edge rationale is not a complete sentence: 'Both use positions'"),
+                ]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn node_validation_matches_actor() {
+        let mut outcomes = Vec::new();
+        for mut path in Path::both(RationalePolicy::Off) {
+            let decisions = path
+                .add_nodes(vec![
+                    sample_concept("Graphs model dependencies with directed edges."),
+                    sample_concept("Graphs model dependencies with directed edges."),
+                    NodeProposal {
+                        kind:        NodeKind::LearningOutcome,
+                        granularity: Granularity::Sentence,
+                        level:       2,
+                        text:        "Understand recursion across modules.".to_string(),
+                        tags:        Some(vec!["purpose".to_string()]),
+                        generator:   None,
+                        namespace:   None,
+                        topic:       None,
+                    },
+                    NodeProposal {
+                        kind:        NodeKind::LearningOutcome,
+                        granularity: Granularity::Sentence,
+                        level:       2,
+                        text:        "I can trace prerequisite chains in a learning network."
+                            .to_string(),
+                        tags:        Some(vec!["implementation".to_string()]),
+                        generator:   None,
+                        namespace:   None,
+                        topic:       None,
+                    },
+                ])
+                .await;
+
+            let accepted: Vec<bool> = decisions.iter().map(|d| d.accepted).collect();
+            assert_eq!(accepted, vec![true, false, false, true]);
+            let repeated = path
+                .add_nodes(vec![sample_concept(
+                    "Graphs model dependencies with directed edges.",
+                )])
+                .await;
+            assert_eq!(
+                repeated[0].explanation,
+                Some(Explanation::DuplicateNode {
+                    existing: decisions[0].assigned_id.unwrap(),
+                })
+            );
+            outcomes.push(decisions.iter().map(outcome).collect::<Vec<_>>());
+        }
+        assert_eq!(outcomes[0], outcomes[1]);
+    }
+
+    #[tokio::test]
+    async fn cycle_rejection_matches_actor() {
+        let mut outcomes = Vec::new();
+        for mut path in Path::both(RationalePolicy::Off) {
+            let node_ids = ids(&path
+                .add_nodes(vec![
+                    sample_concept("Concept A establishes foundational syntax."),
+                    sample_concept("Concept B introduces control flow variations."),
+                    sample_concept("Concept C covers data encapsulation."),
+                ])
+                .await);
+            assert_eq!(node_ids.len(), 3);
+
+            let edge = |from: usize, to: usize| EdgeProposal {
+                relation:  Relation::PrerequisiteFor,
+                from_id:   node_ids[from],
+                to_id:     node_ids[to],
+                rationale: "Each concept builds on the last.".to_string(),
+                generator: None,
+            };
+            let decisions = path
+                .add_edges(vec![edge(0, 1), edge(1, 2), edge(2, 0)])
+                .await;
+
+            let Some(Explanation::CyclePath { path: cycle }) = &decisions[2].explanation else {
+                panic!("cycle rejection should carry the offending path");
+            };
+            let cycle_ids: Vec<Uuid> = cycle.iter().map(|node| node.id).collect();
+            assert_eq!(cycle_ids, node_ids);
+            outcomes.push(decisions.iter().map(outcome).collect::<Vec<_>>());
+        }
+        assert_eq!(outcomes[0], outcomes[1]);
+    }
+
+    #[tokio::test]
+    async fn rationale_policies_match_actor() {
+        for policy in [RationalePolicy::Warn, RationalePolicy::Strict] {
+            let mut outcomes = Vec::new();
+            for mut path in Path::both(policy) {
+                let node_ids = ids(&path
+                    .add_nodes(vec![
+                        sample_concept("A method signature names parameter types."),
+                        sample_concept("Write tests before implementation."),
+                    ])
+                    .await);
+                let decisions = path
+                    .add_edges(vec![
+                        EdgeProposal {
+                            relation:  Relation::Supports,
+                            from_id:   node_ids[0],
+                            to_id:     node_ids[1],
+                            rationale: "Recursion Base Cases underpin each method signature."
+                                .to_string(),
+                            generator: None,
+                        },
+                        EdgeProposal {
+                            relation:  Relation::Supports,
+                            from_id:   node_ids[0],
+                            to_id:     node_ids[1],
+                            rationale: "Duplicate in the same batch.".to_string(),
+                            generator: None,
+                        },
+                    ])
+                    .await;
+                assert_eq!(decisions[0].accepted, policy == RationalePolicy::Warn);
+                outcomes.push(decisions.iter().map(outcome).collect::<Vec<_>>());
+            }
+            assert_eq!(outcomes[0], outcomes[1], "{policy:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn dry_run_matches_seeded_ids_and_capacity() {
+        let config = AdderConfig {
+            max_nodes: Some(2),
+            max_edges: Some(1),
+            namespace: Some("course".to_string()),
+            id_seed: Some(7),
+            ..AdderConfig::default()
+        };
+        let adder = GraphAdder::spawn(
+            GraphAdder::with_event_sender(GraphStore::new(), None).with_config(config.clone()),
+        );
+        let mut dry_run = DryRun::new(config);
+        let proposals = vec![
+            sample_concept("Variables name stored values."),
+            sample_concept("Loops repeat a block of statements."),
+            sample_concept("Functions package reusable steps."),
+        ];
+
+        let seen = |decisions: &[Decision]| {
+            decisions
+                .iter()
+                .map(|decision| (outcome(decision), decision.assigned_id))
+                .collect::<Vec<_>>()
+        };
+        let expected = adder.ask(AddNodes(proposals.clone())).await.unwrap();
+        let decisions = dry_run.add_nodes(proposals);
+        assert_eq!(seen(&decisions), seen(&expected));
+        assert!(
+            decisions[2]
+                .reason
+                .as_deref()
+                .unwrap()
+                .starts_with("CapacityExceeded")
+        );
+
+        let (first, second) = (ids(&decisions)[0], ids(&decisions)[1]);
+        let edge = |from, to| EdgeProposal {
+            relation:  Relation::RelatedTo,
+            from_id:   from,
+            to_id:     to,
+            rationale: "Loops reuse the values that variables name.".to_string(),
+            generator: None,
+        };
+        let edges = vec![edge(first, second), edge(second, first)];
+        let expected = adder.ask(AddEdges(edges.clone())).await.unwrap();
+        assert_eq!(seen(&dry_run.add_edges(edges)), seen(&expected));
+
+        let snapshot = dry_run.snapshot();
+        assert_eq!(snapshot.nodes.iter().map(|n| n.id).collect::<Vec<_>>(), vec![first, second]);
+        assert_eq!(snapshot.nodes[0].namespace.as_deref(), Some("course"));
+        assert_eq!(snapshot.edges.len(), 1);
+        assert_eq!(dry_run.inventory().len(), 2);
+        let summary = dry_run.summary();
+        assert_eq!((summary.total_nodes, summary.total_edges), (2, 1));
+        assert_eq!(summary.remaining_node_capacity, Some(0));
+        assert_eq!(summary.generators[0].nodes.accepted, 2);
+    }
+
+    #[test]
+    fn snapshot_replay_reports_each_broken_rule() {
+        let node = |text: &str, kind: NodeKind, level: u8| Node {
+            id: Uuid::new_v4(),
+            kind,
+            granularity: Granularity::Sentence,
+            level,
+            text: text.into(),
+            tags: None,
+            generator: None,
+            namespace: None,
+            topic: None,
+            pinned: false,
+        };
+        let first = node("Loops repeat a block of code.", NodeKind::Concept, 0);
+        let second = node("Recursion calls the same function.", NodeKind::Concept, 1);
+        let duplicate = node("Loops  repeat a block of code.", NodeKind::Concept, 0);
+        let outcome = node("Understand loops deeply.", NodeKind::LearningOutcome, 2);
+        let edge = |from: &Node, to: &Node| SnapshotEdge {
+            from:      from.id,
+            to:        to.id,
+            relation:  Relation::PrerequisiteFor,
+            rationale: "One idea builds on the other.".to_string(),
+            generator: None,
+            pinned:    false,
+        };
+        let snapshot = GraphSnapshot {
+            edges: vec![edge(&first, &second), edge(&second, &first)],
+            nodes: vec![
+                first.clone(),
+                second.clone(),
+                duplicate.clone(),
+                outcome.clone(),
+            ],
+        };
+
+        let violations = validate_snapshot(&snapshot, RationalePolicy::Off, &NodeRules::default());
+        let flagged: Vec<_> = violations
+            .iter()
+            .map(|violation| match violation {
+                Violation::Node { id, .. } => (*id, None),
+                Violation::Edge { from, to, .. } => (*from, Some(*to)),
+            })
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                (duplicate.id, None),
+                (outcome.id, None),
+                (second.id, Some(first.id)),
+            ],
+            "{violations:?}"
+        );
+        assert!(
+            violations[2]
+                .to_string()
+                .starts_with(&format!("edge {}", second.id))
+        );
+
+        let clean = GraphSnapshot {
+            edges: vec![edge(&first, &second)],
+            nodes: vec![first, second],
+        };
+        assert!(validate_snapshot(&clean, RationalePolicy::Off, &NodeRules::default()).is_empty());
+    }
+}
@@ -1,26 +1,30 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, io, path::PathBuf, sync::Arc};
 
 use rerun::{
-    GraphEdges, GraphNodes, RecordingStream, RecordingStreamBuilder, archetypes::TextLog,
-    components::TextLogLevel,
+    GraphEdges, GraphNodes, RecordingStream, RecordingStreamBuilder, RecordingStreamError,
+    SpawnError, archetypes::TextLog, components::TextLogLevel,
 };
-use tokio::sync::mpsc::UnboundedReceiver;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use uuid::Uuid;
 
 use crate::{
     edge_synth::truncate_sentence,
-    model::{NodeKind, Relation},
+    model::{DroppedTag, NodeKind, Relation},
 };
 
 /// Events emitted by the GraphAdder for visualization purposes.
 #[derive(Debug, Clone)]
 pub enum Event {
     NodeAccepted {
-        id:    Uuid,
-        kind:  NodeKind,
-        level: u8,
-        tags:  Option<Vec<String>>,
-        text:  String,
+        id:           Uuid,
+        kind:         NodeKind,
+        level:        u8,
+        tags:         Option<Vec<String>>,
+        /// Shares the interned text stored in the graph.
+        text:         Arc<str>,
+        /// Proposed tags left off the node.
+        dropped_tags: Vec<DroppedTag>,
     },
     NodeRejected {
         text:   String,
@@ -36,9 +40,115 @@ pub enum Event {
         relation: Relation,
         reason:   String,
     },
+    NodeUpdated {
+        id:    Uuid,
+        level: u8,
+        tags:  Option<Vec<String>>,
+        text:  Arc<str>,
+    },
+    NodeRemoved {
+        id:     Uuid,
+        reason: String,
+    },
+    EdgeRemoved {
+        relation: Relation,
+        from:     Uuid,
+        to:       Uuid,
+        reason:   String,
+    },
     SummaryLine {
         message: String,
     },
+    CapacityReached {
+        resource: CapacityResource,
+        limit:    usize,
+    },
+    PhaseStarted {
+        phase:    Phase,
+        /// Number of items the phase will process, when known up front.
+        expected: usize,
+    },
+    PhaseFinished {
+        phase:  Phase,
+        detail: String,
+    },
+    /// A learning outcome has fewer supports edges than the adder's minimum.
+    CoverageWarning {
+        id:       Uuid,
+        text:     Arc<str>,
+        supports: usize,
+        minimum:  usize,
+    },
+    /// Sent before the adder decides a vector of proposals.
+    BatchStarted {
+        kind: BatchKind,
+        size: usize,
+    },
+    /// Sent after every proposal of the batch was decided.
+    BatchCompleted {
+        accepted: usize,
+        rejected: usize,
+    },
+}
+
+/// What a batch of proposals holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BatchKind {
+    Nodes,
+    Edges,
+}
+
+impl fmt::Display for BatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BatchKind::Nodes => "nodes",
+            BatchKind::Edges => "edges",
+        })
+    }
+}
+
+/// Stage of a run, announced so consumers can show progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    GeneratingNodes,
+    AddingNodes,
+    GeneratingEdges,
+    AddingEdges,
+}
+
+impl Phase {
+    /// Whether the phase waits on generator (LLM) calls rather than the adder.
+    pub fn is_generation(self) -> bool {
+        matches!(self, Phase::GeneratingNodes | Phase::GeneratingEdges)
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Phase::GeneratingNodes => "Generating nodes",
+            Phase::AddingNodes => "Adding nodes",
+            Phase::GeneratingEdges => "Generating edges",
+            Phase::AddingEdges => "Adding edges",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Forward every event from `rx` to each of `sinks` until the source closes.
+///
+/// Sinks whose receiver has gone away are dropped.
+pub async fn fan_out(mut rx: UnboundedReceiver<Event>, mut sinks: Vec<UnboundedSender<Event>>) {
+    while let Some(event) = rx.recv().await {
+        sinks.retain(|sink| sink.send(event.clone()).is_ok());
+    }
+}
+
+/// Graph resource guarded by a GraphAdder capacity cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CapacityResource {
+    Nodes,
+    Edges,
 }
 
 #[derive(Debug, Clone)]
@@ -48,28 +158,135 @@ struct NodeCache {
     #[allow(dead_code)]
     level: u8,
     #[allow(dead_code)]
-    text:  String,
+    text:  Arc<str>,
     order: usize,
 }
 
+/// Where visualization events are sent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VizTarget {
+    Off,
+    /// Spawn (or connect to) a local rerun viewer.
+    Spawn,
+    /// Write an `.rrd` recording to disk.
+    Save(PathBuf),
+}
+
+impl fmt::Display for VizTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VizTarget::Off => f.write_str("off"),
+            VizTarget::Spawn => f.write_str("spawn"),
+            VizTarget::Save(path) => write!(f, "save:{}", path.display()),
+        }
+    }
+}
+
+/// Why the viewer could not be reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VizFailure {
+    ViewerNotFound,
+    VersionMismatch,
+    ConnectionRefused,
+    Other,
+}
+
+impl VizFailure {
+    /// Classify a recording-stream setup error.
+    pub fn classify(err: &RecordingStreamError) -> Self {
+        match err {
+            RecordingStreamError::SpawnViewer(
+                SpawnError::ExecutableNotFound { .. } | SpawnError::ExecutableNotFoundInPath { .. },
+            ) => VizFailure::ViewerNotFound,
+            RecordingStreamError::SpawnViewer(SpawnError::Io(io_err)) => match io_err.kind() {
+                io::ErrorKind::NotFound => VizFailure::ViewerNotFound,
+                io::ErrorKind::ConnectionRefused => VizFailure::ConnectionRefused,
+                _ => Self::classify_message(&io_err.to_string()),
+            },
+            other => Self::classify_message(&other.to_string()),
+        }
+    }
+
+    /// Classify from an error message when the error type is not specific.
+    pub fn classify_message(message: &str) -> Self {
+        let lowered = message.to_lowercase();
+        if lowered.contains("connection refused") {
+            VizFailure::ConnectionRefused
+        } else if lowered.contains("version") || lowered.contains("incompatible") {
+            VizFailure::VersionMismatch
+        } else if lowered.contains("not found") || lowered.contains("failed to find") {
+            VizFailure::ViewerNotFound
+        } else {
+            VizFailure::Other
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            VizFailure::ViewerNotFound => "rerun viewer not found",
+            VizFailure::VersionMismatch => "rerun viewer version does not match the SDK",
+            VizFailure::ConnectionRefused => "connection to the rerun viewer was refused",
+            VizFailure::Other => "rerun viewer unavailable",
+        }
+    }
+}
+
+/// Outcome of setting up visualization, recorded in the run report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VizStatus {
+    pub target:    String,
+    pub connected: bool,
+    pub failure:   Option<VizFailure>,
+    pub error:     Option<String>,
+}
+
+impl VizStatus {
+    /// One-line warning with a fallback hint, when setup failed.
+    pub fn warning(&self) -> Option<String> {
+        let failure = self.failure?;
+        let hint = if self.target == VizTarget::Spawn.to_string() {
+            "; try --viz save to record to a file instead"
+        } else {
+            ""
+        };
+        Some(format!("visualization disabled: {}{hint}", failure.description()))
+    }
+}
+
 /// Rerun-backed visualizer actor state.
 #[derive(Debug)]
 pub struct Viz {
     stream:       Option<RecordingStream>,
+    status:       VizStatus,
     nodes:        HashMap<Uuid, NodeCache>,
     edges:        Vec<(Uuid, Uuid, Relation, String)>,
     level_counts: HashMap<u8, usize>,
 }
 
 impl Viz {
-    pub fn new(enabled: bool) -> Self {
-        let stream = if enabled {
-            RecordingStreamBuilder::new("weaver-mvp").spawn().ok()
-        } else {
-            None
+    /// Set up the recording stream; `Off` never touches rerun, but the
+    /// returned actor still drains its channel.
+    pub fn new(target: VizTarget) -> Self {
+        let builder = || RecordingStreamBuilder::new("weaver-mvp");
+        let result = match &target {
+            VizTarget::Off => Ok(None),
+            VizTarget::Spawn => builder().spawn().map(Some),
+            VizTarget::Save(path) => builder().save(path).map(Some),
+        };
+        let (stream, failure, error) = match result {
+            Ok(stream) => (stream, None, None),
+            Err(err) => (None, Some(VizFailure::classify(&err)), Some(err.to_string())),
         };
 
         Self {
+            status: VizStatus {
+                target: target.to_string(),
+                connected: stream.is_some(),
+                failure,
+                error,
+            },
             stream,
             nodes: HashMap::new(),
             edges: Vec::new(),
@@ -77,6 +294,10 @@ impl Viz {
         }
     }
 
+    pub fn status(&self) -> &VizStatus {
+        &self.status
+    }
+
     pub async fn run(mut self, mut rx: UnboundedReceiver<Event>) {
         while let Some(event) = rx.recv().await {
             self.handle_event(event);
@@ -91,6 +312,7 @@ impl Viz {
                 level,
                 tags,
                 text,
+                ..
             } => self.handle_node_accepted(id, kind, level, tags, text),
             Event::NodeRejected { text, reason } => self.log_text(
                 "graph/nodes_rejected",
@@ -108,9 +330,56 @@ impl Viz {
                 TextLogLevel::WARN,
                 format!("REJECT edge {:?}: {reason}", relation),
             ),
+            Event::NodeUpdated {
+                id,
+                level,
+                tags,
+                text,
+            } => self.handle_node_updated(id, level, tags, text),
+            Event::NodeRemoved { id, reason } => self.handle_node_removed(id, reason),
+            Event::EdgeRemoved {
+                relation,
+                from,
+                to,
+                reason,
+            } => self.handle_edge_removed(relation, from, to, reason),
             Event::SummaryLine { message } => {
                 self.log_text("graph/summary", TextLogLevel::INFO, message);
             }
+            Event::CapacityReached { resource, limit } => self.log_text(
+                "graph/capacity",
+                TextLogLevel::WARN,
+                format!("CAPACITY {:?} limit of {limit} reached", resource),
+            ),
+            Event::PhaseStarted { phase, .. } => {
+                self.log_text("graph/phases", TextLogLevel::INFO, format!("START {phase}"));
+            }
+            Event::PhaseFinished { phase, detail } => {
+                self.log_text("graph/phases", TextLogLevel::INFO, format!("DONE {phase}: {detail}"))
+            }
+            Event::CoverageWarning {
+                id,
+                text,
+                supports,
+                minimum,
+            } => self.log_text(
+                "graph/coverage",
+                TextLogLevel::WARN,
+                format!(
+                    "UNDERSUPPORTED {id} ({supports} of {minimum} supports): {}",
+                    truncate_sentence(&text)
+                ),
+            ),
+            Event::BatchStarted { kind, size } => self.log_text(
+                "graph/batches",
+                TextLogLevel::INFO,
+                format!("START {kind} batch of {size}"),
+            ),
+            Event::BatchCompleted { accepted, rejected } => self.log_text(
+                "graph/batches",
+                TextLogLevel::INFO,
+                format!("DONE batch: {accepted} accepted, {rejected} rejected"),
+            ),
         }
     }
 
@@ -120,7 +389,7 @@ impl Viz {
         kind: NodeKind,
         level: u8,
         tags: Option<Vec<String>>,
-        text: String,
+        text: Arc<str>,
     ) {
         let order = match self.nodes.get(&id) {
             Some(cache) => cache.order,
@@ -158,6 +427,55 @@ impl Viz {
         self.log_nodes();
     }
 
+    fn handle_node_updated(
+        &mut self,
+        id: Uuid,
+        level: u8,
+        tags: Option<Vec<String>>,
+        text: Arc<str>,
+    ) {
+        if let Some(cache) = self.nodes.get_mut(&id) {
+            cache.level = level;
+            cache.text = text.clone();
+        }
+
+        let tag_suffix = tags
+            .filter(|t| !t.is_empty())
+            .map(|t| format!(" tags=[{}]", t.join(",")))
+            .unwrap_or_default();
+        self.log_text(
+            "graph/events",
+            TextLogLevel::INFO,
+            format!("UPDATE node lvl {} {}: {}{}", level, id, truncate_sentence(&text), tag_suffix),
+        );
+
+        self.log_nodes();
+    }
+
+    fn handle_node_removed(&mut self, id: Uuid, reason: String) {
+        self.nodes.remove(&id);
+        self.edges
+            .retain(|(from, to, _, _)| *from != id && *to != id);
+
+        self.log_text("graph/events", TextLogLevel::INFO, format!("REMOVE node {id}: {reason}"));
+
+        self.log_nodes();
+        self.log_edges();
+    }
+
+    fn handle_edge_removed(&mut self, relation: Relation, from: Uuid, to: Uuid, reason: String) {
+        self.edges
+            .retain(|(f, t, r, _)| !(*f == from && *t == to && *r == relation));
+
+        self.log_text(
+            "graph/events",
+            TextLogLevel::INFO,
+            format!("REMOVE edge {relation:?} {from} -> {to}: {reason}"),
+        );
+
+        self.log_edges();
+    }
+
     fn handle_edge_accepted(
         &mut self,
         relation: Relation,
@@ -226,7 +544,7 @@ impl Viz {
             let edge = (from.to_string(), to.to_string());
             match relation {
                 Relation::PrerequisiteFor => directed.push(edge),
-                Relation::Supports => supports.push(edge),
+                Relation::Supports | Relation::RelatedTo => supports.push(edge),
             }
         }
 
@@ -245,3 +563,75 @@ impl Viz {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_failures_are_classified() {
+        let missing = RecordingStreamError::SpawnViewer(SpawnError::ExecutableNotFound {
+            executable_path: "/nowhere/rerun".to_string(),
+        });
+        assert_eq!(VizFailure::classify(&missing), VizFailure::ViewerNotFound);
+
+        let refused = RecordingStreamError::SpawnViewer(SpawnError::Io(io::Error::from(
+            io::ErrorKind::ConnectionRefused,
+        )));
+        assert_eq!(VizFailure::classify(&refused), VizFailure::ConnectionRefused);
+
+        assert_eq!(
+            VizFailure::classify_message("viewer reported incompatible protocol version"),
+            VizFailure::VersionMismatch
+        );
+        assert_eq!(VizFailure::classify_message("boom"), VizFailure::Other);
+    }
+
+    #[test]
+    fn failed_spawn_suggests_saving_instead() {
+        let status = VizStatus {
+            target:    VizTarget::Spawn.to_string(),
+            connected: false,
+            failure:   Some(VizFailure::ViewerNotFound),
+            error:     Some("executable not found".to_string()),
+        };
+        let warning = status.warning().expect("failure produces a warning");
+        assert!(warning.starts_with("visualization disabled: rerun viewer not found"));
+        assert!(warning.contains("--viz save"));
+
+        let path = std::env::temp_dir().join(format!("weaver-viz-{}.rrd", Uuid::new_v4()));
+        let viz = Viz::new(VizTarget::Save(path.clone()));
+        assert!(viz.status().connected, "{:?}", viz.status());
+        assert_eq!(viz.status().warning(), None);
+        drop(viz);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn disabled_viz_never_spawns_and_still_drains_events() {
+        let viz = Viz::new(VizTarget::Off);
+        assert!(viz.stream.is_none());
+        assert_eq!(
+            viz.status(),
+            &VizStatus {
+                target:    "off".to_string(),
+                connected: false,
+                failure:   None,
+                error:     None,
+            }
+        );
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let drained = tokio::spawn(viz.run(rx));
+        for index in 0..100 {
+            tx.send(Event::SummaryLine {
+                message: format!("line {index}"),
+            })
+            .expect("viz is still receiving");
+        }
+        drop(tx);
+        drained
+            .await
+            .expect("viz task finishes once the channel closes");
+    }
+}
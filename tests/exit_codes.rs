@@ -0,0 +1,48 @@
+use std::process::Command;
+
+fn weaver(args: &str) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_weaver"))
+        .args(args.split_whitespace())
+        .current_dir(std::env::temp_dir())
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("weaver runs")
+}
+
+#[test]
+fn argument_errors_exit_with_2() {
+    let output = weaver("mvp run --concepts many");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("invalid integer 'many' for --concepts"), "{stderr}");
+    assert!(stderr.contains("Run 'weaver mvp run --help' for usage."), "{stderr}");
+}
+
+#[test]
+fn help_and_version_exit_with_0() {
+    let output = weaver("mvp run --help");
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("Usage: weaver mvp run [FLAGS]"), "{stdout}");
+
+    let output = weaver("--version");
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("weaver "));
+}
+
+#[test]
+fn unwritable_export_path_exits_with_5() {
+    let output = weaver(
+        "mvp run --no-viz --seed 5 --concepts 4 --los 1 --edges 3 --export-dot \
+         weaver-missing-dir/nested/graph.dot",
+    );
+    assert_eq!(output.status.code(), Some(5));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("error: 1 export(s) failed"), "{stderr}");
+}
+
+#[test]
+fn unreadable_input_exits_with_5() {
+    let output = weaver("validate --input weaver-missing-graph.json");
+    assert_eq!(output.status.code(), Some(5));
+}
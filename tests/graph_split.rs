@@ -0,0 +1,79 @@
+use std::process::Command;
+
+fn weaver(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_weaver"))
+        .args(args)
+        .current_dir(dir)
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("weaver runs")
+}
+
+#[test]
+fn split_rewrites_the_saved_graph_with_edges_on_every_replacement() {
+    let dir = std::env::temp_dir().join(format!("weaver-split-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let run = "mvp run --no-viz --seed 5 --concepts 4 --los 1 --edges 3 --export-json graph.json";
+    let output = weaver(&dir, &run.split_whitespace().collect::<Vec<_>>());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let read = |name: &str| -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(dir.join(name)).expect("read graph"))
+            .expect("JSON graph")
+    };
+    let graph = read("graph.json");
+    let edges = graph["edges"].as_array().expect("edges");
+    let node = edges[0]["from"].as_str().expect("edge source").to_string();
+    let incident = edges
+        .iter()
+        .filter(|edge| edge["from"] == node.as_str() || edge["to"] == node.as_str())
+        .count();
+
+    let split = [
+        "graph",
+        "split",
+        "--input",
+        "graph.json",
+        "--node",
+        &node[..8],
+        "--into",
+        "Learners name each part of a program.",
+        "--into",
+        "Learners explain why each part exists.",
+        "--edges-to",
+        "all",
+    ];
+    let output = weaver(&dir, &split);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(&format!("Edges re-attached: {} / {}", incident * 2, incident * 2)),
+        "{stdout}"
+    );
+
+    let split = read("graph.json");
+    let nodes = split["nodes"].as_array().expect("nodes");
+    assert_eq!(nodes.len(), graph["nodes"].as_array().expect("nodes").len() + 1);
+    assert!(nodes.iter().all(|n| n["id"] != node.as_str()), "original is removed");
+    assert_eq!(split["edges"].as_array().expect("edges").len(), edges.len() + incident);
+
+    let (id, text) =
+        (nodes[0]["id"].as_str().expect("id"), nodes[0]["text"].as_str().expect("text"));
+    let output = weaver(
+        &dir,
+        &[
+            "graph",
+            "split",
+            "--input",
+            "graph.json",
+            "--node",
+            id,
+            "--into",
+            text,
+            "--into",
+            "Learners explain it again.",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(4), "a replacement repeating a node is rejected");
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,74 @@
+use std::process::Command;
+
+fn weaver(dir: &std::path::Path, args: &str) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_weaver"))
+        .args(args.split_whitespace())
+        .current_dir(dir)
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("weaver runs")
+}
+
+#[test]
+fn resume_generates_only_the_shortfall() {
+    let dir = std::env::temp_dir().join(format!("weaver-resume-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+
+    let output = weaver(
+        &dir,
+        "mvp run --no-viz --seed 5 --concepts 4 --los 1 --edges 3 --export-json saved.json",
+    );
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = weaver(
+        &dir,
+        "mvp resume --input saved.json --no-viz --seed 5 --concepts 8 --los 2 --edges 8 --output \
+         json",
+    );
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON report");
+    let resumed = &report["resumed"];
+    assert_eq!(resumed["existing_nodes"], 5);
+    assert_eq!(resumed["existing_edges"], 3);
+    assert_eq!(resumed["added_nodes"], 5);
+    assert_eq!(report["summary"]["concepts"], 8);
+    assert_eq!(report["summary"]["learning_outcomes"], 2);
+
+    let output = weaver(
+        &dir,
+        "mvp resume --input saved.json --no-viz --seed 5 --concepts 4 --los 1 --edges 3",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("5 nodes and 3 edges already present, 0 nodes and 0 edges added"),
+        "{stdout}"
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn if_absent_regenerates_after_the_seed_csv_changes() {
+    let dir = std::env::temp_dir().join(format!("weaver-if-absent-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let run = "mvp run --no-viz --seed 5 --concepts 4 --los 1 --edges 3 --import-nodes seed.csv \
+               --if-absent stored.json";
+
+    std::fs::write(dir.join("seed.csv"), "concept,0,Loops repeat statements.,\n").expect("csv");
+    let output = weaver(&dir, run);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = weaver(&dir, run);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Reusing stored.json"), "{stdout}");
+
+    std::fs::write(dir.join("seed.csv"), "concept,0,Branches choose between paths.,\n")
+        .expect("csv");
+    let output = weaver(&dir, run);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Regenerating stored.json: configuration changed since the stored run"),
+        "{stdout}"
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,42 @@
+use std::process::Command;
+
+use weaver::export::ExportFormat;
+
+fn weaver(args: &str) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_weaver"))
+        .args(args.split_whitespace())
+        .current_dir(std::env::temp_dir())
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("weaver runs")
+}
+
+#[test]
+fn dot_export_to_stdout_carries_only_the_graph() {
+    let output = weaver("mvp run --no-viz --seed 5 --concepts 4 --los 1 --edges 3 --export-dot -");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).expect("utf-8 DOT");
+    assert!(stdout.starts_with("digraph weaver {"), "{stdout}");
+    assert!(stdout.trim_end().ends_with('}'), "{stdout}");
+    ExportFormat::Dot
+        .verify(&stdout)
+        .expect("stdout parses as DOT");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Nodes accepted:"), "{stderr}");
+    assert!(stderr.contains("DOT graph written to stdout"), "{stderr}");
+}
+
+#[test]
+fn two_exports_to_stdout_are_rejected() {
+    let output = weaver("mvp run --no-viz --export-dot - --export-json -");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr
+            .contains("only one export can write to stdout ('-'), got --export-dot, --export-json"),
+        "{stderr}"
+    );
+}